@@ -0,0 +1,360 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use azoni_core::{
+  budget::{budget_status, PeriodStatus},
+  change::Operation,
+  models::{
+    compartment::CompartmentTxnT,
+    filter::{self, BudgetPeriod, FilterTxnT},
+    label::LabelTxnT,
+  },
+  traits::TxnT,
+  types::{Money, UId},
+};
+use chrono::NaiveDate;
+use clap::Subcommand;
+
+use super::{compartment::load_compartment, label::load_label, load_space, record_ops};
+use crate::repository::Repository;
+
+#[derive(Subcommand, Debug)]
+pub enum Budget {
+  /// List the budgets.
+  List,
+  /// Create a budget on a new filter.
+  New {
+    name: String,
+    /// What may be spent in each period, such as "500.00 USD".
+    #[clap(long = "limit")]
+    limit: Money,
+    /// One of monthly, quarterly or custom.
+    #[clap(long = "period", default_value = "monthly")]
+    period: BudgetPeriod,
+    /// First day counted, defaults to the start of the current period.
+    #[clap(long = "start")]
+    start: Option<NaiveDate>,
+    /// Last day counted. Custom periods need one.
+    #[clap(long = "end")]
+    end: Option<NaiveDate>,
+    /// Add what is left unspent at the end of a period to the next one.
+    #[clap(long = "rollover")]
+    rollover: bool,
+    /// Count the entries labelled by this label, or one below it.
+    #[clap(long = "label")]
+    labels: Vec<String>,
+    /// Count the entries posting to this compartment, or one below it.
+    #[clap(long = "compartment")]
+    compartments: Vec<String>,
+    /// Record the change in this space instead of the current one, and look
+    /// the labels up in it.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Show what a budget counts.
+  Show {
+    name: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Change a budget. Its periods stay the same.
+  Edit {
+    name: String,
+    #[clap(long = "rename")]
+    rename: Option<String>,
+    #[clap(long = "limit")]
+    limit: Option<Money>,
+    #[clap(long = "end")]
+    end: Option<NaiveDate>,
+    /// Whether what is left unspent rolls over to the next period.
+    #[clap(long = "rollover")]
+    rollover: Option<bool>,
+    #[clap(long = "add-label")]
+    add_labels: Vec<String>,
+    #[clap(long = "rm-label")]
+    rm_labels: Vec<String>,
+    #[clap(long = "add-compartment")]
+    add_compartments: Vec<String>,
+    #[clap(long = "rm-compartment")]
+    rm_compartments: Vec<String>,
+    /// Record the change in this space instead of the current one, and look
+    /// the labels up in it.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Stop a filter being a budget. The filter stays.
+  Rm {
+    name: String,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Show what was spent and what remains of a budget in each of its
+  /// periods, or without a name, of every budget in its current period.
+  Status {
+    name: Option<String>,
+    /// Show the periods started by this day, defaults to today.
+    #[clap(long = "until")]
+    until: Option<NaiveDate>,
+    /// Count the entries of this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+}
+
+impl Budget {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Budget::List => {
+        let txn = repo.encyc.txn_begin()?;
+        let mut budgets = Vec::new();
+        for f in txn.list_filters()? {
+          if let Some(b) = txn.get_budget(&f.id)? {
+            budgets.push(b)
+          }
+        }
+        budgets.sort_by(|a, b| a.name.cmp(&b.name));
+        for b in budgets {
+          let rollover = if b.rollover { ", rollover" } else { "" };
+          outln!(
+            "{:<24} {:<9} {:>16}{}",
+            b.name,
+            b.period.to_string(),
+            b.limit.to_string(),
+            rollover
+          )?;
+        }
+      }
+      Budget::New {
+        name,
+        limit,
+        period,
+        start,
+        end,
+        rollover,
+        labels,
+        compartments,
+        space,
+      } => {
+        let id = UId::new();
+        let budget = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let start = start.unwrap_or_else(|| period.start_of(chrono::Local::now().date_naive()));
+          if period == BudgetPeriod::Custom && end.is_none() {
+            bail!("A custom period needs an end, use `--end`")
+          }
+          filter::Budget {
+            name,
+            period,
+            start,
+            end,
+            limit,
+            rollover,
+            labels: labels
+              .iter()
+              .map(|l| load_label(&txn, &s, l).map(|l| l.id))
+              .collect::<Result<_>>()?,
+            compartments: compartments
+              .iter()
+              .map(|c| load_compartment(&txn, &s, c).map(|c| c.id))
+              .collect::<Result<_>>()?,
+          }
+        };
+        check(&budget)?;
+        record_ops(
+          &repo,
+          space.as_deref(),
+          vec![
+            Operation::AddFilter { id },
+            Operation::SetBudget {
+              id,
+              old: None,
+              new: Some(budget),
+            },
+          ],
+        )?;
+        outln!("{}", id)?;
+      }
+      Budget::Show { name, space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let (id, b) = load_budget(&txn, &name)?;
+        outln!("Budget: {}", b.name)?;
+        outln!("Filter: {}", id)?;
+        outln!("Period: {}", b.period)?;
+        outln!("Limit: {}", b.limit)?;
+        outln!("Start: {}", b.start)?;
+        if let Some(end) = b.end {
+          outln!("End: {}", end)?;
+        }
+        outln!("Rollover: {}", if b.rollover { "yes" } else { "no" })?;
+        let mut labels = Vec::new();
+        for l in b.labels.iter() {
+          labels.push(txn.label_path(l)?)
+        }
+        if !labels.is_empty() {
+          labels.sort();
+          outln!("Labels: {}", labels.join(", "))?;
+        }
+        let mut compartments = Vec::new();
+        for c in b.compartments.iter() {
+          compartments.push(txn.compartment_path(&space, c)?)
+        }
+        if !compartments.is_empty() {
+          compartments.sort();
+          outln!("Compartments: {}", compartments.join(", "))?;
+        }
+      }
+      Budget::Edit {
+        name,
+        rename,
+        limit,
+        end,
+        rollover,
+        add_labels,
+        rm_labels,
+        add_compartments,
+        rm_compartments,
+        space,
+      } => {
+        let op = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let (id, old) = load_budget(&txn, &name)?;
+          let mut new = old.clone();
+          if let Some(name) = rename {
+            new.name = name
+          }
+          new.limit = limit.unwrap_or(new.limit);
+          new.end = end.or(new.end);
+          new.rollover = rollover.unwrap_or(new.rollover);
+          for l in add_labels {
+            let l = load_label(&txn, &s, &l)?.id;
+            if !new.labels.contains(&l) {
+              new.labels.push(l)
+            }
+          }
+          for l in rm_labels {
+            let l = load_label(&txn, &s, &l)?.id;
+            new.labels.retain(|x| *x != l)
+          }
+          for c in add_compartments {
+            let c = load_compartment(&txn, &s, &c)?.id;
+            if !new.compartments.contains(&c) {
+              new.compartments.push(c)
+            }
+          }
+          for c in rm_compartments {
+            let c = load_compartment(&txn, &s, &c)?.id;
+            new.compartments.retain(|x| *x != c)
+          }
+          if new == old {
+            bail!("Nothing to change")
+          }
+          check(&new)?;
+          Operation::SetBudget {
+            id,
+            old: Some(old),
+            new: Some(new),
+          }
+        };
+        record_ops(&repo, space.as_deref(), vec![op])?;
+      }
+      Budget::Rm { name, space } => {
+        let op = {
+          let txn = repo.encyc.txn_begin()?;
+          let (id, old) = load_budget(&txn, &name)?;
+          Operation::SetBudget {
+            id,
+            old: Some(old),
+            new: None,
+          }
+        };
+        record_ops(&repo, space.as_deref(), vec![op])?;
+      }
+      Budget::Status { name, until, space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let until = until.unwrap_or_else(|| chrono::Local::now().date_naive());
+        match name {
+          Some(name) => {
+            let (_, b) = load_budget(&txn, &name)?;
+            outln!(
+              "{:<23} {:>16} {:>16} {:>16}",
+              "Period",
+              "Available",
+              "Spent",
+              "Remaining"
+            )?;
+            for p in budget_status(&txn, &space, &b, until)? {
+              outln!("{}", row(&p))?;
+            }
+          }
+          None => {
+            let mut budgets = Vec::new();
+            for f in txn.list_filters()? {
+              if let Some(b) = txn.get_budget(&f.id)? {
+                budgets.push(b)
+              }
+            }
+            budgets.sort_by(|a, b| a.name.cmp(&b.name));
+            for b in budgets {
+              match budget_status(&txn, &space, &b, until)?.last() {
+                Some(p) => outln!("{:<24} {}", b.name, row(p))?,
+                None => outln!("{:<24} starts on {}", b.name, b.start)?,
+              }
+            }
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+fn load_budget<T: TxnT>(txn: &T, name: &str) -> Result<(UId, filter::Budget)> {
+  let id = txn
+    .budget_by_name(name)?
+    .ok_or_else(|| anyhow!("No such budget: {}", name))?
+    .id;
+  let budget = txn
+    .get_budget(&id)?
+    .ok_or_else(|| anyhow!("No such budget: {}", name))?;
+  Ok((id, budget))
+}
+
+fn check(budget: &filter::Budget) -> Result<()> {
+  if budget.labels.is_empty() && budget.compartments.is_empty() {
+    bail!("A budget needs at least one label or compartment, use `--label` or `--compartment`")
+  }
+  if budget.limit.amount <= 0 {
+    bail!("The limit of a budget must be positive")
+  }
+  if budget.end.is_some_and(|end| end < budget.start) {
+    bail!("A budget cannot end before it starts, on {}", budget.start)
+  }
+  Ok(())
+}
+
+fn row(p: &PeriodStatus) -> String {
+  let over = if p.remaining.is_negative() {
+    " (overspent)"
+  } else {
+    ""
+  };
+  format!(
+    "{}..{} {:>16} {:>16} {:>16}{}",
+    p.start,
+    p.end,
+    p.available.to_string(),
+    p.spent.to_string(),
+    p.remaining.to_string(),
+    over
+  )
+}
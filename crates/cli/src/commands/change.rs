@@ -0,0 +1,242 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::{
+  apply::apply_change,
+  change::Operation,
+  changestore::ChangeStore,
+  models::graph::GraphTxnT,
+  traits::{MutTxnT, TxnT},
+  types::{Base32, ChangeId, Hash},
+};
+use clap::Subcommand;
+
+use super::load_space;
+use crate::repository::Repository;
+
+#[derive(Subcommand, Debug)]
+pub enum Change {
+  /// List the changes known to this repository, oldest first.
+  List,
+  /// Show the header and operations of a change.
+  Show {
+    /// Hash of the change, or a prefix of it.
+    hash: String,
+  },
+  /// Apply a change from the change store to a space.
+  Apply {
+    /// Hash of the change, or a prefix of it.
+    hash: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// List the changes a change depends on.
+  Deps {
+    /// Hash of the change, or a prefix of it.
+    hash: String,
+    /// Also list their own dependencies, recursively.
+    #[clap(long = "all")]
+    all: bool,
+  },
+  /// List the changes that depend on a change.
+  ReverseDeps {
+    /// Hash of the change, or a prefix of it.
+    hash: String,
+    /// Also list their own dependents, recursively.
+    #[clap(long = "all")]
+    all: bool,
+  },
+}
+
+impl Change {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Change::List => {
+        let txn = repo.encyc.txn_begin()?;
+        let mut changes = Vec::new();
+        for (_, hash) in txn.list_changes()? {
+          // Entries recorded before changes existed have no change file.
+          let change = repo.changes.get_change(&hash).ok();
+          changes.push((hash, change));
+        }
+        changes.sort_by_key(|(_, c)| c.as_ref().map(|c| c.hashed.header.timestamp));
+        for (hash, change) in changes {
+          match change {
+            Some(change) => {
+              let ops: Vec<_> = change.hashed.operations.iter().map(describe).collect();
+              outln!(
+                "{} {} {}",
+                hash.to_base32(),
+                change.hashed.header.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                ops.join(", ")
+              )?;
+            }
+            None => outln!("{} (no change file)", hash.to_base32())?,
+          }
+        }
+      }
+      Change::Show { hash } => {
+        let txn = repo.encyc.txn_begin()?;
+        let hash = find_change(&txn, &hash)?;
+        let change = repo.changes.get_change(&hash)?;
+        let header = &change.hashed.header;
+        outln!("Change: {}", hash.to_base32())?;
+        outln!("Date: {}", header.timestamp)?;
+        for author in header.authors.iter() {
+          match author.id() {
+            Some(id) => outln!("Author: {} ({})", author, id)?,
+            None => outln!("Author: {}", author)?,
+          }
+        }
+        for device in header.devices.iter() {
+          outln!("Device: {}", device.name)?;
+        }
+        for dep in change.hashed.dependencies.iter() {
+          outln!("Dependency: {}", dep.to_base32())?;
+        }
+        for sig in change.signatures.iter() {
+          let valid = if sig.key.verify(&hash, &sig.signature) {
+            "valid"
+          } else {
+            "INVALID"
+          };
+          outln!("Signature: {} ({})", sig.key, valid)?;
+        }
+        outln!("Operations:")?;
+        for op in change.hashed.operations.iter() {
+          outln!("  {}", describe(op))?;
+        }
+      }
+      Change::Apply { hash, space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let Some(hash) = Hash::from_base32(hash.as_bytes()) else {
+          bail!("Invalid change hash: {}", hash)
+        };
+        let applied = apply_change(&repo.changes, &mut txn, &space, &hash)?;
+        txn.commit()?;
+        if let Some(closed) = applied.closed {
+          eprintln!("Warning: this change alters {}", closed)
+        }
+      }
+      Change::Deps { hash, all } => {
+        let txn = repo.encyc.txn_begin()?;
+        let hash = find_change(&txn, &hash)?;
+        for dep in follow(&txn, &hash, all, |txn, id| txn.dependencies(id))? {
+          outln!("{}", dep.to_base32())?;
+        }
+      }
+      Change::ReverseDeps { hash, all } => {
+        let txn = repo.encyc.txn_begin()?;
+        let hash = find_change(&txn, &hash)?;
+        for dep in follow(&txn, &hash, all, |txn, id| txn.dependents(id))? {
+          outln!("{}", dep.to_base32())?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Find the change whose hash starts with `prefix`.
+fn find_change<T: TxnT>(txn: &T, prefix: &str) -> Result<Hash> {
+  let mut found = None;
+  for (_, hash) in txn.list_changes()? {
+    if hash.to_base32().starts_with(prefix) {
+      if found.is_some() {
+        bail!("Ambiguous change hash: {}", prefix)
+      }
+      found = Some(hash)
+    }
+  }
+  match found {
+    Some(hash) => Ok(hash),
+    None => bail!("No such change: {}", prefix),
+  }
+}
+
+/// The changes reached from `hash` by one `edges` step, or by any number
+/// of them if `all` is set, in the order they are found.
+fn follow<T: TxnT>(txn: &T, hash: &Hash, all: bool, edges: impl Fn(&T, &ChangeId) -> Result<Vec<ChangeId>, T::GraphError>) -> Result<Vec<Hash>> {
+  let Some(id) = txn.get_internal(hash)? else {
+    bail!("No such change: {}", hash.to_base32())
+  };
+  let mut found = Vec::new();
+  let mut stack = vec![id];
+  while let Some(id) = stack.pop() {
+    for next in edges(txn, &id)? {
+      if found.contains(&next) {
+        continue;
+      }
+      found.push(next);
+      if all {
+        stack.push(next)
+      }
+    }
+  }
+  let mut hashes = Vec::with_capacity(found.len());
+  for id in found {
+    if let Some(hash) = txn.get_external(&id)? {
+      hashes.push(hash)
+    }
+  }
+  Ok(hashes)
+}
+
+fn describe(op: &Operation) -> String {
+  match op {
+    Operation::AddEntry { entry } => format!("add entry {} {}", entry.date, entry.payee),
+    Operation::EditEntry { entry, new, .. } => format!(
+      "edit entry {} ({} {})",
+      entry.to_base32(),
+      new.date,
+      new.payee
+    ),
+    Operation::DelEntry { entry, old } => format!(
+      "delete entry {} ({} {})",
+      entry.to_base32(),
+      old.date,
+      old.payee
+    ),
+    Operation::AddCompartment { id, name, currency } => format!("add compartment {} {} ({})", id, name, currency),
+    Operation::AddVault { id, name } => format!("add vault {} {}", id, name),
+    Operation::AddLabel { id, name, group } => format!("add label {} {} ({})", id, name, group),
+    Operation::AddFilter { id } => format!("add filter {}", id),
+    Operation::RenameCompartment { id, old, new } => format!("rename compartment {} {} to {}", id, old, new),
+    Operation::CloseCompartment { id } => format!("close compartment {}", id),
+    Operation::ReopenCompartment { id } => format!("reopen compartment {}", id),
+    Operation::SetCompartmentKind { id, new, .. } => format!("set the kind of compartment {} to {}", id, new),
+    Operation::SetCompartmentParent { id, new, .. } => match new {
+      Some(parent) => format!("put compartment {} under {}", id, parent),
+      None => format!("make compartment {} a root", id),
+    },
+    Operation::MoveCompartment { id, new, .. } => match new {
+      Some(vault) => format!("move compartment {} to vault {}", id, vault),
+      None => format!("move compartment {} out of its vault", id),
+    },
+    Operation::RenameLabel { id, old, new } => format!("rename label {} {} to {}", id, old, new),
+    Operation::DelLabel { id, name, .. } => format!("delete label {} {}", id, name),
+    Operation::MoveLabel { id, new, .. } => match new {
+      Some(vault) => format!("move label {} to vault {}", id, vault),
+      None => format!("move label {} out of its vault", id),
+    },
+    Operation::SetLabelParent { id, new, .. } => match new {
+      Some(parent) => format!("put label {} under {}", id, parent),
+      None => format!("make label {} a root", id),
+    },
+    Operation::AttachLabel { entry, label } => format!("attach label {} to entry {}", label, entry.to_base32()),
+    Operation::DetachLabel { entry, label } => format!("detach label {} from entry {}", label, entry.to_base32()),
+    Operation::SetBudget { id, new, .. } => match new {
+      Some(b) => format!(
+        "make filter {} budget {} ({} {})",
+        id, b.name, b.limit, b.period
+      ),
+      None => format!("stop filter {} being a budget", id),
+    },
+  }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::pristine::CheckOptions;
+use clap::Parser;
+
+use crate::repository::Repository;
+
+#[derive(Parser, Debug)]
+pub struct Check {
+  /// Fix the problems found.
+  #[clap(long = "repair")]
+  repair: bool,
+}
+
+impl Check {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    let report = repo.encyc.check(&CheckOptions {
+      repair: self.repair,
+    })?;
+    for problem in report.problems.iter() {
+      outln!("{}", problem)?;
+    }
+
+    if report.is_ok() {
+      outln!("No problems found, {} pages checked", report.pages)?;
+    } else if report.repaired {
+      outln!("Repaired {} problems", report.problems.len())?;
+    } else {
+      bail!(
+        "{} problems found, run `azoni check --repair` to fix them",
+        report.problems.len()
+      )
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,371 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{bail, Result};
+use azoni_core::{
+  change::Operation,
+  history::entries_as_of,
+  models::{
+    compartment::{CompartmentKind, CompartmentTxnT, SerializedCompartment},
+    space::SpaceRef,
+    vault::VaultTxnT,
+  },
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::{Currency, Money, UId},
+};
+use chrono::DateTime;
+use clap::Subcommand;
+
+use super::{load_space, load_tag, load_vault, record_ops};
+use crate::{identity::signed_header, repository::Repository};
+
+#[derive(Subcommand, Debug)]
+pub enum Compartment {
+  /// List the compartments and their balances. The balance of a
+  /// compartment includes those of the compartments below it.
+  List {
+    /// Show the balances as they were when this tag of the space was
+    /// created.
+    #[clap(long = "as-of", value_name = "TAG")]
+    as_of: Option<String>,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Create a new compartment.
+  New {
+    name: String,
+    /// Currency of the amounts held in this compartment.
+    #[clap(long = "currency")]
+    currency: Currency,
+    /// One of asset, liability, equity, income or expense. Defaults to the
+    /// kind of the parent, or asset.
+    #[clap(long = "kind")]
+    kind: Option<CompartmentKind>,
+    /// Put the compartment under this one.
+    #[clap(long = "parent")]
+    parent: Option<String>,
+    /// Put the compartment in this vault of the space.
+    #[clap(long = "vault")]
+    vault: Option<String>,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Show the balance and activity of a compartment.
+  Show {
+    name: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Rename a compartment.
+  Rename {
+    name: String,
+    new_name: String,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Close a compartment, whose balance must be zero. Closed compartments
+  /// get no new postings.
+  Close {
+    name: String,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Reopen a closed compartment.
+  Reopen {
+    name: String,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Change the kind of a compartment and of all the compartments below
+  /// it. Only the root of a tree can change kind.
+  Kind {
+    name: String,
+    kind: CompartmentKind,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Put a compartment under another one, of the same kind and currency.
+  Reparent {
+    name: String,
+    /// The new parent. Without it, the compartment becomes the root of a
+    /// tree.
+    #[clap(long = "parent")]
+    parent: Option<String>,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Move a compartment to another vault of the space.
+  Move {
+    name: String,
+    /// The vault to move the compartment to. Without it, the compartment
+    /// is taken out of its vault.
+    #[clap(long = "vault")]
+    vault: Option<String>,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+}
+
+impl Compartment {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Compartment::List { as_of, space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let balances = match as_of {
+          Some(tag) => {
+            let tag = load_tag(&txn, &space, &tag)?;
+            let mut balances = HashMap::new();
+            for (_, entry) in entries_as_of(&repo.changes, &txn, &space, tag.position)? {
+              for p in entry.postings {
+                let b = balances
+                  .entry(p.compartment)
+                  .or_insert(Money::zero(p.amount.currency));
+                *b = b.checked_add(&p.amount)?;
+              }
+            }
+            Some(balances)
+          }
+          None => None,
+        };
+        let own = |c: &SerializedCompartment| match balances {
+          Some(ref balances) => balances
+            .get(&c.id)
+            .copied()
+            .unwrap_or(Money::zero(c.balance.currency)),
+          None => Money::from(c.balance),
+        };
+        let mut lines = Vec::new();
+        for c in txn.list_compartments(&space)? {
+          let mut balance = own(c);
+          for d in txn.descendants(&space, &c.id)? {
+            if let Some(d) = txn.get_compartment(&space, &d)? {
+              balance = balance.checked_add(&own(d))?;
+            }
+          }
+          let closed = if c.is_closed() { " (closed)" } else { "" };
+          lines.push((
+            txn.compartment_path(&space, &c.id)?,
+            c.id,
+            c.kind,
+            balance,
+            closed,
+          ));
+        }
+        lines.sort_by(|a, b| a.0.cmp(&b.0));
+        for (path, id, kind, balance, closed) in lines {
+          outln!(
+            "{} {:<9} {:<32} {:>20}{}",
+            id,
+            kind.to_string(),
+            path,
+            balance.to_string(),
+            closed
+          )?;
+        }
+      }
+      Compartment::New {
+        name,
+        currency,
+        kind,
+        parent,
+        vault,
+        space,
+      } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let id = UId::new();
+        let parent = parent
+          .map(|p| load_compartment(&txn, &space, &p).map(|p| (p.id, p.kind)))
+          .transpose()?;
+        let kind = kind
+          .or(parent.map(|(_, kind)| kind))
+          .unwrap_or(CompartmentKind::Asset);
+        let mut ops = vec![Operation::AddCompartment { id, name, currency }];
+        if kind != CompartmentKind::Asset {
+          ops.push(Operation::SetCompartmentKind {
+            id,
+            old: CompartmentKind::Asset,
+            new: kind,
+          })
+        }
+        if let Some((parent, _)) = parent {
+          ops.push(Operation::SetCompartmentParent {
+            id,
+            old: None,
+            new: Some(parent),
+          })
+        }
+        if let Some(vault) = vault {
+          ops.push(Operation::MoveCompartment {
+            id,
+            old: None,
+            new: Some(load_vault(&txn, &space, &vault)?),
+          })
+        }
+        let (header, key) = signed_header()?;
+        record(&repo.changes, &mut txn, &space, header, ops, &key)?;
+        txn.commit()?;
+        outln!("{}", id)?;
+      }
+      Compartment::Show { name, space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let c = load_compartment(&txn, &space, &name)?;
+        outln!("Compartment: {}", c.id)?;
+        outln!("Name: {}", c.name.as_str())?;
+        outln!("Kind: {}", c.kind)?;
+        if c.parent().is_some() {
+          outln!("Path: {}", txn.compartment_path(&space, &c.id)?)?;
+        }
+        if let Some(vault) = c.vault() {
+          match txn.get_vault(&vault)? {
+            Some(v) => outln!("Vault: {}", v.name.as_str())?,
+            None => outln!("Vault: {}", vault)?,
+          }
+        }
+        if c.is_closed() {
+          outln!("Closed: {}", timestamp(c.closed.as_u64()))?;
+        }
+        outln!("Balance: {}", Money::from(c.balance))?;
+        if !txn.children(&space, &c.id)?.is_empty() {
+          if let Some(total) = txn.total_balance(&space, &c.id)? {
+            outln!("Total: {}", total)?;
+          }
+        }
+        outln!(
+          "Postings: {} in {} entries",
+          c.counter.as_u64(),
+          txn.compartment_entries(&space, &c.id)?.len()
+        )?;
+        outln!("Last activity: {}", timestamp(c.last_modified.as_u64()))?;
+      }
+      Compartment::Rename {
+        name,
+        new_name,
+        space,
+      } => {
+        let id = {
+          let txn = repo.encyc.txn_begin()?;
+          let space = load_space(&txn, space.as_deref())?;
+          load_compartment(&txn, &space, &name)?.id
+        };
+        let op = Operation::RenameCompartment {
+          id,
+          old: name,
+          new: new_name,
+        };
+        record_ops(&repo, space.as_deref(), vec![op])?;
+      }
+      Compartment::Close { name, space } => {
+        let id = {
+          let txn = repo.encyc.txn_begin()?;
+          let space = load_space(&txn, space.as_deref())?;
+          let c = load_compartment(&txn, &space, &name)?;
+          if c.is_closed() {
+            bail!("Compartment {} is already closed", name)
+          }
+          c.id
+        };
+        record_ops(
+          &repo,
+          space.as_deref(),
+          vec![Operation::CloseCompartment { id }],
+        )?;
+      }
+      Compartment::Reopen { name, space } => {
+        let id = {
+          let txn = repo.encyc.txn_begin()?;
+          let space = load_space(&txn, space.as_deref())?;
+          let c = load_compartment(&txn, &space, &name)?;
+          if !c.is_closed() {
+            bail!("Compartment {} is not closed", name)
+          }
+          c.id
+        };
+        record_ops(
+          &repo,
+          space.as_deref(),
+          vec![Operation::ReopenCompartment { id }],
+        )?;
+      }
+      Compartment::Kind { name, kind, space } => {
+        let op = {
+          let txn = repo.encyc.txn_begin()?;
+          let space = load_space(&txn, space.as_deref())?;
+          let c = load_compartment(&txn, &space, &name)?;
+          Operation::SetCompartmentKind {
+            id: c.id,
+            old: c.kind,
+            new: kind,
+          }
+        };
+        record_ops(&repo, space.as_deref(), vec![op])?;
+      }
+      Compartment::Reparent {
+        name,
+        parent,
+        space,
+      } => {
+        let op = {
+          let txn = repo.encyc.txn_begin()?;
+          let space = load_space(&txn, space.as_deref())?;
+          let c = load_compartment(&txn, &space, &name)?;
+          Operation::SetCompartmentParent {
+            id: c.id,
+            old: c.parent(),
+            new: parent
+              .map(|p| load_compartment(&txn, &space, &p).map(|p| p.id))
+              .transpose()?,
+          }
+        };
+        record_ops(&repo, space.as_deref(), vec![op])?;
+      }
+      Compartment::Move { name, vault, space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let c = load_compartment(&txn, &space, &name)?;
+        let new = vault.map(|v| load_vault(&txn, &space, &v)).transpose()?;
+        let op = Operation::MoveCompartment {
+          id: c.id,
+          old: c.vault(),
+          new,
+        };
+        let (header, key) = signed_header()?;
+        record(&repo.changes, &mut txn, &space, header, vec![op], &key)?;
+        txn.commit()?;
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Find a compartment of `space` by name, or by path such as
+/// `Assets:Bank:Checking`.
+pub(super) fn load_compartment<'a, T: TxnT>(txn: &'a T, space: &SpaceRef<T>, name: &str) -> Result<&'a SerializedCompartment> {
+  let last = name.rsplit(':').next().unwrap_or(name);
+  match txn.compartment_by_name(space, last)? {
+    Some(c) if last == name || txn.compartment_path(space, &c.id)? == name => Ok(c),
+    _ => bail!("No such compartment: {}", name),
+  }
+}
+
+fn timestamp(secs: u64) -> String {
+  match DateTime::from_timestamp(secs as i64, 0) {
+    Some(t) => t.format("%Y-%m-%d %H:%M:%S").to_string(),
+    None => secs.to_string(),
+  }
+}
@@ -0,0 +1,146 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use azoni_core::{
+  change::Operation,
+  changestore::ChangeStore,
+  models::{conflict::ConflictTxnT, entry, space::SpaceRef},
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::{Base32, ChangeId, Hash},
+};
+use clap::Subcommand;
+
+use super::{
+  entry::{find_entry, total},
+  load_space, warn_closed,
+};
+use crate::{identity::signed_header, repository::Repository};
+
+#[derive(Subcommand, Debug)]
+pub enum Conflicts {
+  /// List the entries edited concurrently in incompatible ways, with the
+  /// value each change wants.
+  List {
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Resolve a conflict by keeping the value of one of the competing
+  /// changes. To merge values instead, use `azoni entry edit`: any edit of
+  /// an entry in conflict resolves it.
+  Resolve {
+    /// Id of the entry, or a prefix of it.
+    entry: String,
+    /// Hash of the change whose value is kept, or a prefix of it.
+    #[clap(long = "pick")]
+    pick: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+}
+
+/// A change competing for an entry, and the value it wants: `None` to
+/// delete the entry.
+struct Side {
+  hash: Hash,
+  value: Option<entry::Entry>,
+  current: bool,
+}
+
+fn sides<T: TxnT, C: ChangeStore>(txn: &T, changes: &C, space: &SpaceRef<T>, entry: &ChangeId, entry_hash: &Hash) -> Result<Vec<Side>> {
+  let current = txn.last_change(entry)?;
+  let mut sides = Vec::new();
+  for id in txn.entry_conflicts(space, entry)? {
+    let hash = txn
+      .get_external(&id)?
+      .ok_or_else(|| anyhow!("Unknown change in conflict"))?;
+    let change = changes.get_change(&hash)?;
+    let Some(value) = change.proposed_entry(&hash, entry_hash) else {
+      bail!(
+        "Change {} does not touch entry {}",
+        hash.to_base32(),
+        entry_hash.to_base32()
+      )
+    };
+    sides.push(Side {
+      hash,
+      value: value.cloned(),
+      current: current == Some(id),
+    })
+  }
+  Ok(sides)
+}
+
+impl Conflicts {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Conflicts::List { space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        for (entry, _) in txn.list_conflicts(&space)? {
+          let Some(entry_hash) = txn.get_external(&entry)? else {
+            continue;
+          };
+          outln!("Entry {}", entry_hash.to_base32())?;
+          for side in sides(&txn, &repo.changes, &space, &entry, &entry_hash)? {
+            let mark = if side.current { "*" } else { " " };
+            match side.value {
+              Some(e) => outln!(
+                "  {} {} {} {:<24} {}",
+                mark,
+                side.hash.to_base32(),
+                e.date,
+                e.payee,
+                total(&e)?
+              )?,
+              None => outln!("  {} {} deleted", mark, side.hash.to_base32())?,
+            }
+          }
+        }
+      }
+      Conflicts::Resolve { entry, pick, space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let (entry_hash, old) = find_entry(&txn, &space, &entry)?;
+        let id = txn
+          .get_internal(&entry_hash)?
+          .ok_or_else(|| anyhow!("No such entry: {}", entry))?;
+        let sides = sides(&txn, &repo.changes, &space, &id, &entry_hash)?;
+        if sides.is_empty() {
+          bail!("Entry {} is not in conflict", entry_hash.to_base32())
+        }
+        let mut picked = sides
+          .into_iter()
+          .filter(|s| s.hash.to_base32().starts_with(&pick));
+        let side = match (picked.next(), picked.next()) {
+          (Some(side), None) => side,
+          (Some(_), Some(_)) => bail!("Ambiguous change hash: {}", pick),
+          (None, _) => bail!("Change {} is not in this conflict", pick),
+        };
+        let op = match side.value {
+          Some(new) => Operation::EditEntry {
+            entry: entry_hash,
+            old,
+            new,
+          },
+          None => Operation::DelEntry {
+            entry: entry_hash,
+            old,
+          },
+        };
+        let (header, key) = signed_header()?;
+        let recorded = record(&repo.changes, &mut txn, &space, header, vec![op], &key)?;
+        txn.commit()?;
+        warn_closed(&recorded);
+        outln!("{}", recorded.hash.to_base32())?;
+      }
+    }
+    Ok(())
+  }
+}
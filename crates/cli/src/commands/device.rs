@@ -0,0 +1,107 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::{
+  models::device::{DeviceMutTxnT, DeviceTxnT, RegisteredDevice},
+  traits::MutTxnT,
+};
+use chrono::Utc;
+use clap::Subcommand;
+
+use crate::{device::current_device, repository::Repository};
+
+#[derive(Subcommand, Debug)]
+pub enum Device {
+  /// List the devices that wrote to this repository.
+  List,
+  /// Give a device a friendlier name, or remove it.
+  Rename {
+    /// Name or alias of the device.
+    device: String,
+    /// New alias. Without one, the alias is removed.
+    alias: Option<String>,
+  },
+  /// Refuse the changes of a device that this repository does not have
+  /// yet, e.g. those of a lost laptop.
+  Revoke {
+    /// Name or alias of the device.
+    device: String,
+  },
+}
+
+/// Find a device by name, or else by alias.
+fn find_device<T: DeviceTxnT>(txn: &T, name: &str) -> Result<RegisteredDevice> {
+  if let Some(device) = txn.get_device(name)? {
+    return Ok(device);
+  }
+  let mut found = txn
+    .list_devices()?
+    .into_iter()
+    .filter(|d| d.device.alias.as_deref() == Some(name));
+  match (found.next(), found.next()) {
+    (Some(device), None) => Ok(device),
+    (Some(_), Some(_)) => bail!("Ambiguous device alias: {}", name),
+    (None, _) => bail!("No such device: {}", name),
+  }
+}
+
+impl Device {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Device::List => {
+        let txn = repo.encyc.txn_begin()?;
+        let current = current_device().name;
+        for d in txn.list_devices()? {
+          let device = &d.device;
+          let mark = if device.name == current { "*" } else { " " };
+          let system: Vec<_> = [&device.os, &device.os_version, &device.platform]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+          out!(
+            "{} {:<20} {:<16} {:<32} last {}",
+            mark,
+            device.name,
+            device.alias.as_deref().unwrap_or("-"),
+            system.join(" "),
+            device.last_access.format("%Y-%m-%d %H:%M:%S")
+          )?;
+          match d.revoked {
+            Some(t) => outln!(", revoked {}", t.format("%Y-%m-%d %H:%M:%S"))?,
+            None => outln!()?,
+          }
+        }
+      }
+      Device::Rename { device, alias } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let d = find_device(&txn, &device)?;
+        if let Some(ref alias) = alias {
+          let taken = txn
+            .list_devices()?
+            .into_iter()
+            .any(|o| o.device.name != d.device.name && (o.device.name == *alias || o.device.alias.as_ref() == Some(alias)));
+          if taken {
+            bail!("Alias {} is already used by another device", alias)
+          }
+        }
+        txn.rename_device(&d.device.name, alias.as_deref())?;
+        txn.commit()?;
+      }
+      Device::Revoke { device } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let d = find_device(&txn, &device)?;
+        if d.is_revoked() {
+          bail!("Device {} is already revoked", d.device.name)
+        }
+        txn.revoke_device(&d.device.name, Utc::now())?;
+        txn.commit()?;
+      }
+    }
+    Ok(())
+  }
+}
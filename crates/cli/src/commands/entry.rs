@@ -0,0 +1,289 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use azoni_core::{
+  change::Operation,
+  history::entries_as_of,
+  models::{
+    compartment::CompartmentTxnT,
+    conflict::ConflictTxnT,
+    entry::{self, EntryTxnT, Posting},
+    label::LabelTxnT,
+    space::SpaceRef,
+  },
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::{Base32, Hash, Money},
+};
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+
+use super::{load_space, load_tag, warn_closed};
+use crate::{identity::signed_header, repository::Repository};
+
+#[derive(Subcommand, Debug)]
+pub enum Entry {
+  /// Record a new entry.
+  Add(Add),
+  /// List the entries of a space.
+  List {
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+    /// List the entries as they were when this tag was created.
+    #[clap(long = "as-of", value_name = "TAG")]
+    as_of: Option<String>,
+  },
+  /// Show an entry and its postings.
+  Show {
+    id: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Replace some fields of an entry. The entry keeps its id.
+  Edit(Edit),
+  /// Remove an entry.
+  Rm {
+    id: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+}
+
+#[derive(Parser, Debug)]
+pub struct Add {
+  /// Date of the entry, defaults to today.
+  #[clap(long = "date")]
+  date: Option<NaiveDate>,
+  #[clap(long = "payee", default_value = "")]
+  payee: String,
+  #[clap(long = "memo", default_value = "")]
+  memo: String,
+  /// A posting, as `COMPARTMENT=AMOUNT`, e.g. `Groceries=50.00 EUR`.
+  #[clap(short = 'p', long = "posting", required = true)]
+  postings: Vec<String>,
+  /// Use this space instead of the current one.
+  #[clap(long = "space")]
+  space: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct Edit {
+  id: String,
+  #[clap(long = "date")]
+  date: Option<NaiveDate>,
+  #[clap(long = "payee")]
+  payee: Option<String>,
+  #[clap(long = "memo")]
+  memo: Option<String>,
+  /// Replace all the postings, given as `COMPARTMENT=AMOUNT`.
+  #[clap(short = 'p', long = "posting")]
+  postings: Vec<String>,
+  /// Use this space instead of the current one.
+  #[clap(long = "space")]
+  space: Option<String>,
+}
+
+impl Entry {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Entry::Add(add) => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, add.space.as_deref())?;
+        let entry = entry::Entry {
+          date: add
+            .date
+            .unwrap_or_else(|| chrono::Local::now().date_naive()),
+          payee: add.payee,
+          memo: add.memo,
+          postings: parse_postings(&txn, &space, &add.postings)?,
+        };
+        let (header, key) = signed_header()?;
+        let recorded = record(
+          &repo.changes,
+          &mut txn,
+          &space,
+          header,
+          vec![Operation::AddEntry { entry }],
+          &key,
+        )?;
+        txn.commit()?;
+        warn_closed(&recorded);
+        outln!("{}", recorded.hash.to_base32())?;
+      }
+      Entry::List { space, as_of } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let mut entries = Vec::new();
+        if let Some(tag) = as_of {
+          let tag = load_tag(&txn, &space, &tag)?;
+          entries.extend(entries_as_of(&repo.changes, &txn, &space, tag.position)?);
+        } else {
+          for id in txn.space_entries(&space)? {
+            if let (Some(hash), Some(entry)) = (txn.get_external(&id)?, txn.load_entry(&id)?) {
+              entries.push((hash, entry));
+            }
+          }
+        }
+        entries.sort_by_key(|(_, e)| e.date);
+        for (id, entry) in entries {
+          outln!(
+            "{} {} {:<24} {}",
+            id.to_base32(),
+            entry.date,
+            entry.payee,
+            total(&entry)?
+          )?;
+        }
+      }
+      Entry::Show { id, space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let (hash, entry) = find_entry(&txn, &space, &id)?;
+        outln!("Entry: {}", hash.to_base32())?;
+        if let Some(id) = txn.get_internal(&hash)? {
+          let conflicts = txn.entry_conflicts(&space, &id)?;
+          if !conflicts.is_empty() {
+            outln!(
+              "Conflict: {} competing changes, see `azoni conflicts list`",
+              conflicts.len()
+            )?;
+          }
+        }
+        outln!("Date: {}", entry.date)?;
+        outln!("Payee: {}", entry.payee)?;
+        if !entry.memo.is_empty() {
+          outln!("Memo: {}", entry.memo)?;
+        }
+        if let Some(id) = txn.get_internal(&hash)? {
+          let mut labels = Vec::new();
+          for label in txn.entry_labels(&id)? {
+            if let Some(l) = txn.get_label(&label)? {
+              labels.push(l.name.as_str().to_string())
+            }
+          }
+          if !labels.is_empty() {
+            outln!("Labels: {}", labels.join(", "))?;
+          }
+        }
+        outln!("Postings:")?;
+        for p in entry.postings.iter() {
+          let name = match txn.get_compartment(&space, &p.compartment)? {
+            Some(c) => c.name.as_str().to_string(),
+            None => p.compartment.to_string(),
+          };
+          outln!("  {:<24} {:>20}", name, p.amount.to_string())?;
+        }
+      }
+      Entry::Edit(edit) => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, edit.space.as_deref())?;
+        let (hash, old) = find_entry(&txn, &space, &edit.id)?;
+        let mut entry = old.clone();
+        if let Some(date) = edit.date {
+          entry.date = date
+        }
+        if let Some(payee) = edit.payee {
+          entry.payee = payee
+        }
+        if let Some(memo) = edit.memo {
+          entry.memo = memo
+        }
+        if !edit.postings.is_empty() {
+          entry.postings = parse_postings(&txn, &space, &edit.postings)?
+        }
+        let op = Operation::EditEntry {
+          entry: hash,
+          old,
+          new: entry,
+        };
+        let (header, key) = signed_header()?;
+        let recorded = record(&repo.changes, &mut txn, &space, header, vec![op], &key)?;
+        txn.commit()?;
+        warn_closed(&recorded);
+        outln!("{}", hash.to_base32())?;
+      }
+      Entry::Rm { id, space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let (hash, old) = find_entry(&txn, &space, &id)?;
+        // Detach the labels first, so that unrecording gives them back.
+        let mut ops = Vec::new();
+        if let Some(id) = txn.get_internal(&hash)? {
+          for label in txn.entry_labels(&id)? {
+            for _ in 0..txn.attachments(&id, &label)? {
+              ops.push(Operation::DetachLabel { entry: hash, label })
+            }
+          }
+        }
+        ops.push(Operation::DelEntry { entry: hash, old });
+        let (header, key) = signed_header()?;
+        let recorded = record(&repo.changes, &mut txn, &space, header, ops, &key)?;
+        txn.commit()?;
+        warn_closed(&recorded);
+      }
+    }
+    Ok(())
+  }
+}
+
+fn parse_postings<T: CompartmentTxnT>(txn: &T, space: &SpaceRef<T>, postings: &[String]) -> Result<Vec<Posting>> {
+  postings
+    .iter()
+    .map(|p| {
+      let Some((name, amount)) = p.split_once('=') else {
+        bail!("Invalid posting {:?}, expected COMPARTMENT=AMOUNT", p)
+      };
+      let Some(compartment) = txn.compartment_by_name(space, name.trim())? else {
+        bail!("No such compartment: {}", name.trim())
+      };
+      Ok(Posting {
+        compartment: compartment.id,
+        amount: amount.parse()?,
+      })
+    })
+    .collect()
+}
+
+/// Find the entry of `space` whose id, the hash of the change that added
+/// it, starts with `prefix`.
+pub(super) fn find_entry<T: TxnT>(txn: &T, space: &SpaceRef<T>, prefix: &str) -> Result<(Hash, entry::Entry)> {
+  let mut found = None;
+  for id in txn.space_entries(space)? {
+    let Some(hash) = txn.get_external(&id)? else {
+      continue;
+    };
+    if hash.to_base32().starts_with(prefix) {
+      if found.is_some() {
+        bail!("Ambiguous entry id: {}", prefix)
+      }
+      found = Some((id, hash));
+    }
+  }
+  let Some((id, hash)) = found else {
+    bail!("No such entry: {}", prefix)
+  };
+  let entry = txn
+    .load_entry(&id)?
+    .ok_or_else(|| anyhow!("No such entry: {}", prefix))?;
+  Ok((hash, entry))
+}
+
+/// How much money `entry` moves.
+pub(super) fn total(entry: &entry::Entry) -> Result<String> {
+  Ok(amounts(&entry.moved()?))
+}
+
+pub(super) fn amounts(amounts: &[Money]) -> String {
+  amounts
+    .iter()
+    .map(|m| m.to_string())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
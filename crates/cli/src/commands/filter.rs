@@ -0,0 +1,59 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use azoni_core::{change::Operation, models::filter::FilterTxnT, record::record, traits::MutTxnT, types::UId};
+use clap::Subcommand;
+
+use super::load_space;
+use crate::{identity::signed_header, repository::Repository};
+
+#[derive(Subcommand, Debug)]
+pub enum Filter {
+  /// List the filters.
+  List,
+  /// Create a new, empty filter.
+  New {
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+}
+
+impl Filter {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Filter::List => {
+        let txn = repo.encyc.txn_begin()?;
+        for f in txn.list_filters()? {
+          let system = if f.is_system { " (system)" } else { "" };
+          if f.is_budget() {
+            outln!("{}{} budget {}", f.id, system, f.name.as_str())?;
+          } else {
+            outln!("{}{}", f.id, system)?;
+          }
+        }
+      }
+      Filter::New { space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let id = UId::new();
+        let (header, key) = signed_header()?;
+        record(
+          &repo.changes,
+          &mut txn,
+          &space,
+          header,
+          vec![Operation::AddFilter { id }],
+          &key,
+        )?;
+        txn.commit()?;
+        outln!("{}", id)?;
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use anyhow::{bail, Result};
+use azoni_core::{identity, key::SecretKey};
+use clap::Subcommand;
+
+use crate::identity::{
+  default_identity, list_identities, load_identity, save_identity, save_secret_key, selected_identity, set_default_identity, take_legacy_secret_key,
+};
+
+#[derive(Subcommand, Debug)]
+pub enum Identity {
+  /// Create an identity, with a new key pair. The first identity becomes
+  /// the default one.
+  New {
+    /// Local name of the identity, used to select it.
+    name: String,
+    /// Name recorded in changes. Defaults to the local name.
+    #[clap(long = "display-name")]
+    display_name: Option<String>,
+    #[clap(long = "email")]
+    email: Option<String>,
+  },
+  /// List the identities. The default one is marked with `*`.
+  List,
+  /// Show an identity, the selected one by default.
+  Show { name: Option<String> },
+  /// Set the identity changes are recorded with, or print it.
+  Default { name: Option<String> },
+}
+
+impl Identity {
+  pub fn run(self) -> Result<()> {
+    match self {
+      Identity::New {
+        name,
+        display_name,
+        email,
+      } => {
+        let existing = list_identities()?;
+        if existing.contains(&name) {
+          bail!("Identity {} already exists", name)
+        }
+        let key = if existing.is_empty() {
+          take_legacy_secret_key()?.unwrap_or_else(SecretKey::generate)
+        } else {
+          SecretKey::generate()
+        };
+        let display_name = display_name.unwrap_or_else(|| name.clone());
+        let id = identity::Identity::new(display_name, email, Some(key.public_key()));
+        save_identity(&name, &id)?;
+        save_secret_key(&name, &key)?;
+        if default_identity()?.is_none() {
+          set_default_identity(&name)?;
+        }
+        outln!("{}", id.id)?;
+      }
+      Identity::List => {
+        let default = default_identity()?;
+        for name in list_identities()? {
+          let id = load_identity(&name)?;
+          let mark = if default.as_deref() == Some(name.as_str()) {
+            "*"
+          } else {
+            " "
+          };
+          outln!("{} {:<16} {} {}", mark, name, id.id, id.to_author())?;
+        }
+      }
+      Identity::Show { name } => {
+        let Some(name) = selected_identity(name.as_deref())? else {
+          bail!("No identity, run `azoni identity new` to create one")
+        };
+        let id = load_identity(&name)?;
+        outln!("Identity: {}", name)?;
+        outln!("Id: {}", id.id)?;
+        outln!("Name: {}", id.display_name)?;
+        if let Some(ref email) = id.email {
+          outln!("Email: {}", email)?;
+        }
+        if let Some(ref key) = id.public_key {
+          outln!("Key: {}", key)?;
+        }
+        outln!("Created: {}", id.created)?;
+      }
+      Identity::Default { name: Some(name) } => {
+        load_identity(&name)?;
+        set_default_identity(&name)?;
+      }
+      Identity::Default { name: None } => match default_identity()? {
+        Some(name) => outln!("{}", name)?,
+        None => bail!("No default identity"),
+      },
+    }
+    Ok(())
+  }
+}
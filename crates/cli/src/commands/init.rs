@@ -0,0 +1,29 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use azoni_core::traits::MutTxnT;
+use clap::Parser;
+
+use crate::repository::Repository;
+
+#[derive(Parser, Debug)]
+pub struct Init {
+  /// Name of the first space.
+  #[clap(long = "space", default_value = "main")]
+  space: String,
+}
+
+impl Init {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::init(repo_path)?;
+    let mut txn = repo.encyc.mut_txn_begin()?;
+    txn.open_or_create_space(&self.space)?;
+    txn.set_current_space(&self.space)?;
+    txn.commit()?;
+    outln!("Initialized empty repository in {}", repo.path.display())?;
+    Ok(())
+  }
+}
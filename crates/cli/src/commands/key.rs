@@ -0,0 +1,69 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use anyhow::{bail, Result};
+use azoni_core::key::SecretKey;
+use clap::Subcommand;
+
+use crate::identity::{load_identity, load_secret_key, save_identity, save_secret_key, selected_identity};
+
+#[derive(Subcommand, Debug)]
+pub enum Key {
+  /// Generate the key pair an identity signs changes with.
+  Generate {
+    /// Use this identity instead of the selected one.
+    #[clap(long = "identity")]
+    identity: Option<String>,
+    /// Replace the existing key. Changes signed with it stay valid, and
+    /// still belong to the identity, whose id does not change.
+    #[clap(long = "force")]
+    force: bool,
+  },
+  /// Print the public key of an identity.
+  Show {
+    /// Use this identity instead of the selected one.
+    #[clap(long = "identity")]
+    identity: Option<String>,
+  },
+}
+
+fn identity_name(name: Option<&str>) -> Result<String> {
+  match selected_identity(name)? {
+    Some(name) => Ok(name),
+    None => bail!("No identity, run `azoni identity new` to create one"),
+  }
+}
+
+impl Key {
+  pub fn run(self) -> Result<()> {
+    match self {
+      Key::Generate { identity, force } => {
+        let name = identity_name(identity.as_deref())?;
+        let mut id = load_identity(&name)?;
+        if !force && load_secret_key(&name)?.is_some() {
+          bail!(
+            "Identity {} already has a key, use --force to replace it",
+            name
+          )
+        }
+        let key = SecretKey::generate();
+        let path = save_secret_key(&name, &key)?;
+        id.public_key = Some(key.public_key());
+        save_identity(&name, &id)?;
+        eprintln!("Secret key saved to {}", path.display());
+        outln!("{}", key.public_key())?;
+      }
+      Key::Show { identity } => {
+        let name = identity_name(identity.as_deref())?;
+        match load_identity(&name)?.public_key {
+          Some(key) => outln!("{}", key)?,
+          None => bail!(
+            "Identity {} has no key, run `azoni key generate` to create one",
+            name
+          ),
+        }
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,407 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use azoni_core::{
+  change::Operation,
+  models::{
+    entry::EntryTxnT,
+    label::{LabelGroup, LabelTxnT, SerializedLabel},
+    space::SpaceRef,
+    vault::VaultTxnT,
+  },
+  record::merge_labels,
+  traits::TxnT,
+  types::{Base32, UId},
+};
+use clap::Subcommand;
+
+use super::{
+  entry::{amounts, find_entry, total},
+  load_space, load_vault, record_ops,
+};
+use crate::repository::Repository;
+
+#[derive(Subcommand, Debug)]
+pub enum Label {
+  /// List the labels of a space, with the number of entries and the total
+  /// amount labelled by each label or one below it.
+  List {
+    /// Only list the labels filed in this vault.
+    #[clap(long = "vault")]
+    vault: Option<String>,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Create a new label.
+  New {
+    name: String,
+    /// One of income, expense, debt, loan or tag. Defaults to the group of
+    /// the parent, or tag.
+    #[clap(long = "group")]
+    group: Option<LabelGroup>,
+    /// Put the label under this one.
+    #[clap(long = "parent")]
+    parent: Option<String>,
+    /// File the label in this vault of the space.
+    #[clap(long = "vault")]
+    vault: Option<String>,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Show a label and the entries labelled by it or by a label below it.
+  Show {
+    name: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Rename a label. The entries it is attached to keep it.
+  Rename {
+    name: String,
+    new_name: String,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Delete a label, which must not be attached to any entry nor have
+  /// labels below it.
+  Rm {
+    name: String,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// File a label in another vault of the space.
+  Move {
+    name: String,
+    /// The vault to file the label in. Without it, the label is taken out
+    /// of its vault.
+    #[clap(long = "vault")]
+    vault: Option<String>,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Put a label, and the labels below it, under another one. They take
+  /// the group of their new root.
+  Reparent {
+    name: String,
+    /// The new parent. Without it, the label becomes the root of a tree.
+    #[clap(long = "parent")]
+    parent: Option<String>,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Attach labels to an entry.
+  Attach {
+    /// Id of the entry, or a prefix of it.
+    entry: String,
+    #[clap(required = true)]
+    labels: Vec<String>,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Detach labels from an entry.
+  Detach {
+    /// Id of the entry, or a prefix of it.
+    entry: String,
+    #[clap(required = true)]
+    labels: Vec<String>,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Merge a label into another one: its entries get the other label, the
+  /// labels below it move under the other label, and it is deleted, all in
+  /// one change.
+  Merge {
+    from: String,
+    into: String,
+    /// Record the change in this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+}
+
+impl Label {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Label::List { vault, space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let labels = match vault {
+          Some(vault) => txn.vault_labels(&load_vault(&txn, &space, &vault)?)?,
+          None => txn.space_labels(&space)?,
+        };
+        let mut lines = Vec::new();
+        for id in labels {
+          if let Some(label) = txn.get_label(&id)? {
+            let entries = txn
+              .tree_entries(&id)?
+              .into_iter()
+              .filter(|e| txn.has_entry(&space, e).unwrap_or(false))
+              .count();
+            lines.push((
+              txn.label_path(&id)?,
+              id,
+              label.group,
+              entries,
+              txn.label_total(&space, &id)?,
+            ));
+          }
+        }
+        lines.sort_by(|a, b| a.0.cmp(&b.0));
+        for (path, id, group, entries, total) in lines {
+          outln!(
+            "{} {:<8} {:<40} {:>6} {}",
+            id,
+            group.to_string(),
+            path,
+            entries,
+            amounts(&total)
+          )?;
+        }
+      }
+      Label::New {
+        name,
+        group,
+        parent,
+        vault,
+        space,
+      } => {
+        let id = UId::new();
+        let mut ops = Vec::new();
+        {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let parent = parent.map(|p| load_label(&txn, &s, &p)).transpose()?;
+          let group = match (group, parent) {
+            (Some(group), Some(p)) if group != p.group => {
+              bail!("Labels under {} are {} labels", p.name.as_str(), p.group)
+            }
+            (group, parent) => group.or(parent.map(|p| p.group)).unwrap_or(LabelGroup::TAG),
+          };
+          ops.push(Operation::AddLabel { id, name, group });
+          if let Some(p) = parent {
+            ops.push(Operation::SetLabelParent {
+              id,
+              old: None,
+              new: Some(p.id),
+              group,
+            })
+          }
+          if let Some(vault) = vault {
+            ops.push(Operation::MoveLabel {
+              id,
+              old: None,
+              new: Some(load_vault(&txn, &s, &vault)?),
+            })
+          }
+        }
+        record_ops(&repo, space.as_deref(), ops)?;
+        outln!("{}", id)?;
+      }
+      Label::Show { name, space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let l = load_label(&txn, &space, &name)?;
+        outln!("Label: {}", l.id)?;
+        outln!("Name: {}", l.name.as_str())?;
+        if l.parent().is_some() {
+          outln!("Path: {}", txn.label_path(&l.id)?)?;
+        }
+        outln!("Group: {}", l.group)?;
+        if let Some(vault) = l.vault().and_then(|v| txn.get_vault(&v).ok().flatten()) {
+          outln!("Vault: {}", vault.name.as_str())?;
+        }
+        let mut children = Vec::new();
+        for c in txn.label_children(&l.id)? {
+          if let Some(c) = txn.get_label(&c)? {
+            children.push(c.name.as_str().to_string())
+          }
+        }
+        if !children.is_empty() {
+          children.sort();
+          outln!("Sub-labels: {}", children.join(", "))?;
+        }
+        let mut entries = Vec::new();
+        for id in txn.tree_entries(&l.id)? {
+          if !txn.has_entry(&space, &id)? {
+            continue;
+          }
+          if let (Some(hash), Some(entry)) = (txn.get_external(&id)?, txn.load_entry(&id)?) {
+            entries.push((hash, entry));
+          }
+        }
+        entries.sort_by_key(|(_, e)| e.date);
+        outln!("Total: {}", amounts(&txn.label_total(&space, &l.id)?))?;
+        outln!("Entries: {}", entries.len())?;
+        for (hash, entry) in entries {
+          outln!(
+            "  {} {} {:<24} {}",
+            hash.to_base32(),
+            entry.date,
+            entry.payee,
+            total(&entry)?
+          )?;
+        }
+      }
+      Label::Rename {
+        name,
+        new_name,
+        space,
+      } => {
+        let op = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let l = load_label(&txn, &s, &name)?;
+          Operation::RenameLabel {
+            id: l.id,
+            old: name,
+            new: new_name,
+          }
+        };
+        record_ops(&repo, space.as_deref(), vec![op])?;
+      }
+      Label::Rm { name, space } => {
+        let ops = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let l = load_label(&txn, &s, &name)?;
+          if !txn.label_children(&l.id)?.is_empty() {
+            bail!(
+              "Label {} has labels below it, move or remove them first",
+              name
+            )
+          }
+          let mut ops = Vec::new();
+          if let Some(parent) = l.parent() {
+            ops.push(Operation::SetLabelParent {
+              id: l.id,
+              old: Some(parent),
+              new: None,
+              group: l.group,
+            })
+          }
+          ops.push(Operation::DelLabel {
+            id: l.id,
+            name,
+            group: l.group,
+            vault: l.vault(),
+          });
+          ops
+        };
+        record_ops(&repo, space.as_deref(), ops)?;
+      }
+      Label::Move { name, vault, space } => {
+        let op = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let l = load_label(&txn, &s, &name)?;
+          Operation::MoveLabel {
+            id: l.id,
+            old: l.vault(),
+            new: vault.map(|v| load_vault(&txn, &s, &v)).transpose()?,
+          }
+        };
+        record_ops(&repo, space.as_deref(), vec![op])?;
+      }
+      Label::Reparent {
+        name,
+        parent,
+        space,
+      } => {
+        let op = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let l = load_label(&txn, &s, &name)?;
+          Operation::SetLabelParent {
+            id: l.id,
+            old: l.parent(),
+            new: parent
+              .map(|p| load_label(&txn, &s, &p).map(|p| p.id))
+              .transpose()?,
+            group: l.group,
+          }
+        };
+        record_ops(&repo, space.as_deref(), vec![op])?;
+      }
+      Label::Attach {
+        entry,
+        labels,
+        space,
+      } => {
+        let ops = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let (entry, _) = find_entry(&txn, &s, &entry)?;
+          let id = txn
+            .get_internal(&entry)?
+            .ok_or_else(|| anyhow!("No such entry: {}", entry.to_base32()))?;
+          let mut ops = Vec::new();
+          for name in labels {
+            let label = load_label(&txn, &s, &name)?.id;
+            if txn.attachments(&id, &label)? > 0 {
+              bail!("Label {} is already attached to this entry", name)
+            }
+            ops.push(Operation::AttachLabel { entry, label })
+          }
+          ops
+        };
+        record_ops(&repo, space.as_deref(), ops)?;
+      }
+      Label::Detach {
+        entry,
+        labels,
+        space,
+      } => {
+        let ops = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let (entry, _) = find_entry(&txn, &s, &entry)?;
+          let id = txn
+            .get_internal(&entry)?
+            .ok_or_else(|| anyhow!("No such entry: {}", entry.to_base32()))?;
+          let mut ops = Vec::new();
+          for name in labels {
+            let label = load_label(&txn, &s, &name)?.id;
+            let n = txn.attachments(&id, &label)?;
+            if n == 0 {
+              bail!("Label {} is not attached to this entry", name)
+            }
+            ops.extend((0..n).map(|_| Operation::DetachLabel { entry, label }))
+          }
+          ops
+        };
+        record_ops(&repo, space.as_deref(), ops)?;
+      }
+      Label::Merge { from, into, space } => {
+        let ops = {
+          let txn = repo.encyc.txn_begin()?;
+          let s = load_space(&txn, space.as_deref())?;
+          let (from, into) = (load_label(&txn, &s, &from)?, load_label(&txn, &s, &into)?);
+          if from.id == into.id || txn.label_descendants(&from.id)?.contains(&into.id) {
+            bail!("Cannot merge a label into itself or a label below it")
+          }
+          merge_labels(&txn, &s, &from.id, &into.id)?
+        };
+        record_ops(&repo, space.as_deref(), ops)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+pub(super) fn load_label<'a, T: TxnT>(txn: &'a T, space: &SpaceRef<T>, name: &str) -> Result<&'a SerializedLabel> {
+  txn
+    .label_by_name(space, name)?
+    .ok_or_else(|| anyhow!("No such label: {}", name))
+}
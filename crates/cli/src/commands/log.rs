@@ -0,0 +1,42 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use azoni_core::{models::space::SpaceTxnT, traits::TxnT, types::Base32};
+use clap::Parser;
+
+use super::load_space;
+use crate::repository::Repository;
+
+#[derive(Parser, Debug)]
+pub struct Log {
+  /// Use this space instead of the current one.
+  #[clap(long = "space")]
+  space: Option<String>,
+  /// Only print the current state of the space. Two spaces with the same
+  /// state have the same changes.
+  #[clap(long = "state")]
+  state: bool,
+}
+
+impl Log {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    let txn = repo.encyc.txn_begin()?;
+    let space = load_space(&txn, self.space.as_deref())?;
+    if self.state {
+      outln!("{}", txn.current_state(&space)?.to_base32())?;
+      return Ok(());
+    }
+    for (pos, id, state) in txn.log(&space, 0)? {
+      let hash = match txn.get_external(&id)? {
+        Some(hash) => hash.to_base32(),
+        None => id.to_base32(),
+      };
+      outln!("{:>6} {} {}", pos, hash, state.to_base32())?;
+    }
+    Ok(())
+  }
+}
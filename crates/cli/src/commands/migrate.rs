@@ -0,0 +1,46 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use azoni_core::pristine::MigrateOptions;
+use clap::Parser;
+
+use crate::repository::Repository;
+
+#[derive(Parser, Debug)]
+pub struct Migrate {
+  /// Run the migrations without saving the result.
+  #[clap(long = "dry-run")]
+  dry_run: bool,
+  /// Do not back up the pristine before migrating it.
+  #[clap(long = "no-backup")]
+  no_backup: bool,
+}
+
+impl Migrate {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    let report = repo.encyc.migrate(&MigrateOptions {
+      dry_run: self.dry_run,
+      no_backup: self.no_backup,
+    })?;
+    if report.is_up_to_date() {
+      outln!("Pristine is up to date (version {})", report.to)?;
+      return Ok(());
+    }
+    for description in report.applied.iter() {
+      outln!("Applied: {}", description)?;
+    }
+    if let Some(ref backup) = report.backup {
+      outln!("Backup saved to {}", backup.display())?;
+    }
+    if report.dry_run {
+      outln!("Dry run, version {} left unchanged", report.from)?;
+    } else {
+      outln!("Migrated from version {} to {}", report.from, report.to)?;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use anyhow::{anyhow, bail, Result};
+use azoni_core::{
+  change::Operation,
+  models::space::{SpaceRef, Tag as SpaceTag},
+  record::{record, Recorded},
+  traits::{MutTxnT, TxnT},
+  types::UId,
+};
+
+mod budget;
+pub use budget::Budget;
+mod change;
+pub use change::Change;
+mod check;
+pub use check::Check;
+mod compartment;
+pub use compartment::Compartment;
+mod conflicts;
+pub use conflicts::Conflicts;
+mod device;
+pub use device::Device;
+mod entry;
+pub use entry::Entry;
+mod filter;
+pub use filter::Filter;
+mod identity;
+pub use identity::Identity;
+mod init;
+pub use init::Init;
+mod key;
+pub use key::Key;
+mod label;
+pub use label::Label;
+mod log;
+pub use log::Log;
+mod migrate;
+pub use migrate::Migrate;
+mod protocol;
+pub use protocol::Protocol;
+mod pushpull;
+pub use pushpull::{Pull, Push};
+mod reset;
+pub use reset::Reset;
+mod serve;
+pub use serve::Serve;
+mod space;
+pub use space::Space;
+mod tag;
+pub use tag::Tag;
+mod unrecord;
+pub use unrecord::Unrecord;
+mod vault;
+pub use vault::Vault;
+mod verify;
+pub use verify::Verify;
+
+use crate::{identity::signed_header, repository::Repository};
+
+/// Load space `name`, or the current space if `name` is `None`.
+fn load_space<T: TxnT>(txn: &T, name: Option<&str>) -> Result<SpaceRef<T>> {
+  let Some(name) = name.or(txn.current_space()) else {
+    bail!("No current space, use `azoni space switch` or `--space`")
+  };
+  txn
+    .load_space(name)?
+    .ok_or_else(|| anyhow!("No such space: {}", name))
+}
+
+fn load_tag<T: TxnT>(txn: &T, space: &SpaceRef<T>, name: &str) -> Result<SpaceTag> {
+  txn
+    .get_tag(space, name)?
+    .ok_or_else(|| anyhow!("No such tag: {}", name))
+}
+
+/// Find vault `name` among the vaults of `space`.
+fn load_vault<T: TxnT>(txn: &T, space: &SpaceRef<T>, name: &str) -> Result<UId> {
+  for id in txn.space_vaults(space)? {
+    if txn.get_vault(&id)?.is_some_and(|v| v.name.as_str() == name) {
+      return Ok(id);
+    }
+  }
+  bail!("No such vault: {}", name)
+}
+
+/// Record `operations` as one change of space `space`, or of the current
+/// space.
+fn record_ops(repo: &Repository, space: Option<&str>, operations: Vec<Operation>) -> Result<Recorded> {
+  let mut txn = repo.encyc.mut_txn_begin()?;
+  let space = load_space(&txn, space)?;
+  let (header, key) = signed_header()?;
+  let recorded = record(&repo.changes, &mut txn, &space, header, operations, &key)?;
+  txn.commit()?;
+  Ok(recorded)
+}
+
+fn warn_closed(recorded: &Recorded) {
+  if let Some(ref closed) = recorded.closed {
+    eprintln!("Warning: this change alters {}", closed)
+  }
+}
@@ -0,0 +1,22 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use azoni_core::protocol::serve;
+use clap::Parser;
+
+use crate::repository::Repository;
+
+#[derive(Parser, Debug)]
+pub struct Protocol {}
+
+impl Protocol {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    let (stdin, stdout) = (std::io::stdin(), std::io::stdout());
+    serve(&repo.encyc, &repo.changes, stdin.lock(), stdout.lock())?;
+    Ok(())
+  }
+}
@@ -0,0 +1,226 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::{
+  changestore::ChangeStore,
+  history::ClosedPeriod,
+  sync::{missing, space_log, transfer},
+  traits::{MutTxnT, TxnT},
+  types::{Base32, Hash},
+};
+use clap::Parser;
+
+use crate::{
+  remote::{Remote, SshClient},
+  repository::Repository,
+};
+
+#[derive(Parser, Debug)]
+pub struct Push {
+  /// Path of the other repository, or of its `.azoni` directory, or
+  /// `ssh://[user@]host[:port]/path`.
+  to: String,
+  /// Push this space instead of the current one.
+  #[clap(long = "space")]
+  space: Option<String>,
+  /// Space to push to, created if needed. Defaults to the name of the
+  /// local space.
+  #[clap(long = "to-space")]
+  to_space: Option<String>,
+  /// Only print the changes that would be pushed.
+  #[clap(long = "dry-run")]
+  dry_run: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct Pull {
+  /// Path of the other repository, or of its `.azoni` directory, or
+  /// `ssh://[user@]host[:port]/path`.
+  from: String,
+  /// Pull into this space, created if needed, instead of the current one.
+  #[clap(long = "space")]
+  space: Option<String>,
+  /// Space to pull from. Defaults to the name of the local space.
+  #[clap(long = "from-space")]
+  from_space: Option<String>,
+  /// Only print the changes that would be pulled.
+  #[clap(long = "dry-run")]
+  dry_run: bool,
+}
+
+/// Open both repositories: ours, and the one at `other`.
+fn open_both(repo_path: Option<PathBuf>, other: &str) -> Result<(Repository, Remote)> {
+  let local = Repository::find(repo_path)?;
+  let remote = Remote::open(other)?;
+  if let Remote::Local(ref remote) = remote {
+    if local.path.canonicalize()? == remote.path.canonicalize()? {
+      bail!("Cannot synchronize a repository with itself")
+    }
+  }
+  Ok((local, remote))
+}
+
+/// Name of the local space: `name`, or the current one.
+fn space_name<T: TxnT>(txn: &T, name: Option<String>) -> Result<String> {
+  match name.or_else(|| txn.current_space().map(String::from)) {
+    Some(name) => Ok(name),
+    None => bail!("No current space, use `azoni space switch` or `--space`"),
+  }
+}
+
+fn report(hashes: &[Hash], verb: &str, dry_run: bool) -> Result<()> {
+  for hash in hashes.iter() {
+    outln!("{}", hash.to_base32())?;
+  }
+  match (hashes.len(), dry_run) {
+    (0, _) => eprintln!("Nothing to {}", verb),
+    (n, true) => eprintln!("Would {} {} changes", verb, n),
+    (n, false) => eprintln!("{} {} changes", past_tense(verb), n),
+  }
+  Ok(())
+}
+
+/// "push" -> "Pushed".
+fn past_tense(verb: &str) -> String {
+  let mut past = format!("{}ed", verb);
+  past[..1].make_ascii_uppercase();
+  past
+}
+
+impl Push {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let (local, remote) = open_both(repo_path, &self.to)?;
+    let txn = local.encyc.txn_begin()?;
+    let name = space_name(&txn, self.space)?;
+    let Some(space) = txn.load_space(&name)? else {
+      bail!("No such space: {}", name)
+    };
+    let ours = space_log(&txn, &space)?;
+    let to_space = self.to_space.as_deref().unwrap_or(&name);
+
+    let hashes = match remote {
+      Remote::Local(remote) => {
+        let mut rtxn = remote.encyc.mut_txn_begin()?;
+        let rspace = rtxn.open_or_create_space(to_space)?;
+        let hashes = missing(&space_log(&rtxn, &rspace)?, &ours);
+        if !self.dry_run && !hashes.is_empty() {
+          warn_closed(transfer(
+            &local.changes,
+            &remote.changes,
+            &mut rtxn,
+            &rspace,
+            &hashes,
+          )?);
+          rtxn.commit()?;
+        }
+        hashes
+      }
+      Remote::Ssh(ssh) => {
+        let hashes = missing(&ssh.client.log(to_space, 0)?, &ours);
+        if !self.dry_run {
+          push_ssh(&local, &ssh.client, to_space, &hashes)?;
+        }
+        ssh.close()?;
+        hashes
+      }
+      Remote::Http(client) => {
+        let hashes = missing(&client.log(to_space, None)?, &ours);
+        if !self.dry_run {
+          for hash in hashes.iter() {
+            client.push(to_space, &local.changes.get_change(hash)?)?;
+          }
+        }
+        hashes
+      }
+    };
+    report(&hashes, "push", self.dry_run)
+  }
+}
+
+fn warn_closed(closed: Option<ClosedPeriod>) {
+  if let Some(closed) = closed {
+    eprintln!("Warning: these changes alter {}", closed)
+  }
+}
+
+/// Send and apply `hashes` one at a time, each committed by the remote on
+/// its own, so that an interrupted push resumes where it stopped.
+fn push_ssh(local: &Repository, client: &SshClient, space: &str, hashes: &[Hash]) -> Result<()> {
+  for hash in hashes.iter() {
+    if !client.has_change(hash) {
+      client.save_change(&local.changes.get_change(hash)?)?;
+    }
+    client.apply(space, hash)?;
+  }
+  Ok(())
+}
+
+impl Pull {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let (local, remote) = open_both(repo_path, &self.from)?;
+    let mut txn = local.encyc.mut_txn_begin()?;
+    let name = space_name(&txn, self.space)?;
+    let from_space = self.from_space.as_deref().unwrap_or(&name);
+
+    let hashes = match remote {
+      Remote::Local(remote) => {
+        let rtxn = remote.encyc.txn_begin()?;
+        let Some(rspace) = rtxn.load_space(from_space)? else {
+          bail!("No such space in {}: {}", remote.path.display(), from_space)
+        };
+        let space = txn.open_or_create_space(&name)?;
+        let hashes = missing(&space_log(&txn, &space)?, &space_log(&rtxn, &rspace)?);
+        if !self.dry_run && !hashes.is_empty() {
+          warn_closed(transfer(
+            &remote.changes,
+            &local.changes,
+            &mut txn,
+            &space,
+            &hashes,
+          )?);
+          txn.commit()?;
+        }
+        hashes
+      }
+      Remote::Ssh(ssh) => {
+        let theirs = ssh.client.log(from_space, 0)?;
+        let space = txn.open_or_create_space(&name)?;
+        let hashes = missing(&space_log(&txn, &space)?, &theirs);
+        if !self.dry_run && !hashes.is_empty() {
+          warn_closed(transfer(
+            &ssh.client,
+            &local.changes,
+            &mut txn,
+            &space,
+            &hashes,
+          )?);
+          txn.commit()?;
+        }
+        ssh.close()?;
+        hashes
+      }
+      Remote::Http(client) => {
+        let space = txn.open_or_create_space(&name)?;
+        let ours = space_log(&txn, &space)?;
+        // Only what came after our last state, if the remote knows it.
+        let theirs = client.log(from_space, ours.last().map(|e| &e.state))?;
+        let hashes = missing(&ours, &theirs);
+        if !self.dry_run && !hashes.is_empty() {
+          warn_closed(transfer(
+            &client,
+            &local.changes,
+            &mut txn,
+            &space,
+            &hashes,
+          )?);
+          txn.commit()?;
+        }
+        hashes
+      }
+    };
+    report(&hashes, "pull", self.dry_run)
+  }
+}
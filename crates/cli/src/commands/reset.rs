@@ -0,0 +1,60 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::{
+  models::space::SpaceTxnT,
+  traits::MutTxnT,
+  types::{Base32, Merkle},
+  unrecord::reset_to_state,
+};
+use clap::Parser;
+
+use super::load_space;
+use crate::repository::Repository;
+
+#[derive(Parser, Debug)]
+pub struct Reset {
+  /// Unrecord every change applied after this state, as printed by
+  /// `azoni log`. A prefix is enough.
+  #[clap(long = "to-state", value_name = "MERKLE")]
+  to_state: String,
+  /// Use this space instead of the current one.
+  #[clap(long = "space")]
+  space: Option<String>,
+}
+
+impl Reset {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    let mut txn = repo.encyc.mut_txn_begin()?;
+    let space = load_space(&txn, self.space.as_deref())?;
+
+    let mut states = vec![Merkle::zero()];
+    states.extend(txn.log(&space, 0)?.into_iter().map(|(_, _, m)| m));
+    let matching: Vec<_> = states
+      .into_iter()
+      .filter(|m| m.to_base32().starts_with(&self.to_state))
+      .collect();
+    let state = match matching[..] {
+      [state] => state,
+      [] => bail!("No such state in this space: {}", self.to_state),
+      _ => bail!("Ambiguous state: {}", self.to_state),
+    };
+
+    let unrecorded = reset_to_state(&repo.changes, &mut txn, &space, &state)?;
+    for hash in unrecorded.hashes.iter() {
+      outln!("Unrecorded {}", hash.to_base32())?;
+    }
+    if let Some(closed) = unrecorded.closed {
+      eprintln!("Warning: this reset alters {}", closed)
+    }
+    for tag in unrecorded.tags.iter() {
+      eprintln!("Deleted tag {}, past the new state", tag)
+    }
+    txn.commit()?;
+    Ok(())
+  }
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use azoni_core::{http::HttpServer, key::PublicKey};
+use clap::Parser;
+
+use crate::{
+  identity::{list_identities, load_identity},
+  repository::Repository,
+};
+
+#[derive(Parser, Debug)]
+pub struct Serve {
+  /// Address to listen on. Use `0.0.0.0:8490` to accept other machines.
+  #[clap(long = "bind", default_value = "127.0.0.1:8490")]
+  bind: String,
+  /// Accept the changes signed by this public key. Defaults to the keys of
+  /// the local identities.
+  #[clap(long = "authorize")]
+  authorize: Vec<PublicKey>,
+}
+
+impl Serve {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    let mut keys = self.authorize;
+    if keys.is_empty() {
+      for name in list_identities()? {
+        keys.extend(load_identity(&name)?.public_key)
+      }
+    }
+    if keys.is_empty() {
+      eprintln!("No authorized key, pushes will be refused")
+    }
+    let server = HttpServer::bind(&self.bind)?.with_authorized_keys(keys);
+    eprintln!(
+      "Serving {} on http://{}",
+      repo.path.display(),
+      server.addr()
+    );
+    server.serve(&repo.encyc, &repo.changes)?;
+    Ok(())
+  }
+}
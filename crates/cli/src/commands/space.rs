@@ -0,0 +1,66 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::traits::{MutTxnT, TxnT};
+use clap::Subcommand;
+
+use crate::repository::Repository;
+
+#[derive(Subcommand, Debug)]
+pub enum Space {
+  /// List the spaces, marking the current one with `*`.
+  List,
+  /// Create a new space.
+  New { name: String },
+  /// Make `name` the current space.
+  Switch { name: String },
+  /// Delete a space and forget about its entries.
+  Delete { name: String },
+}
+
+impl Space {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Space::List => {
+        let txn = repo.encyc.txn_begin()?;
+        let current = txn.current_space();
+        for name in txn.space_names()? {
+          let mark = if Some(name.as_str()) == current {
+            '*'
+          } else {
+            ' '
+          };
+          outln!("{} {}", mark, name)?;
+        }
+      }
+      Space::New { name } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        if txn.load_space(&name)?.is_some() {
+          bail!("Space {} already exists", name)
+        }
+        txn.open_or_create_space(&name)?;
+        txn.commit()?;
+      }
+      Space::Switch { name } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        if txn.load_space(&name)?.is_none() {
+          bail!("No such space: {}", name)
+        }
+        txn.set_current_space(&name)?;
+        txn.commit()?;
+      }
+      Space::Delete { name } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        if !txn.drop_space(&name)? {
+          bail!("No such space: {}", name)
+        }
+        txn.commit()?;
+      }
+    }
+    Ok(())
+  }
+}
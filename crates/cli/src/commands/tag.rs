@@ -0,0 +1,115 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::{
+  models::space::{SpaceMutTxnT, SpaceTxnT, Tag as SpaceTag, TagPolicy},
+  traits::MutTxnT,
+  types::Base32,
+};
+use chrono::{NaiveDate, Utc};
+use clap::Subcommand;
+
+use super::load_space;
+use crate::repository::Repository;
+
+#[derive(Subcommand, Debug)]
+pub enum Tag {
+  /// Tag the current state of a space, closing its books up to a date.
+  Create {
+    name: String,
+    /// Last day of the closed period, defaults to today.
+    #[clap(long = "date")]
+    date: Option<NaiveDate>,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// List the tags of a space.
+  List {
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Delete a tag, reopening its period.
+  Delete {
+    name: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Show or set what happens when recording a change to a closed period:
+  /// `warn` or `reject`.
+  Policy {
+    policy: Option<TagPolicy>,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+}
+
+impl Tag {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Tag::Create { name, date, space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let log = txn.log(&space, 0)?;
+        let position = log.last().map(|(pos, _, _)| pos + 1).unwrap_or(0);
+        let tag = SpaceTag {
+          name,
+          state: txn.current_state(&space)?,
+          position,
+          date: date.unwrap_or_else(|| chrono::Local::now().date_naive()),
+          created: Utc::now(),
+        };
+        txn.put_tag(&space, &tag)?;
+        txn.commit()?;
+        outln!("{}", tag.state.to_base32())?;
+      }
+      Tag::List { space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        for tag in txn.space_tags(&space)? {
+          // The tagged state is gone if a change before the tag was
+          // unrecorded since.
+          let stale = match txn.state_position(&space, &tag.state)? {
+            Some(pos) if pos + 1 == tag.position => "",
+            None if tag.position == 0 => "",
+            _ => " (stale)",
+          };
+          outln!(
+            "{:<24} {} {}{}",
+            tag.name,
+            tag.date,
+            tag.state.to_base32(),
+            stale
+          )?;
+        }
+      }
+      Tag::Delete { name, space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        if !txn.del_tag(&space, &name)? {
+          bail!("No such tag: {}", name)
+        }
+        txn.commit()?;
+      }
+      Tag::Policy { policy, space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        match policy {
+          Some(policy) => {
+            txn.set_tag_policy(&space, policy);
+            txn.commit()?;
+          }
+          None => outln!("{}", txn.tag_policy(&space))?,
+        }
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,70 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::{
+  models::space::{SpaceRef, SpaceTxnT},
+  traits::{MutTxnT, TxnT},
+  types::{Base32, Hash},
+  unrecord::unrecord,
+};
+use clap::Parser;
+
+use super::load_space;
+use crate::repository::Repository;
+
+#[derive(Parser, Debug)]
+pub struct Unrecord {
+  /// Hash of the change to remove, or a prefix of it.
+  hash: String,
+  /// Remove the change even if later changes depend on it.
+  #[clap(long = "force")]
+  force: bool,
+  /// Use this space instead of the current one.
+  #[clap(long = "space")]
+  space: Option<String>,
+}
+
+impl Unrecord {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    let mut txn = repo.encyc.mut_txn_begin()?;
+    let space = load_space(&txn, self.space.as_deref())?;
+    let hash = find_space_change(&txn, &space, &self.hash)?;
+    let tags = txn.space_tags(&space)?;
+    if let Some(closed) = unrecord(&repo.changes, &mut txn, &space, &hash, self.force)? {
+      eprintln!("Warning: unrecording this change alters {}", closed)
+    }
+    for tag in tags {
+      if txn.get_tag(&space, &tag.name)?.is_none() {
+        eprintln!("Deleted tag {}, past the new state", tag.name)
+      }
+    }
+    let state = txn.current_state(&space)?;
+    txn.commit()?;
+    outln!("{}", state.to_base32())?;
+    Ok(())
+  }
+}
+
+/// Find the change of `space` whose hash starts with `prefix`.
+fn find_space_change<T: TxnT>(txn: &T, space: &SpaceRef<T>, prefix: &str) -> Result<Hash> {
+  let mut found = None;
+  for (_, id, _) in txn.log(space, 0)? {
+    let Some(hash) = txn.get_external(&id)? else {
+      continue;
+    };
+    if hash.to_base32().starts_with(prefix) {
+      if found.is_some() {
+        bail!("Ambiguous change hash: {}", prefix)
+      }
+      found = Some(hash)
+    }
+  }
+  match found {
+    Some(hash) => Ok(hash),
+    None => bail!("No such change in this space: {}", prefix),
+  }
+}
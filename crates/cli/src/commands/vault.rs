@@ -0,0 +1,56 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use azoni_core::{change::Operation, models::vault::VaultTxnT, record::record, traits::MutTxnT, types::UId};
+use clap::Subcommand;
+
+use super::load_space;
+use crate::{identity::signed_header, repository::Repository};
+
+#[derive(Subcommand, Debug)]
+pub enum Vault {
+  /// List the vaults of a space.
+  List {
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+  /// Create a new vault.
+  New {
+    name: String,
+    /// Use this space instead of the current one.
+    #[clap(long = "space")]
+    space: Option<String>,
+  },
+}
+
+impl Vault {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    match self {
+      Vault::List { space } => {
+        let txn = repo.encyc.txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        for id in txn.space_vaults(&space)? {
+          if let Some(vault) = txn.get_vault(&id)? {
+            outln!("{} {}", id, vault.name.as_str())?;
+          }
+        }
+      }
+      Vault::New { name, space } => {
+        let mut txn = repo.encyc.mut_txn_begin()?;
+        let space = load_space(&txn, space.as_deref())?;
+        let id = UId::new();
+        let op = Operation::AddVault { id, name };
+        let (header, key) = signed_header()?;
+        record(&repo.changes, &mut txn, &space, header, vec![op], &key)?;
+        txn.commit()?;
+        outln!("{}", id)?;
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,62 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use azoni_core::{change::SignatureError, changestore::ChangeStore, models::space::SpaceTxnT, traits::TxnT, types::Base32};
+use clap::Parser;
+
+use super::load_space;
+use crate::repository::Repository;
+
+#[derive(Parser, Debug)]
+pub struct Verify {
+  /// Use this space instead of the current one.
+  #[clap(long = "space")]
+  space: Option<String>,
+}
+
+impl Verify {
+  pub fn run(self, repo_path: Option<PathBuf>) -> Result<()> {
+    let repo = Repository::find(repo_path)?;
+    let txn = repo.encyc.txn_begin()?;
+    let space = load_space(&txn, self.space.as_deref())?;
+    let (mut signed, mut unsigned, mut invalid) = (0, 0, 0);
+    for (_, id, _) in txn.log(&space, 0)? {
+      let Some(hash) = txn.get_external(&id)? else {
+        continue;
+      };
+      // Entries recorded before the change store existed have no change
+      // file, hence no signature.
+      if !repo.changes.has_change(&hash) {
+        unsigned += 1;
+        outln!("{} {}", hash.to_base32(), SignatureError::Unsigned)?;
+        continue;
+      }
+      let change = repo.changes.get_change(&hash)?;
+      match change.check_signatures(&hash) {
+        Ok(keys) => {
+          signed += 1;
+          let keys: Vec<_> = keys.iter().map(|k| k.to_string()).collect();
+          outln!("{} signed by {}", hash.to_base32(), keys.join(", "))?;
+        }
+        Err(e) => {
+          match e {
+            SignatureError::Unsigned => unsigned += 1,
+            _ => invalid += 1,
+          }
+          outln!("{} {}", hash.to_base32(), e)?;
+        }
+      }
+    }
+    eprintln!("{} signed, {} unsigned", signed, unsigned);
+    if invalid + unsigned > 0 {
+      bail!(
+        "{} changes have invalid or missing signatures",
+        invalid + unsigned
+      )
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,21 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use azoni_core::models::Device;
+use chrono::Utc;
+
+/// The device we are running on, as stamped in the header of new changes.
+pub fn current_device() -> Device {
+  let system = azoni_x::device::current();
+  let now = Utc::now();
+  Device {
+    name: system.hostname,
+    alias: None,
+    created_at: now,
+    last_access: now,
+    raw: None,
+    platform: Some(system.platform),
+    os: Some(system.os),
+    os_version: system.os_version,
+  }
+}
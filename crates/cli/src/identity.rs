@@ -0,0 +1,175 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use azoni_core::{identity::Identity, key::SecretKey, models::ChangeHeader, types::Base32};
+use azoni_x::path::config_dir;
+use serde::{Deserialize, Serialize};
+
+use crate::device::current_device;
+
+/// Environment variable selecting the identity to record changes with,
+/// instead of the default one.
+pub const IDENTITY_VAR: &str = "AZONI_IDENTITY";
+
+/// User-level settings, in the configuration directory.
+pub const CONFIG_FILE: &str = "config.toml";
+/// Directory of the identities, one sub-directory each.
+pub const IDENTITIES_DIR: &str = "identities";
+pub const IDENTITY_FILE: &str = "identity.toml";
+pub const SECRET_KEY_FILE: &str = "secretkey";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Config {
+  default_identity: Option<String>,
+}
+
+fn load_config() -> Result<Config> {
+  let path = config_dir()?.join(CONFIG_FILE);
+  match fs::read_to_string(&path) {
+    Ok(s) => Ok(toml::from_str(&s).map_err(|e| anyhow!("Invalid configuration in {}: {}", path.display(), e))?),
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+    Err(e) => Err(e.into()),
+  }
+}
+
+fn save_config(config: &Config) -> Result<()> {
+  let dir = config_dir()?;
+  fs::create_dir_all(&dir)?;
+  fs::write(dir.join(CONFIG_FILE), toml::to_string(config)?)?;
+  Ok(())
+}
+
+fn identity_dir(name: &str) -> Result<PathBuf> {
+  if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+    bail!("Invalid identity name: {:?}", name)
+  }
+  Ok(config_dir()?.join(IDENTITIES_DIR).join(name))
+}
+
+/// Names of all identities, sorted.
+pub fn list_identities() -> Result<Vec<String>> {
+  let dir = config_dir()?.join(IDENTITIES_DIR);
+  let mut names = Vec::new();
+  let entries = match fs::read_dir(&dir) {
+    Ok(entries) => entries,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(names),
+    Err(e) => return Err(e.into()),
+  };
+  for entry in entries {
+    let entry = entry?;
+    if entry.path().join(IDENTITY_FILE).is_file() {
+      names.extend(entry.file_name().to_str().map(String::from))
+    }
+  }
+  names.sort();
+  Ok(names)
+}
+
+pub fn load_identity(name: &str) -> Result<Identity> {
+  let path = identity_dir(name)?.join(IDENTITY_FILE);
+  let s = match fs::read_to_string(&path) {
+    Ok(s) => s,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => bail!("No such identity: {}", name),
+    Err(e) => return Err(e.into()),
+  };
+  toml::from_str(&s).map_err(|e| anyhow!("Invalid identity in {}: {}", path.display(), e))
+}
+
+pub fn save_identity(name: &str, identity: &Identity) -> Result<()> {
+  let dir = identity_dir(name)?;
+  fs::create_dir_all(&dir)?;
+  fs::write(dir.join(IDENTITY_FILE), toml::to_string(identity)?)?;
+  Ok(())
+}
+
+/// The secret key of identity `name`, if it has one.
+pub fn load_secret_key(name: &str) -> Result<Option<SecretKey>> {
+  let path = identity_dir(name)?.join(SECRET_KEY_FILE);
+  let s = match fs::read_to_string(&path) {
+    Ok(s) => s,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+    Err(e) => return Err(e.into()),
+  };
+  match SecretKey::from_base32(s.trim().as_bytes()) {
+    Some(key) => Ok(Some(key)),
+    None => bail!("Invalid secret key in {}", path.display()),
+  }
+}
+
+pub fn save_secret_key(name: &str, key: &SecretKey) -> Result<PathBuf> {
+  let dir = identity_dir(name)?;
+  fs::create_dir_all(&dir)?;
+  let path = dir.join(SECRET_KEY_FILE);
+  fs::write(&path, key.to_base32())?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+  }
+  Ok(path)
+}
+
+/// The key generated by `azoni key generate` before identities existed,
+/// taken over by the first identity.
+pub fn take_legacy_secret_key() -> Result<Option<SecretKey>> {
+  let path = config_dir()?.join(SECRET_KEY_FILE);
+  let Ok(s) = fs::read_to_string(&path) else {
+    return Ok(None);
+  };
+  let key = SecretKey::from_base32(s.trim().as_bytes());
+  if key.is_some() {
+    fs::remove_file(&path)?;
+  }
+  Ok(key)
+}
+
+pub fn default_identity() -> Result<Option<String>> {
+  Ok(load_config()?.default_identity)
+}
+
+pub fn set_default_identity(name: &str) -> Result<()> {
+  let mut config = load_config()?;
+  config.default_identity = Some(name.to_string());
+  save_config(&config)
+}
+
+/// The identity `name`, or the one `AZONI_IDENTITY` names, or the
+/// default one.
+pub fn selected_identity(name: Option<&str>) -> Result<Option<String>> {
+  if let Some(name) = name {
+    return Ok(Some(name.to_string()));
+  }
+  if let Ok(name) = std::env::var(IDENTITY_VAR) {
+    return Ok(Some(name));
+  }
+  default_identity()
+}
+
+/// The header of a new change, stamped with the current device, and the
+/// key to sign it with. Every change is signed, so recording needs an
+/// identity with a key.
+pub fn signed_header() -> Result<(ChangeHeader, SecretKey)> {
+  let mut header = ChangeHeader::default();
+  header.devices.push(current_device());
+  let Some(name) = selected_identity(None)? else {
+    bail!("No identity to sign changes with, run `azoni identity new` to create one")
+  };
+  let identity = load_identity(&name)?;
+  let Some(key) = load_secret_key(&name)? else {
+    bail!(
+      "Identity {} has no key, run `azoni key generate` to create one",
+      name
+    )
+  };
+  if identity.public_key != Some(key.public_key()) {
+    bail!(
+      "The secret key of identity {} does not match its public key",
+      name
+    )
+  }
+  header.authors.push(identity.to_author());
+  Ok((header, key))
+}
@@ -1,25 +1,171 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 10.
 
-use std::fs;
+/// Like `print!`, but returns the error instead of panicking, e.g. when
+/// the output is piped into `head`.
+macro_rules! out {
+  ($($arg:tt)*) => {
+    std::io::Write::write_fmt(&mut std::io::stdout().lock(), format_args!($($arg)*))
+  };
+}
+
+/// Like `println!`, but returns the error instead of panicking.
+macro_rules! outln {
+  () => {
+    out!("\n")
+  };
+  ($($arg:tt)*) => {
+    out!("{}\n", format_args!($($arg)*))
+  };
+}
+
+mod commands;
+mod device;
+mod identity;
+mod remote;
+mod repository;
+
+use std::path::PathBuf;
 
 use anyhow::Result;
-use azoni_core::pristine;
-use azoni_x::path::current_dir;
+use clap::{Parser, Subcommand};
 
-const DOT_DIR: &str = ".azoni";
+use commands::*;
 
-fn main() -> Result<()> {
-  let cur = current_dir().unwrap();
-  let azoni_dir = cur.join(DOT_DIR);
+/// Exit code of commands that failed. Usage errors are reported by clap,
+/// with exit code 2.
+const EXIT_FAILURE: i32 = 1;
 
-  if fs::metadata(&azoni_dir).is_err() {
-    fs::create_dir(&azoni_dir).unwrap();
-  }
+#[derive(Parser, Debug)]
+#[clap(
+  name = "azoni",
+  version,
+  about = "A distributed ledger for personal and team finances"
+)]
+pub struct Opts {
+  /// Use the repository at PATH instead of looking for one in $AZONI_DIR,
+  /// then in the current directory and its parents.
+  #[clap(long = "repository", global = true, value_name = "PATH")]
+  pub repo_path: Option<PathBuf>,
+
+  #[clap(subcommand)]
+  pub subcmd: SubCommand,
+}
 
-  let _encyc = pristine::Encyc::new(&azoni_dir.join("azoni.db"))?;
+#[derive(Subcommand, Debug)]
+pub enum SubCommand {
+  /// Create a new repository.
+  Init(Init),
+  /// Manage spaces.
+  #[clap(subcommand)]
+  Space(Space),
+  /// Manage vaults.
+  #[clap(subcommand)]
+  Vault(Vault),
+  /// Manage compartments.
+  #[clap(subcommand)]
+  Compartment(Compartment),
+  /// Record and inspect entries.
+  #[clap(subcommand)]
+  Entry(Entry),
+  /// Manage labels.
+  #[clap(subcommand)]
+  Label(Label),
+  /// Manage filters.
+  #[clap(subcommand)]
+  Filter(Filter),
+  /// Manage budgets, and see what remains of them.
+  #[clap(subcommand)]
+  Budget(Budget),
+  /// List, inspect and apply changes.
+  #[clap(subcommand)]
+  Change(Change),
+  /// List and resolve the entries edited concurrently in incompatible
+  /// ways.
+  #[clap(subcommand)]
+  Conflicts(Conflicts),
+  /// Show the changes applied to a space and its state.
+  Log(Log),
+  /// Remove a change from a space, undoing its effects.
+  Unrecord(Unrecord),
+  /// Roll a space back to an earlier state.
+  Reset(Reset),
+  /// Send the changes another repository lacks, e.g. a copy on a USB
+  /// drive, or a repository reached over ssh or HTTP.
+  Push(Push),
+  /// Fetch the changes we lack from another repository.
+  Pull(Pull),
+  /// Serve the sync protocol on the standard input and output, for push
+  /// and pull over ssh.
+  Protocol(Protocol),
+  /// Serve the repository over HTTP, for push and pull from other
+  /// machines.
+  Serve(Serve),
+  /// Tag the state of a space to close its books.
+  #[clap(subcommand)]
+  Tag(Tag),
+  /// List, rename and revoke the devices writing to the repository.
+  #[clap(subcommand)]
+  Device(Device),
+  /// Manage the identities changes are recorded with.
+  #[clap(subcommand)]
+  Identity(Identity),
+  /// Manage the key changes are signed with.
+  #[clap(subcommand)]
+  Key(Key),
+  /// Check the signatures of the changes of a space.
+  Verify(Verify),
+  /// Check the pristine for corruption.
+  Check(Check),
+  /// Upgrade the pristine to the latest layout.
+  Migrate(Migrate),
+}
 
-  println!("Current directory: {}", cur.display());
+fn main() {
+  env_logger::init();
+  let opts = Opts::parse();
+  if let Err(e) = run(opts) {
+    // Whoever reads our output stopped reading: that is not a failure.
+    if is_broken_pipe(&e) {
+      return;
+    }
+    eprintln!("Error: {:#}", e);
+    std::process::exit(EXIT_FAILURE);
+  }
+}
 
-  Ok(())
+fn is_broken_pipe(e: &anyhow::Error) -> bool {
+  e.chain()
+    .filter_map(|e| e.downcast_ref::<std::io::Error>())
+    .any(|e| e.kind() == std::io::ErrorKind::BrokenPipe)
+}
+
+fn run(opts: Opts) -> Result<()> {
+  let repo_path = opts.repo_path;
+  match opts.subcmd {
+    SubCommand::Init(init) => init.run(repo_path),
+    SubCommand::Space(space) => space.run(repo_path),
+    SubCommand::Vault(vault) => vault.run(repo_path),
+    SubCommand::Compartment(compartment) => compartment.run(repo_path),
+    SubCommand::Entry(entry) => entry.run(repo_path),
+    SubCommand::Label(label) => label.run(repo_path),
+    SubCommand::Filter(filter) => filter.run(repo_path),
+    SubCommand::Budget(budget) => budget.run(repo_path),
+    SubCommand::Change(change) => change.run(repo_path),
+    SubCommand::Conflicts(conflicts) => conflicts.run(repo_path),
+    SubCommand::Log(log) => log.run(repo_path),
+    SubCommand::Unrecord(unrecord) => unrecord.run(repo_path),
+    SubCommand::Reset(reset) => reset.run(repo_path),
+    SubCommand::Push(push) => push.run(repo_path),
+    SubCommand::Pull(pull) => pull.run(repo_path),
+    SubCommand::Protocol(protocol) => protocol.run(repo_path),
+    SubCommand::Serve(serve) => serve.run(repo_path),
+    SubCommand::Tag(tag) => tag.run(repo_path),
+    SubCommand::Device(device) => device.run(repo_path),
+    SubCommand::Identity(identity) => identity.run(),
+    SubCommand::Key(key) => key.run(),
+    SubCommand::Verify(verify) => verify.run(repo_path),
+    SubCommand::Check(check) => check.run(repo_path),
+    SubCommand::Migrate(migrate) => migrate.run(repo_path),
+  }
 }
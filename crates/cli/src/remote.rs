@@ -0,0 +1,106 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::{
+  io::BufReader,
+  path::Path,
+  process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use azoni_core::{http::HttpClient, protocol::Client};
+
+use crate::repository::Repository;
+
+/// The other side of a push or a pull.
+pub enum Remote {
+  /// A repository on a local path, e.g. a copy on a USB drive.
+  Local(Repository),
+  /// A repository reached through `azoni protocol` run over ssh.
+  Ssh(Ssh),
+  /// A repository served by `azoni serve`.
+  Http(HttpClient),
+}
+
+impl Remote {
+  /// Open `url`: either `ssh://[user@]host[:port]/path`, an `http://` or
+  /// `https://` URL, or a local path.
+  pub fn open(url: &str) -> Result<Self> {
+    if let Some(rest) = url.strip_prefix("ssh://") {
+      Ok(Remote::Ssh(Ssh::connect(rest)?))
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+      Ok(Remote::Http(HttpClient::new(url)))
+    } else {
+      Ok(Remote::Local(Repository::open(Path::new(url))?))
+    }
+  }
+}
+
+pub type SshClient = Client<BufReader<ChildStdout>, ChildStdin>;
+
+pub struct Ssh {
+  pub client: SshClient,
+  child: Child,
+}
+
+impl Ssh {
+  /// Spawn `azoni protocol` on the host of `url`, which has the scheme
+  /// stripped off.
+  fn connect(url: &str) -> Result<Self> {
+    let Some((host, path)) = url.split_once('/') else {
+      bail!("Missing repository path in ssh://{}", url)
+    };
+    let mut cmd = Command::new("ssh");
+    let host = match host.rsplit_once(':') {
+      Some((host, port)) => {
+        if port.parse::<u16>().is_err() {
+          bail!("Invalid port {:?} in ssh://{}", port, url)
+        }
+        cmd.arg("-p").arg(port);
+        host
+      }
+      None => host,
+    };
+    if host.is_empty() {
+      bail!("Missing host in ssh://{}", url)
+    }
+    // ssh would read a host such as `-oProxyCommand=...` as an option,
+    // running whatever the URL says.
+    if host.starts_with('-') {
+      bail!("Invalid host {:?} in ssh://{}", host, url)
+    }
+    // The remote shell splits the command again, hence the quotes.
+    cmd
+      .arg("--")
+      .arg(host)
+      .arg(format!(
+        "azoni --repository {} protocol",
+        quote(&format!("/{}", path))
+      ))
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped());
+    let mut child = cmd.spawn().context("Could not run ssh")?;
+    let stdin = child.stdin.take().ok_or_else(|| anyhow!("No ssh stdin"))?;
+    let stdout = child
+      .stdout
+      .take()
+      .ok_or_else(|| anyhow!("No ssh stdout"))?;
+    let client = Client::connect(BufReader::new(stdout), stdin).with_context(|| format!("Could not connect to ssh://{}", url))?;
+    Ok(Ssh { client, child })
+  }
+
+  /// Say goodbye, and wait for ssh to exit.
+  pub fn close(mut self) -> Result<()> {
+    self.client.quit()?;
+    let status = self.child.wait()?;
+    if !status.success() {
+      bail!("ssh exited with {}", status)
+    }
+    Ok(())
+  }
+}
+
+/// Quote `s` for a POSIX shell.
+fn quote(s: &str) -> String {
+  format!("'{}'", s.replace('\'', r"'\''"))
+}
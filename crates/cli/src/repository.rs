@@ -0,0 +1,78 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use azoni_core::{changestore::filesystem::FileSystem, pristine::Encyc};
+use azoni_x::path::{current_dir, find_repository_root, DIR_VAR, DOT_DIR};
+
+pub const PRISTINE_FILE: &str = "azoni.db";
+
+pub struct Repository {
+  pub path: PathBuf,
+  pub encyc: Encyc,
+  pub changes: FileSystem,
+}
+
+impl Repository {
+  fn pristine(root: &Path) -> PathBuf {
+    root.join(DOT_DIR).join(PRISTINE_FILE)
+  }
+
+  /// Open the repository at `path`, or the one `AZONI_DIR` points to, or
+  /// the closest one above the current directory.
+  pub fn find(path: Option<PathBuf>) -> Result<Self> {
+    let Some(root) = find_repository_root(path.as_deref())? else {
+      match path {
+        Some(path) => bail!("No repository found in {}", path.display()),
+        None if std::env::var_os(DIR_VAR).is_some() => bail!("No repository found in ${}", DIR_VAR),
+        None => bail!("Not in a repository, run `azoni init` to create one"),
+      }
+    };
+    let db = Self::pristine(&root);
+    if fs::metadata(&db).is_err() {
+      bail!("No pristine found at {}", db.display())
+    }
+    let encyc = Encyc::new(db)?;
+    let changes = FileSystem::from_root(root.join(DOT_DIR));
+    Ok(Repository {
+      path: root,
+      encyc,
+      changes,
+    })
+  }
+
+  /// Open another repository, e.g. a copy on a USB drive, at `path` or
+  /// whose `.azoni` directory is `path`.
+  pub fn open(path: &Path) -> Result<Self> {
+    let root = match path.file_name() {
+      Some(name) if name == DOT_DIR => path.parent().unwrap_or(path),
+      _ => path,
+    };
+    Self::find(Some(root.to_path_buf()))
+  }
+
+  /// Create a repository at `path`, or in the current directory. This is the
+  /// only way to create a pristine.
+  pub fn init(path: Option<PathBuf>) -> Result<Self> {
+    let path = match path {
+      Some(path) => path,
+      None => current_dir()?,
+    };
+    if path.join(DOT_DIR).exists() {
+      bail!("A repository already exists in {}", path.display())
+    }
+    fs::create_dir_all(path.join(DOT_DIR))?;
+    let encyc = Encyc::new(Self::pristine(&path))?;
+    let changes = FileSystem::from_root(path.join(DOT_DIR));
+    Ok(Repository {
+      path,
+      encyc,
+      changes,
+    })
+  }
+}
@@ -0,0 +1,631 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use log::debug;
+use thiserror_impl::Error;
+
+use crate::{
+  change::{Change, ChangeError, Operation, SignatureError},
+  changestore::ChangeStore,
+  history::{closed_period, ClosedPeriod},
+  models::{
+    compartment::{CompartmentKind, SerializedCompartment},
+    conflict::merge,
+    entry::{Entry, EntryError, InvalidEntry},
+    space::{SpaceRef, TagPolicy},
+  },
+  traits::MutTxnT,
+  types::{Base32, ChangeId, Hash, Money, UId},
+};
+
+#[derive(Debug, Error)]
+pub enum ApplyError<C: std::error::Error + 'static, T: std::error::Error + 'static> {
+  #[error("Changestore error: {0}")]
+  Changestore(C),
+  #[error(transparent)]
+  Txn(T),
+  #[error(transparent)]
+  Change(#[from] ChangeError),
+  #[error(transparent)]
+  Entry(InvalidEntry),
+  #[error("Compartment {0} does not exist")]
+  UnknownCompartment(UId),
+  #[error("Compartment {0} is closed")]
+  ClosedCompartment(String),
+  #[error("Compartment {0} cannot be closed, its balance is {1}")]
+  NonZeroBalance(String, Money),
+  #[error("Vault {0} does not exist")]
+  UnknownVault(UId),
+  #[error("Compartment {0} cannot be its own ancestor")]
+  CompartmentCycle(String),
+  #[error("Compartment {0} must have the kind and currency of its parent {1}")]
+  ParentMismatch(String, String),
+  #[error("The balance of {kind} compartment {name} would be {balance}")]
+  WrongSign {
+    name: String,
+    kind: CompartmentKind,
+    balance: Money,
+  },
+  #[error("Label {0} does not exist")]
+  UnknownLabel(UId),
+  #[error("Label {0} cannot be deleted, it is attached to {1} entries")]
+  LabelInUse(String, usize),
+  #[error("Label {0} cannot be deleted while it has a parent or sub-labels")]
+  LabelInTree(String),
+  #[error("Label {0} cannot be its own ancestor")]
+  LabelCycle(String),
+  #[error("Label {0} is not attached to entry {}", .1.to_base32())]
+  NotAttached(String, Hash),
+  #[error("Entry {} does not exist", .0.to_base32())]
+  UnknownEntry(Hash),
+  #[error("Filter {0} does not exist")]
+  UnknownFilter(UId),
+  #[error("Compartment {0} does not hold the currency of budget {1}")]
+  BudgetCurrency(String, String),
+  #[error("Change {} is already applied", .0.to_base32())]
+  AlreadyApplied(Hash),
+  #[error("Missing dependency {}", .0.to_base32())]
+  MissingDependency(Hash),
+  #[error("A change can add at most one entry")]
+  MultipleEntries,
+  #[error("This change alters {0}")]
+  ClosedPeriod(ClosedPeriod),
+  #[error(transparent)]
+  Signature(#[from] SignatureError),
+  #[error("Device {0} was revoked")]
+  RevokedDevice(String),
+  #[error("Change made on no device, in a pristine that registers devices")]
+  NoDevice,
+}
+
+impl<C: std::error::Error + 'static, T: std::error::Error + 'static> From<EntryError<T>> for ApplyError<C, T> {
+  fn from(e: EntryError<T>) -> Self {
+    match e {
+      EntryError::Txn(e) => ApplyError::Txn(e),
+      EntryError::Invalid(e) => ApplyError::Entry(e),
+      EntryError::UnknownCompartment(id) => ApplyError::UnknownCompartment(id),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Applied {
+  pub id: ChangeId,
+  /// Set if the change alters a period closed by a tag, and the policy of
+  /// the space is to warn about it.
+  pub closed: Option<ClosedPeriod>,
+}
+
+/// Apply the change with hash `hash`, read from `changes`, to `space`.
+/// Changes with an invalid or missing signature are refused. Changes
+/// coming from elsewhere follow the tag policy of `space` like recorded
+/// ones: they are refused if they alter a closed period and the policy is
+/// to reject such changes.
+pub fn apply_change<T: MutTxnT, C: ChangeStore>(
+  changes: &C,
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  hash: &Hash,
+) -> Result<Applied, ApplyError<C::Error, T::GraphError>> {
+  let change = changes.get_change(hash).map_err(ApplyError::Changestore)?;
+  change.check_signatures(hash)?;
+  let closed = closed_period(txn, space, &change.hashed.operations).map_err(ApplyError::Txn)?;
+  if let Some(closed) = closed.clone() {
+    if txn.tag_policy(space) == TagPolicy::Reject {
+      return Err(ApplyError::ClosedPeriod(closed));
+    }
+  }
+  let id = apply_local_change(txn, space, &change, hash)?;
+  Ok(Applied { id, closed })
+}
+
+/// Apply `change`, whose hash is `hash`, to `space`, returning the local id
+/// of the change. The dependencies of `change` must already be applied to
+/// `space`; they are added to the dependency graph. On error, the
+/// transaction must be dropped rather than committed, since some
+/// operations may have been applied.
+pub fn apply_local_change<T: MutTxnT, C: std::error::Error + 'static>(
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  change: &Change,
+  hash: &Hash,
+) -> Result<ChangeId, ApplyError<C, T::GraphError>> {
+  debug!("apply_local_change {}", hash.to_base32());
+  let ops = &change.hashed.operations;
+  if ops
+    .iter()
+    .filter(|op| matches!(op, Operation::AddEntry { .. }))
+    .count()
+    > 1
+  {
+    return Err(ApplyError::MultipleEntries);
+  }
+
+  // Revocation is decided by what the pristine knew when the device was
+  // revoked, not by the timestamp of the change, which its author sets:
+  // changes already known, e.g. applied to another space, are still
+  // accepted, and no new change from a revoked device is.
+  let known = txn.get_internal(hash).map_err(ApplyError::Txn)?.is_some();
+  let devices = &change.hashed.header.devices;
+  if !known && devices.is_empty() && txn.has_devices().map_err(ApplyError::Txn)? {
+    return Err(ApplyError::NoDevice);
+  }
+  let timestamp = change.hashed.header.timestamp;
+  for device in devices.iter() {
+    let revoked = txn
+      .get_device(&device.name)
+      .map_err(ApplyError::Txn)?
+      .is_some_and(|d| d.is_revoked());
+    if revoked && !known {
+      return Err(ApplyError::RevokedDevice(device.name.clone()));
+    }
+    txn
+      .touch_device(device, timestamp)
+      .map_err(ApplyError::Txn)?;
+  }
+
+  let mut dependencies = Vec::with_capacity(change.hashed.dependencies.len());
+  for dep in change.hashed.dependencies.iter() {
+    match txn.get_internal(dep).map_err(ApplyError::Txn)? {
+      Some(d)
+        if txn
+          .get_changeset(space, &d)
+          .map_err(ApplyError::Txn)?
+          .is_some() =>
+      {
+        dependencies.push(d)
+      }
+      _ => return Err(ApplyError::MissingDependency(*dep)),
+    }
+  }
+
+  let id = txn.register_change(hash).map_err(ApplyError::Txn)?;
+  if txn
+    .put_changes(space, id, hash)
+    .map_err(ApplyError::Txn)?
+    .is_none()
+  {
+    return Err(ApplyError::AlreadyApplied(*hash));
+  }
+  for dep in dependencies.iter() {
+    txn.put_dependency(&id, dep).map_err(ApplyError::Txn)?;
+  }
+  for op in ops.iter() {
+    match op {
+      Operation::AddEntry { entry } => {
+        check_open(txn, space, entry, None)?;
+        txn.put_entry(space, &id, entry)?;
+        check_signs(txn, space, entry.postings.iter().map(|p| p.compartment))?;
+      }
+      Operation::EditEntry { entry, old, new } => {
+        let target = internal_entry(txn, entry)?;
+        if !txn.has_entry(space, &target).map_err(ApplyError::Txn)? {
+          return Err(ApplyError::UnknownEntry(*entry));
+        }
+        let new = match competing(txn, space, change, &target)? {
+          Competing::No => {
+            let current = txn
+              .load_entry(&target)
+              .map_err(ApplyError::Txn)?
+              .ok_or(ApplyError::UnknownEntry(*entry))?;
+            merge(old, &current, new)
+          }
+          Competing::Resolved => Some(new.clone()),
+          Competing::Yes => None,
+        };
+        match new {
+          Some(new) => {
+            check_open(txn, space, &new, Some(old))?;
+            txn.replace_entry(space, &target, &new, &id)?;
+            let touched = old.postings.iter().chain(new.postings.iter());
+            check_signs(txn, space, touched.map(|p| p.compartment))?;
+          }
+          None => add_conflict(txn, space, &target, &id)?,
+        }
+      }
+      Operation::DelEntry { entry, old } => {
+        let target = internal_entry(txn, entry)?;
+        if !txn.has_entry(space, &target).map_err(ApplyError::Txn)? {
+          return Err(ApplyError::UnknownEntry(*entry));
+        }
+        let delete = match competing(txn, space, change, &target)? {
+          Competing::No => txn.load_entry(&target).map_err(ApplyError::Txn)?.as_ref() == Some(old),
+          Competing::Resolved => true,
+          Competing::Yes => false,
+        };
+        if delete {
+          txn.del_entry(space, &target)?;
+          check_signs(txn, space, old.postings.iter().map(|p| p.compartment))?;
+        } else {
+          add_conflict(txn, space, &target, &id)?
+        }
+      }
+      Operation::AddCompartment {
+        id: compartment,
+        name,
+        currency,
+      } => {
+        txn
+          .create_compartment(space, *compartment, name, *currency)
+          .map_err(ApplyError::Txn)?;
+        txn.put_creator(compartment, &id).map_err(ApplyError::Txn)?;
+      }
+      Operation::AddVault { id: vault, name } => {
+        txn
+          .create_vault(space, *vault, name)
+          .map_err(ApplyError::Txn)?;
+        txn.put_creator(vault, &id).map_err(ApplyError::Txn)?;
+      }
+      Operation::AddLabel {
+        id: label,
+        name,
+        group,
+      } => {
+        txn
+          .create_label(space, *label, name, *group)
+          .map_err(ApplyError::Txn)?;
+        txn.put_creator(label, &id).map_err(ApplyError::Txn)?;
+      }
+      Operation::AddFilter { id: filter } => {
+        txn.create_filter(*filter).map_err(ApplyError::Txn)?;
+        txn.put_creator(filter, &id).map_err(ApplyError::Txn)?;
+      }
+      Operation::RenameCompartment {
+        id: compartment,
+        new,
+        ..
+      } => {
+        if !txn
+          .rename_compartment(space, compartment, new)
+          .map_err(ApplyError::Txn)?
+        {
+          return Err(ApplyError::UnknownCompartment(*compartment));
+        }
+      }
+      Operation::CloseCompartment { id: compartment } => {
+        let Some(c) = txn
+          .get_compartment(space, compartment)
+          .map_err(ApplyError::Txn)?
+        else {
+          return Err(ApplyError::UnknownCompartment(*compartment));
+        };
+        let balance = Money::from(c.balance);
+        if !balance.is_zero() {
+          return Err(ApplyError::NonZeroBalance(
+            c.name.as_str().to_string(),
+            balance,
+          ));
+        }
+        txn
+          .set_compartment_closed(space, compartment, true)
+          .map_err(ApplyError::Txn)?;
+      }
+      Operation::ReopenCompartment { id: compartment } => {
+        if !txn
+          .set_compartment_closed(space, compartment, false)
+          .map_err(ApplyError::Txn)?
+        {
+          return Err(ApplyError::UnknownCompartment(*compartment));
+        }
+      }
+      Operation::SetCompartmentKind {
+        id: compartment,
+        new,
+        ..
+      } => {
+        let c = known_compartment(txn, space, compartment)?;
+        if let Some(parent) = c.parent() {
+          let parent = known_compartment(txn, space, &parent)?;
+          if parent.kind != *new {
+            return Err(ApplyError::ParentMismatch(
+              c.name.as_str().to_string(),
+              parent.name.as_str().to_string(),
+            ));
+          }
+        }
+        // The whole tree below has the kind of its root.
+        let mut tree = txn
+          .descendants(space, compartment)
+          .map_err(ApplyError::Txn)?;
+        tree.push(*compartment);
+        for c in tree.iter() {
+          txn
+            .set_compartment_kind(space, c, *new)
+            .map_err(ApplyError::Txn)?;
+        }
+        check_signs(txn, space, tree.into_iter())?;
+      }
+      Operation::SetCompartmentParent {
+        id: compartment,
+        new,
+        ..
+      } => {
+        let c = known_compartment(txn, space, compartment)?;
+        if let Some(parent) = new {
+          let p = known_compartment(txn, space, parent)?;
+          if parent == compartment
+            || txn
+              .descendants(space, compartment)
+              .map_err(ApplyError::Txn)?
+              .contains(parent)
+          {
+            return Err(ApplyError::CompartmentCycle(c.name.as_str().to_string()));
+          }
+          if p.kind != c.kind || p.balance.currency != c.balance.currency {
+            return Err(ApplyError::ParentMismatch(
+              c.name.as_str().to_string(),
+              p.name.as_str().to_string(),
+            ));
+          }
+        }
+        txn
+          .set_compartment_parent(space, compartment, *new)
+          .map_err(ApplyError::Txn)?;
+      }
+      Operation::MoveCompartment {
+        id: compartment,
+        new,
+        ..
+      } => {
+        if let Some(vault) = new {
+          if txn.get_vault(vault).map_err(ApplyError::Txn)?.is_none() {
+            return Err(ApplyError::UnknownVault(*vault));
+          }
+        }
+        if !txn
+          .set_compartment_vault(space, compartment, *new)
+          .map_err(ApplyError::Txn)?
+        {
+          return Err(ApplyError::UnknownCompartment(*compartment));
+        }
+      }
+      Operation::RenameLabel { id: label, new, .. } => {
+        if !txn
+          .rename_label(space, label, new)
+          .map_err(ApplyError::Txn)?
+        {
+          return Err(ApplyError::UnknownLabel(*label));
+        }
+      }
+      Operation::DelLabel { id: label, .. } => {
+        let Some(l) = txn.get_label(label).map_err(ApplyError::Txn)? else {
+          return Err(ApplyError::UnknownLabel(*label));
+        };
+        let entries = txn.label_entries(label).map_err(ApplyError::Txn)?;
+        if !entries.is_empty() {
+          return Err(ApplyError::LabelInUse(
+            l.name.as_str().to_string(),
+            entries.len(),
+          ));
+        }
+        if l.parent().is_some()
+          || !txn
+            .label_children(label)
+            .map_err(ApplyError::Txn)?
+            .is_empty()
+        {
+          return Err(ApplyError::LabelInTree(l.name.as_str().to_string()));
+        }
+        if !txn.del_label(space, label).map_err(ApplyError::Txn)? {
+          return Err(ApplyError::UnknownLabel(*label));
+        }
+      }
+      Operation::MoveLabel { id: label, new, .. } => {
+        if let Some(vault) = new {
+          if txn.get_vault(vault).map_err(ApplyError::Txn)?.is_none() {
+            return Err(ApplyError::UnknownVault(*vault));
+          }
+        }
+        if !txn.set_label_vault(label, *new).map_err(ApplyError::Txn)? {
+          return Err(ApplyError::UnknownLabel(*label));
+        }
+      }
+      Operation::SetLabelParent { id: label, new, .. } => {
+        let Some(l) = txn.get_label(label).map_err(ApplyError::Txn)?.cloned() else {
+          return Err(ApplyError::UnknownLabel(*label));
+        };
+        let mut tree = txn.label_descendants(label).map_err(ApplyError::Txn)?;
+        let group = match new {
+          Some(parent) => {
+            if !txn
+              .space_labels(space)
+              .map_err(ApplyError::Txn)?
+              .contains(parent)
+            {
+              return Err(ApplyError::UnknownLabel(*parent));
+            }
+            if parent == label || tree.contains(parent) {
+              return Err(ApplyError::LabelCycle(l.name.as_str().to_string()));
+            }
+            // The parent has the group of its root.
+            txn
+              .get_label(parent)
+              .map_err(ApplyError::Txn)?
+              .ok_or(ApplyError::UnknownLabel(*parent))?
+              .group
+          }
+          None => l.group,
+        };
+        txn.set_label_parent(label, *new).map_err(ApplyError::Txn)?;
+        tree.push(*label);
+        for l in tree.iter() {
+          txn.set_label_group(l, group).map_err(ApplyError::Txn)?;
+        }
+      }
+      Operation::AttachLabel { entry, label } => {
+        let target = internal_entry(txn, entry)?;
+        if !txn.has_entry(space, &target).map_err(ApplyError::Txn)? {
+          return Err(ApplyError::UnknownEntry(*entry));
+        }
+        if !txn
+          .space_labels(space)
+          .map_err(ApplyError::Txn)?
+          .contains(label)
+          || !txn.attach_label(&target, label).map_err(ApplyError::Txn)?
+        {
+          return Err(ApplyError::UnknownLabel(*label));
+        }
+      }
+      Operation::DetachLabel { entry, label } => {
+        let target = internal_entry(txn, entry)?;
+        if !txn.has_entry(space, &target).map_err(ApplyError::Txn)? {
+          return Err(ApplyError::UnknownEntry(*entry));
+        }
+        if !txn.detach_label(&target, label).map_err(ApplyError::Txn)? {
+          let name = match txn.get_label(label).map_err(ApplyError::Txn)? {
+            Some(l) => l.name.as_str().to_string(),
+            None => return Err(ApplyError::UnknownLabel(*label)),
+          };
+          return Err(ApplyError::NotAttached(name, *entry));
+        }
+      }
+      Operation::SetBudget {
+        id: filter, new, ..
+      } => {
+        if let Some(budget) = new {
+          for label in budget.labels.iter() {
+            if txn.get_label(label).map_err(ApplyError::Txn)?.is_none() {
+              return Err(ApplyError::UnknownLabel(*label));
+            }
+          }
+          for compartment in budget.compartments.iter() {
+            let Some(c) = txn
+              .get_compartment(space, compartment)
+              .map_err(ApplyError::Txn)?
+            else {
+              return Err(ApplyError::UnknownCompartment(*compartment));
+            };
+            if c.balance.currency != budget.limit.currency {
+              return Err(ApplyError::BudgetCurrency(
+                c.name.as_str().to_string(),
+                budget.name.clone(),
+              ));
+            }
+          }
+        }
+        if !txn
+          .set_budget(filter, new.as_ref())
+          .map_err(ApplyError::Txn)?
+        {
+          return Err(ApplyError::UnknownFilter(*filter));
+        }
+      }
+    }
+  }
+  Ok(id)
+}
+
+enum Competing {
+  /// The entry is not in conflict.
+  No,
+  /// The entry is in conflict, and the change depends on all the changes
+  /// competing for it: it resolves the conflict.
+  Resolved,
+  /// The entry is in conflict, and the change is one more contender.
+  Yes,
+}
+
+/// Whether `change` competes with others in `space` for the content of
+/// `entry`. A
+/// resolved conflict is cleared.
+fn competing<T: MutTxnT, C: std::error::Error + 'static>(
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  change: &Change,
+  entry: &ChangeId,
+) -> Result<Competing, ApplyError<C, T::GraphError>> {
+  let conflicts = txn.entry_conflicts(space, entry).map_err(ApplyError::Txn)?;
+  if conflicts.is_empty() {
+    return Ok(Competing::No);
+  }
+  for c in conflicts.iter() {
+    let hash = txn.get_external(c).map_err(ApplyError::Txn)?;
+    if !hash.is_some_and(|h| change.hashed.dependencies.contains(&h)) {
+      return Ok(Competing::Yes);
+    }
+  }
+  txn.clear_conflicts(space, entry).map_err(ApplyError::Txn)?;
+  Ok(Competing::Resolved)
+}
+
+/// Record that change `id` competes with the one that gave `entry` its
+/// current content, which is kept.
+fn add_conflict<T: MutTxnT, C: std::error::Error + 'static>(
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  entry: &ChangeId,
+  id: &ChangeId,
+) -> Result<(), ApplyError<C, T::GraphError>> {
+  debug!("conflict on entry {:?}", entry);
+  if let Some(current) = txn.last_change(entry).map_err(ApplyError::Txn)? {
+    txn
+      .add_conflict(space, entry, &current)
+      .map_err(ApplyError::Txn)?;
+  }
+  txn.add_conflict(space, entry, id).map_err(ApplyError::Txn)
+}
+
+fn known_compartment<T: MutTxnT, C: std::error::Error + 'static>(
+  txn: &T,
+  space: &SpaceRef<T>,
+  id: &UId,
+) -> Result<SerializedCompartment, ApplyError<C, T::GraphError>> {
+  txn
+    .get_compartment(space, id)
+    .map_err(ApplyError::Txn)?
+    .cloned()
+    .ok_or(ApplyError::UnknownCompartment(*id))
+}
+
+/// Check that the balances of `compartments` are allowed by their kinds.
+fn check_signs<T: MutTxnT, C: std::error::Error + 'static>(
+  txn: &T,
+  space: &SpaceRef<T>,
+  compartments: impl Iterator<Item = UId>,
+) -> Result<(), ApplyError<C, T::GraphError>> {
+  for id in compartments {
+    let Some(c) = txn.get_compartment(space, &id).map_err(ApplyError::Txn)? else {
+      continue;
+    };
+    let balance = Money::from(c.balance);
+    if !c.kind.allows(&balance) {
+      return Err(ApplyError::WrongSign {
+        name: c.name.as_str().to_string(),
+        kind: c.kind,
+        balance,
+      });
+    }
+  }
+  Ok(())
+}
+
+/// Refuse postings of `entry` to closed compartments, unless they were
+/// already in `old`, the content it replaces.
+fn check_open<T: MutTxnT, C: std::error::Error + 'static>(
+  txn: &T,
+  space: &SpaceRef<T>,
+  entry: &Entry,
+  old: Option<&Entry>,
+) -> Result<(), ApplyError<C, T::GraphError>> {
+  for p in entry.postings.iter() {
+    if old.is_some_and(|old| old.postings.contains(p)) {
+      continue;
+    }
+    if let Some(c) = txn
+      .get_compartment(space, &p.compartment)
+      .map_err(ApplyError::Txn)?
+    {
+      if c.is_closed() {
+        return Err(ApplyError::ClosedCompartment(c.name.as_str().to_string()));
+      }
+    }
+  }
+  Ok(())
+}
+
+fn internal_entry<T: MutTxnT, C: std::error::Error + 'static>(txn: &T, entry: &Hash) -> Result<ChangeId, ApplyError<C, T::GraphError>> {
+  txn
+    .get_internal(entry)
+    .map_err(ApplyError::Txn)?
+    .ok_or(ApplyError::UnknownEntry(*entry))
+}
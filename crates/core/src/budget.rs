@@ -0,0 +1,107 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+
+use crate::{
+  models::{entry::EntryError, filter::Budget, space::SpaceRef},
+  traits::TxnT,
+  types::{Money, MoneyError},
+};
+
+/// Where a budget stands in one of its periods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodStatus {
+  pub start: NaiveDate,
+  pub end: NaiveDate,
+  /// What was left unspent in the previous period, if the budget rolls
+  /// over. Overspending is not carried.
+  pub carried: Money,
+  /// The limit plus what was carried.
+  pub available: Money,
+  pub spent: Money,
+  /// What is left of `available`, negative if it was overspent.
+  pub remaining: Money,
+}
+
+/// The status of `budget` in `space`, for each of its periods started on
+/// or before `until`.
+///
+/// An entry posting to a compartment of the budget, or one below it,
+/// spends the sum of these postings, so that refunds come back to the
+/// budget. Other entries labelled by a label of the budget, or one below
+/// it, spend the amount they move. Either way, only amounts in the
+/// currency of the limit count, and each entry counts once. Fails if an
+/// amount overflows.
+pub fn budget_status<T: TxnT>(txn: &T, space: &SpaceRef<T>, budget: &Budget, until: NaiveDate) -> Result<Vec<PeriodStatus>, EntryError<T::GraphError>> {
+  let invalid = |e: MoneyError| EntryError::Invalid(e.into());
+  let currency = budget.limit.currency;
+  let mut compartments = HashSet::new();
+  for c in budget.compartments.iter() {
+    compartments.insert(*c);
+    compartments.extend(txn.descendants(space, c)?);
+  }
+  let mut entries = Vec::new();
+  for c in compartments.iter() {
+    entries.extend(txn.compartment_entries(space, c)?);
+  }
+  for l in budget.labels.iter() {
+    entries.extend(txn.tree_entries(l)?);
+  }
+  entries.sort();
+  entries.dedup();
+
+  let periods = budget.periods(until);
+  let mut spent = vec![Money::zero(currency); periods.len()];
+  for e in entries {
+    if !txn.has_entry(space, &e)? {
+      continue;
+    }
+    let Some(entry) = txn.load_entry(&e)? else {
+      continue;
+    };
+    let Some(i) = periods
+      .iter()
+      .position(|(start, end)| *start <= entry.date && entry.date <= *end)
+    else {
+      continue;
+    };
+    let posted: Vec<_> = entry
+      .postings
+      .iter()
+      .filter(|p| compartments.contains(&p.compartment))
+      .map(|p| p.amount)
+      .collect();
+    let amounts = if posted.is_empty() {
+      entry.moved().map_err(invalid)?
+    } else {
+      posted
+    };
+    for m in amounts.iter().filter(|m| m.currency == currency) {
+      spent[i] = spent[i].checked_add(m).map_err(invalid)?;
+    }
+  }
+
+  let mut status = Vec::new();
+  let mut carried = Money::zero(currency);
+  for ((start, end), spent) in periods.into_iter().zip(spent) {
+    let available = budget.limit.checked_add(&carried).map_err(invalid)?;
+    let remaining = available.checked_sub(&spent).map_err(invalid)?;
+    status.push(PeriodStatus {
+      start,
+      end,
+      carried,
+      available,
+      spent,
+      remaining,
+    });
+    carried = if budget.rollover && !remaining.is_negative() {
+      remaining
+    } else {
+      Money::zero(currency)
+    };
+  }
+  Ok(status)
+}
@@ -0,0 +1,330 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+use thiserror_impl::Error;
+
+use crate::{
+  key::{PublicKey, SecretKey, Signature, AUTHOR_KEY},
+  models::{compartment::CompartmentKind, entry::Entry, filter::Budget, label::LabelGroup, ChangeHeader},
+  types::{hash::Hasher, Base32, Currency, Hash, UId},
+};
+
+/// Version of the change format.
+pub const VERSION: u64 = 1;
+
+#[derive(Debug, Error)]
+pub enum ChangeError {
+  #[error("Version mismatch: got {0}, expected {VERSION}")]
+  VersionMismatch(u64),
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  #[error(transparent)]
+  Bincode(#[from] bincode::Error),
+  #[error("Change not found: {}", .0.to_base32())]
+  ChangeNotFound(Hash),
+  #[error("Change hash mismatch, claimed {}, computed {}", claimed.to_base32(), computed.to_base32())]
+  ChangeHashMismatch { claimed: Hash, computed: Hash },
+}
+
+/// An atomic edit of a ledger. Entries are identified by the hash of the
+/// change that added them, everything else by the `UId` chosen when it
+/// was created.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Operation {
+  AddEntry {
+    entry: Entry,
+  },
+  EditEntry {
+    entry: Hash,
+    old: Entry,
+    new: Entry,
+  },
+  DelEntry {
+    entry: Hash,
+    old: Entry,
+  },
+  AddCompartment {
+    id: UId,
+    name: String,
+    currency: Currency,
+  },
+  AddVault {
+    id: UId,
+    name: String,
+  },
+  AddLabel {
+    id: UId,
+    name: String,
+    group: LabelGroup,
+  },
+  AddFilter {
+    id: UId,
+  },
+  // Variants are only ever added at the end: their index is part of the
+  // serialized form.
+  RenameCompartment {
+    id: UId,
+    old: String,
+    new: String,
+  },
+  /// Refused if the balance of the compartment is not zero. A closed
+  /// compartment gets no new postings.
+  CloseCompartment {
+    id: UId,
+  },
+  ReopenCompartment {
+    id: UId,
+  },
+  /// Move a compartment from vault `old` to vault `new`, `None` being no
+  /// vault.
+  MoveCompartment {
+    id: UId,
+    old: Option<UId>,
+    new: Option<UId>,
+  },
+  /// Refused if the compartment has a parent or children of another kind,
+  /// or a balance its new kind does not allow.
+  SetCompartmentKind {
+    id: UId,
+    old: CompartmentKind,
+    new: CompartmentKind,
+  },
+  /// Put a compartment under parent `new`, `None` making it the root of a
+  /// tree. Refused if the parent has another kind or currency, or is one
+  /// of its descendants.
+  SetCompartmentParent {
+    id: UId,
+    old: Option<UId>,
+    new: Option<UId>,
+  },
+  /// Entries refer to labels by id, so they keep their labels.
+  RenameLabel {
+    id: UId,
+    old: String,
+    new: String,
+  },
+  /// Refused while the label is attached to entries, or is part of a tree.
+  /// The rest is what unrecording needs to create it again.
+  DelLabel {
+    id: UId,
+    name: String,
+    group: LabelGroup,
+    vault: Option<UId>,
+  },
+  /// File a label in vault `new` instead of `old`, `None` being no vault.
+  MoveLabel {
+    id: UId,
+    old: Option<UId>,
+    new: Option<UId>,
+  },
+  /// Attachments are counted: a label attached by two concurrent changes
+  /// is detached by two `DetachLabel`.
+  AttachLabel {
+    entry: Hash,
+    label: UId,
+  },
+  DetachLabel {
+    entry: Hash,
+    label: UId,
+  },
+  /// Put a label under parent `new`, `None` making it the root of a tree.
+  /// The label and its descendants take the group of the new root;
+  /// `group` is the one they had. Refused if `new` is one of its
+  /// descendants.
+  SetLabelParent {
+    id: UId,
+    old: Option<UId>,
+    new: Option<UId>,
+    group: LabelGroup,
+  },
+  /// Make filter `id` budget `new`, or with `None`, stop it being a
+  /// budget. `old` is what it was. Refused if a label or compartment of
+  /// the budget does not exist, or a compartment holds another currency
+  /// than its limit.
+  SetBudget {
+    id: UId,
+    old: Option<Budget>,
+    new: Option<Budget>,
+  },
+}
+
+/// The part of a change covered by its hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hashed {
+  /// Must stay the first field, so that it can be read before the rest.
+  pub version: u64,
+  pub header: ChangeHeader,
+  pub dependencies: Vec<Hash>,
+  pub operations: Vec<Operation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+  pub hashed: Hashed,
+  /// Signatures of the hash of `hashed`. They are not part of the hash, so
+  /// that signing doesn't change it.
+  pub signatures: Vec<ChangeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeSignature {
+  pub key: PublicKey,
+  pub signature: Signature,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SignatureError {
+  #[error("Invalid signature by {0}")]
+  Invalid(String),
+  #[error("Author {0} did not sign this change")]
+  Missing(String),
+  #[error("Change is not signed")]
+  Unsigned,
+}
+
+impl Change {
+  pub fn new(header: ChangeHeader, dependencies: Vec<Hash>, operations: Vec<Operation>) -> Self {
+    Change {
+      hashed: Hashed {
+        version: VERSION,
+        header,
+        dependencies,
+        operations,
+      },
+      signatures: Vec::new(),
+    }
+  }
+
+  pub fn hash(&self) -> Result<Hash, ChangeError> {
+    let bytes = bincode::serialize(&self.hashed)?;
+    let mut hasher = Hasher::default();
+    hasher.update(&bytes);
+    Ok(hasher.finish())
+  }
+
+  /// Write this change to `w`, returning its hash. Signatures, if any,
+  /// follow the hashed part.
+  pub fn serialize<W: Write>(&self, mut w: W) -> Result<Hash, ChangeError> {
+    let bytes = bincode::serialize(&self.hashed)?;
+    let mut hasher = Hasher::default();
+    hasher.update(&bytes);
+    w.write_all(&bytes)?;
+    if !self.signatures.is_empty() {
+      bincode::serialize_into(&mut w, &self.signatures)?;
+    }
+    Ok(hasher.finish())
+  }
+
+  /// Sign this change, whose hash is `hash`, with `key`.
+  pub fn sign(&mut self, key: &SecretKey, hash: &Hash) {
+    let public = key.public_key();
+    self.signatures.retain(|s| s.key != public);
+    self.signatures.push(ChangeSignature {
+      key: public,
+      signature: key.sign(hash),
+    })
+  }
+
+  /// Check the signatures of this change, whose hash is `hash`: there
+  /// must be at least one, they must all be valid, and every author with a
+  /// key must have signed. Returns the keys that signed.
+  pub fn check_signatures(&self, hash: &Hash) -> Result<Vec<PublicKey>, SignatureError> {
+    if self.signatures.is_empty() {
+      return Err(SignatureError::Unsigned);
+    }
+    for s in self.signatures.iter() {
+      if !s.key.verify(hash, &s.signature) {
+        return Err(SignatureError::Invalid(s.key.to_base32()));
+      }
+    }
+    for author in self.hashed.header.authors.iter() {
+      if let Some(key) = author.0.get(AUTHOR_KEY) {
+        if !self.signatures.iter().any(|s| s.key.to_base32() == *key) {
+          return Err(SignatureError::Missing(key.clone()));
+        }
+      }
+    }
+    Ok(self.signatures.iter().map(|s| s.key).collect())
+  }
+
+  /// Read a change from `bytes`, checking its hash if `hash` is given.
+  pub fn deserialize(bytes: &[u8], hash: Option<&Hash>) -> Result<Self, ChangeError> {
+    if bytes.len() < 8 {
+      return Err(ChangeError::VersionMismatch(0));
+    }
+    let mut version = [0; 8];
+    version.copy_from_slice(&bytes[..8]);
+    let version = u64::from_le_bytes(version);
+    if version != VERSION {
+      return Err(ChangeError::VersionMismatch(version));
+    }
+
+    let mut r = bytes;
+    let hashed: Hashed = bincode::deserialize_from(&mut r)?;
+    let signatures = if r.is_empty() {
+      Vec::new()
+    } else {
+      bincode::deserialize(r)?
+    };
+    let change = Change { hashed, signatures };
+    if let Some(claimed) = hash {
+      let computed = change.hash()?;
+      if *claimed != computed {
+        return Err(ChangeError::ChangeHashMismatch {
+          claimed: *claimed,
+          computed,
+        });
+      }
+    }
+    Ok(change)
+  }
+
+  /// Whether this change needs `other`, whose hash is `hash`: it lists
+  /// `other` as a dependency, edits or deletes an entry `other` added or
+  /// edited, or has postings in a compartment `other` created.
+  pub fn depends_on(&self, other: &Change, hash: &Hash) -> bool {
+    if self.hashed.dependencies.contains(hash) {
+      return true;
+    }
+    self.hashed.operations.iter().any(|op| {
+      let touched = match op {
+        Operation::EditEntry { entry, .. } | Operation::DelEntry { entry, .. } => Some(entry),
+        _ => None,
+      };
+      let postings = match op {
+        Operation::AddEntry { entry } | Operation::EditEntry { new: entry, .. } => &entry.postings[..],
+        _ => &[],
+      };
+      other.hashed.operations.iter().any(|o| match o {
+        Operation::AddEntry { .. } => touched == Some(hash),
+        Operation::EditEntry { entry, .. } => touched == Some(entry),
+        Operation::AddCompartment { id, .. } => postings.iter().any(|p| p.compartment == *id),
+        _ => false,
+      })
+    })
+  }
+
+  /// The entry added by this change, if any. A change adds at most one
+  /// entry, which is then identified by the change's hash.
+  pub fn added_entry(&self) -> Option<&Entry> {
+    self.hashed.operations.iter().find_map(|op| match op {
+      Operation::AddEntry { entry } => Some(entry),
+      _ => None,
+    })
+  }
+
+  /// What this change, whose hash is `hash`, makes of entry `entry`: its
+  /// new content, or `Some(None)` if it deletes it. `None` if the change
+  /// does not touch `entry`.
+  pub fn proposed_entry(&self, hash: &Hash, entry: &Hash) -> Option<Option<&Entry>> {
+    self.hashed.operations.iter().find_map(|op| match op {
+      Operation::AddEntry { entry: e } if hash == entry => Some(Some(e)),
+      Operation::EditEntry { entry: e, new, .. } if e == entry => Some(Some(new)),
+      Operation::DelEntry { entry: e, .. } if e == entry => Some(None),
+      _ => None,
+    })
+  }
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::{
+  change::{Change, ChangeError},
+  types::{Base32, Hash},
+};
+
+use super::ChangeStore;
+
+pub const CHANGES_DIR: &str = "changes";
+
+/// A change store in a directory, one file per change, named after the
+/// base32 encoding of its hash and sharded by its first two characters.
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+  changes_dir: PathBuf,
+}
+
+impl FileSystem {
+  /// Change store of the repository whose dot directory is `dot_dir`.
+  pub fn from_root<P: AsRef<Path>>(dot_dir: P) -> Self {
+    Self::from_changes(dot_dir.as_ref().join(CHANGES_DIR))
+  }
+
+  pub fn from_changes(changes_dir: PathBuf) -> Self {
+    FileSystem { changes_dir }
+  }
+
+  pub fn filename(&self, hash: &Hash) -> PathBuf {
+    let b32 = hash.to_base32();
+    let (a, b) = b32.split_at(2);
+    let mut path = self.changes_dir.join(a);
+    path.push(b);
+    path.set_extension("change");
+    path
+  }
+}
+
+impl ChangeStore for FileSystem {
+  type Error = ChangeError;
+
+  fn has_change(&self, hash: &Hash) -> bool {
+    self.filename(hash).is_file()
+  }
+
+  fn get_change(&self, hash: &Hash) -> Result<Change, Self::Error> {
+    let bytes = match fs::read(self.filename(hash)) {
+      Ok(bytes) => bytes,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(ChangeError::ChangeNotFound(*hash)),
+      Err(e) => return Err(e.into()),
+    };
+    Change::deserialize(&bytes, Some(hash))
+  }
+
+  fn save_change(&self, change: &Change) -> Result<Hash, Self::Error> {
+    let mut bytes = Vec::new();
+    let hash = change.serialize(&mut bytes)?;
+    let path = self.filename(&hash);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    // Write to a temporary file first, so that a crash never leaves a
+    // truncated change behind.
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, &bytes)?;
+    fs::rename(&tmp, &path)?;
+    Ok(hash)
+  }
+
+  fn del_change(&self, hash: &Hash) -> Result<bool, Self::Error> {
+    match fs::remove_file(self.filename(hash)) {
+      Ok(()) => Ok(true),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+      Err(e) => Err(e.into()),
+    }
+  }
+}
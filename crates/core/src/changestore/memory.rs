@@ -0,0 +1,52 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::{
+  change::{Change, ChangeError},
+  types::{Hash, HashMap},
+};
+
+use super::ChangeStore;
+
+/// A change store kept in memory, mostly useful for tests.
+#[derive(Debug, Clone, Default)]
+pub struct Memory {
+  changes: Arc<RwLock<HashMap<Hash, Change>>>,
+}
+
+impl Memory {
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+impl ChangeStore for Memory {
+  type Error = ChangeError;
+
+  fn has_change(&self, hash: &Hash) -> bool {
+    self.changes.read().contains_key(hash)
+  }
+
+  fn get_change(&self, hash: &Hash) -> Result<Change, Self::Error> {
+    self
+      .changes
+      .read()
+      .get(hash)
+      .cloned()
+      .ok_or(ChangeError::ChangeNotFound(*hash))
+  }
+
+  fn save_change(&self, change: &Change) -> Result<Hash, Self::Error> {
+    let hash = change.hash()?;
+    self.changes.write().insert(hash, change.clone());
+    Ok(hash)
+  }
+
+  fn del_change(&self, hash: &Hash) -> Result<bool, Self::Error> {
+    Ok(self.changes.write().remove(hash).is_some())
+  }
+}
@@ -0,0 +1,20 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use crate::{change::Change, types::Hash};
+
+pub mod filesystem;
+pub mod memory;
+
+/// Where the contents of changes are kept. The pristine only knows their
+/// hashes.
+pub trait ChangeStore {
+  type Error: std::error::Error + std::fmt::Debug + Send + Sync + 'static;
+
+  fn has_change(&self, hash: &Hash) -> bool;
+  fn get_change(&self, hash: &Hash) -> Result<Change, Self::Error>;
+  /// Save `change`, returning its hash.
+  fn save_change(&self, change: &Change) -> Result<Hash, Self::Error>;
+  /// Returns `false` if there was no such change.
+  fn del_change(&self, hash: &Hash) -> Result<bool, Self::Error>;
+}
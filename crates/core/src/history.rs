@@ -0,0 +1,110 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use thiserror_impl::Error;
+
+use crate::{
+  change::Operation,
+  changestore::ChangeStore,
+  models::{entry::Entry, space::SpaceRef},
+  traits::TxnT,
+  types::Hash,
+};
+
+#[derive(Debug, Error)]
+pub enum HistoryError<C: std::error::Error + 'static, T: std::error::Error + 'static> {
+  #[error("Changestore error: {0}")]
+  Changestore(C),
+  #[error(transparent)]
+  Txn(T),
+}
+
+/// A period closed by a tag, up to and including `date`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosedPeriod {
+  pub tag: String,
+  pub date: NaiveDate,
+}
+
+impl std::fmt::Display for ClosedPeriod {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+      f,
+      "the period closed by tag {} (up to {})",
+      self.tag, self.date
+    )
+  }
+}
+
+/// The latest period of `space` closed by a tag that `operations` would
+/// alter, i.e. with an entry added, edited or deleted on or before its
+/// date.
+pub fn closed_period<T: TxnT>(txn: &T, space: &SpaceRef<T>, operations: &[Operation]) -> Result<Option<ClosedPeriod>, T::GraphError> {
+  let Some(tag) = txn.space_tags(space)?.into_iter().max_by_key(|t| t.date) else {
+    return Ok(None);
+  };
+  let earliest = operations
+    .iter()
+    .filter_map(|op| match op {
+      Operation::AddEntry { entry } => Some(entry.date),
+      Operation::EditEntry { old, new, .. } => Some(old.date.min(new.date)),
+      Operation::DelEntry { old, .. } => Some(old.date),
+      _ => None,
+    })
+    .min();
+  match earliest {
+    Some(date) if date <= tag.date => Ok(Some(ClosedPeriod {
+      tag: tag.name,
+      date: tag.date,
+    })),
+    _ => Ok(None),
+  }
+}
+
+/// The entries of `space` as they were after the first `position` changes
+/// of its log, by hash of the change that added them.
+pub fn entries_as_of<T: TxnT, C: ChangeStore>(
+  changes: &C,
+  txn: &T,
+  space: &SpaceRef<T>,
+  position: u64,
+) -> Result<BTreeMap<Hash, Entry>, HistoryError<C::Error, T::GraphError>> {
+  let mut entries = BTreeMap::new();
+  for (pos, id, _) in txn.log(space, 0).map_err(HistoryError::Txn)? {
+    if pos >= position {
+      break;
+    }
+    let Some(hash) = txn.get_external(&id).map_err(HistoryError::Txn)? else {
+      continue;
+    };
+    if !changes.has_change(&hash) {
+      // Entries recorded before the change store existed: only their
+      // current content is known.
+      if let Some(entry) = txn.load_entry(&id).map_err(HistoryError::Txn)? {
+        entries.insert(hash, entry);
+      }
+      continue;
+    }
+    let change = changes
+      .get_change(&hash)
+      .map_err(HistoryError::Changestore)?;
+    for op in change.hashed.operations {
+      match op {
+        Operation::AddEntry { entry } => {
+          entries.insert(hash, entry);
+        }
+        Operation::EditEntry { entry, new, .. } => {
+          entries.insert(entry, new);
+        }
+        Operation::DelEntry { entry, .. } => {
+          entries.remove(&entry);
+        }
+        _ => {}
+      }
+    }
+  }
+  Ok(entries)
+}
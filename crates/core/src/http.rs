@@ -0,0 +1,368 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Synchronizing with a pristine served over HTTP, e.g. by an always-on
+//! box at home. The routes are:
+//!
+//! - `GET /spaces/<space>/log?since=<state>`: one `<position> <hash>
+//!   <state>` line per change of the log of `space` after the one that
+//!   reached `state`. The whole log if `since` is missing or unknown, and
+//!   nothing if there is no such space.
+//! - `POST /spaces/<space>/changes`: check the signatures of the change in
+//!   the body, store it and apply it to `space`, created if needed.
+//!   Replies with the new state of the space.
+//! - `GET /changes/<hash>`: the bytes of a change. `HEAD` tells whether
+//!   the change is known.
+//! - `POST /changes`: check and store the change in the body, without
+//!   applying it. Replies with its hash.
+//!
+//! Changes are only accepted if they carry a valid signature by one of the
+//! keys authorized on the server, so that reaching the port is not enough
+//! to write. Without authorized keys, the server is read-only.
+//!
+//! Space names are percent-encoded in paths. Errors are replied with a
+//! 4xx or 5xx status and a plain text message.
+
+use std::io::Read;
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use thiserror_impl::Error;
+use tiny_http::{Method, Request, Response, Server};
+
+use crate::{
+  apply::apply_change,
+  change::{Change, ChangeError},
+  changestore::ChangeStore,
+  key::PublicKey,
+  models::space::SpaceTxnT,
+  pristine::Encyc,
+  protocol::MAX_CHANGE_SIZE,
+  sync::{space_log, LogEntry},
+  traits::{MutTxnT, TxnT},
+  types::{Base32, Hash, Merkle},
+};
+
+#[derive(Debug, Error)]
+pub enum HttpError {
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  #[error(transparent)]
+  Change(#[from] ChangeError),
+  #[error("Could not bind {0}")]
+  Bind(String),
+  #[error("HTTP error {0}: {1}")]
+  Status(u16, String),
+  #[error("{0}")]
+  Transport(String),
+  #[error("Malformed reply: {0:?}")]
+  Malformed(String),
+  #[error("Not supported by HTTP remotes")]
+  Unsupported,
+}
+
+/// An HTTP server for a pristine. Requests are handled one at a time.
+pub struct HttpServer {
+  server: Server,
+  authorized: Vec<PublicKey>,
+}
+
+/// Characters escaped in a path segment: all but the unreserved ones of
+/// RFC 3986.
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+  .remove(b'-')
+  .remove(b'.')
+  .remove(b'_')
+  .remove(b'~');
+
+/// A failed request: the status to reply with, and why.
+type Failure = (u16, String);
+
+fn internal<E: std::fmt::Display>(e: E) -> Failure {
+  (500, e.to_string())
+}
+
+impl HttpServer {
+  /// Listen on `addr`, such as `127.0.0.1:8490`. Port 0 picks a free
+  /// port, see `addr`.
+  pub fn bind(addr: &str) -> Result<Self, HttpError> {
+    let server = Server::http(addr).map_err(|e| HttpError::Bind(format!("{}: {}", addr, e)))?;
+    Ok(HttpServer {
+      server,
+      authorized: Vec::new(),
+    })
+  }
+
+  /// Accept the changes signed by one of `keys`.
+  pub fn with_authorized_keys(mut self, keys: Vec<PublicKey>) -> Self {
+    self.authorized = keys;
+    self
+  }
+
+  /// The address the server listens on.
+  pub fn addr(&self) -> String {
+    self.server.server_addr().to_string()
+  }
+
+  /// Serve `encyc`, whose changes are in `changes`, until `stop` is called.
+  /// A client going away before its reply is sent does not stop the
+  /// server.
+  pub fn serve<C: ChangeStore>(&self, encyc: &Encyc, changes: &C) -> Result<(), HttpError> {
+    for mut request in self.server.incoming_requests() {
+      let response = match handle(encyc, changes, &self.authorized, &mut request) {
+        Ok(body) => Response::from_data(body),
+        Err((status, msg)) => {
+          log::debug!("{} {}: {} {}", request.method(), request.url(), status, msg);
+          Response::from_data(msg.into_bytes()).with_status_code(status)
+        }
+      };
+      if let Err(e) = request.respond(response) {
+        log::warn!("Could not reply: {}", e);
+      }
+    }
+    Ok(())
+  }
+
+  /// Make `serve` return, once the request being handled is done.
+  pub fn stop(&self) {
+    self.server.unblock()
+  }
+}
+
+fn handle<C: ChangeStore>(encyc: &Encyc, changes: &C, authorized: &[PublicKey], request: &mut Request) -> Result<Vec<u8>, Failure> {
+  let url = request.url().to_string();
+  let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+  let segments = path
+    .trim_matches('/')
+    .split('/')
+    .map(|s| percent_decode_str(s).decode_utf8())
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|_| (400, format!("Invalid path: {}", path)))?;
+  let segments: Vec<&str> = segments.iter().map(|s| s.as_ref()).collect();
+  match (request.method(), segments.as_slice()) {
+    (Method::Get, ["spaces", space, "log"]) => {
+      let since = query
+        .split('&')
+        .find_map(|q| q.strip_prefix("since="))
+        .map(|s| Merkle::from_base32(s.as_bytes()).ok_or((400, format!("Invalid state: {}", s))))
+        .transpose()?;
+      serve_log(encyc, space, since)
+    }
+    (Method::Post, ["spaces", space, "changes"]) => {
+      let change = read_change(request)?;
+      serve_push(encyc, changes, authorized, space, &change)
+    }
+    (Method::Get | Method::Head, ["changes", hash]) => {
+      let hash = Hash::from_base32(hash.as_bytes()).ok_or((400, format!("Invalid hash: {}", hash)))?;
+      if !changes.has_change(&hash) {
+        return Err((404, format!("No such change: {}", hash.to_base32())));
+      }
+      let change = changes.get_change(&hash).map_err(internal)?;
+      let mut bytes = Vec::new();
+      change.serialize(&mut bytes).map_err(internal)?;
+      Ok(bytes)
+    }
+    (Method::Post, ["changes"]) => {
+      let change = read_change(request)?;
+      let hash = save_change(changes, authorized, &change)?;
+      Ok(format!("{}\n", hash.to_base32()).into_bytes())
+    }
+    _ => Err((404, format!("No route for {} {}", request.method(), path))),
+  }
+}
+
+fn read_change(request: &mut Request) -> Result<Change, Failure> {
+  let mut bytes = Vec::new();
+  request
+    .as_reader()
+    .take(MAX_CHANGE_SIZE as u64 + 1)
+    .read_to_end(&mut bytes)
+    .map_err(internal)?;
+  if bytes.len() > MAX_CHANGE_SIZE {
+    return Err((413, "Change too large".to_string()));
+  }
+  Change::deserialize(&bytes, None).map_err(|e| (400, e.to_string()))
+}
+
+/// Store `change` once its signatures are checked, and one of them is by
+/// an authorized key.
+fn save_change<C: ChangeStore>(changes: &C, authorized: &[PublicKey], change: &Change) -> Result<Hash, Failure> {
+  let hash = change.hash().map_err(|e| (400, e.to_string()))?;
+  let signers = change
+    .check_signatures(&hash)
+    .map_err(|e| (403, e.to_string()))?;
+  if !signers.iter().any(|k| authorized.contains(k)) {
+    return Err((
+      403,
+      format!(
+        "Change {} is not signed by an authorized key",
+        hash.to_base32()
+      ),
+    ));
+  }
+  changes.save_change(change).map_err(internal)?;
+  Ok(hash)
+}
+
+fn serve_log(encyc: &Encyc, name: &str, since: Option<Merkle>) -> Result<Vec<u8>, Failure> {
+  let txn = encyc.txn_begin().map_err(internal)?;
+  let mut reply = String::new();
+  if let Some(space) = txn.load_space(name).map_err(internal)? {
+    let log = space_log(&txn, &space).map_err(internal)?;
+    let start = since
+      .and_then(|since| log.iter().position(|e| e.state == since))
+      .map_or(0, |n| n + 1);
+    for (n, e) in log.iter().enumerate().skip(start) {
+      reply.push_str(&format!("{} {}\n", n, e.to_line()));
+    }
+  }
+  Ok(reply.into_bytes())
+}
+
+/// Store `change` and apply it to space `name`. A change refused by the
+/// space is not kept, unless it was stored before this push, since other
+/// spaces may have applied it.
+fn serve_push<C: ChangeStore>(encyc: &Encyc, changes: &C, authorized: &[PublicKey], name: &str, change: &Change) -> Result<Vec<u8>, Failure> {
+  let known = change.hash().is_ok_and(|hash| changes.has_change(&hash));
+  let hash = save_change(changes, authorized, change)?;
+  match push_to_space(encyc, changes, name, &hash) {
+    Ok(state) => Ok(format!("{}\n", state.to_base32()).into_bytes()),
+    Err(e) => {
+      if !known {
+        changes.del_change(&hash).map_err(internal)?;
+      }
+      Err(e)
+    }
+  }
+}
+
+fn push_to_space<C: ChangeStore>(encyc: &Encyc, changes: &C, name: &str, hash: &Hash) -> Result<Merkle, Failure> {
+  let mut txn = encyc.mut_txn_begin().map_err(internal)?;
+  let space = txn
+    .open_or_create_space(name)
+    .map_err(|e| (400, e.to_string()))?;
+  apply_change(changes, &mut txn, &space, hash).map_err(|e| (409, e.to_string()))?;
+  let state = txn.current_state(&space).map_err(internal)?;
+  txn.commit().map_err(internal)?;
+  Ok(state)
+}
+
+/// The client side: a pristine served at `url`. It is also the change
+/// store of that pristine, so that pulling is a `sync::transfer` from it.
+pub struct HttpClient {
+  url: String,
+  agent: ureq::Agent,
+}
+
+impl HttpClient {
+  /// `url` is the root of the server, such as `http://box:8490`.
+  pub fn new(url: &str) -> Self {
+    HttpClient {
+      url: url.trim_end_matches('/').to_string(),
+      agent: ureq::Agent::new(),
+    }
+  }
+
+  /// The log of remote space `space`, after the change that reached
+  /// `since`, or the whole log if the remote does not know `since`.
+  pub fn log(&self, space: &str, since: Option<&Merkle>) -> Result<Vec<LogEntry>, HttpError> {
+    let mut request = self
+      .agent
+      .get(&format!("{}/spaces/{}/log", self.url, segment(space)));
+    if let Some(since) = since {
+      request = request.query("since", &since.to_base32());
+    }
+    let body = read_string(call(request.call())?)?;
+    body
+      .lines()
+      .map(|line| {
+        line
+          .split_once(' ')
+          .and_then(|(_, e)| LogEntry::from_line(e))
+          .ok_or_else(|| HttpError::Malformed(line.to_string()))
+      })
+      .collect()
+  }
+
+  /// Send `change` and apply it to remote space `space`. Returns the new
+  /// state of the space.
+  pub fn push(&self, space: &str, change: &Change) -> Result<Merkle, HttpError> {
+    let mut bytes = Vec::new();
+    change.serialize(&mut bytes)?;
+    let response = call(
+      self
+        .agent
+        .post(&format!("{}/spaces/{}/changes", self.url, segment(space)))
+        .send_bytes(&bytes),
+    )?;
+    let body = read_string(response)?;
+    Merkle::from_base32(body.trim().as_bytes()).ok_or(HttpError::Malformed(body))
+  }
+
+  fn change_url(&self, hash: &Hash) -> String {
+    format!("{}/changes/{}", self.url, hash.to_base32())
+  }
+}
+
+fn segment(s: &str) -> String {
+  utf8_percent_encode(s, SEGMENT).to_string()
+}
+
+/// Turn error statuses into `HttpError::Status`, with the message sent by
+/// the server.
+fn call(result: Result<ureq::Response, ureq::Error>) -> Result<ureq::Response, HttpError> {
+  match result {
+    Ok(response) => Ok(response),
+    Err(ureq::Error::Status(status, response)) => Err(HttpError::Status(
+      status,
+      response.into_string().unwrap_or_default(),
+    )),
+    Err(e) => Err(HttpError::Transport(e.to_string())),
+  }
+}
+
+fn read_string(response: ureq::Response) -> Result<String, HttpError> {
+  let mut body = String::new();
+  response
+    .into_reader()
+    .take(MAX_CHANGE_SIZE as u64)
+    .read_to_string(&mut body)?;
+  Ok(body)
+}
+
+impl ChangeStore for HttpClient {
+  type Error = HttpError;
+
+  fn has_change(&self, hash: &Hash) -> bool {
+    call(self.agent.head(&self.change_url(hash)).call()).is_ok()
+  }
+
+  fn get_change(&self, hash: &Hash) -> Result<Change, Self::Error> {
+    let response = call(self.agent.get(&self.change_url(hash)).call())?;
+    let mut bytes = Vec::new();
+    response
+      .into_reader()
+      .take(MAX_CHANGE_SIZE as u64)
+      .read_to_end(&mut bytes)?;
+    Ok(Change::deserialize(&bytes, Some(hash))?)
+  }
+
+  fn save_change(&self, change: &Change) -> Result<Hash, Self::Error> {
+    let mut bytes = Vec::new();
+    let hash = change.serialize(&mut bytes)?;
+    let response = call(
+      self
+        .agent
+        .post(&format!("{}/changes", self.url))
+        .send_bytes(&bytes),
+    )?;
+    let body = read_string(response)?;
+    if body.trim() != hash.to_base32() {
+      return Err(HttpError::Malformed(body));
+    }
+    Ok(hash)
+  }
+
+  fn del_change(&self, _: &Hash) -> Result<bool, Self::Error> {
+    Err(HttpError::Unsupported)
+  }
+}
@@ -0,0 +1,90 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  key::{PublicKey, AUTHOR_KEY},
+  models::Author,
+  types::UId,
+};
+
+/// Field of an `Author` holding the stable id of its identity.
+pub const AUTHOR_ID: &str = "id";
+/// Field of an `Author` holding its display name.
+pub const AUTHOR_NAME: &str = "name";
+/// Field of an `Author` holding its email address.
+pub const AUTHOR_EMAIL: &str = "email";
+
+/// A person recording changes. Identities live outside of repositories,
+/// so that the same person is recognized in all of them by `id`, which
+/// never changes even if the name, email or key do.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+  pub id: String,
+  pub display_name: String,
+  pub email: Option<String>,
+  pub public_key: Option<PublicKey>,
+  pub created: DateTime<Utc>,
+}
+
+impl Identity {
+  pub fn new(display_name: String, email: Option<String>, public_key: Option<PublicKey>) -> Self {
+    Identity {
+      id: UId::new().to_string(),
+      display_name,
+      email,
+      public_key,
+      created: Utc::now(),
+    }
+  }
+
+  /// The author recorded in the header of changes made by this identity.
+  pub fn to_author(&self) -> Author {
+    let mut author = BTreeMap::new();
+    author.insert(AUTHOR_ID.to_string(), self.id.clone());
+    author.insert(AUTHOR_NAME.to_string(), self.display_name.clone());
+    if let Some(ref email) = self.email {
+      author.insert(AUTHOR_EMAIL.to_string(), email.clone());
+    }
+    if let Some(ref key) = self.public_key {
+      author.insert(AUTHOR_KEY.to_string(), key.to_string());
+    }
+    Author(author)
+  }
+}
+
+impl Author {
+  pub fn id(&self) -> Option<&str> {
+    self.0.get(AUTHOR_ID).map(String::as_str)
+  }
+
+  pub fn name(&self) -> Option<&str> {
+    self.0.get(AUTHOR_NAME).map(String::as_str)
+  }
+
+  pub fn email(&self) -> Option<&str> {
+    self.0.get(AUTHOR_EMAIL).map(String::as_str)
+  }
+
+  pub fn key(&self) -> Option<&str> {
+    self.0.get(AUTHOR_KEY).map(String::as_str)
+  }
+}
+
+impl std::fmt::Display for Author {
+  fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match (self.name(), self.email()) {
+      (Some(name), Some(email)) => write!(fmt, "{} <{}>", name, email),
+      (Some(name), None) => write!(fmt, "{}", name),
+      (None, Some(email)) => write!(fmt, "<{}>", email),
+      (None, None) => {
+        let fields: Vec<_> = self.0.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        write!(fmt, "{}", fields.join(" "))
+      }
+    }
+  }
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+  types::{Base32, Hash, BASE32},
+  ParseError,
+};
+
+/// Field of an `Author` holding the base32 public key the author signs
+/// changes with.
+pub const AUTHOR_KEY: &str = "key";
+
+/// An ed25519 secret key. It never goes into the pristine nor into
+/// changes: only signatures made with it do.
+#[derive(Clone)]
+pub struct SecretKey(SigningKey);
+
+impl SecretKey {
+  pub fn generate() -> Self {
+    SecretKey(SigningKey::generate(&mut rand::rngs::OsRng))
+  }
+
+  pub fn public_key(&self) -> PublicKey {
+    PublicKey(self.0.verifying_key())
+  }
+
+  /// Sign the hash of a change.
+  pub fn sign(&self, hash: &Hash) -> Signature {
+    Signature(self.0.sign(&hash.to_bytes()).to_bytes())
+  }
+}
+
+impl Base32 for SecretKey {
+  fn to_base32(&self) -> String {
+    BASE32.encode(self.0.as_bytes())
+  }
+
+  fn from_base32(b: &[u8]) -> Option<Self> {
+    let bytes: [u8; 32] = BASE32.decode(b).ok()?.try_into().ok()?;
+    Some(SecretKey(SigningKey::from_bytes(&bytes)))
+  }
+}
+
+impl std::fmt::Debug for SecretKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "SecretKey({:?})", self.public_key())
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(VerifyingKey);
+
+impl PublicKey {
+  /// Check that `signature` was made by this key over `hash`.
+  pub fn verify(&self, hash: &Hash, signature: &Signature) -> bool {
+    let signature = ed25519_dalek::Signature::from_bytes(&signature.0);
+    self.0.verify(&hash.to_bytes(), &signature).is_ok()
+  }
+}
+
+impl Base32 for PublicKey {
+  fn to_base32(&self) -> String {
+    BASE32.encode(self.0.as_bytes())
+  }
+
+  fn from_base32(b: &[u8]) -> Option<Self> {
+    let bytes: [u8; 32] = BASE32.decode(b).ok()?.try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok().map(PublicKey)
+  }
+}
+
+impl std::fmt::Debug for PublicKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{:?}", self.to_base32())
+  }
+}
+
+impl std::fmt::Display for PublicKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    f.write_str(&self.to_base32())
+  }
+}
+
+impl std::str::FromStr for PublicKey {
+  type Err = ParseError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::from_base32(s.as_bytes()).ok_or_else(|| ParseError { s: s.to_string() })
+  }
+}
+
+impl Serialize for PublicKey {
+  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&self.to_base32())
+  }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+  fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    let s = String::deserialize(d)?;
+    s.parse().map_err(de::Error::custom)
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Signature(pub [u8; 64]);
+
+impl std::fmt::Debug for Signature {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "Signature({})", BASE32.encode(&self.0))
+  }
+}
+
+impl Serialize for Signature {
+  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_bytes(&self.0)
+  }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+  fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    let bytes = Vec::<u8>::deserialize(d)?;
+    let len = bytes.len();
+    let bytes: [u8; 64] = bytes
+      .try_into()
+      .map_err(|_| de::Error::invalid_length(len, &"64 bytes"))?;
+    Ok(Signature(bytes))
+  }
+}
@@ -4,7 +4,19 @@
 mod errors;
 pub use errors::*;
 
-mod models;
+pub mod apply;
+pub mod budget;
+pub mod change;
+pub mod changestore;
+pub mod history;
+pub mod http;
+pub mod identity;
+pub mod key;
+pub mod models;
 pub mod pristine;
-mod traits;
+pub mod protocol;
+pub mod record;
+pub mod sync;
+pub mod traits;
 pub mod types;
+pub mod unrecord;
@@ -1,15 +1,361 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 24.
-use crate::types::{SmallString, UId, L64};
 
-#[derive(Debug, PartialOrd, Ord, PartialEq, Eq)]
+mod prelude;
+pub use prelude::*;
+
+use chrono::Utc;
+use sanakirja::{btree, LoadPage, RootPage};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  models::{
+    entry::{EntryError, InvalidEntry, Posting},
+    space::SpaceRef,
+  },
+  pristine::{check_name, types::Db, EncycError, GenericTxn, MutTxn},
+  types::{ChangeId, Currency, Money, MoneyError, SerializedMoney, SmallString, UId, L64},
+  ParseError,
+};
+
+/// What a compartment accounts for, as in a chart of accounts. Amounts
+/// moved into a compartment are positive, so assets and expenses normally
+/// have positive balances, and liabilities, equity and income negative
+/// ones.
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum CompartmentKind {
+  Asset,
+  Liability,
+  Equity,
+  Income,
+  Expense,
+}
+
+impl CompartmentKind {
+  /// The sign of a normal balance: 1 for assets and expenses, -1 for the
+  /// others.
+  pub fn sign(&self) -> i64 {
+    match self {
+      CompartmentKind::Asset | CompartmentKind::Expense => 1,
+      CompartmentKind::Liability | CompartmentKind::Equity | CompartmentKind::Income => -1,
+    }
+  }
+
+  /// Whether a compartment of this kind may have balance `balance`.
+  /// Income and expenses only add up, refunds aside, so their balances
+  /// never go against their normal sign. Assets, liabilities and equity
+  /// may: an overdrawn account, a prepaid card, a deficit.
+  pub fn allows(&self, balance: &Money) -> bool {
+    match self {
+      CompartmentKind::Income | CompartmentKind::Expense => balance.amount * self.sign() >= 0,
+      _ => true,
+    }
+  }
+}
+
+impl std::fmt::Display for CompartmentKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let s = match self {
+      CompartmentKind::Asset => "asset",
+      CompartmentKind::Liability => "liability",
+      CompartmentKind::Equity => "equity",
+      CompartmentKind::Income => "income",
+      CompartmentKind::Expense => "expense",
+    };
+    f.write_str(s)
+  }
+}
+
+impl std::str::FromStr for CompartmentKind {
+  type Err = ParseError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "asset" => Ok(CompartmentKind::Asset),
+      "liability" => Ok(CompartmentKind::Liability),
+      "equity" => Ok(CompartmentKind::Equity),
+      "income" => Ok(CompartmentKind::Income),
+      "expense" => Ok(CompartmentKind::Expense),
+      _ => Err(ParseError { s: s.to_string() }),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
 pub struct SerializedCompartment {
-  entries: L64,
-  counter: L64, // counter for entries
-  last_modified: L64,
-  balance: L64,
-  name: SmallString,
-  id: UId,
+  pub entries: L64, // entries posting to the compartment, with their number of postings
+  pub counter: L64, // counter for entries
+  pub last_modified: L64,
+  pub closed: L64, // when the compartment was closed, 0 if it is open
+  pub balance: SerializedMoney,
+  pub vault: UId,  // `UId::nil()` if the compartment is in no vault
+  pub parent: UId, // `UId::nil()` for the root of a tree
+  pub kind: CompartmentKind,
+  pub name: SmallString,
+  pub id: UId,
+}
+
+/// Deepest chain of parents followed, in case the tree is corrupted.
+const MAX_DEPTH: usize = 64;
+
+impl SerializedCompartment {
+  pub fn is_closed(&self) -> bool {
+    self.closed.as_u64() != 0
+  }
+
+  pub fn vault(&self) -> Option<UId> {
+    (self.vault != UId::nil()).then_some(self.vault)
+  }
+
+  pub fn parent(&self) -> Option<UId> {
+    (self.parent != UId::nil()).then_some(self.parent)
+  }
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> CompartmentTxnT for GenericTxn<T> {
+  fn get_compartment(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Option<&SerializedCompartment>, Self::GraphError> {
+    let space = space.read();
+    match btree::get(&self.txn, &space.compartments, id, None)? {
+      Some((k, v)) if k == id => Ok(Some(v)),
+      _ => Ok(None),
+    }
+  }
+
+  fn list_compartments(&self, space: &SpaceRef<Self>) -> Result<Vec<&SerializedCompartment>, Self::GraphError> {
+    let space = space.read();
+    let mut compartments = Vec::new();
+    for x in btree::iter(&self.txn, &space.compartments, None)? {
+      let (_, c) = x?;
+      compartments.push(c);
+    }
+    Ok(compartments)
+  }
+
+  fn compartment_by_name(&self, space: &SpaceRef<Self>, name: &str) -> Result<Option<&SerializedCompartment>, Self::GraphError> {
+    let space = space.read();
+    for x in btree::iter(&self.txn, &space.compartments, None)? {
+      let (_, c) = x?;
+      if c.name.as_str() == name {
+        return Ok(Some(c));
+      }
+    }
+    Ok(None)
+  }
+
+  fn children(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Vec<UId>, Self::GraphError> {
+    let space = space.read();
+    let mut children = Vec::new();
+    for x in btree::iter(&self.txn, &space.compartments, None)? {
+      let (_, c) = x?;
+      if c.parent == *id && c.id != *id {
+        children.push(c.id);
+      }
+    }
+    Ok(children)
+  }
+
+  fn descendants(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Vec<UId>, Self::GraphError> {
+    let mut descendants = Vec::new();
+    let mut stack = vec![*id];
+    while let Some(id) = stack.pop() {
+      for child in self.children(space, &id)? {
+        // A cycle can't be recorded, but don't loop on a corrupted tree.
+        if !descendants.contains(&child) {
+          descendants.push(child);
+          stack.push(child);
+        }
+      }
+    }
+    Ok(descendants)
+  }
+
+  fn compartment_path(&self, space: &SpaceRef<Self>, id: &UId) -> Result<String, Self::GraphError> {
+    let mut names = Vec::new();
+    let mut next = Some(*id);
+    while let Some(id) = next {
+      let Some(c) = self.get_compartment(space, &id)? else {
+        break;
+      };
+      names.push(c.name.as_str());
+      next = c.parent().filter(|_| names.len() <= MAX_DEPTH);
+    }
+    names.reverse();
+    Ok(names.join(":"))
+  }
+
+  fn total_balance(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Option<Money>, EntryError<Self::GraphError>> {
+    let Some(c) = self.get_compartment(space, id)? else {
+      return Ok(None);
+    };
+    let mut total = Money::from(c.balance);
+    for d in self.descendants(space, id)? {
+      if let Some(d) = self.get_compartment(space, &d)? {
+        total = total
+          .checked_add(&d.balance.into())
+          .map_err(|e| EntryError::Invalid(e.into()))?;
+      }
+    }
+    Ok(Some(total))
+  }
+
+  fn compartment_entries(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Vec<ChangeId>, Self::GraphError> {
+    let Some(c) = self.get_compartment(space, id)? else {
+      return Ok(Vec::new());
+    };
+    let db: Db<ChangeId, L64> = unsafe { Db::from_page(c.entries.into()) };
+    let mut entries = Vec::new();
+    for x in btree::iter(&self.txn, &db, None)? {
+      let (id, _) = x?;
+      entries.push(*id);
+    }
+    Ok(entries)
+  }
+}
+
+impl MutTxn<()> {
+  fn put_compartment(&mut self, space: &SpaceRef<Self>, c: &SerializedCompartment) -> Result<(), EncycError> {
+    let mut space = space.write();
+    btree::del(&mut self.txn, &mut space.compartments, &c.id, None)?;
+    btree::put(&mut self.txn, &mut space.compartments, &c.id, c)?;
+    Ok(())
+  }
+
+  /// Count the postings of entry `id` in the balances of their
+  /// compartments in `space`, or with `undo`, stop counting them. Called whenever an
+  /// entry is written, so that balances change in the same transaction.
+  /// The currencies of the postings were checked against the compartments.
+  /// Fails if a balance would overflow, leaving the transaction to be
+  /// aborted.
+  pub(crate) fn post(&mut self, space: &SpaceRef<Self>, id: &ChangeId, postings: &[Posting], undo: bool) -> Result<(), EntryError<EncycError>> {
+    let invalid = |e: MoneyError| EntryError::Invalid(InvalidEntry::Money(e));
+    for p in postings.iter() {
+      let Some(mut c) = self.get_compartment(space, &p.compartment)?.cloned() else {
+        continue;
+      };
+      let mut entries: Db<ChangeId, L64> = unsafe { Db::from_page(c.entries.into()) };
+      let count = match btree::get(&self.txn, &entries, id, None)? {
+        Some((k, n)) if k == id => n.as_u64(),
+        _ => 0,
+      };
+      btree::del(&mut self.txn, &mut entries, id, None)?;
+      let (count, counter, amount) = if undo {
+        (
+          count.saturating_sub(1),
+          c.counter.as_u64().saturating_sub(1),
+          p.amount.checked_neg().map_err(invalid)?,
+        )
+      } else {
+        (count + 1, c.counter.as_u64() + 1, p.amount)
+      };
+      if count > 0 {
+        btree::put(&mut self.txn, &mut entries, id, &count.into())?;
+      }
+      c.balance = Money::from(c.balance)
+        .checked_add(&amount)
+        .map_err(invalid)?
+        .into();
+      c.entries = entries.db.get().into();
+      c.counter = counter.into();
+      c.last_modified = (Utc::now().timestamp() as u64).into();
+      self.put_compartment(space, &c)?;
+    }
+    Ok(())
+  }
+}
+
+impl CompartmentMutTxnT for MutTxn<()> {
+  fn create_compartment(&mut self, space: &SpaceRef<Self>, id: UId, name: &str, currency: Currency) -> Result<(), Self::GraphError> {
+    check_name(name)?;
+    if self.compartment_by_name(space, name)?.is_some() {
+      return Err(EncycError::AlreadyExists(name.to_string()));
+    }
+    if self.get_compartment(space, &id)?.is_some() {
+      return Err(EncycError::AlreadyExists(id.to_string()));
+    }
+    let entries: Db<ChangeId, L64> = unsafe { btree::create_db_(&mut self.txn)? };
+    let compartment = SerializedCompartment {
+      entries: entries.db.get().into(),
+      counter: 0u64.into(),
+      last_modified: (Utc::now().timestamp() as u64).into(),
+      closed: 0u64.into(),
+      balance: Money::zero(currency).into(),
+      vault: UId::nil(),
+      parent: UId::nil(),
+      kind: CompartmentKind::Asset,
+      name: SmallString::from_str(name),
+      id,
+    };
+    self.put_compartment(space, &compartment)?;
+    Ok(())
+  }
+
+  fn del_compartment(&mut self, space: &SpaceRef<Self>, id: &UId) -> Result<bool, Self::GraphError> {
+    let Some(c) = self.get_compartment(space, id)?.cloned() else {
+      return Ok(false);
+    };
+    let entries: Db<ChangeId, L64> = unsafe { Db::from_page(c.entries.into()) };
+    unsafe { btree::drop(&mut self.txn, entries)? };
+    btree::del(&mut self.txn, &mut space.write().compartments, id, None)?;
+    Ok(true)
+  }
+
+  fn rename_compartment(&mut self, space: &SpaceRef<Self>, id: &UId, name: &str) -> Result<bool, Self::GraphError> {
+    check_name(name)?;
+    let Some(mut c) = self.get_compartment(space, id)?.cloned() else {
+      return Ok(false);
+    };
+    if self
+      .compartment_by_name(space, name)?
+      .is_some_and(|other| other.id != *id)
+    {
+      return Err(EncycError::AlreadyExists(name.to_string()));
+    }
+    c.name = SmallString::from_str(name);
+    c.last_modified = (Utc::now().timestamp() as u64).into();
+    self.put_compartment(space, &c)?;
+    Ok(true)
+  }
+
+  fn set_compartment_closed(&mut self, space: &SpaceRef<Self>, id: &UId, closed: bool) -> Result<bool, Self::GraphError> {
+    let Some(mut c) = self.get_compartment(space, id)?.cloned() else {
+      return Ok(false);
+    };
+    let now = Utc::now().timestamp() as u64;
+    c.closed = if closed { now.max(1) } else { 0 }.into();
+    c.last_modified = now.into();
+    self.put_compartment(space, &c)?;
+    Ok(true)
+  }
+
+  fn set_compartment_kind(&mut self, space: &SpaceRef<Self>, id: &UId, kind: CompartmentKind) -> Result<bool, Self::GraphError> {
+    let Some(mut c) = self.get_compartment(space, id)?.cloned() else {
+      return Ok(false);
+    };
+    c.kind = kind;
+    c.last_modified = (Utc::now().timestamp() as u64).into();
+    self.put_compartment(space, &c)?;
+    Ok(true)
+  }
+
+  fn set_compartment_parent(&mut self, space: &SpaceRef<Self>, id: &UId, parent: Option<UId>) -> Result<bool, Self::GraphError> {
+    let Some(mut c) = self.get_compartment(space, id)?.cloned() else {
+      return Ok(false);
+    };
+    c.parent = parent.unwrap_or(UId::nil());
+    c.last_modified = (Utc::now().timestamp() as u64).into();
+    self.put_compartment(space, &c)?;
+    Ok(true)
+  }
+
+  fn set_compartment_vault(&mut self, space: &SpaceRef<Self>, id: &UId, vault: Option<UId>) -> Result<bool, Self::GraphError> {
+    let Some(mut c) = self.get_compartment(space, id)?.cloned() else {
+      return Ok(false);
+    };
+    c.vault = vault.unwrap_or(UId::nil());
+    c.last_modified = (Utc::now().timestamp() as u64).into();
+    self.put_compartment(space, &c)?;
+    Ok(true)
+  }
 }
 // #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
 // #[repr(C)]
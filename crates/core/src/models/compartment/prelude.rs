@@ -0,0 +1,54 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use crate::{
+  models::{
+    entry::EntryError,
+    graph::GraphTxnT,
+    space::{SpaceRef, SpaceTxnT},
+  },
+  types::{ChangeId, Currency, Money, UId},
+};
+
+use super::{CompartmentKind, SerializedCompartment};
+
+pub trait CompartmentTxnT: GraphTxnT + SpaceTxnT {
+  fn get_compartment(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Option<&SerializedCompartment>, Self::GraphError>;
+  fn list_compartments(&self, space: &SpaceRef<Self>) -> Result<Vec<&SerializedCompartment>, Self::GraphError>;
+  fn compartment_by_name(&self, space: &SpaceRef<Self>, name: &str) -> Result<Option<&SerializedCompartment>, Self::GraphError>;
+  /// The compartments whose parent is `id`.
+  fn children(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Vec<UId>, Self::GraphError>;
+  /// The compartments below `id` in its tree, parents before children.
+  fn descendants(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Vec<UId>, Self::GraphError>;
+  /// The names of `id` and its ancestors from the root down, separated by
+  /// `:`, as in `Assets:Bank:Checking`.
+  fn compartment_path(&self, space: &SpaceRef<Self>, id: &UId) -> Result<String, Self::GraphError>;
+  /// The balance of `id` plus those of its descendants, which hold the
+  /// same currency. Fails if the sum overflows.
+  fn total_balance(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Option<Money>, EntryError<Self::GraphError>>;
+  /// Ids of the entries posting to compartment `id`.
+  fn compartment_entries(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Vec<ChangeId>, Self::GraphError>;
+}
+
+pub trait CompartmentMutTxnT: CompartmentTxnT {
+  /// Create a compartment of `space` holding amounts in `currency`. Names
+  /// are unique within a space.
+  fn create_compartment(&mut self, space: &SpaceRef<Self>, id: UId, name: &str, currency: Currency) -> Result<(), Self::GraphError>;
+  /// Returns `false` if `space` had no such compartment.
+  fn del_compartment(&mut self, space: &SpaceRef<Self>, id: &UId) -> Result<bool, Self::GraphError>;
+  /// Returns `false` if there was no such compartment.
+  fn rename_compartment(&mut self, space: &SpaceRef<Self>, id: &UId, name: &str) -> Result<bool, Self::GraphError>;
+  /// Close or reopen compartment `id`. Whether a closed compartment can
+  /// still be posted to is up to the caller. Returns `false` if there was
+  /// no such compartment.
+  fn set_compartment_closed(&mut self, space: &SpaceRef<Self>, id: &UId, closed: bool) -> Result<bool, Self::GraphError>;
+  /// Returns `false` if there was no such compartment.
+  fn set_compartment_kind(&mut self, space: &SpaceRef<Self>, id: &UId, kind: CompartmentKind) -> Result<bool, Self::GraphError>;
+  /// Put compartment `id` under `parent`, or make it the root of a tree.
+  /// The caller checks that this makes no cycle. Returns `false` if there
+  /// was no such compartment.
+  fn set_compartment_parent(&mut self, space: &SpaceRef<Self>, id: &UId, parent: Option<UId>) -> Result<bool, Self::GraphError>;
+  /// Move compartment `id` into `vault`, or out of any vault. Returns
+  /// `false` if there was no such compartment.
+  fn set_compartment_vault(&mut self, space: &SpaceRef<Self>, id: &UId, vault: Option<UId>) -> Result<bool, Self::GraphError>;
+}
@@ -0,0 +1,123 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Conflicts between changes made concurrently to the same entry.
+//!
+//! An edit is made against the content of the entry it was recorded on.
+//! If that content changed meanwhile, fields edited on one side only are
+//! merged; if both sides edited the same field differently, or one side
+//! deleted the entry, the entry keeps its content and the competing
+//! changes are recorded here until a change that depends on all of them
+//! resolves the conflict. Amounts are never silently overwritten. Conflicts
+//! belong to the space they arose in.
+
+mod prelude;
+pub use prelude::*;
+
+use sanakirja::{btree, LoadPage, RootPage};
+
+use crate::{
+  models::{entry::Entry, space::SpaceRef},
+  pristine::{GenericTxn, MutTxn},
+  types::ChangeId,
+};
+
+/// Three-way merge of an edit from `old` to `new` into `current`, the
+/// content of the entry when the edit is applied. Returns `None` if a
+/// field was changed differently on both sides. The postings are a single
+/// field, so that a merge always balances.
+pub fn merge(old: &Entry, current: &Entry, new: &Entry) -> Option<Entry> {
+  fn pick<T: PartialEq + Clone>(old: &T, current: &T, new: &T) -> Option<T> {
+    if new == old || new == current {
+      Some(current.clone())
+    } else if current == old {
+      Some(new.clone())
+    } else {
+      None
+    }
+  }
+  Some(Entry {
+    date: pick(&old.date, &current.date, &new.date)?,
+    payee: pick(&old.payee, &current.payee, &new.payee)?,
+    memo: pick(&old.memo, &current.memo, &new.memo)?,
+    postings: pick(&old.postings, &current.postings, &new.postings)?,
+  })
+}
+
+/// Undo, in `current`, an edit from `old` to `new` that was merged into
+/// it: the fields the edit changed, and that still have the value it gave
+/// them, go back to `old`. The others, merged in from concurrent edits or
+/// changed since, are kept.
+pub fn unmerge(old: &Entry, current: &Entry, new: &Entry) -> Entry {
+  fn pick<T: PartialEq + Clone>(old: &T, current: &T, new: &T) -> T {
+    if new != old && current == new {
+      old.clone()
+    } else {
+      current.clone()
+    }
+  }
+  Entry {
+    date: pick(&old.date, &current.date, &new.date),
+    payee: pick(&old.payee, &current.payee, &new.payee),
+    memo: pick(&old.memo, &current.memo, &new.memo),
+    postings: pick(&old.postings, &current.postings, &new.postings),
+  }
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> ConflictTxnT for GenericTxn<T> {
+  fn entry_conflicts(&self, space: &SpaceRef<Self>, entry: &ChangeId) -> Result<Vec<ChangeId>, Self::GraphError> {
+    let space = space.read();
+    let mut changes = Vec::new();
+    for x in btree::iter(&self.txn, &space.conflicts, Some((entry, None)))? {
+      let (k, v) = x?;
+      if k != entry {
+        break;
+      }
+      changes.push(*v);
+    }
+    Ok(changes)
+  }
+
+  fn list_conflicts(&self, space: &SpaceRef<Self>) -> Result<Vec<(ChangeId, Vec<ChangeId>)>, Self::GraphError> {
+    let space = space.read();
+    let mut conflicts: Vec<(ChangeId, Vec<ChangeId>)> = Vec::new();
+    for x in btree::iter(&self.txn, &space.conflicts, None)? {
+      let (k, v) = x?;
+      match conflicts.last_mut() {
+        Some((entry, changes)) if entry == k => changes.push(*v),
+        _ => conflicts.push((*k, vec![*v])),
+      }
+    }
+    Ok(conflicts)
+  }
+}
+
+impl ConflictMutTxnT for MutTxn<()> {
+  fn add_conflict(&mut self, space: &SpaceRef<Self>, entry: &ChangeId, change: &ChangeId) -> Result<(), Self::GraphError> {
+    if !self.entry_conflicts(space, entry)?.contains(change) {
+      btree::put(&mut self.txn, &mut space.write().conflicts, entry, change)?;
+    }
+    Ok(())
+  }
+
+  fn del_conflict(&mut self, space: &SpaceRef<Self>, entry: &ChangeId, change: &ChangeId) -> Result<bool, Self::GraphError> {
+    Ok(btree::del(
+      &mut self.txn,
+      &mut space.write().conflicts,
+      entry,
+      Some(change),
+    )?)
+  }
+
+  fn clear_conflicts(&mut self, space: &SpaceRef<Self>, entry: &ChangeId) -> Result<(), Self::GraphError> {
+    for change in self.entry_conflicts(space, entry)? {
+      btree::del(
+        &mut self.txn,
+        &mut space.write().conflicts,
+        entry,
+        Some(&change),
+      )?;
+    }
+    Ok(())
+  }
+}
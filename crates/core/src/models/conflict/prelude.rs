@@ -0,0 +1,28 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use crate::{
+  models::{
+    graph::GraphTxnT,
+    space::{SpaceRef, SpaceTxnT},
+  },
+  types::ChangeId,
+};
+
+pub trait ConflictTxnT: GraphTxnT + SpaceTxnT {
+  /// The changes competing in `space` for the content of entry `entry`, or
+  /// nothing if it is not in conflict there.
+  fn entry_conflicts(&self, space: &SpaceRef<Self>, entry: &ChangeId) -> Result<Vec<ChangeId>, Self::GraphError>;
+  /// Every entry in conflict in `space`, with its competing changes.
+  fn list_conflicts(&self, space: &SpaceRef<Self>) -> Result<Vec<(ChangeId, Vec<ChangeId>)>, Self::GraphError>;
+}
+
+pub trait ConflictMutTxnT: ConflictTxnT {
+  /// Add `change` to the changes competing in `space` for `entry`.
+  fn add_conflict(&mut self, space: &SpaceRef<Self>, entry: &ChangeId, change: &ChangeId) -> Result<(), Self::GraphError>;
+  /// Remove `change` from the changes competing in `space` for `entry`.
+  /// Returns `false` if it was not one of them.
+  fn del_conflict(&mut self, space: &SpaceRef<Self>, entry: &ChangeId, change: &ChangeId) -> Result<bool, Self::GraphError>;
+  /// Mark `entry` as no longer in conflict in `space`.
+  fn clear_conflicts(&mut self, space: &SpaceRef<Self>, entry: &ChangeId) -> Result<(), Self::GraphError>;
+}
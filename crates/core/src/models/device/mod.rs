@@ -0,0 +1,173 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod prelude;
+pub use prelude::*;
+
+use chrono::{DateTime, Utc};
+use sanakirja::{btree, LoadPage, RootPage};
+
+use crate::{
+  models::Device,
+  pristine::{check_name, EncycError, GenericTxn, MutTxn},
+  types::{SmallString, L64, MAX_LEN},
+};
+
+/// A device that wrote to this pristine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredDevice {
+  pub device: Device,
+  /// When the device was revoked. Changes made on it that the pristine
+  /// did not know by then are refused.
+  pub revoked: Option<DateTime<Utc>>,
+}
+
+impl RegisteredDevice {
+  pub fn is_revoked(&self) -> bool {
+    self.revoked.is_some()
+  }
+}
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedDevice {
+  pub created_at: L64,
+  pub last_access: L64,
+  pub revoked: L64, // 0 if the device is not revoked
+  pub alias: SmallString,
+  /// Platform, OS and OS version, separated by newlines, to keep the
+  /// record small enough for a page.
+  pub system: SmallString,
+}
+
+fn timestamp(t: L64) -> DateTime<Utc> {
+  DateTime::from_timestamp(t.as_u64() as i64, 0).unwrap_or_default()
+}
+
+/// Longest prefix of `s` that fits in a `SmallString`.
+fn truncate(s: &str) -> &str {
+  let mut end = s.len().min(MAX_LEN);
+  while !s.is_char_boundary(end) {
+    end -= 1
+  }
+  &s[..end]
+}
+
+impl SerializedDevice {
+  pub fn to_device(&self, name: &str) -> RegisteredDevice {
+    let mut system = self.system.as_str().splitn(3, '\n');
+    let mut field = || system.next().filter(|s| !s.is_empty()).map(String::from);
+    let (platform, os, os_version) = (field(), field(), field());
+    RegisteredDevice {
+      device: Device {
+        name: name.to_string(),
+        alias: (!self.alias.is_empty()).then(|| self.alias.as_str().to_string()),
+        created_at: timestamp(self.created_at),
+        last_access: timestamp(self.last_access),
+        raw: None,
+        platform,
+        os,
+        os_version,
+      },
+      revoked: (self.revoked.as_u64() != 0).then(|| timestamp(self.revoked)),
+    }
+  }
+}
+
+impl<'a> From<&'a RegisteredDevice> for SerializedDevice {
+  fn from(d: &'a RegisteredDevice) -> Self {
+    let device = &d.device;
+    let system = [&device.platform, &device.os, &device.os_version].map(|s| s.as_deref().unwrap_or(""));
+    SerializedDevice {
+      created_at: (device.created_at.timestamp() as u64).into(),
+      last_access: (device.last_access.timestamp() as u64).into(),
+      revoked: d.revoked.map(|t| t.timestamp() as u64).unwrap_or(0).into(),
+      alias: SmallString::from_str(truncate(device.alias.as_deref().unwrap_or(""))),
+      system: SmallString::from_str(truncate(&system.join("\n"))),
+    }
+  }
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> DeviceTxnT for GenericTxn<T> {
+  fn get_device(&self, name: &str) -> Result<Option<RegisteredDevice>, Self::GraphError> {
+    let key = SmallString::from_str(truncate(name));
+    match btree::get(&self.txn, &self.devices, &key, None)? {
+      Some((k, v)) if k == key.as_ref() => Ok(Some(v.to_device(name))),
+      _ => Ok(None),
+    }
+  }
+
+  fn has_devices(&self) -> Result<bool, Self::GraphError> {
+    match btree::iter(&self.txn, &self.devices, None)?.next() {
+      Some(x) => x.map(|_| true).map_err(Into::into),
+      None => Ok(false),
+    }
+  }
+
+  fn list_devices(&self) -> Result<Vec<RegisteredDevice>, Self::GraphError> {
+    let mut devices = Vec::new();
+    for x in btree::iter(&self.txn, &self.devices, None)? {
+      let (name, d) = x?;
+      devices.push(d.to_device(name.as_str()));
+    }
+    Ok(devices)
+  }
+}
+
+impl MutTxn<()> {
+  fn put_device(&mut self, device: &RegisteredDevice) -> Result<(), EncycError> {
+    let name = SmallString::from_str(&device.device.name);
+    btree::del(&mut self.txn, &mut self.devices, &name, None)?;
+    btree::put(&mut self.txn, &mut self.devices, &name, &device.into())?;
+    Ok(())
+  }
+}
+
+impl DeviceMutTxnT for MutTxn<()> {
+  fn touch_device(&mut self, device: &Device, at: DateTime<Utc>) -> Result<RegisteredDevice, Self::GraphError> {
+    check_name(&device.name)?;
+    let registered = match self.get_device(&device.name)? {
+      Some(mut known) => {
+        if at <= known.device.last_access {
+          return Ok(known);
+        }
+        known.device.last_access = at;
+        known.device.platform = device.platform.clone();
+        known.device.os = device.os.clone();
+        known.device.os_version = device.os_version.clone();
+        known
+      }
+      None => RegisteredDevice {
+        device: Device {
+          alias: None,
+          created_at: at,
+          last_access: at,
+          ..device.clone()
+        },
+        revoked: None,
+      },
+    };
+    self.put_device(&registered)?;
+    Ok(registered)
+  }
+
+  fn rename_device(&mut self, name: &str, alias: Option<&str>) -> Result<bool, Self::GraphError> {
+    let Some(mut device) = self.get_device(name)? else {
+      return Ok(false);
+    };
+    if let Some(alias) = alias {
+      check_name(alias)?
+    }
+    device.device.alias = alias.map(String::from);
+    self.put_device(&device)?;
+    Ok(true)
+  }
+
+  fn revoke_device(&mut self, name: &str, at: DateTime<Utc>) -> Result<bool, Self::GraphError> {
+    let Some(mut device) = self.get_device(name)? else {
+      return Ok(false);
+    };
+    device.revoked = Some(at);
+    self.put_device(&device)?;
+    Ok(true)
+  }
+}
@@ -0,0 +1,30 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{graph::GraphTxnT, Device};
+
+use super::RegisteredDevice;
+
+pub trait DeviceTxnT: GraphTxnT {
+  fn get_device(&self, name: &str) -> Result<Option<RegisteredDevice>, Self::GraphError>;
+  /// Whether any device is registered.
+  fn has_devices(&self) -> Result<bool, Self::GraphError>;
+  /// All registered devices, sorted by name.
+  fn list_devices(&self) -> Result<Vec<RegisteredDevice>, Self::GraphError>;
+}
+
+pub trait DeviceMutTxnT: DeviceTxnT {
+  /// Register `device` if it is unknown, and record that it wrote to the
+  /// pristine at `at`, unless it was seen more recently. The alias and
+  /// revocation of a known device are kept.
+  fn touch_device(&mut self, device: &Device, at: DateTime<Utc>) -> Result<RegisteredDevice, Self::GraphError>;
+  /// Set or clear the alias of device `name`. Returns `false` if there was
+  /// no such device.
+  fn rename_device(&mut self, name: &str, alias: Option<&str>) -> Result<bool, Self::GraphError>;
+  /// Revoke device `name` at `at`: the changes made on it that the
+  /// pristine does not know yet are refused from now on. Returns `false`
+  /// if there was no such device.
+  fn revoke_device(&mut self, name: &str, at: DateTime<Utc>) -> Result<bool, Self::GraphError>;
+}
@@ -1,17 +1,377 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 20.
 
-use crate::types::{UId, L64};
+mod prelude;
+pub use prelude::*;
 
-#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use sanakirja::{btree, LoadPage, RootPage};
+use serde::{Deserialize, Serialize};
+use thiserror_impl::Error;
+
+use crate::{
+  models::{compartment::CompartmentTxnT, space::SpaceRef},
+  pristine::{types::Db, EncycError, GenericTxn, MutTxn},
+  types::{ChangeId, Money, MoneyError, SerializedMoney, SmallString, UId, L64, MAX_LEN},
+};
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
 pub struct SerializedEntry {
   pub id: UId,
 
   // DB fields
   pub changes: L64,
   pub tags: L64,
+  pub postings: L64,
 
   // readable fields
+  pub date: L64, // days since January 1st of year 1
   pub last_modified: L64,
   pub change_count: L64,
+  pub payee: SmallString,
+  pub memo: SmallString,
+}
+
+impl SerializedEntry {
+  pub fn date(&self) -> NaiveDate {
+    NaiveDate::from_num_days_from_ce_opt(self.date.as_u64() as i32).unwrap_or_default()
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedPosting {
+  pub compartment: UId,
+  pub amount: SerializedMoney,
+}
+
+/// One leg of an entry: `amount` moved into (positive) or out of
+/// (negative) a compartment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Posting {
+  pub compartment: UId,
+  pub amount: Money,
+}
+
+impl<'a> From<&'a SerializedPosting> for Posting {
+  fn from(p: &'a SerializedPosting) -> Self {
+    Posting {
+      compartment: p.compartment,
+      amount: p.amount.into(),
+    }
+  }
+}
+
+impl<'a> From<&'a Posting> for SerializedPosting {
+  fn from(p: &'a Posting) -> Self {
+    SerializedPosting {
+      compartment: p.compartment,
+      amount: p.amount.into(),
+    }
+  }
+}
+
+/// The content of a ledger entry. Postings must balance to zero in each
+/// currency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+  pub date: NaiveDate,
+  pub payee: String,
+  pub memo: String,
+  pub postings: Vec<Posting>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InvalidEntry {
+  #[error("An entry needs at least two postings")]
+  TooFewPostings,
+  #[error("Postings do not balance: they sum to {0}")]
+  Unbalanced(Money),
+  #[error(transparent)]
+  Money(#[from] MoneyError),
+  #[error("The {field} is too long ({len} bytes, the maximum is {MAX_LEN})")]
+  TooLong { field: &'static str, len: usize },
+}
+
+#[derive(Debug, Error)]
+pub enum EntryError<E: std::error::Error + 'static> {
+  #[error(transparent)]
+  Txn(#[from] E),
+  #[error(transparent)]
+  Invalid(InvalidEntry),
+  #[error("Compartment {0} does not exist")]
+  UnknownCompartment(UId),
+}
+
+impl From<sanakirja::Error> for EntryError<EncycError> {
+  fn from(e: sanakirja::Error) -> Self {
+    EntryError::Txn(e.into())
+  }
+}
+
+impl Entry {
+  pub fn validate(&self) -> Result<(), InvalidEntry> {
+    if self.payee.len() > MAX_LEN {
+      return Err(InvalidEntry::TooLong {
+        field: "payee",
+        len: self.payee.len(),
+      });
+    }
+    if self.memo.len() > MAX_LEN {
+      return Err(InvalidEntry::TooLong {
+        field: "memo",
+        len: self.memo.len(),
+      });
+    }
+    if self.postings.len() < 2 {
+      return Err(InvalidEntry::TooFewPostings);
+    }
+
+    let mut totals = BTreeMap::new();
+    for p in self.postings.iter() {
+      let total = totals
+        .entry(p.amount.currency)
+        .or_insert_with(|| Money::zero(p.amount.currency));
+      *total = total.checked_add(&p.amount)?;
+    }
+    if let Some(total) = totals.into_values().find(|total| !total.is_zero()) {
+      return Err(InvalidEntry::Unbalanced(total));
+    }
+    Ok(())
+  }
+
+  /// How much money the entry moves: the sum of its positive amounts, in
+  /// each currency. Fails if a sum overflows.
+  pub fn moved(&self) -> Result<Vec<Money>, MoneyError> {
+    let mut totals: BTreeMap<_, Money> = BTreeMap::new();
+    for p in self.postings.iter().filter(|p| !p.amount.is_negative()) {
+      let t = totals
+        .entry(p.amount.currency)
+        .or_insert(Money::zero(p.amount.currency));
+      *t = t.checked_add(&p.amount)?;
+    }
+    Ok(totals.into_values().collect())
+  }
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> GenericTxn<T> {
+  fn postings(&self, db: &Db<L64, SerializedPosting>) -> Result<Vec<Posting>, EncycError> {
+    let mut postings = Vec::new();
+    for x in btree::iter(&self.txn, db, None)? {
+      let (_, p) = x?;
+      postings.push(p.into());
+    }
+    Ok(postings)
+  }
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> EntryTxnT for GenericTxn<T> {
+  fn get_entry(&self, id: &ChangeId) -> Result<Option<&SerializedEntry>, Self::GraphError> {
+    match btree::get(&self.txn, &self.entries, id, None)? {
+      Some((k, v)) if k == id => Ok(Some(v)),
+      _ => Ok(None),
+    }
+  }
+
+  fn load_entry(&self, id: &ChangeId) -> Result<Option<Entry>, Self::GraphError> {
+    let Some(e) = self.get_entry(id)? else {
+      return Ok(None);
+    };
+    let db: Db<L64, SerializedPosting> = unsafe { Db::from_page(e.postings.into()) };
+    Ok(Some(Entry {
+      date: e.date(),
+      payee: e.payee.as_str().to_string(),
+      memo: e.memo.as_str().to_string(),
+      postings: self.postings(&db)?,
+    }))
+  }
+
+  fn last_change(&self, id: &ChangeId) -> Result<Option<ChangeId>, Self::GraphError> {
+    let Some(e) = self.get_entry(id)? else {
+      return Ok(None);
+    };
+    let Some(last) = e.change_count.as_u64().checked_sub(1) else {
+      return Ok(None);
+    };
+    let db: Db<L64, ChangeId> = unsafe { Db::from_page(e.changes.into()) };
+    match btree::get(&self.txn, &db, &last.into(), None)? {
+      Some((k, v)) if k.as_u64() == last => Ok(Some(*v)),
+      _ => Ok(None),
+    }
+  }
+
+  fn has_entry(&self, space: &SpaceRef<Self>, id: &ChangeId) -> Result<bool, Self::GraphError> {
+    let space = space.read();
+    match btree::get(&self.txn, &space.entries, id, None)? {
+      Some((k, _)) => Ok(k == id),
+      None => Ok(false),
+    }
+  }
+
+  fn space_entries(&self, space: &SpaceRef<Self>) -> Result<Vec<ChangeId>, Self::GraphError> {
+    let space = space.read();
+    let mut entries = Vec::new();
+    for x in btree::iter(&self.txn, &space.entries, None)? {
+      let (id, _) = x?;
+      entries.push(*id);
+    }
+    Ok(entries)
+  }
+}
+
+impl MutTxn<()> {
+  fn check_entry(&self, space: &SpaceRef<Self>, entry: &Entry) -> Result<(), EntryError<EncycError>> {
+    entry.validate().map_err(EntryError::Invalid)?;
+    for p in entry.postings.iter() {
+      let Some(c) = self.get_compartment(space, &p.compartment)? else {
+        return Err(EntryError::UnknownCompartment(p.compartment));
+      };
+      if c.balance.currency != p.amount.currency {
+        let mismatch = MoneyError::CurrencyMismatch(c.balance.currency, p.amount.currency);
+        return Err(EntryError::Invalid(mismatch.into()));
+      }
+    }
+    Ok(())
+  }
+
+  fn create_postings(&mut self, entry: &Entry) -> Result<L64, EncycError> {
+    let mut postings: Db<L64, SerializedPosting> = unsafe { btree::create_db_(&mut self.txn)? };
+    for (i, p) in entry.postings.iter().enumerate() {
+      btree::put(&mut self.txn, &mut postings, &i.into(), &p.into())?;
+    }
+    Ok(postings.db.get().into())
+  }
+
+  fn insert_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, entry: &Entry) -> Result<(), EntryError<EncycError>> {
+    let mut changes: Db<L64, ChangeId> = unsafe { btree::create_db_(&mut self.txn)? };
+    btree::put(&mut self.txn, &mut changes, &0u64.into(), id)?;
+    let tags: Db<UId, L64> = unsafe { btree::create_db_(&mut self.txn)? };
+
+    let date: L64 = (entry.date.num_days_from_ce() as u64).into();
+    let serialized = SerializedEntry {
+      id: UId::new(),
+      changes: changes.db.get().into(),
+      tags: tags.db.get().into(),
+      postings: self.create_postings(entry)?,
+      date,
+      last_modified: (Utc::now().timestamp() as u64).into(),
+      change_count: 1u64.into(),
+      payee: SmallString::from_str(&entry.payee),
+      memo: SmallString::from_str(&entry.memo),
+    };
+    btree::put(&mut self.txn, &mut self.entries, id, &serialized)?;
+    self.post(space, id, &entry.postings, false)?;
+
+    let mut space = space.write();
+    btree::put(&mut self.txn, &mut space.entries, id, &date)?;
+    Ok(())
+  }
+
+  fn update_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, entry: &Entry, change: &ChangeId) -> Result<(), EntryError<EncycError>> {
+    let Some(old) = self.get_entry(id)?.cloned() else {
+      return Ok(());
+    };
+    let mut changes: Db<L64, ChangeId> = unsafe { Db::from_page(old.changes.into()) };
+    btree::put(&mut self.txn, &mut changes, &old.change_count, change)?;
+    let change_count = old.change_count + 1;
+    self.write_entry(space, id, entry, old, changes, change_count)
+  }
+
+  /// Undo `update_entry`: `entry` is the content before `change`.
+  fn revert_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, entry: &Entry, change: &ChangeId) -> Result<(), EntryError<EncycError>> {
+    let Some(old) = self.get_entry(id)?.cloned() else {
+      return Ok(());
+    };
+    let mut changes: Db<L64, ChangeId> = unsafe { Db::from_page(old.changes.into()) };
+    let change_count: L64 = old.change_count.as_u64().saturating_sub(1).into();
+    btree::del(&mut self.txn, &mut changes, &change_count, Some(change))?;
+    self.write_entry(space, id, entry, old, changes, change_count)
+  }
+
+  /// Rewrite entry `id`, previously `old`, with the content of `entry` and
+  /// the history `changes`, of length `change_count`.
+  fn write_entry(
+    &mut self,
+    space: &SpaceRef<Self>,
+    id: &ChangeId,
+    entry: &Entry,
+    old: SerializedEntry,
+    changes: Db<L64, ChangeId>,
+    change_count: L64,
+  ) -> Result<(), EntryError<EncycError>> {
+    let old_postings: Db<L64, SerializedPosting> = unsafe { Db::from_page(old.postings.into()) };
+    let undone = self.postings(&old_postings)?;
+    self.post(space, id, &undone, true)?;
+    self.post(space, id, &entry.postings, false)?;
+    unsafe { btree::drop(&mut self.txn, old_postings)? };
+
+    let date: L64 = (entry.date.num_days_from_ce() as u64).into();
+    let serialized = SerializedEntry {
+      changes: changes.db.get().into(),
+      postings: self.create_postings(entry)?,
+      date,
+      last_modified: (Utc::now().timestamp() as u64).into(),
+      change_count,
+      payee: SmallString::from_str(&entry.payee),
+      memo: SmallString::from_str(&entry.memo),
+      ..old
+    };
+    btree::del(&mut self.txn, &mut self.entries, id, None)?;
+    btree::put(&mut self.txn, &mut self.entries, id, &serialized)?;
+
+    let mut space = space.write();
+    btree::del(&mut self.txn, &mut space.entries, id, None)?;
+    btree::put(&mut self.txn, &mut space.entries, id, &date)?;
+    Ok(())
+  }
+}
+
+impl EntryMutTxnT for MutTxn<()> {
+  fn put_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, entry: &Entry) -> Result<(), EntryError<Self::GraphError>> {
+    self.check_entry(space, entry)?;
+    self.insert_entry(space, id, entry)
+  }
+
+  fn replace_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, entry: &Entry, change: &ChangeId) -> Result<bool, EntryError<Self::GraphError>> {
+    if !self.has_entry(space, id)? {
+      return Ok(false);
+    }
+    self.check_entry(space, entry)?;
+    self.update_entry(space, id, entry, change)?;
+    Ok(true)
+  }
+
+  fn unreplace_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, old: &Entry, change: &ChangeId) -> Result<bool, EntryError<Self::GraphError>> {
+    if !self.has_entry(space, id)? {
+      return Ok(false);
+    }
+    self.check_entry(space, old)?;
+    self.revert_entry(space, id, old, change)?;
+    Ok(true)
+  }
+
+  fn del_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId) -> Result<bool, EntryError<Self::GraphError>> {
+    {
+      let mut space = space.write();
+      if !btree::del(&mut self.txn, &mut space.entries, id, None)? {
+        return Ok(false);
+      }
+    }
+    if let Some(e) = self.get_entry(id)?.cloned() {
+      let changes: Db<L64, ChangeId> = unsafe { Db::from_page(e.changes.into()) };
+      let tags: Db<UId, L64> = unsafe { Db::from_page(e.tags.into()) };
+      let postings: Db<L64, SerializedPosting> = unsafe { Db::from_page(e.postings.into()) };
+      let undone = self.postings(&postings)?;
+      self.post(space, id, &undone, true)?;
+      self.forget_labels(id, &tags)?;
+      unsafe {
+        btree::drop(&mut self.txn, changes)?;
+        btree::drop(&mut self.txn, tags)?;
+        btree::drop(&mut self.txn, postings)?;
+      }
+      btree::del(&mut self.txn, &mut self.entries, id, None)?;
+    }
+    Ok(true)
+  }
 }
@@ -0,0 +1,43 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use crate::{
+  models::{graph::GraphTxnT, space::SpaceRef, space::SpaceTxnT},
+  types::ChangeId,
+};
+
+use super::{Entry, EntryError, SerializedEntry};
+
+pub trait EntryTxnT: GraphTxnT + SpaceTxnT {
+  fn get_entry(&self, id: &ChangeId) -> Result<Option<&SerializedEntry>, Self::GraphError>;
+
+  /// Load the full content of entry `id`, including its postings.
+  fn load_entry(&self, id: &ChangeId) -> Result<Option<Entry>, Self::GraphError>;
+
+  /// The change that gave entry `id` its current content: the last one
+  /// that edited it, or else the one that added it.
+  fn last_change(&self, id: &ChangeId) -> Result<Option<ChangeId>, Self::GraphError>;
+
+  fn has_entry(&self, space: &SpaceRef<Self>, id: &ChangeId) -> Result<bool, Self::GraphError>;
+
+  /// Ids of the entries recorded in `space`.
+  fn space_entries(&self, space: &SpaceRef<Self>) -> Result<Vec<ChangeId>, Self::GraphError>;
+}
+
+pub trait EntryMutTxnT: EntryTxnT {
+  /// Validate `entry` and add it to `space`. `id` is the id of the change
+  /// that created it.
+  fn put_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, entry: &Entry) -> Result<(), EntryError<Self::GraphError>>;
+
+  /// Replace the content of entry `id` by `entry`, as edited by `change`.
+  /// Returns `false` if `space` didn't contain `id`.
+  fn replace_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, entry: &Entry, change: &ChangeId) -> Result<bool, EntryError<Self::GraphError>>;
+
+  /// Undo `replace_entry`: give entry `id` back its content `old`, from
+  /// before `change`. Returns `false` if `space` didn't contain `id`.
+  fn unreplace_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId, old: &Entry, change: &ChangeId) -> Result<bool, EntryError<Self::GraphError>>;
+
+  /// Remove entry `id` from `space`. Returns `false` if `space` didn't
+  /// contain it.
+  fn del_entry(&mut self, space: &SpaceRef<Self>, id: &ChangeId) -> Result<bool, EntryError<Self::GraphError>>;
+}
@@ -2,16 +2,149 @@
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 24.
 #![allow(dead_code)]
 
+mod prelude;
+pub use prelude::*;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use sanakirja::{btree, LoadPage, RootPage};
+use serde::{Deserialize, Serialize};
+
 use crate::{
-  pristine::types::UDb,
-  types::{UId, L64},
+  pristine::{
+    check_name,
+    types::{Db, UDb},
+    EncycError, GenericTxn, MutTxn,
+  },
+  types::{Currency, Money, SerializedMoney, SmallString, UId, L64},
+  ParseError,
 };
 
-#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+/// How often the limit of a budget starts over.
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum BudgetPeriod {
+  /// Calendar months.
+  Monthly,
+  /// Calendar quarters, starting in January, April, July and October.
+  Quarterly,
+  /// A single period, from the start of the budget to its end.
+  Custom,
+}
+
+impl BudgetPeriod {
+  /// The first day of the period containing `date`. Custom periods start
+  /// whenever their budget does, so this is `date` itself.
+  pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+    let month = match self {
+      BudgetPeriod::Monthly => date.month(),
+      BudgetPeriod::Quarterly => (date.month() - 1) / 3 * 3 + 1,
+      BudgetPeriod::Custom => return date,
+    };
+    NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap_or(date)
+  }
+
+  /// The first day of the period after the one containing `date`, or
+  /// `None` for custom periods, which have no next one.
+  fn next(&self, date: NaiveDate) -> Option<NaiveDate> {
+    let months = match self {
+      BudgetPeriod::Monthly => 1,
+      BudgetPeriod::Quarterly => 3,
+      BudgetPeriod::Custom => return None,
+    };
+    self
+      .start_of(date)
+      .checked_add_months(chrono::Months::new(months))
+  }
+}
+
+impl std::fmt::Display for BudgetPeriod {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let s = match self {
+      BudgetPeriod::Monthly => "monthly",
+      BudgetPeriod::Quarterly => "quarterly",
+      BudgetPeriod::Custom => "custom",
+    };
+    f.write_str(s)
+  }
+}
+
+impl std::str::FromStr for BudgetPeriod {
+  type Err = ParseError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "monthly" => Ok(BudgetPeriod::Monthly),
+      "quarterly" => Ok(BudgetPeriod::Quarterly),
+      "custom" => Ok(BudgetPeriod::Custom),
+      _ => Err(ParseError { s: s.to_string() }),
+    }
+  }
+}
+
+/// A limit on what the entries labelled by some labels, or posting to some
+/// compartments, may spend in each period. Labels and compartments count
+/// with the ones below them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Budget {
+  pub name: String,
+  pub period: BudgetPeriod,
+  /// The first day counted. The first period starts on it, even if the
+  /// calendar month or quarter started earlier.
+  pub start: NaiveDate,
+  /// The last day counted. Without it, custom periods run on to the day
+  /// the budget is looked at.
+  pub end: Option<NaiveDate>,
+  /// What may be spent in each period.
+  pub limit: Money,
+  /// Whether what is left unspent at the end of a period is added to the
+  /// limit of the next one.
+  pub rollover: bool,
+  pub labels: Vec<UId>,
+  pub compartments: Vec<UId>,
+}
+
+impl Budget {
+  /// The periods of the budget started on or before `until`, as their
+  /// first and last days.
+  pub fn periods(&self, until: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    let last = self.end.map_or(until, |end| end.min(until));
+    let mut periods = Vec::new();
+    let mut start = self.start;
+    while start <= last {
+      let next = self.period.next(start);
+      let end = match (next.and_then(|n| n.pred_opt()), self.end) {
+        (Some(d), Some(end)) => d.min(end),
+        (Some(d), None) => d,
+        (None, end) => end.unwrap_or(until),
+      };
+      periods.push((start, end));
+      match next {
+        Some(next) => start = next,
+        None => break,
+      }
+    }
+    periods
+  }
+}
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
 pub struct SerializedFilter {
-  header: L64, // is a page for now
-  is_system: bool,
-  id: UId,
+  pub header: L64,       // is a page for now
+  pub labels: L64,       // labels whose entries a budget counts
+  pub compartments: L64, // compartments whose entries a budget counts
+  pub start: L64,        // first day of a budget, in days since January 1st of year 1
+  pub end: L64,          // last day of a budget, 0 if it has none
+  pub limit: SerializedMoney,
+  pub period: BudgetPeriod,
+  pub rollover: bool,
+  pub is_system: bool,
+  pub name: SmallString, // empty unless the filter is a budget
+  pub id: UId,
+}
+
+impl SerializedFilter {
+  pub fn is_budget(&self) -> bool {
+    !self.name.as_str().is_empty()
+  }
 }
 
 pub struct Filter {
@@ -19,6 +152,166 @@ pub struct Filter {
   pub id: UId,
 }
 
+fn date(days: L64) -> NaiveDate {
+  NaiveDate::from_num_days_from_ce_opt(days.as_u64() as i32).unwrap_or_default()
+}
+
+fn days(date: NaiveDate) -> L64 {
+  (date.num_days_from_ce() as u64).into()
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> GenericTxn<T> {
+  fn members(&self, page: L64) -> Result<Vec<UId>, EncycError> {
+    let db: Db<UId, L64> = unsafe { Db::from_page(page.into()) };
+    let mut members = Vec::new();
+    for x in btree::iter(&self.txn, &db, None)? {
+      let (id, _) = x?;
+      members.push(*id);
+    }
+    Ok(members)
+  }
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> FilterTxnT for GenericTxn<T> {
+  fn get_filter(&self, id: &UId) -> Result<Option<&SerializedFilter>, Self::GraphError> {
+    match btree::get(&self.txn, &self.filters, id, None)? {
+      Some((k, v)) if k == id => Ok(Some(v)),
+      _ => Ok(None),
+    }
+  }
+
+  fn list_filters(&self) -> Result<Vec<&SerializedFilter>, Self::GraphError> {
+    let mut filters = Vec::new();
+    for x in btree::iter(&self.txn, &self.filters, None)? {
+      let (_, f) = x?;
+      filters.push(f);
+    }
+    Ok(filters)
+  }
+
+  fn budget_by_name(&self, name: &str) -> Result<Option<&SerializedFilter>, Self::GraphError> {
+    for f in self.list_filters()? {
+      if f.is_budget() && f.name.as_str() == name {
+        return Ok(Some(f));
+      }
+    }
+    Ok(None)
+  }
+
+  fn get_budget(&self, id: &UId) -> Result<Option<Budget>, Self::GraphError> {
+    let Some(f) = self.get_filter(id)?.filter(|f| f.is_budget()) else {
+      return Ok(None);
+    };
+    Ok(Some(Budget {
+      name: f.name.as_str().to_string(),
+      period: f.period,
+      start: date(f.start),
+      end: (f.end.as_u64() != 0).then(|| date(f.end)),
+      limit: f.limit.into(),
+      rollover: f.rollover,
+      labels: self.members(f.labels)?,
+      compartments: self.members(f.compartments)?,
+    }))
+  }
+}
+
+impl MutTxn<()> {
+  /// A new page holding `members`.
+  fn put_members(&mut self, members: &[UId]) -> Result<L64, EncycError> {
+    let mut db: Db<UId, L64> = unsafe { btree::create_db_(&mut self.txn)? };
+    let now: L64 = (Utc::now().timestamp() as u64).into();
+    for id in members {
+      btree::put(&mut self.txn, &mut db, id, &now)?;
+    }
+    Ok(db.db.get().into())
+  }
+
+  fn drop_members(&mut self, f: &SerializedFilter) -> Result<(), EncycError> {
+    let labels: Db<UId, L64> = unsafe { Db::from_page(f.labels.into()) };
+    let compartments: Db<UId, L64> = unsafe { Db::from_page(f.compartments.into()) };
+    unsafe {
+      btree::drop(&mut self.txn, labels)?;
+      btree::drop(&mut self.txn, compartments)?;
+    }
+    Ok(())
+  }
+}
+
+impl FilterMutTxnT for MutTxn<()> {
+  fn create_filter(&mut self, id: UId) -> Result<(), Self::GraphError> {
+    if self.get_filter(&id)?.is_some() {
+      return Err(EncycError::AlreadyExists(id.to_string()));
+    }
+    let header: UDb<UId, L64> = unsafe { btree::create_db_(&mut self.txn)? };
+    let filter = SerializedFilter {
+      header: header.db.get().into(),
+      labels: self.put_members(&[])?,
+      compartments: self.put_members(&[])?,
+      start: 0u64.into(),
+      end: 0u64.into(),
+      limit: Money::zero(Currency::NONE).into(),
+      period: BudgetPeriod::Monthly,
+      rollover: false,
+      is_system: false,
+      name: SmallString::new(),
+      id,
+    };
+    btree::put(&mut self.txn, &mut self.filters, &id, &filter)?;
+    Ok(())
+  }
+
+  fn del_filter(&mut self, id: &UId) -> Result<bool, Self::GraphError> {
+    let Some(f) = self.get_filter(id)?.cloned() else {
+      return Ok(false);
+    };
+    let header: UDb<UId, L64> = unsafe { UDb::from_page(f.header.into()) };
+    unsafe { btree::drop(&mut self.txn, header)? };
+    self.drop_members(&f)?;
+    btree::del(&mut self.txn, &mut self.filters, id, None)?;
+    Ok(true)
+  }
+
+  fn set_budget(&mut self, id: &UId, budget: Option<&Budget>) -> Result<bool, Self::GraphError> {
+    let Some(mut f) = self.get_filter(id)?.cloned() else {
+      return Ok(false);
+    };
+    if let Some(b) = budget {
+      check_name(&b.name)?;
+      if self
+        .budget_by_name(&b.name)?
+        .is_some_and(|other| other.id != *id)
+      {
+        return Err(EncycError::AlreadyExists(b.name.clone()));
+      }
+    }
+    self.drop_members(&f)?;
+    let empty = Vec::new();
+    f.labels = self.put_members(budget.map_or(&empty, |b| &b.labels))?;
+    f.compartments = self.put_members(budget.map_or(&empty, |b| &b.compartments))?;
+    match budget {
+      Some(b) => {
+        f.start = days(b.start);
+        f.end = b.end.map_or(0u64.into(), days);
+        f.limit = b.limit.into();
+        f.period = b.period;
+        f.rollover = b.rollover;
+        f.name = SmallString::from_str(&b.name);
+      }
+      None => {
+        f.start = 0u64.into();
+        f.end = 0u64.into();
+        f.limit = Money::zero(Currency::NONE).into();
+        f.period = BudgetPeriod::Monthly;
+        f.rollover = false;
+        f.name = SmallString::new();
+      }
+    }
+    btree::del(&mut self.txn, &mut self.filters, id, None)?;
+    btree::put(&mut self.txn, &mut self.filters, id, &f)?;
+    Ok(true)
+  }
+}
+
 // #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
 // #[repr(C)]
 // pub struct SerializedChannel {
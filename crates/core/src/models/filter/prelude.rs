@@ -0,0 +1,25 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use crate::{models::graph::GraphTxnT, types::UId};
+
+use super::{Budget, SerializedFilter};
+
+pub trait FilterTxnT: GraphTxnT {
+  fn get_filter(&self, id: &UId) -> Result<Option<&SerializedFilter>, Self::GraphError>;
+  fn list_filters(&self) -> Result<Vec<&SerializedFilter>, Self::GraphError>;
+  /// The filter that is budget `name`.
+  fn budget_by_name(&self, name: &str) -> Result<Option<&SerializedFilter>, Self::GraphError>;
+  /// The budget filter `id` defines, if it is a budget.
+  fn get_budget(&self, id: &UId) -> Result<Option<Budget>, Self::GraphError>;
+}
+
+pub trait FilterMutTxnT: FilterTxnT {
+  fn create_filter(&mut self, id: UId) -> Result<(), Self::GraphError>;
+  /// Returns `false` if there was no such filter.
+  fn del_filter(&mut self, id: &UId) -> Result<bool, Self::GraphError>;
+  /// Make filter `id` budget `budget`, or with `None`, a plain filter
+  /// again. Budget names are unique. Returns `false` if there was no such
+  /// filter.
+  fn set_budget(&mut self, id: &UId, budget: Option<&Budget>) -> Result<bool, Self::GraphError>;
+}
@@ -4,10 +4,61 @@
 mod prelude;
 pub use prelude::*;
 
-use sanakirja::{LoadPage, RootPage};
+use sanakirja::{btree, LoadPage, RootPage};
 
-use crate::pristine::{EncycError, GenericTxn};
+use crate::{
+  pristine::{types::UDb, EncycError, GenericTxn, MutTxn},
+  types::{ChangeId, UId},
+};
+
+/// The values bound to `key` in `db`, which has duplicate keys.
+fn values<T: LoadPage<Error = sanakirja::Error> + RootPage>(txn: &T, db: &UDb<ChangeId, ChangeId>, key: &ChangeId) -> Result<Vec<ChangeId>, EncycError> {
+  let mut values = Vec::new();
+  for x in btree::iter(txn, db, Some((key, None)))? {
+    let (k, v) = x?;
+    if k != key {
+      break;
+    }
+    values.push(*v);
+  }
+  Ok(values)
+}
 
 impl<T: LoadPage<Error = sanakirja::Error> + RootPage> GraphTxnT for GenericTxn<T> {
   type GraphError = EncycError;
+
+  fn dependencies(&self, change: &ChangeId) -> Result<Vec<ChangeId>, Self::GraphError> {
+    values(&self.txn, &self.dependencies, change)
+  }
+
+  fn dependents(&self, change: &ChangeId) -> Result<Vec<ChangeId>, Self::GraphError> {
+    values(&self.txn, &self.dependents, change)
+  }
+
+  fn creator(&self, id: &UId) -> Result<Option<ChangeId>, Self::GraphError> {
+    match btree::get(&self.txn, &self.creators, id, None)? {
+      Some((k, v)) if k == id => Ok(Some(*v)),
+      _ => Ok(None),
+    }
+  }
+}
+
+impl GraphMutTxnT for MutTxn<()> {
+  fn put_dependency(&mut self, change: &ChangeId, dependency: &ChangeId) -> Result<(), Self::GraphError> {
+    if !self.dependencies(change)?.contains(dependency) {
+      btree::put(&mut self.txn, &mut self.dependencies, change, dependency)?;
+      btree::put(&mut self.txn, &mut self.dependents, dependency, change)?;
+    }
+    Ok(())
+  }
+
+  fn put_creator(&mut self, id: &UId, change: &ChangeId) -> Result<(), Self::GraphError> {
+    btree::del(&mut self.txn, &mut self.creators, id, None)?;
+    btree::put(&mut self.txn, &mut self.creators, id, change)?;
+    Ok(())
+  }
+
+  fn del_creator(&mut self, id: &UId) -> Result<bool, Self::GraphError> {
+    Ok(btree::del(&mut self.txn, &mut self.creators, id, None)?)
+  }
 }
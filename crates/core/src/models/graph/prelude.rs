@@ -1,6 +1,27 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 16.
 
+use crate::types::{ChangeId, UId};
+
+/// The dependency graph of the changes known to a pristine. Its edges are
+/// the dependencies listed in the changes themselves, so they hold in
+/// every space, whether or not the changes are applied there.
 pub trait GraphTxnT: Sized {
   type GraphError: std::error::Error + std::fmt::Debug + Send + Sync + 'static;
+
+  /// The changes `change` depends on.
+  fn dependencies(&self, change: &ChangeId) -> Result<Vec<ChangeId>, Self::GraphError>;
+  /// The changes that depend on `change`.
+  fn dependents(&self, change: &ChangeId) -> Result<Vec<ChangeId>, Self::GraphError>;
+  /// The change that created the compartment, vault, label or filter `id`.
+  fn creator(&self, id: &UId) -> Result<Option<ChangeId>, Self::GraphError>;
+}
+
+pub trait GraphMutTxnT: GraphTxnT {
+  /// Record that `change` depends on `dependency`.
+  fn put_dependency(&mut self, change: &ChangeId, dependency: &ChangeId) -> Result<(), Self::GraphError>;
+  /// Record that `change` created `id`.
+  fn put_creator(&mut self, id: &UId, change: &ChangeId) -> Result<(), Self::GraphError>;
+  /// Forget who created `id`, once it is removed.
+  fn del_creator(&mut self, id: &UId) -> Result<bool, Self::GraphError>;
 }
@@ -2,9 +2,30 @@
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 24.
 #![allow(dead_code)]
 
-use crate::types::{SmallString, UId, L64};
+mod prelude;
+pub use prelude::*;
 
-#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+use chrono::Utc;
+use sanakirja::{btree, LoadPage, RootPage};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  models::{
+    entry::{EntryError, EntryTxnT},
+    space::SpaceRef,
+    vault::VaultTxnT,
+  },
+  pristine::{
+    check_name,
+    types::{Db, UDb},
+    EncycError, GenericTxn, MutTxn,
+  },
+  types::{ChangeId, Money, SmallString, UId, L64},
+  ParseError,
+};
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum LabelGroup {
   INCOME,
   EXPENSE,
@@ -16,13 +37,415 @@ pub enum LabelGroup {
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
 #[repr(C)]
 pub struct SerializedLabel {
-  header: L64,       // store owner, change, metadata
-  group: LabelGroup, // store group like income, expense, etc.
-  name: SmallString,
-  id: UId,
+  pub header: L64,       // store owner, change, metadata
+  pub entries: L64,      // entries the label is attached to, with the number of attachments
+  pub vault: UId,        // `UId::nil()` if the label is in no vault
+  pub parent: UId,       // `UId::nil()` for the root of a tree
+  pub group: LabelGroup, // store group like income, expense, etc.
+  pub name: SmallString,
+  pub id: UId,
+}
+
+/// Deepest chain of parents followed, in case the tree is corrupted.
+const MAX_DEPTH: usize = 64;
+
+impl SerializedLabel {
+  pub fn vault(&self) -> Option<UId> {
+    (self.vault != UId::nil()).then_some(self.vault)
+  }
+
+  pub fn parent(&self) -> Option<UId> {
+    (self.parent != UId::nil()).then_some(self.parent)
+  }
 }
 
 pub struct Label {}
+
+impl std::fmt::Display for LabelGroup {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    let s = match self {
+      LabelGroup::INCOME => "income",
+      LabelGroup::EXPENSE => "expense",
+      LabelGroup::DEBT => "debt",
+      LabelGroup::LOAN => "loan",
+      LabelGroup::TAG => "tag",
+    };
+    f.write_str(s)
+  }
+}
+
+impl std::str::FromStr for LabelGroup {
+  type Err = ParseError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "income" => Ok(LabelGroup::INCOME),
+      "expense" => Ok(LabelGroup::EXPENSE),
+      "debt" => Ok(LabelGroup::DEBT),
+      "loan" => Ok(LabelGroup::LOAN),
+      "tag" => Ok(LabelGroup::TAG),
+      _ => Err(ParseError { s: s.to_string() }),
+    }
+  }
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> LabelTxnT for GenericTxn<T> {
+  fn get_label(&self, id: &UId) -> Result<Option<&SerializedLabel>, Self::GraphError> {
+    match btree::get(&self.txn, &self.labels, id, None)? {
+      Some((k, v)) if k == id => Ok(Some(v)),
+      _ => Ok(None),
+    }
+  }
+
+  fn space_labels(&self, space: &SpaceRef<Self>) -> Result<Vec<UId>, Self::GraphError> {
+    let space = space.read();
+    let mut labels = Vec::new();
+    for x in btree::iter(&self.txn, &space.labels, None)? {
+      let (id, _) = x?;
+      labels.push(*id);
+    }
+    Ok(labels)
+  }
+
+  fn label_by_name(&self, space: &SpaceRef<Self>, name: &str) -> Result<Option<&SerializedLabel>, Self::GraphError> {
+    for id in self.space_labels(space)? {
+      match self.get_label(&id)? {
+        Some(l) if l.name.as_str() == name => return Ok(Some(l)),
+        _ => {}
+      }
+    }
+    Ok(None)
+  }
+
+  fn vault_labels(&self, vault: &UId) -> Result<Vec<UId>, Self::GraphError> {
+    let Some(v) = self.get_vault(vault)? else {
+      return Ok(Vec::new());
+    };
+    let db: UDb<L64, UId> = unsafe { UDb::from_page(v.labels.into()) };
+    let mut labels = Vec::new();
+    for x in btree::iter(&self.txn, &db, None)? {
+      let (_, id) = x?;
+      labels.push(*id);
+    }
+    Ok(labels)
+  }
+
+  fn label_children(&self, id: &UId) -> Result<Vec<UId>, Self::GraphError> {
+    let mut children = Vec::new();
+    for x in btree::iter(&self.txn, &self.labels, None)? {
+      let (_, l) = x?;
+      if l.parent == *id && l.id != *id {
+        children.push(l.id);
+      }
+    }
+    Ok(children)
+  }
+
+  fn label_descendants(&self, id: &UId) -> Result<Vec<UId>, Self::GraphError> {
+    let mut descendants = Vec::new();
+    let mut stack = vec![*id];
+    while let Some(id) = stack.pop() {
+      for child in self.label_children(&id)? {
+        // A cycle can't be recorded, but don't loop on a corrupted tree.
+        if !descendants.contains(&child) {
+          descendants.push(child);
+          stack.push(child);
+        }
+      }
+    }
+    Ok(descendants)
+  }
+
+  fn label_path(&self, id: &UId) -> Result<String, Self::GraphError> {
+    let mut names = Vec::new();
+    let mut next = Some(*id);
+    while let Some(id) = next {
+      let Some(l) = self.get_label(&id)? else {
+        break;
+      };
+      names.push(l.name.as_str());
+      next = l.parent().filter(|_| names.len() <= MAX_DEPTH);
+    }
+    names.reverse();
+    Ok(names.join(" > "))
+  }
+
+  fn tree_entries(&self, id: &UId) -> Result<Vec<ChangeId>, Self::GraphError> {
+    let mut entries = self.label_entries(id)?;
+    for d in self.label_descendants(id)? {
+      entries.extend(self.label_entries(&d)?);
+    }
+    entries.sort();
+    entries.dedup();
+    Ok(entries)
+  }
+
+  fn label_total(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Vec<Money>, EntryError<Self::GraphError>> {
+    let mut totals: Vec<Money> = Vec::new();
+    for e in self.tree_entries(id)? {
+      if !self.has_entry(space, &e)? {
+        continue;
+      }
+      let Some(entry) = self.load_entry(&e)? else {
+        continue;
+      };
+      let moved = entry.moved().map_err(|e| EntryError::Invalid(e.into()))?;
+      for m in moved {
+        match totals.iter_mut().find(|t| t.currency == m.currency) {
+          Some(t) => {
+            *t = t
+              .checked_add(&m)
+              .map_err(|e| EntryError::Invalid(e.into()))?
+          }
+          None => totals.push(m),
+        }
+      }
+    }
+    totals.sort_by_key(|t| t.currency);
+    Ok(totals)
+  }
+
+  fn label_entries(&self, id: &UId) -> Result<Vec<ChangeId>, Self::GraphError> {
+    let Some(l) = self.get_label(id)? else {
+      return Ok(Vec::new());
+    };
+    let db: Db<ChangeId, L64> = unsafe { Db::from_page(l.entries.into()) };
+    let mut entries = Vec::new();
+    for x in btree::iter(&self.txn, &db, None)? {
+      let (id, _) = x?;
+      entries.push(*id);
+    }
+    Ok(entries)
+  }
+
+  fn attachments(&self, entry: &ChangeId, label: &UId) -> Result<u64, Self::GraphError> {
+    let Some(e) = self.get_entry(entry)? else {
+      return Ok(0);
+    };
+    let db: Db<UId, L64> = unsafe { Db::from_page(e.tags.into()) };
+    match btree::get(&self.txn, &db, label, None)? {
+      Some((k, n)) if k == label => Ok(n.as_u64()),
+      _ => Ok(0),
+    }
+  }
+  fn entry_labels(&self, entry: &ChangeId) -> Result<Vec<UId>, Self::GraphError> {
+    let Some(e) = self.get_entry(entry)? else {
+      return Ok(Vec::new());
+    };
+    let db: Db<UId, L64> = unsafe { Db::from_page(e.tags.into()) };
+    let mut labels = Vec::new();
+    for x in btree::iter(&self.txn, &db, None)? {
+      let (id, _) = x?;
+      labels.push(*id);
+    }
+    Ok(labels)
+  }
+}
+
+impl MutTxn<()> {
+  fn put_label(&mut self, l: &SerializedLabel) -> Result<(), EncycError> {
+    btree::del(&mut self.txn, &mut self.labels, &l.id, None)?;
+    btree::put(&mut self.txn, &mut self.labels, &l.id, l)?;
+    Ok(())
+  }
+
+  /// Add `label` to the `labels` db of `vault`, or with `remove`, take it
+  /// out.
+  fn file_label(&mut self, vault: &UId, label: &UId, remove: bool) -> Result<(), EncycError> {
+    let Some(mut v) = self.get_vault(vault)?.cloned() else {
+      return Ok(());
+    };
+    let mut db: UDb<L64, UId> = unsafe { UDb::from_page(v.labels.into()) };
+    let mut found = None;
+    for x in btree::iter(&self.txn, &db, None)? {
+      let (k, id) = x?;
+      if id == label {
+        found = Some(*k);
+        break;
+      }
+    }
+    match (found, remove) {
+      (Some(k), true) => {
+        btree::del(&mut self.txn, &mut db, &k, Some(label))?;
+      }
+      (None, false) => {
+        btree::put(
+          &mut self.txn,
+          &mut db,
+          &(Utc::now().timestamp() as u64).into(),
+          label,
+        )?;
+      }
+      _ => return Ok(()),
+    }
+    v.labels = db.db.get().into();
+    btree::del(&mut self.txn, &mut self.vaults, vault, None)?;
+    btree::put(&mut self.txn, &mut self.vaults, vault, &v)?;
+    Ok(())
+  }
+
+  /// Add `n` to the number of times `key` is in `db`, which may not go
+  /// below zero, and return the new root page of `db`.
+  fn count<K: sanakirja::Storable + PartialEq>(&mut self, mut db: Db<K, L64>, key: &K, n: i64) -> Result<L64, EncycError> {
+    let count = match btree::get(&self.txn, &db, key, None)? {
+      Some((k, c)) if k == key => c.as_u64() as i64,
+      _ => 0,
+    };
+    btree::del(&mut self.txn, &mut db, key, None)?;
+    if count + n > 0 {
+      btree::put(&mut self.txn, &mut db, key, &((count + n) as u64).into())?;
+    }
+    Ok(db.db.get().into())
+  }
+
+  /// Remove entry `id`, about to be deleted, from the index of each of
+  /// the labels in `tags`, its `tags` page.
+  pub(crate) fn forget_labels(&mut self, id: &ChangeId, tags: &Db<UId, L64>) -> Result<(), EncycError> {
+    let mut labels = Vec::new();
+    for x in btree::iter(&self.txn, tags, None)? {
+      let (label, n) = x?;
+      labels.push((*label, n.as_u64() as i64));
+    }
+    for (label, n) in labels {
+      let Some(mut l) = self.get_label(&label)?.cloned() else {
+        continue;
+      };
+      l.entries = self.count(unsafe { Db::from_page(l.entries.into()) }, id, -n)?;
+      self.put_label(&l)?;
+    }
+    Ok(())
+  }
+
+  /// Count one more attachment of `label` to `entry`, or `n` fewer.
+  fn attach(&mut self, entry: &ChangeId, label: &UId, n: i64) -> Result<bool, EncycError> {
+    let (Some(mut e), Some(mut l)) = (
+      self.get_entry(entry)?.cloned(),
+      self.get_label(label)?.cloned(),
+    ) else {
+      return Ok(false);
+    };
+    e.tags = self.count(unsafe { Db::from_page(e.tags.into()) }, label, n)?;
+    e.last_modified = (Utc::now().timestamp() as u64).into();
+    btree::del(&mut self.txn, &mut self.entries, entry, None)?;
+    btree::put(&mut self.txn, &mut self.entries, entry, &e)?;
+    l.entries = self.count(unsafe { Db::from_page(l.entries.into()) }, entry, n)?;
+    self.put_label(&l)?;
+    Ok(true)
+  }
+}
+
+impl LabelMutTxnT for MutTxn<()> {
+  fn create_label(&mut self, space: &SpaceRef<Self>, id: UId, name: &str, group: LabelGroup) -> Result<(), Self::GraphError> {
+    check_name(name)?;
+    if self.label_by_name(space, name)?.is_some() {
+      return Err(EncycError::AlreadyExists(name.to_string()));
+    }
+    if self.get_label(&id)?.is_some() {
+      return Err(EncycError::AlreadyExists(id.to_string()));
+    }
+    let header: UDb<UId, L64> = unsafe { btree::create_db_(&mut self.txn)? };
+    let entries: Db<ChangeId, L64> = unsafe { btree::create_db_(&mut self.txn)? };
+    let label = SerializedLabel {
+      header: header.db.get().into(),
+      entries: entries.db.get().into(),
+      vault: UId::nil(),
+      parent: UId::nil(),
+      group,
+      name: SmallString::from_str(name),
+      id,
+    };
+    btree::put(&mut self.txn, &mut self.labels, &id, &label)?;
+    let mut space = space.write();
+    btree::put(
+      &mut self.txn,
+      &mut space.labels,
+      &id,
+      &(Utc::now().timestamp() as u64).into(),
+    )?;
+    Ok(())
+  }
+
+  fn del_label(&mut self, space: &SpaceRef<Self>, id: &UId) -> Result<bool, Self::GraphError> {
+    {
+      let mut space = space.write();
+      if !btree::del(&mut self.txn, &mut space.labels, id, None)? {
+        return Ok(false);
+      }
+    }
+    if let Some(l) = self.get_label(id)?.cloned() {
+      if let Some(vault) = l.vault() {
+        self.file_label(&vault, id, true)?;
+      }
+      let header: UDb<UId, L64> = unsafe { UDb::from_page(l.header.into()) };
+      let entries: Db<ChangeId, L64> = unsafe { Db::from_page(l.entries.into()) };
+      unsafe {
+        btree::drop(&mut self.txn, header)?;
+        btree::drop(&mut self.txn, entries)?;
+      }
+      btree::del(&mut self.txn, &mut self.labels, id, None)?;
+    }
+    Ok(true)
+  }
+
+  fn rename_label(&mut self, space: &SpaceRef<Self>, id: &UId, name: &str) -> Result<bool, Self::GraphError> {
+    check_name(name)?;
+    let Some(mut l) = self.get_label(id)?.cloned() else {
+      return Ok(false);
+    };
+    if self
+      .label_by_name(space, name)?
+      .is_some_and(|other| other.id != *id)
+    {
+      return Err(EncycError::AlreadyExists(name.to_string()));
+    }
+    l.name = SmallString::from_str(name);
+    self.put_label(&l)?;
+    Ok(true)
+  }
+
+  fn set_label_vault(&mut self, id: &UId, vault: Option<UId>) -> Result<bool, Self::GraphError> {
+    let Some(mut l) = self.get_label(id)?.cloned() else {
+      return Ok(false);
+    };
+    if let Some(old) = l.vault() {
+      self.file_label(&old, id, true)?;
+    }
+    if let Some(new) = vault {
+      self.file_label(&new, id, false)?;
+    }
+    l.vault = vault.unwrap_or(UId::nil());
+    self.put_label(&l)?;
+    Ok(true)
+  }
+
+  fn set_label_parent(&mut self, id: &UId, parent: Option<UId>) -> Result<bool, Self::GraphError> {
+    let Some(mut l) = self.get_label(id)?.cloned() else {
+      return Ok(false);
+    };
+    l.parent = parent.unwrap_or(UId::nil());
+    self.put_label(&l)?;
+    Ok(true)
+  }
+
+  fn set_label_group(&mut self, id: &UId, group: LabelGroup) -> Result<bool, Self::GraphError> {
+    let Some(mut l) = self.get_label(id)?.cloned() else {
+      return Ok(false);
+    };
+    l.group = group;
+    self.put_label(&l)?;
+    Ok(true)
+  }
+
+  fn attach_label(&mut self, entry: &ChangeId, label: &UId) -> Result<bool, Self::GraphError> {
+    self.attach(entry, label, 1)
+  }
+
+  fn detach_label(&mut self, entry: &ChangeId, label: &UId) -> Result<bool, Self::GraphError> {
+    if self.attachments(entry, label)? == 0 {
+      return Ok(false);
+    }
+    self.attach(entry, label, -1)
+  }
+}
 // #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
 // #[repr(C)]
 // pub struct SerializedChannel {
@@ -0,0 +1,79 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use crate::{
+  models::{
+    entry::EntryError,
+    graph::GraphTxnT,
+    space::{SpaceRef, SpaceTxnT},
+  },
+  types::{ChangeId, Money, UId},
+};
+
+use super::{LabelGroup, SerializedLabel};
+
+pub trait LabelTxnT: GraphTxnT + SpaceTxnT {
+  fn get_label(&self, id: &UId) -> Result<Option<&SerializedLabel>, Self::GraphError>;
+
+  /// Ids of the labels of `space`.
+  fn space_labels(&self, space: &SpaceRef<Self>) -> Result<Vec<UId>, Self::GraphError>;
+
+  fn label_by_name(&self, space: &SpaceRef<Self>, name: &str) -> Result<Option<&SerializedLabel>, Self::GraphError>;
+
+  /// Ids of the labels filed in `vault`.
+  fn vault_labels(&self, vault: &UId) -> Result<Vec<UId>, Self::GraphError>;
+
+  fn label_children(&self, id: &UId) -> Result<Vec<UId>, Self::GraphError>;
+
+  /// All the labels below `id`, children first.
+  fn label_descendants(&self, id: &UId) -> Result<Vec<UId>, Self::GraphError>;
+
+  /// Names of `id` and its ancestors, from the root, e.g.
+  /// `Food > Restaurants > Coffee`.
+  fn label_path(&self, id: &UId) -> Result<String, Self::GraphError>;
+
+  /// Ids of the entries labelled `id` or one of its descendants, each
+  /// once.
+  fn tree_entries(&self, id: &UId) -> Result<Vec<ChangeId>, Self::GraphError>;
+
+  /// How much the entries of `space` in `tree_entries(id)` move, in each
+  /// currency. Fails if a total overflows.
+  fn label_total(&self, space: &SpaceRef<Self>, id: &UId) -> Result<Vec<Money>, EntryError<Self::GraphError>>;
+
+  /// Ids of the entries `id` itself is attached to.
+  fn label_entries(&self, id: &UId) -> Result<Vec<ChangeId>, Self::GraphError>;
+
+  /// How many times `label` is attached to `entry`.
+  fn attachments(&self, entry: &ChangeId, label: &UId) -> Result<u64, Self::GraphError>;
+
+  /// Ids of the labels attached to `entry`.
+  fn entry_labels(&self, entry: &ChangeId) -> Result<Vec<UId>, Self::GraphError>;
+}
+
+pub trait LabelMutTxnT: LabelTxnT {
+  fn create_label(&mut self, space: &SpaceRef<Self>, id: UId, name: &str, group: LabelGroup) -> Result<(), Self::GraphError>;
+  /// Returns `false` if `space` had no such label.
+  fn del_label(&mut self, space: &SpaceRef<Self>, id: &UId) -> Result<bool, Self::GraphError>;
+
+  /// Returns `false` if there is no such label.
+  fn rename_label(&mut self, space: &SpaceRef<Self>, id: &UId, name: &str) -> Result<bool, Self::GraphError>;
+
+  /// File label `id` in `vault`, `None` being no vault. Returns `false` if
+  /// there is no such label.
+  fn set_label_vault(&mut self, id: &UId, vault: Option<UId>) -> Result<bool, Self::GraphError>;
+
+  /// Returns `false` if there is no such label.
+  fn set_label_parent(&mut self, id: &UId, parent: Option<UId>) -> Result<bool, Self::GraphError>;
+
+  /// Returns `false` if there is no such label.
+  fn set_label_group(&mut self, id: &UId, group: LabelGroup) -> Result<bool, Self::GraphError>;
+
+  /// Attach `label` to `entry` once more: a label attached twice, by two
+  /// concurrent changes, must be detached twice. Returns `false` if either
+  /// doesn't exist.
+  fn attach_label(&mut self, entry: &ChangeId, label: &UId) -> Result<bool, Self::GraphError>;
+
+  /// Undo one `attach_label`. Returns `false` if `label` is not attached
+  /// to `entry`.
+  fn detach_label(&mut self, entry: &ChangeId, label: &UId) -> Result<bool, Self::GraphError>;
+}
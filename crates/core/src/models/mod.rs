@@ -9,6 +9,8 @@ pub mod space;
 
 // core model
 pub mod compartment;
+pub mod conflict;
+pub mod device;
 pub mod entry;
 pub mod filter;
 pub mod label;
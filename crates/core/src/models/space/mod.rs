@@ -3,16 +3,25 @@
 #![allow(dead_code)]
 
 mod prelude;
-pub use prelude::SpaceTxnT;
+pub use prelude::{SpaceMutTxnT, SpaceTxnT};
+mod tag;
+pub use tag::*;
 
 use std::sync::Arc;
 
+use chrono::Utc;
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
-use sanakirja::{LoadPage, RootPage};
+use sanakirja::{btree, LoadPage, RootPage};
 
 use crate::{
-  pristine::{types::Db, GenericTxn},
-  types::{ChangeId, SmallString, UId, L64},
+  models::compartment::SerializedCompartment,
+  pristine::{
+    check_name,
+    types::{Db, UDb},
+    EncycError, GenericTxn, MutTxn,
+  },
+  traits::TxnT,
+  types::{ChangeId, Hash, Merkle, Pair, SerializedMerkle, SmallStr, SmallString, UId, L64},
 };
 
 pub struct SpaceRef<T: SpaceTxnT> {
@@ -28,10 +37,10 @@ impl<T: SpaceTxnT> SpaceRef<T> {
 }
 
 impl<T: SpaceTxnT> SpaceRef<T> {
-  pub fn read(&self) -> RwLockReadGuard<T::Space> {
+  pub fn read(&self) -> RwLockReadGuard<'_, T::Space> {
     self.r.read()
   }
-  pub fn write(&self) -> RwLockWriteGuard<T::Space> {
+  pub fn write(&self) -> RwLockWriteGuard<'_, T::Space> {
     self.r.write()
   }
 }
@@ -49,6 +58,38 @@ pub struct Space {
 
   pub entries: Db<ChangeId, L64>,
   pub vaults: Db<UId, L64>,
+  pub labels: Db<UId, L64>,
+  pub compartments: UDb<UId, SerializedCompartment>,
+  pub conflicts: UDb<ChangeId, ChangeId>, // entries in conflict to the competing changes
+
+  pub changes: Db<ChangeId, L64>,                             // change to its position in the log
+  pub revchanges: UDb<L64, Pair<ChangeId, SerializedMerkle>>, // position to change, and the state it led to
+  pub states: UDb<SerializedMerkle, L64>,                     // state to the position that produced it
+  pub apply_counter: u64,
+
+  pub tags: UDb<SmallStr, SerializedTag>,
+  pub tag_policy: TagPolicy,
+}
+
+impl Space {
+  pub(crate) fn from_serialized(name: SmallString, s: &SerializedSpace) -> Self {
+    Space {
+      id: s.id,
+      name,
+      last_modified: s.last_modified,
+      entries: unsafe { Db::from_page(s.entries.into()) },
+      vaults: unsafe { Db::from_page(s.vaults.into()) },
+      labels: unsafe { Db::from_page(s.labels.into()) },
+      compartments: unsafe { UDb::from_page(s.compartments.into()) },
+      conflicts: unsafe { UDb::from_page(s.conflicts.into()) },
+      changes: unsafe { Db::from_page(s.changes.into()) },
+      revchanges: unsafe { UDb::from_page(s.revchanges.into()) },
+      states: unsafe { UDb::from_page(s.states.into()) },
+      apply_counter: s.apply_counter.into(),
+      tags: unsafe { UDb::from_page(s.tags.into()) },
+      tag_policy: s.tag_policy,
+    }
+  }
 }
 
 impl<T: LoadPage<Error = sanakirja::Error> + RootPage> SpaceTxnT for GenericTxn<T> {
@@ -63,6 +104,166 @@ impl<T: LoadPage<Error = sanakirja::Error> + RootPage> SpaceTxnT for GenericTxn<
   }
 
   type Changeset = Db<ChangeId, L64>;
+
+  fn get_changeset(&self, space: &SpaceRef<Self>, id: &ChangeId) -> Result<Option<u64>, Self::GraphError> {
+    let space = space.read();
+    match btree::get(&self.txn, &space.changes, id, None)? {
+      Some((k, v)) if k == id => Ok(Some(v.as_u64())),
+      _ => Ok(None),
+    }
+  }
+
+  fn get_revchangeset(&self, space: &SpaceRef<Self>, pos: u64) -> Result<Option<(ChangeId, Merkle)>, Self::GraphError> {
+    let space = space.read();
+    let pos: L64 = pos.into();
+    match btree::get(&self.txn, &space.revchanges, &pos, None)? {
+      Some((k, v)) if *k == pos => Ok(Some((v.a, v.b.into()))),
+      _ => Ok(None),
+    }
+  }
+
+  fn current_state(&self, space: &SpaceRef<Self>) -> Result<Merkle, Self::GraphError> {
+    let space = space.read();
+    match btree::rev_iter(&self.txn, &space.revchanges, None)?.next() {
+      Some(x) => Ok(x?.1.b.into()),
+      None => Ok(Merkle::zero()),
+    }
+  }
+
+  fn state_position(&self, space: &SpaceRef<Self>, state: &Merkle) -> Result<Option<u64>, Self::GraphError> {
+    let space = space.read();
+    let state: SerializedMerkle = state.into();
+    match btree::get(&self.txn, &space.states, &state, None)? {
+      Some((k, v)) if *k == state => Ok(Some(v.as_u64())),
+      _ => Ok(None),
+    }
+  }
+
+  fn log(&self, space: &SpaceRef<Self>, from: u64) -> Result<Vec<(u64, ChangeId, Merkle)>, Self::GraphError> {
+    let space = space.read();
+    let mut log = Vec::new();
+    for x in btree::iter(&self.txn, &space.revchanges, Some((&from.into(), None)))? {
+      let (pos, v) = x?;
+      log.push((pos.as_u64(), v.a, v.b.into()));
+    }
+    Ok(log)
+  }
+
+  fn get_tag(&self, space: &SpaceRef<Self>, name: &str) -> Result<Option<Tag>, Self::GraphError> {
+    let space = space.read();
+    let key = SmallString::from_str(name);
+    match btree::get(&self.txn, &space.tags, &key, None)? {
+      Some((k, v)) if k == key.as_ref() => Ok(Some(v.to_tag(name))),
+      _ => Ok(None),
+    }
+  }
+
+  fn space_tags(&self, space: &SpaceRef<Self>) -> Result<Vec<Tag>, Self::GraphError> {
+    let space = space.read();
+    let mut tags = Vec::new();
+    for x in btree::iter(&self.txn, &space.tags, None)? {
+      let (name, t) = x?;
+      tags.push(t.to_tag(name.as_str()));
+    }
+    tags.sort_by_key(|t| (t.date, t.position));
+    Ok(tags)
+  }
+
+  fn tag_policy(&self, space: &SpaceRef<Self>) -> TagPolicy {
+    space.read().tag_policy
+  }
+}
+
+impl SpaceMutTxnT for MutTxn<()> {
+  fn put_changes(&mut self, space: &SpaceRef<Self>, id: ChangeId, hash: &Hash) -> Result<Option<Merkle>, EncycError> {
+    if self.get_changeset(space, &id)?.is_some() {
+      return Ok(None);
+    }
+    let state = self.current_state(space)?.next(hash);
+    let mut space = space.write();
+    let pos: L64 = space.apply_counter.into();
+    let serialized: SerializedMerkle = (&state).into();
+    btree::put(&mut self.txn, &mut space.changes, &id, &pos)?;
+    btree::put(
+      &mut self.txn,
+      &mut space.revchanges,
+      &pos,
+      &Pair {
+        a: id,
+        b: serialized,
+      },
+    )?;
+    btree::put(&mut self.txn, &mut space.states, &serialized, &pos)?;
+    space.apply_counter += 1;
+    space.last_modified = Utc::now().timestamp() as u64;
+    Ok(Some(state))
+  }
+
+  fn del_changes(&mut self, space: &SpaceRef<Self>, id: &ChangeId) -> Result<bool, EncycError> {
+    let Some(pos) = self.get_changeset(space, id)? else {
+      return Ok(false);
+    };
+    let log = self.log(space, 0)?;
+    let mut state = log
+      .iter()
+      .take_while(|(p, _, _)| *p < pos)
+      .last()
+      .map(|(_, _, m)| *m)
+      .unwrap_or_else(Merkle::zero);
+
+    let tail: Vec<_> = log.into_iter().filter(|(p, _, _)| *p >= pos).collect();
+    let mut later = Vec::new();
+    for (p, c, _) in tail.iter().filter(|(p, _, _)| *p > pos) {
+      let hash = self.get_external(c)?.ok_or(EncycError::PristineCorrupted)?;
+      later.push((*p, *c, hash));
+    }
+
+    let mut space = space.write();
+    // Take out the states of `id` and of all the changes after it, then put
+    // the later ones back, with their new states.
+    for (p, _, m) in tail {
+      btree::del(&mut self.txn, &mut space.revchanges, &p.into(), None)?;
+      btree::del(&mut self.txn, &mut space.states, &(&m).into(), None)?;
+    }
+    btree::del(&mut self.txn, &mut space.changes, id, None)?;
+    for (p, c, hash) in later {
+      state = state.next(&hash);
+      let serialized: SerializedMerkle = (&state).into();
+      btree::put(
+        &mut self.txn,
+        &mut space.revchanges,
+        &p.into(),
+        &Pair {
+          a: c,
+          b: serialized,
+        },
+      )?;
+      btree::put(&mut self.txn, &mut space.states, &serialized, &p.into())?;
+    }
+    space.last_modified = Utc::now().timestamp() as u64;
+    Ok(true)
+  }
+
+  fn put_tag(&mut self, space: &SpaceRef<Self>, tag: &Tag) -> Result<(), EncycError> {
+    check_name(&tag.name)?;
+    if self.get_tag(space, &tag.name)?.is_some() {
+      return Err(EncycError::AlreadyExists(tag.name.clone()));
+    }
+    let mut space = space.write();
+    let name = SmallString::from_str(&tag.name);
+    btree::put(&mut self.txn, &mut space.tags, &name, &tag.into())?;
+    Ok(())
+  }
+
+  fn del_tag(&mut self, space: &SpaceRef<Self>, name: &str) -> Result<bool, EncycError> {
+    let mut space = space.write();
+    let name = SmallString::from_str(name);
+    Ok(btree::del(&mut self.txn, &mut space.tags, &name, None)?)
+  }
+
+  fn set_tag_policy(&mut self, space: &SpaceRef<Self>, policy: TagPolicy) {
+    space.write().tag_policy = policy;
+  }
 }
 
 #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
@@ -73,4 +274,34 @@ pub struct SerializedSpace {
 
   pub entries: L64, // transactions
   pub labels: L64,  // like tags
+  pub compartments: L64,
+  pub conflicts: L64,
+
+  pub changes: L64,
+  pub revchanges: L64,
+  pub states: L64,
+  pub apply_counter: L64,
+
+  pub tags: L64,
+  pub tag_policy: TagPolicy,
+}
+
+impl<'a> From<&'a Space> for SerializedSpace {
+  fn from(space: &'a Space) -> Self {
+    SerializedSpace {
+      id: space.id,
+      vaults: space.vaults.db.get().into(),
+      last_modified: space.last_modified,
+      entries: space.entries.db.get().into(),
+      labels: space.labels.db.get().into(),
+      compartments: space.compartments.db.get().into(),
+      conflicts: space.conflicts.db.get().into(),
+      changes: space.changes.db.get().into(),
+      revchanges: space.revchanges.db.get().into(),
+      states: space.states.db.get().into(),
+      apply_counter: space.apply_counter.into(),
+      tags: space.tags.db.get().into(),
+      tag_policy: space.tag_policy,
+    }
+  }
 }
@@ -1,13 +1,50 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 15.
 
-use crate::types::UId;
+use crate::{
+  models::graph::GraphTxnT,
+  types::{ChangeId, Hash, Merkle, UId},
+};
 
-pub trait SpaceTxnT {
+use super::{SpaceRef, Tag, TagPolicy};
+
+pub trait SpaceTxnT: GraphTxnT {
   type Space: Sync + Send;
 
   fn id<'a>(&self, space: &'a Self::Space) -> Option<&'a UId>;
   fn name<'a>(&self, space: &'a Self::Space) -> &'a str;
 
   type Changeset;
+
+  /// Position of change `id` in the log of `space`, if it was applied there.
+  fn get_changeset(&self, space: &SpaceRef<Self>, id: &ChangeId) -> Result<Option<u64>, Self::GraphError>;
+  /// The change applied at position `pos` of the log of `space`, and the
+  /// state it led to.
+  fn get_revchangeset(&self, space: &SpaceRef<Self>, pos: u64) -> Result<Option<(ChangeId, Merkle)>, Self::GraphError>;
+  /// Merkle state of `space` after its last change, or `Merkle::zero()`.
+  fn current_state(&self, space: &SpaceRef<Self>) -> Result<Merkle, Self::GraphError>;
+  /// Position of the change that brought `space` to `state`, if any.
+  fn state_position(&self, space: &SpaceRef<Self>, state: &Merkle) -> Result<Option<u64>, Self::GraphError>;
+  /// Log of `space` from position `from` on, oldest first.
+  fn log(&self, space: &SpaceRef<Self>, from: u64) -> Result<Vec<(u64, ChangeId, Merkle)>, Self::GraphError>;
+
+  fn get_tag(&self, space: &SpaceRef<Self>, name: &str) -> Result<Option<Tag>, Self::GraphError>;
+  /// Tags of `space`, by date.
+  fn space_tags(&self, space: &SpaceRef<Self>) -> Result<Vec<Tag>, Self::GraphError>;
+  fn tag_policy(&self, space: &SpaceRef<Self>) -> TagPolicy;
+}
+
+pub trait SpaceMutTxnT: SpaceTxnT {
+  /// Append change `id`, with hash `hash`, to the log of `space`, returning
+  /// the new state, or `None` if the change was already there.
+  fn put_changes(&mut self, space: &SpaceRef<Self>, id: ChangeId, hash: &Hash) -> Result<Option<Merkle>, Self::GraphError>;
+  /// Remove change `id` from the log of `space`, recomputing the states of
+  /// the changes applied after it. Returns `false` if `id` wasn't there.
+  fn del_changes(&mut self, space: &SpaceRef<Self>, id: &ChangeId) -> Result<bool, Self::GraphError>;
+
+  /// Add `tag` to `space`, failing if there is already a tag with that name.
+  fn put_tag(&mut self, space: &SpaceRef<Self>, tag: &Tag) -> Result<(), Self::GraphError>;
+  /// Returns `false` if `space` had no tag `name`.
+  fn del_tag(&mut self, space: &SpaceRef<Self>, name: &str) -> Result<bool, Self::GraphError>;
+  fn set_tag_policy(&mut self, space: &SpaceRef<Self>, policy: TagPolicy);
 }
@@ -0,0 +1,84 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  types::{Merkle, SerializedMerkle, L64},
+  ParseError,
+};
+
+/// What to do when recording, pulling or unrecording a change that adds,
+/// edits or deletes an entry dated on or before the date of a tag.
+#[derive(Debug, Clone, Copy, Default, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum TagPolicy {
+  #[default]
+  Warn,
+  Reject,
+}
+
+impl std::fmt::Display for TagPolicy {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    match self {
+      TagPolicy::Warn => f.write_str("warn"),
+      TagPolicy::Reject => f.write_str("reject"),
+    }
+  }
+}
+
+impl std::str::FromStr for TagPolicy {
+  type Err = ParseError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "warn" => Ok(TagPolicy::Warn),
+      "reject" => Ok(TagPolicy::Reject),
+      _ => Err(ParseError { s: s.to_string() }),
+    }
+  }
+}
+
+/// A named state of a space, closing the books up to `date`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+  pub name: String,
+  pub state: Merkle,
+  /// Number of positions of the log covered by the tag: the tagged state
+  /// is the one after the change at `position - 1`.
+  pub position: u64,
+  /// Last day of the closed period.
+  pub date: NaiveDate,
+  pub created: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedTag {
+  pub position: L64,
+  pub date: L64, // days since January 1st of year 1
+  pub created: L64,
+  pub state: SerializedMerkle,
+}
+
+impl SerializedTag {
+  pub fn to_tag(&self, name: &str) -> Tag {
+    Tag {
+      name: name.to_string(),
+      state: self.state.into(),
+      position: self.position.into(),
+      date: NaiveDate::from_num_days_from_ce_opt(self.date.as_u64() as i32).unwrap_or_default(),
+      created: DateTime::from_timestamp(self.created.as_u64() as i64, 0).unwrap_or_default(),
+    }
+  }
+}
+
+impl<'a> From<&'a Tag> for SerializedTag {
+  fn from(tag: &'a Tag) -> Self {
+    SerializedTag {
+      position: tag.position.into(),
+      date: (tag.date.num_days_from_ce() as u64).into(),
+      created: (tag.created.timestamp() as u64).into(),
+      state: (&tag.state).into(),
+    }
+  }
+}
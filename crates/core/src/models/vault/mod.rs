@@ -2,28 +2,30 @@
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 25.
 
 mod prelude;
-pub use prelude::VaultTxnT;
-use sanakirja::{LoadPage, RootPage};
+pub use prelude::*;
 
 use std::sync::Arc;
 
+use chrono::Utc;
 use parking_lot::Mutex;
+use sanakirja::{btree, LoadPage, RootPage};
 
 use crate::{
-  pristine::{types::UDb, GenericTxn},
+  models::space::SpaceRef,
+  pristine::{check_name, types::UDb, EncycError, GenericTxn, MutTxn},
   types::{SmallString, UId, L64},
 };
 
-#[derive(Debug, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
 #[repr(C)]
 pub struct SerializedVault {
-  compartments: L64,
-  labels: L64,
+  pub compartments: L64,
+  pub labels: L64,
 
-  mode: u8,
-  alias: SmallString,
-  name: SmallString, // name of the vault
-  id: UId,
+  pub mode: u8,
+  pub alias: SmallString,
+  pub name: SmallString, // name of the vault
+  pub id: UId,
 }
 
 pub struct Vault<T: VaultTxnT> {
@@ -45,7 +47,7 @@ impl<T: VaultTxnT> Clone for VaultRef<T> {
   fn clone(&self) -> Self {
     Self {
       db: self.db.clone(),
-      id: self.id.clone(),
+      id: self.id,
     }
   }
 }
@@ -53,4 +55,77 @@ impl<T: VaultTxnT> Clone for VaultRef<T> {
 impl<T: LoadPage<Error = sanakirja::Error> + RootPage> VaultTxnT for GenericTxn<T> {
   type Labels = UDb<L64, UId>;
   type Compartments = UDb<L64, UId>;
+
+  fn get_vault(&self, id: &UId) -> Result<Option<&SerializedVault>, Self::GraphError> {
+    match btree::get(&self.txn, &self.vaults, id, None)? {
+      Some((k, v)) if k == id => Ok(Some(v)),
+      _ => Ok(None),
+    }
+  }
+
+  fn space_vaults(&self, space: &SpaceRef<Self>) -> Result<Vec<UId>, Self::GraphError> {
+    let space = space.read();
+    let mut vaults = Vec::new();
+    for x in btree::iter(&self.txn, &space.vaults, None)? {
+      let (id, _) = x?;
+      vaults.push(*id);
+    }
+    Ok(vaults)
+  }
+}
+
+impl VaultMutTxnT for MutTxn<()> {
+  fn create_vault(&mut self, space: &SpaceRef<Self>, id: UId, name: &str) -> Result<(), Self::GraphError> {
+    check_name(name)?;
+    for id in self.space_vaults(space)? {
+      if self
+        .get_vault(&id)?
+        .map(|v| v.name.as_str() == name)
+        .unwrap_or(false)
+      {
+        return Err(EncycError::AlreadyExists(name.to_string()));
+      }
+    }
+    if self.get_vault(&id)?.is_some() {
+      return Err(EncycError::AlreadyExists(id.to_string()));
+    }
+    let compartments: UDb<L64, UId> = unsafe { btree::create_db_(&mut self.txn)? };
+    let labels: UDb<L64, UId> = unsafe { btree::create_db_(&mut self.txn)? };
+    let vault = SerializedVault {
+      compartments: compartments.db.get().into(),
+      labels: labels.db.get().into(),
+      mode: 0,
+      alias: SmallString::new(),
+      name: SmallString::from_str(name),
+      id,
+    };
+    btree::put(&mut self.txn, &mut self.vaults, &id, &vault)?;
+    let mut space = space.write();
+    btree::put(
+      &mut self.txn,
+      &mut space.vaults,
+      &id,
+      &(Utc::now().timestamp() as u64).into(),
+    )?;
+    Ok(())
+  }
+
+  fn del_vault(&mut self, space: &SpaceRef<Self>, id: &UId) -> Result<bool, Self::GraphError> {
+    {
+      let mut space = space.write();
+      if !btree::del(&mut self.txn, &mut space.vaults, id, None)? {
+        return Ok(false);
+      }
+    }
+    if let Some(v) = self.get_vault(id)?.cloned() {
+      let compartments: UDb<L64, UId> = unsafe { UDb::from_page(v.compartments.into()) };
+      let labels: UDb<L64, UId> = unsafe { UDb::from_page(v.labels.into()) };
+      unsafe {
+        btree::drop(&mut self.txn, compartments)?;
+        btree::drop(&mut self.txn, labels)?;
+      }
+      btree::del(&mut self.txn, &mut self.vaults, id, None)?;
+    }
+    Ok(true)
+  }
 }
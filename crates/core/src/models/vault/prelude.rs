@@ -1,7 +1,28 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 25.
 
-pub trait VaultTxnT {
+use crate::{
+  models::{
+    graph::GraphTxnT,
+    space::{SpaceRef, SpaceTxnT},
+  },
+  types::UId,
+};
+
+use super::SerializedVault;
+
+pub trait VaultTxnT: GraphTxnT + SpaceTxnT {
   type Compartments;
   type Labels;
+
+  fn get_vault(&self, id: &UId) -> Result<Option<&SerializedVault>, Self::GraphError>;
+
+  /// Ids of the vaults of `space`.
+  fn space_vaults(&self, space: &SpaceRef<Self>) -> Result<Vec<UId>, Self::GraphError>;
+}
+
+pub trait VaultMutTxnT: VaultTxnT {
+  fn create_vault(&mut self, space: &SpaceRef<Self>, id: UId, name: &str) -> Result<(), Self::GraphError>;
+  /// Returns `false` if `space` had no such vault.
+  fn del_vault(&mut self, space: &SpaceRef<Self>, id: &UId) -> Result<bool, Self::GraphError>;
 }
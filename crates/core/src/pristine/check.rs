@@ -0,0 +1,517 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use log::debug;
+use sanakirja::{
+  btree::{self, BTreeMutPage, BTreePage},
+  debug::Check,
+  Commit, Env, LoadPage, RootDb, Storable, UnsizedStorable,
+};
+
+use crate::{
+  models::{
+    compartment::SerializedCompartment,
+    device::SerializedDevice,
+    entry::{SerializedEntry, SerializedPosting},
+    filter::SerializedFilter,
+    label::SerializedLabel,
+    space::{SerializedSpace, SerializedTag},
+    vault::SerializedVault,
+  },
+  types::{Base32, ChangeId, Pair, SerializedHash, SerializedMerkle, SmallStr, SmallString, UId, L64},
+};
+
+use super::{
+  encyc::{N_ROOTS, PAGE_SIZE},
+  migrate::check_version,
+  sanakirja::types::*,
+  Encyc, EncycError, Root,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct CheckOptions {
+  /// Fix the problems found, by resetting counters and replacing broken
+  /// databases with empty ones.
+  pub repair: bool,
+}
+
+/// Key of the record a problem was found in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordKey {
+  ChangeId(ChangeId),
+  UId(UId),
+  Name(String),
+  /// A compartment, in the space of that name.
+  Compartment(String, UId),
+}
+
+impl fmt::Display for RecordKey {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      RecordKey::ChangeId(id) => write!(f, "{}", id.to_base32()),
+      RecordKey::UId(id) => write!(f, "{}", id),
+      RecordKey::Name(name) => write!(f, "{:?}", name),
+      RecordKey::Compartment(space, id) => write!(f, "{:?} {}", space, id),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+  MissingRoot(Root),
+  DanglingRoot {
+    root: Root,
+    page: u64,
+  },
+  DanglingPage {
+    root: Root,
+    key: RecordKey,
+    field: &'static str,
+    page: u64,
+  },
+  SharedPage {
+    root: Root,
+    key: RecordKey,
+    field: &'static str,
+    page: u64,
+  },
+  Counter {
+    root: Root,
+    key: RecordKey,
+    field: &'static str,
+    stored: u64,
+    actual: u64,
+  },
+}
+
+impl fmt::Display for Problem {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Problem::MissingRoot(root) => write!(f, "{:?}: missing root database", root),
+      Problem::DanglingRoot { root, page } => write!(
+        f,
+        "{:?}: root database points to invalid page 0x{:x}",
+        root, page
+      ),
+      Problem::DanglingPage {
+        root,
+        key,
+        field,
+        page,
+      } => {
+        write!(
+          f,
+          "{:?} {}: `{}` points to invalid page 0x{:x}",
+          root, key, field, page
+        )
+      }
+      Problem::SharedPage {
+        root,
+        key,
+        field,
+        page,
+      } => {
+        write!(
+          f,
+          "{:?} {}: `{}` points to page 0x{:x}, already used elsewhere",
+          root, key, field, page
+        )
+      }
+      Problem::Counter {
+        root,
+        key,
+        field,
+        stored,
+        actual,
+      } => {
+        write!(
+          f,
+          "{:?} {}: `{}` is {}, but {} were found",
+          root, key, field, stored, actual
+        )
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CheckReport {
+  pub problems: Vec<Problem>,
+  /// Number of pages reachable from the root databases.
+  pub pages: usize,
+  pub repaired: bool,
+}
+
+impl CheckReport {
+  pub fn is_ok(&self) -> bool {
+    self.problems.is_empty()
+  }
+}
+
+impl Encyc {
+  /// Walk every root database, verifying that all the page references are
+  /// valid and that the stored counters match the databases they count.
+  pub fn check(&self, options: &CheckOptions) -> Result<CheckReport, EncycError> {
+    let end = match self.path {
+      Some(ref path) => std::fs::metadata(path)?.len(),
+      None => u64::MAX,
+    };
+
+    if options.repair {
+      let mut txn = Env::mut_txn_begin(self.env.clone())?;
+      if let Some(version) = txn.root(Root::Version as usize) {
+        check_version(L64(version).as_u64())?;
+      }
+      let mut checker = Checker::new(&txn, end);
+      checker.run()?;
+      let (problems, pages) = (checker.problems, checker.refs.len());
+      for p in problems.iter() {
+        repair(&mut txn, p)?;
+      }
+      txn.commit()?;
+      Ok(CheckReport {
+        problems,
+        pages,
+        repaired: true,
+      })
+    } else {
+      let txn = Env::txn_begin(self.env.clone())?;
+      match txn.root(Root::Version as usize) {
+        0 => {}
+        version => check_version(L64(version).as_u64())?,
+      }
+      let mut checker = Checker::new(&txn, end);
+      checker.run()?;
+      Ok(CheckReport {
+        pages: checker.refs.len(),
+        problems: checker.problems,
+        repaired: false,
+      })
+    }
+  }
+}
+
+struct Checker<'a, T> {
+  txn: &'a T,
+  end: u64,
+  refs: BTreeMap<u64, usize>,
+  problems: Vec<Problem>,
+}
+
+impl<'a, T: LoadPage<Error = sanakirja::Error> + RootDb> Checker<'a, T> {
+  fn new(txn: &'a T, end: u64) -> Self {
+    Checker {
+      txn,
+      end,
+      refs: BTreeMap::new(),
+      problems: Vec::new(),
+    }
+  }
+
+  fn is_valid(&self, page: u64) -> bool {
+    page >= N_ROOTS as u64 * PAGE_SIZE && page % PAGE_SIZE == 0 && page < self.end
+  }
+
+  fn run(&mut self) -> Result<(), EncycError> {
+    if let Some(db) = self.root::<ChangeId, SerializedEntry>(Root::Entries)? {
+      for x in btree::iter(self.txn, &db, None)? {
+        let (id, entry) = x?;
+        let key = RecordKey::ChangeId(*id);
+        if let Some(n) = self.sub_db::<L64, ChangeId, P<_, _>>(Root::Entries, &key, "changes", entry.changes)? {
+          if n != entry.change_count.as_u64() {
+            self.problems.push(Problem::Counter {
+              root: Root::Entries,
+              key: key.clone(),
+              field: "change_count",
+              stored: entry.change_count.as_u64(),
+              actual: n,
+            })
+          }
+        }
+        self.sub_db::<UId, L64, P<_, _>>(Root::Entries, &key, "tags", entry.tags)?;
+        self.sub_db::<L64, SerializedPosting, P<_, _>>(Root::Entries, &key, "postings", entry.postings)?;
+      }
+    }
+
+    if let Some(db) = self.root::<UId, SerializedLabel>(Root::Labels)? {
+      for x in btree::iter(self.txn, &db, None)? {
+        let (id, label) = x?;
+        let key = RecordKey::UId(*id);
+        self.sub_db::<UId, L64, UP<_, _>>(Root::Labels, &key, "header", label.header)?;
+        self.sub_db::<ChangeId, L64, P<_, _>>(Root::Labels, &key, "entries", label.entries)?;
+      }
+    }
+
+    if let Some(db) = self.root::<UId, SerializedFilter>(Root::Filters)? {
+      for x in btree::iter(self.txn, &db, None)? {
+        let (id, filter) = x?;
+        let key = RecordKey::UId(*id);
+        self.sub_db::<UId, L64, UP<_, _>>(Root::Filters, &key, "header", filter.header)?;
+        self.sub_db::<UId, L64, P<_, _>>(Root::Filters, &key, "labels", filter.labels)?;
+        self.sub_db::<UId, L64, P<_, _>>(Root::Filters, &key, "compartments", filter.compartments)?;
+      }
+    }
+
+    if let Some(db) = self.root::<UId, SerializedVault>(Root::Vaults)? {
+      for x in btree::iter(self.txn, &db, None)? {
+        let (id, vault) = x?;
+        let key = RecordKey::UId(*id);
+        self.sub_db::<L64, UId, UP<_, _>>(Root::Vaults, &key, "compartments", vault.compartments)?;
+        self.sub_db::<L64, UId, UP<_, _>>(Root::Vaults, &key, "labels", vault.labels)?;
+      }
+    }
+
+    if let Some(db) = self.root::<SmallStr, SerializedSpace>(Root::Spaces)? {
+      for x in btree::iter(self.txn, &db, None)? {
+        let (name, space) = x?;
+        let key = RecordKey::Name(name.as_str().to_string());
+        self.sub_db::<ChangeId, L64, P<_, _>>(Root::Spaces, &key, "entries", space.entries)?;
+        self.sub_db::<UId, L64, P<_, _>>(Root::Spaces, &key, "vaults", space.vaults)?;
+        self.sub_db::<UId, L64, P<_, _>>(Root::Spaces, &key, "labels", space.labels)?;
+        if self
+          .sub_db::<UId, SerializedCompartment, UP<_, _>>(Root::Spaces, &key, "compartments", space.compartments)?
+          .is_some()
+        {
+          let db: UDb<UId, SerializedCompartment> = unsafe { UDb::from_page(space.compartments.into()) };
+          for x in btree::iter(self.txn, &db, None)? {
+            let (id, compartment) = x?;
+            let key = RecordKey::Compartment(name.as_str().to_string(), *id);
+            self.sub_db::<ChangeId, L64, P<_, _>>(Root::Spaces, &key, "entries", compartment.entries)?;
+          }
+        }
+        self.sub_db::<ChangeId, ChangeId, UP<_, _>>(Root::Spaces, &key, "conflicts", space.conflicts)?;
+        self.sub_db::<ChangeId, L64, P<_, _>>(Root::Spaces, &key, "changes", space.changes)?;
+        self.sub_db::<L64, Pair<ChangeId, SerializedMerkle>, UP<_, _>>(Root::Spaces, &key, "revchanges", space.revchanges)?;
+        self.sub_db::<SerializedMerkle, L64, UP<_, _>>(Root::Spaces, &key, "states", space.states)?;
+        self.sub_db::<SmallStr, SerializedTag, UP<_, _>>(Root::Spaces, &key, "tags", space.tags)?;
+      }
+    }
+
+    self.root::<SerializedHash, ChangeId>(Root::Internal)?;
+    self.root::<ChangeId, SerializedHash>(Root::External)?;
+    self.root::<SmallStr, SerializedDevice>(Root::Devices)?;
+    self.root::<ChangeId, ChangeId>(Root::Dependencies)?;
+    self.root::<ChangeId, ChangeId>(Root::Dependents)?;
+    self.root::<UId, ChangeId>(Root::Creators)?;
+    Ok(())
+  }
+
+  fn root<K, V>(&mut self, root: Root) -> Result<Option<UDb<K, V>>, EncycError>
+  where
+    K: UnsizedStorable + Check + Ord + ?Sized,
+    V: UnsizedStorable + Check + Ord + ?Sized,
+  {
+    match self.txn.root_db::<K, V, UP<K, V>>(root as usize) {
+      None => {
+        self.problems.push(Problem::MissingRoot(root));
+        Ok(None)
+      }
+      Some(db) if !self.is_valid(db.db.get()) => {
+        self.problems.push(Problem::DanglingRoot {
+          root,
+          page: db.db.get(),
+        });
+        Ok(None)
+      }
+      Some(db) => {
+        debug!("check {:?} 0x{:x}", root, db.db);
+        db.add_refs(self.txn, &mut self.refs)?;
+        Ok(Some(db))
+      }
+    }
+  }
+
+  /// Check the database referenced by `field` in record `key`, returning the
+  /// number of bindings it contains if it is valid.
+  fn sub_db<K, V, Page>(&mut self, root: Root, key: &RecordKey, field: &'static str, page: L64) -> Result<Option<u64>, EncycError>
+  where
+    K: UnsizedStorable + Check + Ord + ?Sized,
+    V: UnsizedStorable + Check + Ord + ?Sized,
+    Page: BTreePage<K, V> + fmt::Debug,
+  {
+    let page = page.as_u64();
+    if !self.is_valid(page) {
+      self.problems.push(Problem::DanglingPage {
+        root,
+        key: key.clone(),
+        field,
+        page,
+      });
+      return Ok(None);
+    }
+    if self.refs.contains_key(&page) {
+      self.problems.push(Problem::SharedPage {
+        root,
+        key: key.clone(),
+        field,
+        page,
+      });
+      return Ok(None);
+    }
+    let db: btree::Db_<K, V, Page> = unsafe { btree::Db_::from_page(page) };
+    db.add_refs(self.txn, &mut self.refs)?;
+    let mut n = 0;
+    for x in btree::iter(self.txn, &db, None)? {
+      x?;
+      n += 1
+    }
+    Ok(Some(n))
+  }
+}
+
+type RawMutTxn = sanakirja::MutTxn<Arc<Env>, ()>;
+
+fn repair(txn: &mut RawMutTxn, problem: &Problem) -> Result<(), EncycError> {
+  debug!("repair {:?}", problem);
+  match problem {
+    Problem::MissingRoot(root) | Problem::DanglingRoot { root, .. } => {
+      let page = match root {
+        Root::Version | Root::Compartments | Root::Conflicts => unreachable!(),
+        Root::Entries => new_db::<ChangeId, SerializedEntry, UP<_, _>>(txn)?,
+        Root::Labels => new_db::<UId, SerializedLabel, UP<_, _>>(txn)?,
+        Root::Filters => new_db::<UId, SerializedFilter, UP<_, _>>(txn)?,
+        Root::Vaults => new_db::<UId, SerializedVault, UP<_, _>>(txn)?,
+        Root::Spaces => new_db::<SmallStr, SerializedSpace, UP<_, _>>(txn)?,
+        Root::Internal => new_db::<SerializedHash, ChangeId, UP<_, _>>(txn)?,
+        Root::External => new_db::<ChangeId, SerializedHash, UP<_, _>>(txn)?,
+        Root::Devices => new_db::<SmallStr, SerializedDevice, UP<_, _>>(txn)?,
+        Root::Dependencies => new_db::<ChangeId, ChangeId, UP<_, _>>(txn)?,
+        Root::Dependents => new_db::<ChangeId, ChangeId, UP<_, _>>(txn)?,
+        Root::Creators => new_db::<UId, ChangeId, UP<_, _>>(txn)?,
+      };
+      txn.set_root(*root as usize, page);
+    }
+    Problem::DanglingPage {
+      root, key, field, ..
+    }
+    | Problem::SharedPage {
+      root, key, field, ..
+    } => match (root, key, *field) {
+      (Root::Entries, RecordKey::ChangeId(id), "changes") => update::<ChangeId, SerializedEntry>(txn, *root, id, |txn, e| {
+        e.changes = new_db::<L64, ChangeId, P<_, _>>(txn)?.into();
+        e.change_count = 0u64.into();
+        Ok(())
+      })?,
+      (Root::Entries, RecordKey::ChangeId(id), "tags") => update::<ChangeId, SerializedEntry>(txn, *root, id, |txn, e| {
+        e.tags = new_db::<UId, L64, P<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Entries, RecordKey::ChangeId(id), "postings") => update::<ChangeId, SerializedEntry>(txn, *root, id, |txn, e| {
+        e.postings = new_db::<L64, SerializedPosting, P<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Labels, RecordKey::UId(id), "header") => update::<UId, SerializedLabel>(txn, *root, id, |txn, l| {
+        l.header = new_db::<UId, L64, UP<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Labels, RecordKey::UId(id), "entries") => update::<UId, SerializedLabel>(txn, *root, id, |txn, l| {
+        l.entries = new_db::<ChangeId, L64, P<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Filters, RecordKey::UId(id), "header") => update::<UId, SerializedFilter>(txn, *root, id, |txn, f| {
+        f.header = new_db::<UId, L64, UP<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Filters, RecordKey::UId(id), "labels") => update::<UId, SerializedFilter>(txn, *root, id, |txn, f| {
+        f.labels = new_db::<UId, L64, P<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Filters, RecordKey::UId(id), "compartments") => update::<UId, SerializedFilter>(txn, *root, id, |txn, f| {
+        f.compartments = new_db::<UId, L64, P<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Vaults, RecordKey::UId(id), "compartments") => update::<UId, SerializedVault>(txn, *root, id, |txn, v| {
+        v.compartments = new_db::<L64, UId, UP<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Vaults, RecordKey::UId(id), "labels") => update::<UId, SerializedVault>(txn, *root, id, |txn, v| {
+        v.labels = new_db::<L64, UId, UP<_, _>>(txn)?.into();
+        Ok(())
+      })?,
+      (Root::Spaces, RecordKey::Compartment(name, id), "entries") => {
+        let name = SmallString::from_str(name);
+        update::<SmallStr, SerializedSpace>(txn, *root, name.as_ref(), |txn, s| {
+          let mut db: UDb<UId, SerializedCompartment> = unsafe { UDb::from_page(s.compartments.into()) };
+          let mut c = match btree::get(txn, &db, id, None)? {
+            Some((k, c)) if k == id => c.clone(),
+            _ => return Ok(()),
+          };
+          c.entries = new_db::<ChangeId, L64, P<_, _>>(txn)?.into();
+          btree::del(txn, &mut db, id, None)?;
+          btree::put(txn, &mut db, id, &c)?;
+          s.compartments = db.db.get().into();
+          Ok(())
+        })?
+      }
+      (Root::Spaces, RecordKey::Name(name), field) => {
+        let name = SmallString::from_str(name);
+        update::<SmallStr, SerializedSpace>(txn, *root, name.as_ref(), |txn, s| {
+          match field {
+            "entries" => s.entries = new_db::<ChangeId, L64, P<_, _>>(txn)?.into(),
+            "vaults" => s.vaults = new_db::<UId, L64, P<_, _>>(txn)?.into(),
+            "labels" => s.labels = new_db::<UId, L64, P<_, _>>(txn)?.into(),
+            "compartments" => s.compartments = new_db::<UId, SerializedCompartment, UP<_, _>>(txn)?.into(),
+            "conflicts" => s.conflicts = new_db::<ChangeId, ChangeId, UP<_, _>>(txn)?.into(),
+            "tags" => s.tags = new_db::<SmallStr, SerializedTag, UP<_, _>>(txn)?.into(),
+            // The log is lost: start it over.
+            _ => {
+              s.changes = new_db::<ChangeId, L64, P<_, _>>(txn)?.into();
+              s.revchanges = new_db::<L64, Pair<ChangeId, SerializedMerkle>, UP<_, _>>(txn)?.into();
+              s.states = new_db::<SerializedMerkle, L64, UP<_, _>>(txn)?.into();
+              s.apply_counter = 0u64.into();
+            }
+          }
+          Ok(())
+        })?
+      }
+      _ => unreachable!(),
+    },
+    Problem::Counter {
+      root,
+      key,
+      field,
+      actual,
+      ..
+    } => match (root, key, *field) {
+      (Root::Entries, RecordKey::ChangeId(id), "change_count") => update::<ChangeId, SerializedEntry>(txn, *root, id, |_, e| {
+        e.change_count = (*actual).into();
+        Ok(())
+      })?,
+      _ => unreachable!(),
+    },
+  }
+  Ok(())
+}
+
+fn new_db<K, V, Page>(txn: &mut RawMutTxn) -> Result<u64, EncycError>
+where
+  K: Storable + ?Sized,
+  V: Storable + ?Sized,
+  Page: BTreeMutPage<K, V>,
+{
+  let db: btree::Db_<K, V, Page> = unsafe { btree::create_db_(txn)? };
+  Ok(db.db.get())
+}
+
+/// Replace the value bound to `key` in root database `root` by its image
+/// through `f`.
+fn update<K, V>(txn: &mut RawMutTxn, root: Root, key: &K, f: impl FnOnce(&mut RawMutTxn, &mut V) -> Result<(), EncycError>) -> Result<(), EncycError>
+where
+  K: UnsizedStorable + Ord + ?Sized,
+  V: UnsizedStorable + Ord + Clone,
+{
+  let Some(mut db) = txn.root_db::<K, V, UP<K, V>>(root as usize) else {
+    return Ok(());
+  };
+  let mut value = match btree::get(txn, &db, key, None)? {
+    Some((k, v)) if k == key => v.clone(),
+    _ => return Ok(()),
+  };
+  f(txn, &mut value)?;
+  btree::del(txn, &mut db, key, None)?;
+  btree::put(txn, &mut db, key, &value)?;
+  txn.set_root(root as usize, db.db.get());
+  Ok(())
+}
@@ -3,16 +3,29 @@
 #![allow(dead_code)]
 
 use anyhow::Result;
-use std::{io::ErrorKind, path::Path, sync::Arc};
+use std::{
+  io::ErrorKind,
+  ops::{Deref, DerefMut},
+  path::{Path, PathBuf},
+  sync::Arc,
+};
 
-use sanakirja::Env;
+use sanakirja::{AllocPage, Commit, CowPage, Env, LoadPage, MutPage, RootPage};
 
 use super::EncycError;
 
-const DB_SIZE: u64 = 1 << 13; // 8KB
+// Initial size of the pristine. Sanakirja maps a new chunk, twice as large as
+// the previous one, whenever a transaction runs out of pages, so this only
+// needs to be large enough for a small ledger.
+const DB_SIZE: u64 = 1 << 20; // 1MB
+const DB_MAX_SIZE: u64 = 1 << 36; // 64GB
+pub(crate) const N_ROOTS: usize = 2;
+pub(crate) const PAGE_SIZE: u64 = 4096;
 
 pub struct Encyc {
   pub env: Arc<Env>,
+  pub(crate) path: Option<PathBuf>,
+  pub(crate) max_size: u64,
 }
 
 // Encyc is a short string for Encyclopedia and a wrapper around sanakirja::Env
@@ -22,9 +35,10 @@ impl Encyc {
   }
 
   pub fn new_with_size<P: AsRef<Path>>(name: P, size: u64) -> Result<Self, EncycError> {
-    let env = Env::new(name, size, 2);
+    let path = name.as_ref().to_path_buf();
+    let env = Env::new(name, size, N_ROOTS);
     match env {
-      Ok(env) => Ok(Self { env: Arc::new(env) }),
+      Ok(env) => Ok(Self::from_env(env, Some(path))),
       Err(sanakirja::Error::IO(e)) => {
         if e.kind() == ErrorKind::WouldBlock {
           Err(EncycError::PristineLocked)
@@ -36,13 +50,21 @@ impl Encyc {
     }
   }
 
+  /// # Safety
+  ///
+  /// The pristine must not be opened by any other process, or more than once
+  /// by this process, while a mutable transaction may be started on it.
   pub unsafe fn new_nolock<P: AsRef<Path>>(name: P) -> Result<Self, EncycError> {
     Self::new_with_size_nolock(name, DB_SIZE)
   }
 
+  /// # Safety
+  ///
+  /// See [`Encyc::new_nolock`].
   pub unsafe fn new_with_size_nolock<P: AsRef<Path>>(name: P, size: u64) -> Result<Self, EncycError> {
-    let env = Env::new_nolock(name, size, 2)?;
-    Ok(Self { env: Arc::new(env) })
+    let path = name.as_ref().to_path_buf();
+    let env = Env::new_nolock(name, size, N_ROOTS)?;
+    Ok(Self::from_env(env, Some(path)))
   }
 
   pub fn new_anony() -> Result<Self, EncycError> {
@@ -50,7 +72,150 @@ impl Encyc {
   }
 
   pub fn new_anony_with_size(size: u64) -> Result<Self, EncycError> {
-    let env = Env::new_anon(size, 2)?;
-    Ok(Self { env: Arc::new(env) })
+    let env = Env::new_anon(size, N_ROOTS)?;
+    Ok(Self::from_env(env, None))
+  }
+
+  fn from_env(env: Env, path: Option<PathBuf>) -> Self {
+    Self {
+      env: Arc::new(env),
+      path,
+      max_size: DB_MAX_SIZE,
+    }
+  }
+
+  /// Set the size, in bytes, past which mutable transactions on this
+  /// pristine refuse to allocate pages. Anonymous pristines are limited
+  /// too.
+  pub fn with_max_size(mut self, max_size: u64) -> Self {
+    self.max_size = max_size;
+    self
+  }
+
+  pub fn max_size(&self) -> u64 {
+    self.max_size
+  }
+}
+
+/// A mutable transaction that refuses to allocate pages past `max_size`
+/// bytes, so that a full pristine is reported as soon as it fills up, and
+/// the transaction can be dropped before anything is written past the
+/// limit. Everything else goes to the wrapped transaction.
+pub struct LimitedTxn<T> {
+  txn: T,
+  max_size: u64,
+}
+
+impl<T> LimitedTxn<T> {
+  pub(crate) fn new(txn: T, max_size: u64) -> Self {
+    LimitedTxn { txn, max_size }
+  }
+}
+
+impl<T: AllocPage<Error = sanakirja::Error>> LimitedTxn<T> {
+  /// Give `page`, of `len` bytes, back if it ends past the limit.
+  unsafe fn check(&mut self, page: MutPage, len: u64) -> Result<MutPage, sanakirja::Error> {
+    let size = page.0.offset + len;
+    if size <= self.max_size {
+      return Ok(page);
+    }
+    let mut off = page.0.offset;
+    while off < size {
+      self.txn.decr_rc_owned(off)?;
+      off += PAGE_SIZE;
+    }
+    Err(sanakirja::Error::IO(std::io::Error::other(Full {
+      size,
+      max_size: self.max_size,
+    })))
+  }
+}
+
+impl<T> Deref for LimitedTxn<T> {
+  type Target = T;
+  fn deref(&self) -> &T {
+    &self.txn
+  }
+}
+
+impl<T> DerefMut for LimitedTxn<T> {
+  fn deref_mut(&mut self) -> &mut T {
+    &mut self.txn
   }
 }
+
+impl<T: LoadPage> LoadPage for LimitedTxn<T> {
+  type Error = T::Error;
+  unsafe fn load_page(&self, off: u64) -> Result<CowPage, Self::Error> {
+    self.txn.load_page(off)
+  }
+
+  unsafe fn load_page_contiguous(&self, off: u64, len: u64) -> Result<CowPage, Self::Error> {
+    self.txn.load_page_contiguous(off, len)
+  }
+
+  fn rc(&self, off: u64) -> Result<u64, Self::Error> {
+    self.txn.rc(off)
+  }
+}
+
+impl<T: AllocPage<Error = sanakirja::Error>> AllocPage for LimitedTxn<T> {
+  unsafe fn alloc_page(&mut self) -> Result<MutPage, Self::Error> {
+    let page = self.txn.alloc_page()?;
+    self.check(page, PAGE_SIZE)
+  }
+
+  unsafe fn alloc_page_no_dirty(&mut self) -> Result<MutPage, Self::Error> {
+    let page = self.txn.alloc_page_no_dirty()?;
+    self.check(page, PAGE_SIZE)
+  }
+
+  unsafe fn alloc_contiguous(&mut self, length: u64) -> Result<MutPage, Self::Error> {
+    let page = self.txn.alloc_contiguous(length)?;
+    self.check(page, length)
+  }
+
+  fn incr_rc(&mut self, off: u64) -> Result<usize, Self::Error> {
+    self.txn.incr_rc(off)
+  }
+
+  unsafe fn decr_rc(&mut self, off: u64) -> Result<usize, Self::Error> {
+    self.txn.decr_rc(off)
+  }
+
+  unsafe fn decr_rc_owned(&mut self, off: u64) -> Result<usize, Self::Error> {
+    self.txn.decr_rc_owned(off)
+  }
+}
+
+impl<T: RootPage> RootPage for LimitedTxn<T> {
+  unsafe fn root_page(&self) -> &[u8; 4064] {
+    self.txn.root_page()
+  }
+}
+
+impl<T: Commit> Commit for LimitedTxn<T> {
+  fn commit(self) -> Result<(), sanakirja::Error> {
+    self.txn.commit()
+  }
+}
+
+/// The error a `LimitedTxn` fails with when full, carried by a
+/// `sanakirja::Error` and turned back into `EncycError::PristineFull`.
+#[derive(Debug)]
+pub(crate) struct Full {
+  pub(crate) size: u64,
+  pub(crate) max_size: u64,
+}
+
+impl std::fmt::Display for Full {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+      f,
+      "{} bytes needed, the limit is {} bytes",
+      self.size, self.max_size
+    )
+  }
+}
+
+impl std::error::Error for Full {}
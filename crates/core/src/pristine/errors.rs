@@ -3,16 +3,46 @@
 
 use thiserror_impl::Error;
 
+use super::Full;
+
 #[derive(Debug, Error)]
 pub enum EncycError {
   #[error(transparent)]
-  Sanakirja(#[from] sanakirja::Error),
+  Sanakirja(sanakirja::Error),
   #[error("Pristine locked")]
   PristineLocked,
   #[error("Pristine corrupted")]
   PristineCorrupted,
+  #[error("Pristine is full: {size} bytes used, the limit is {max_size} bytes")]
+  PristineFull { size: u64, max_size: u64 },
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
   #[error(transparent)]
   Borrow(#[from] std::cell::BorrowError),
-  #[error("Pristine version mismatch. Cloning over the network can fix this.")]
-  Version,
+  #[error("Invalid name: {0:?}")]
+  InvalidName(String),
+  #[error("{0:?} already exists")]
+  AlreadyExists(String),
+  #[error("Pristine version {found} is newer than the latest version supported ({expected})")]
+  Version { found: u64, expected: u64 },
+  #[error("Pristine version {found} is older than {expected} and needs to be migrated")]
+  MigrationNeeded { found: u64, expected: u64 },
+  #[error("No migration registered from pristine version {0}")]
+  MissingMigration(u64),
+  #[error("Migration failed: {0}")]
+  MigrationFailed(String),
+}
+
+impl From<sanakirja::Error> for EncycError {
+  fn from(e: sanakirja::Error) -> Self {
+    if let sanakirja::Error::IO(ref io) = e {
+      if let Some(full) = io.get_ref().and_then(|e| e.downcast_ref::<Full>()) {
+        return EncycError::PristineFull {
+          size: full.size,
+          max_size: full.max_size,
+        };
+      }
+    }
+    EncycError::Sanakirja(e)
+  }
 }
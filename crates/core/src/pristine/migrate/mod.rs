@@ -0,0 +1,260 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::{path::PathBuf, sync::Arc};
+
+use log::info;
+use sanakirja::{btree, Commit, Env, RootDb, UnsizedStorable};
+
+use crate::types::L64;
+
+use super::{sanakirja::types::*, Encyc, EncycError, Root, VERSION};
+
+#[cfg(test)]
+mod tests;
+mod v1;
+mod v10;
+mod v11;
+mod v12;
+mod v13;
+mod v14;
+mod v15;
+mod v2;
+mod v3;
+mod v4;
+mod v5;
+mod v6;
+mod v7;
+mod v8;
+mod v9;
+
+pub(crate) type RawMutTxn = sanakirja::MutTxn<Arc<Env>, ()>;
+
+/// An upgrade of the on-disk layout from version `from` to `from + 1`.
+///
+/// Migrations work on the raw sanakirja transaction, since the typed
+/// `MutTxn` only knows about the current layout.
+pub struct Migration {
+  pub from: u64,
+  pub description: &'static str,
+  pub run: fn(&mut RawMutTxn) -> Result<(), EncycError>,
+}
+
+/// Every known migration, in order. Bumping `VERSION` requires registering
+/// the corresponding migration here.
+pub(crate) const MIGRATIONS: &[Migration] = &[
+  Migration {
+    from: 1,
+    description: "add date, payee, memo and postings to entries",
+    run: v1::migrate,
+  },
+  Migration {
+    from: 2,
+    description: "store compartment balances as signed amounts with a currency",
+    run: v2::migrate,
+  },
+  Migration {
+    from: 3,
+    description: "map change hashes to local ids",
+    run: v3::migrate,
+  },
+  Migration {
+    from: 4,
+    description: "keep a log of the changes applied to each space",
+    run: v4::migrate,
+  },
+  Migration {
+    from: 5,
+    description: "add tags to spaces",
+    run: v5::migrate,
+  },
+  Migration {
+    from: 6,
+    description: "add a registry of the devices writing to the pristine",
+    run: v6::migrate,
+  },
+  Migration {
+    from: 7,
+    description: "record conflicts between concurrent edits of an entry",
+    run: v7::migrate,
+  },
+  Migration {
+    from: 8,
+    description: "add the dependency graph of changes",
+    run: v8::migrate,
+  },
+  Migration {
+    from: 9,
+    description: "close compartments, put them in vaults and keep their balances",
+    run: v9::migrate,
+  },
+  Migration {
+    from: 10,
+    description: "give compartments a kind and a parent",
+    run: v10::migrate,
+  },
+  Migration {
+    from: 11,
+    description: "index the entries of labels and file labels in vaults",
+    run: v11::migrate,
+  },
+  Migration {
+    from: 12,
+    description: "give labels a parent",
+    run: v12::migrate,
+  },
+  Migration {
+    from: 13,
+    description: "let filters define budgets",
+    run: v13::migrate,
+  },
+  Migration {
+    from: 14,
+    description: "keep compartments and their balances per space",
+    run: v14::migrate,
+  },
+  Migration {
+    from: 15,
+    description: "keep conflicts per space",
+    run: v15::migrate,
+  },
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct MigrateOptions {
+  /// Run the migrations, but abort the transaction instead of committing.
+  pub dry_run: bool,
+  /// Do not copy the pristine before migrating it.
+  pub no_backup: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+  pub from: u64,
+  pub to: u64,
+  pub applied: Vec<&'static str>,
+  pub backup: Option<PathBuf>,
+  pub dry_run: bool,
+}
+
+impl MigrationReport {
+  pub fn is_up_to_date(&self) -> bool {
+    self.applied.is_empty()
+  }
+}
+
+pub(crate) fn check_version(found: u64) -> Result<(), EncycError> {
+  let expected = VERSION.as_u64();
+  match found.cmp(&expected) {
+    std::cmp::Ordering::Equal => Ok(()),
+    std::cmp::Ordering::Less => Err(EncycError::MigrationNeeded { found, expected }),
+    std::cmp::Ordering::Greater => Err(EncycError::Version { found, expected }),
+  }
+}
+
+/// Rewrite every value of root database `root` from its old layout `V0` to
+/// its new layout `V1`, through `f`.
+pub(crate) fn rewrite_root<K, V0, V1>(txn: &mut RawMutTxn, root: Root, f: impl Fn(&mut RawMutTxn, &K, &V0) -> Result<V1, EncycError>) -> Result<(), EncycError>
+where
+  K: UnsizedStorable + Ord + Clone,
+  V0: UnsizedStorable + Clone,
+  V1: UnsizedStorable,
+{
+  let Some(old): Option<UDb<K, V0>> = txn.root_db(root as usize) else {
+    return Ok(());
+  };
+  let mut bindings = Vec::new();
+  for x in btree::iter(txn, &old, None)? {
+    let (k, v) = x?;
+    bindings.push((k.clone(), v.clone()));
+  }
+  let mut new: UDb<K, V1> = unsafe { btree::create_db_(txn)? };
+  for (k, v) in bindings.iter() {
+    let v = f(txn, k, v)?;
+    btree::put(txn, &mut new, k, &v)?;
+  }
+  unsafe { btree::drop(txn, old)? };
+  txn.set_root(root as usize, new.db.get());
+  Ok(())
+}
+
+impl Encyc {
+  /// Version of the on-disk layout, or `None` if nothing was ever committed
+  /// to this pristine.
+  pub fn version(&self) -> Result<Option<u64>, EncycError> {
+    let txn = Env::txn_begin(self.env.clone())?;
+    match txn.root(Root::Version as usize) {
+      0 => Ok(None),
+      v => Ok(Some(L64(v).as_u64())),
+    }
+  }
+
+  /// Upgrade this pristine to `VERSION`, running all the migrations in a
+  /// single mutable transaction.
+  pub fn migrate(&self, options: &MigrateOptions) -> Result<MigrationReport, EncycError> {
+    let mut txn = Env::mut_txn_begin(self.env.clone())?;
+    let to = VERSION.as_u64();
+    let from = txn
+      .root(Root::Version as usize)
+      .map(|v| L64(v).as_u64())
+      .unwrap_or(to);
+
+    let mut report = MigrationReport {
+      from,
+      to,
+      applied: Vec::new(),
+      backup: None,
+      dry_run: options.dry_run,
+    };
+
+    if from > to {
+      return Err(EncycError::Version {
+        found: from,
+        expected: to,
+      });
+    } else if from == to {
+      return Ok(report);
+    }
+
+    let steps = (from..to)
+      .map(|v| {
+        MIGRATIONS
+          .iter()
+          .find(|m| m.from == v)
+          .ok_or(EncycError::MissingMigration(v))
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    // The mutable transaction holds the lock, so nobody can write to the
+    // file while we copy it.
+    if !options.dry_run && !options.no_backup {
+      report.backup = self.backup(from)?;
+    }
+
+    for m in steps {
+      info!(
+        "migrating pristine from version {}: {}",
+        m.from, m.description
+      );
+      (m.run)(&mut txn)?;
+      txn.set_root(Root::Version as usize, L64::from(m.from + 1).0);
+      report.applied.push(m.description);
+    }
+
+    if !options.dry_run {
+      txn.commit()?;
+    }
+    Ok(report)
+  }
+
+  fn backup(&self, version: u64) -> Result<Option<PathBuf>, EncycError> {
+    let Some(ref path) = self.path else {
+      return Ok(None);
+    };
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    let backup = path.with_file_name(name);
+    std::fs::copy(path, &backup)?;
+    Ok(Some(backup))
+  }
+}
@@ -0,0 +1,857 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::path::PathBuf;
+
+use sanakirja::{btree, Commit, Env, RootDb, Storable, UnsizedStorable};
+
+use crate::{
+  models::{
+    compartment::{self, CompartmentKind},
+    device::SerializedDevice,
+    entry::{SerializedEntry, SerializedPosting},
+    filter,
+    label::{self, LabelGroup},
+    space::{self, TagPolicy},
+  },
+  pristine::{sanakirja::types::*, Encyc, EncycError, Root, VERSION},
+  traits::MutTxnT,
+  types::{hash::Hasher, ChangeId, Currency, Hash, Merkle, Money, Pair, SerializedHash, SerializedMerkle, SmallStr, SmallString, UId, L64},
+};
+
+use super::{v1, v10, v11, v12, v13, v14, v15, v2, v4, v5, v9, MigrateOptions, RawMutTxn, MIGRATIONS};
+
+fn raw(encyc: &Encyc) -> RawMutTxn {
+  Env::mut_txn_begin(encyc.env.clone()).unwrap()
+}
+
+/// Set root `root` of `txn` to a new database holding `bindings`.
+fn put_root<K: UnsizedStorable + Ord + ?Sized, V: UnsizedStorable + ?Sized>(txn: &mut RawMutTxn, root: Root, bindings: &[(&K, &V)]) {
+  let mut db: UDb<K, V> = unsafe { btree::create_db_(txn).unwrap() };
+  for (k, v) in bindings {
+    btree::put(txn, &mut db, k, v).unwrap();
+  }
+  txn.set_root(root as usize, db.db.get());
+}
+
+/// The bindings of root database `root` of `txn`.
+fn read_root<K: UnsizedStorable + Clone, V: UnsizedStorable + Clone>(txn: &RawMutTxn, root: Root) -> Vec<(K, V)> {
+  read_udb(txn, txn.root(root as usize).unwrap().into())
+}
+
+fn read_udb<K: UnsizedStorable + Clone, V: UnsizedStorable + Clone>(txn: &RawMutTxn, page: L64) -> Vec<(K, V)> {
+  let db: UDb<K, V> = unsafe { UDb::from_page(page.into()) };
+  btree::iter(txn, &db, None)
+    .unwrap()
+    .map(|x| {
+      let (k, v) = x.unwrap();
+      (k.clone(), v.clone())
+    })
+    .collect()
+}
+
+/// A new database of `bindings`, returning its page.
+fn new_db<K: Storable + Ord, V: Storable>(txn: &mut RawMutTxn, bindings: &[(K, V)]) -> L64 {
+  let mut db: Db<K, V> = unsafe { btree::create_db_(txn).unwrap() };
+  for (k, v) in bindings {
+    btree::put(txn, &mut db, k, v).unwrap();
+  }
+  db.db.get().into()
+}
+
+fn read_db<K: Storable + Clone, V: Storable + Clone>(txn: &RawMutTxn, page: L64) -> Vec<(K, V)> {
+  let db: Db<K, V> = unsafe { Db::from_page(page.into()) };
+  btree::iter(txn, &db, None)
+    .unwrap()
+    .map(|x| {
+      let (k, v) = x.unwrap();
+      (k.clone(), v.clone())
+    })
+    .collect()
+}
+
+/// Run the migration from version `from` alone, as `Encyc::migrate` would.
+fn run(txn: &mut RawMutTxn, from: u64) -> Result<(), EncycError> {
+  let m = MIGRATIONS.iter().find(|m| m.from == from).unwrap();
+  (m.run)(txn)
+}
+
+fn eur() -> Currency {
+  Currency::new("EUR").unwrap()
+}
+
+/// An entry of the current layout, posting `postings`.
+fn entry(txn: &mut RawMutTxn, id: UId, postings: &[(UId, Money)]) -> SerializedEntry {
+  let postings: Vec<(L64, SerializedPosting)> = postings
+    .iter()
+    .enumerate()
+    .map(|(i, (c, m))| {
+      (
+        (i as u64).into(),
+        SerializedPosting {
+          compartment: *c,
+          amount: (*m).into(),
+        },
+      )
+    })
+    .collect();
+  SerializedEntry {
+    id,
+    changes: new_db::<L64, ChangeId>(txn, &[]),
+    tags: new_db::<UId, L64>(txn, &[]),
+    postings: new_db(txn, &postings),
+    date: 0u64.into(),
+    last_modified: 0u64.into(),
+    change_count: 0u64.into(),
+    payee: SmallString::from_str("grocer"),
+    memo: SmallString::new(),
+  }
+}
+
+fn change_id(n: u64) -> ChangeId {
+  ChangeId(n.into())
+}
+
+fn hash(n: u64) -> Hash {
+  let mut hasher = Hasher::default();
+  hasher.update(&n.to_le_bytes());
+  hasher.finish()
+}
+
+#[test]
+fn v1_entries_get_postings() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let (id, changes, tags) = (
+    UId::new(),
+    new_db::<L64, ChangeId>(&mut txn, &[]),
+    new_db::<UId, L64>(&mut txn, &[]),
+  );
+  let old = v1::SerializedEntry {
+    id,
+    changes,
+    tags,
+    last_modified: 42u64.into(),
+    change_count: 1u64.into(),
+  };
+  put_root(&mut txn, Root::Entries, &[(&change_id(1), &old)]);
+  run(&mut txn, 1).unwrap();
+
+  let entries = read_root::<ChangeId, SerializedEntry>(&txn, Root::Entries);
+  assert_eq!(entries.len(), 1);
+  let (k, e) = &entries[0];
+  assert_eq!(*k, change_id(1));
+  assert_eq!((e.id, e.changes, e.tags), (id, changes, tags));
+  assert_eq!(e.last_modified, 42u64.into());
+  assert_eq!(e.date, 0u64.into());
+  assert!(e.payee.as_str().is_empty());
+  assert!(read_db::<L64, SerializedPosting>(&txn, e.postings).is_empty());
+}
+
+#[test]
+fn v2_balances_get_a_currency() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let (posted, idle) = (UId::new(), UId::new());
+  let e = entry(&mut txn, UId::new(), &[(posted, Money::new(500, eur()))]);
+  put_root(&mut txn, Root::Entries, &[(&change_id(1), &e)]);
+  let old = |id, balance: u64| v2::SerializedCompartment {
+    entries: 0u64.into(),
+    counter: 0u64.into(),
+    last_modified: 0u64.into(),
+    balance: balance.into(),
+    name: SmallString::from_str("cash"),
+    id,
+  };
+  put_root(
+    &mut txn,
+    Root::Compartments,
+    &[(&posted, &old(posted, 500)), (&idle, &old(idle, 0))],
+  );
+  run(&mut txn, 2).unwrap();
+
+  let compartments = read_root::<UId, v9::SerializedCompartment>(&txn, Root::Compartments);
+  let balance = |id| {
+    let (_, c) = compartments.iter().find(|(k, _)| *k == id).unwrap();
+    Money::from(&c.balance)
+  };
+  assert_eq!(balance(posted), Money::new(500, eur()));
+  assert_eq!(balance(idle), Money::zero(Currency::NONE));
+}
+
+#[test]
+fn v3_entries_get_hashes() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let e = entry(&mut txn, UId::new(), &[]);
+  put_root(&mut txn, Root::Entries, &[(&change_id(7), &e)]);
+  run(&mut txn, 3).unwrap();
+
+  let external = read_root::<ChangeId, SerializedHash>(&txn, Root::External);
+  let internal = read_root::<SerializedHash, ChangeId>(&txn, Root::Internal);
+  assert_eq!(external.len(), 1);
+  assert_eq!(external[0].0, change_id(7));
+  assert_eq!(internal, vec![(external[0].1, change_id(7))]);
+}
+
+#[test]
+fn v4_spaces_get_a_log() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  // Two entries, the later one edited by a third change.
+  let (a, b, edit) = (change_id(1), change_id(2), change_id(3));
+  let mut ea = entry(&mut txn, UId::new(), &[]);
+  ea.changes = new_db(&mut txn, &[(L64::from(0u64), a)]);
+  let mut eb = entry(&mut txn, UId::new(), &[]);
+  eb.changes = new_db(&mut txn, &[(L64::from(0u64), b), (L64::from(1u64), edit)]);
+  put_root(&mut txn, Root::Entries, &[(&a, &ea), (&b, &eb)]);
+  let external: Vec<(ChangeId, SerializedHash)> = [a, b, edit]
+    .iter()
+    .map(|c| (*c, (&hash(c.0.as_u64())).into()))
+    .collect();
+  let bindings: Vec<_> = external.iter().map(|(k, v)| (k, v)).collect();
+  put_root(&mut txn, Root::External, &bindings);
+  let space = v4::SerializedSpace {
+    id: UId::new(),
+    vaults: new_db::<UId, L64>(&mut txn, &[]),
+    last_modified: 0,
+    // Entry ids, with their dates: `b` is older.
+    entries: new_db(&mut txn, &[(a, L64::from(20u64)), (b, L64::from(10u64))]),
+    labels: new_db::<UId, L64>(&mut txn, &[]),
+  };
+  put_root::<SmallStr, _>(
+    &mut txn,
+    Root::Spaces,
+    &[(&SmallString::from_str("main"), &space)],
+  );
+  run(&mut txn, 4).unwrap();
+
+  let db: UDb<SmallStr, v5::SerializedSpace> = txn.root_db(Root::Spaces as usize).unwrap();
+  let (name, s) = btree::iter(&txn, &db, None)
+    .unwrap()
+    .next()
+    .unwrap()
+    .unwrap();
+  assert_eq!(name.as_str(), "main");
+  let s = *s;
+  assert_eq!((s.id, s.entries), (space.id, space.entries));
+  assert_eq!(s.apply_counter, 3u64.into());
+  let mut state = Merkle::zero();
+  let mut expected = Vec::new();
+  for (pos, c) in [b, edit, a].into_iter().enumerate() {
+    state = state.next(&hash(c.0.as_u64()));
+    expected.push((
+      L64::from(pos as u64),
+      Pair {
+        a: c,
+        b: SerializedMerkle::from(&state),
+      },
+    ));
+  }
+  assert_eq!(
+    read_udb::<L64, Pair<ChangeId, SerializedMerkle>>(&txn, s.revchanges),
+    expected
+  );
+  assert_eq!(read_db::<ChangeId, L64>(&txn, s.changes).len(), 3);
+  assert_eq!(read_udb::<SerializedMerkle, L64>(&txn, s.states).len(), 3);
+}
+
+#[test]
+fn v5_spaces_get_tags() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let space = v5::SerializedSpace {
+    id: UId::new(),
+    vaults: 1u64.into(),
+    last_modified: 7,
+    entries: 2u64.into(),
+    labels: 3u64.into(),
+    changes: 4u64.into(),
+    revchanges: 5u64.into(),
+    states: 6u64.into(),
+    apply_counter: 9u64.into(),
+  };
+  put_root::<SmallStr, _>(
+    &mut txn,
+    Root::Spaces,
+    &[(&SmallString::from_str("main"), &space)],
+  );
+  run(&mut txn, 5).unwrap();
+
+  let db: UDb<SmallStr, v14::SerializedSpace> = txn.root_db(Root::Spaces as usize).unwrap();
+  let (_, s) = btree::iter(&txn, &db, None)
+    .unwrap()
+    .next()
+    .unwrap()
+    .unwrap();
+  let s = *s;
+  assert_eq!(s.id, space.id);
+  assert_eq!(
+    (
+      s.vaults,
+      s.entries,
+      s.labels,
+      s.changes,
+      s.revchanges,
+      s.states,
+      s.apply_counter
+    ),
+    (
+      space.vaults,
+      space.entries,
+      space.labels,
+      space.changes,
+      space.revchanges,
+      space.states,
+      space.apply_counter
+    )
+  );
+  assert_eq!(s.last_modified, 7);
+  assert_eq!(s.tag_policy, TagPolicy::Warn);
+  let tags: UDb<SmallStr, space::SerializedTag> = unsafe { UDb::from_page(s.tags.into()) };
+  assert!(btree::iter(&txn, &tags, None).unwrap().next().is_none());
+}
+
+#[test]
+fn v6_adds_devices() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  assert!(txn.root(Root::Devices as usize).is_none());
+  run(&mut txn, 6).unwrap();
+  let db: UDb<SmallStr, SerializedDevice> = txn.root_db(Root::Devices as usize).unwrap();
+  assert!(btree::iter(&txn, &db, None).unwrap().next().is_none());
+}
+
+#[test]
+fn v7_adds_conflicts() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  assert!(txn.root(Root::Conflicts as usize).is_none());
+  run(&mut txn, 7).unwrap();
+  assert!(read_root::<ChangeId, ChangeId>(&txn, Root::Conflicts).is_empty());
+}
+
+#[test]
+fn v8_adds_the_dependency_graph() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  run(&mut txn, 8).unwrap();
+  assert!(read_root::<ChangeId, ChangeId>(&txn, Root::Dependencies).is_empty());
+  assert!(read_root::<ChangeId, ChangeId>(&txn, Root::Dependents).is_empty());
+  assert!(read_root::<UId, ChangeId>(&txn, Root::Creators).is_empty());
+}
+
+fn v9_compartment(txn: &mut RawMutTxn, id: UId, currency: Currency) -> v9::SerializedCompartment {
+  v9::SerializedCompartment {
+    entries: new_db::<ChangeId, L64>(txn, &[]),
+    counter: 0u64.into(),
+    last_modified: 5u64.into(),
+    balance: Money::new(12345, currency).into(),
+    name: SmallString::from_str("cash"),
+    id,
+  }
+}
+
+#[test]
+fn v9_balances_are_recomputed() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let (cash, food, idle) = (UId::new(), UId::new(), UId::new());
+  let e1 = entry(
+    &mut txn,
+    UId::new(),
+    &[
+      (cash, Money::new(-300, eur())),
+      (food, Money::new(300, eur())),
+    ],
+  );
+  // Two postings to the same compartment count as two.
+  let e2 = entry(
+    &mut txn,
+    UId::new(),
+    &[
+      (cash, Money::new(-50, eur())),
+      (cash, Money::new(-25, eur())),
+      (food, Money::new(75, eur())),
+    ],
+  );
+  put_root(
+    &mut txn,
+    Root::Entries,
+    &[(&change_id(1), &e1), (&change_id(2), &e2)],
+  );
+  let compartments: Vec<_> = [cash, food, idle]
+    .into_iter()
+    .map(|id| (id, v9_compartment(&mut txn, id, eur())))
+    .collect();
+  let bindings: Vec<_> = compartments.iter().map(|(k, v)| (k, v)).collect();
+  put_root(&mut txn, Root::Compartments, &bindings);
+
+  run(&mut txn, 9).unwrap();
+
+  let compartments = read_root::<UId, v10::SerializedCompartment>(&txn, Root::Compartments);
+  let get = |id| {
+    compartments
+      .iter()
+      .find(|(k, _)| *k == id)
+      .unwrap()
+      .1
+      .clone()
+  };
+  let c = get(cash);
+  assert_eq!(Money::from(&c.balance), Money::new(-375, eur()));
+  assert_eq!(c.counter, 3u64.into());
+  assert_eq!(
+    read_db::<ChangeId, L64>(&txn, c.entries),
+    vec![(change_id(1), 1u64.into()), (change_id(2), 2u64.into())]
+  );
+  assert_eq!(
+    (c.closed, c.vault, c.last_modified),
+    (0u64.into(), UId::nil(), 5u64.into())
+  );
+  assert_eq!(Money::from(&get(food).balance), Money::new(375, eur()));
+  let i = get(idle);
+  assert_eq!(Money::from(&i.balance), Money::zero(eur()));
+  assert!(read_db::<ChangeId, L64>(&txn, i.entries).is_empty());
+}
+
+#[test]
+fn v9_refuses_mixed_currencies_and_overflows() {
+  for postings in [
+    vec![
+      Money::new(100, eur()),
+      Money::new(100, Currency::new("USD").unwrap()),
+    ],
+    vec![Money::new(i64::MAX, eur()), Money::new(1, eur())],
+  ] {
+    let encyc = Encyc::new_anony().unwrap();
+    let mut txn = raw(&encyc);
+    let cash = UId::new();
+    let entries: Vec<_> = postings
+      .iter()
+      .map(|m| entry(&mut txn, UId::new(), &[(cash, *m)]))
+      .collect();
+    let ids: Vec<_> = (0..entries.len() as u64).map(change_id).collect();
+    let bindings: Vec<_> = ids.iter().zip(entries.iter()).collect();
+    put_root(&mut txn, Root::Entries, &bindings);
+    let c = v9_compartment(&mut txn, cash, eur());
+    put_root(&mut txn, Root::Compartments, &[(&cash, &c)]);
+    match run(&mut txn, 9) {
+      Err(EncycError::MigrationFailed(msg)) => assert!(msg.contains("cash"), "{}", msg),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+}
+
+#[test]
+fn v10_compartments_become_assets() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let (id, vault) = (UId::new(), UId::new());
+  let old = v10::SerializedCompartment {
+    entries: 1u64.into(),
+    counter: 2u64.into(),
+    last_modified: 3u64.into(),
+    closed: 4u64.into(),
+    balance: Money::new(-5, eur()).into(),
+    vault,
+    name: SmallString::from_str("cash"),
+    id,
+  };
+  put_root(&mut txn, Root::Compartments, &[(&id, &old)]);
+  run(&mut txn, 10).unwrap();
+
+  let compartments = read_root::<UId, compartment::SerializedCompartment>(&txn, Root::Compartments);
+  let c = &compartments[0].1;
+  assert_eq!(
+    (c.entries, c.counter, c.last_modified, c.closed),
+    (old.entries, old.counter, old.last_modified, old.closed)
+  );
+  assert_eq!((c.balance, c.vault, c.id), (old.balance, vault, id));
+  assert_eq!(c.name.as_str(), "cash");
+  assert_eq!((c.kind, c.parent), (CompartmentKind::Asset, UId::nil()));
+}
+
+#[test]
+fn v11_labels_index_their_entries() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let (food, idle) = (UId::new(), UId::new());
+  let mut e = entry(&mut txn, UId::new(), &[]);
+  e.tags = new_db(&mut txn, &[(food, L64::from(1u64))]);
+  put_root(&mut txn, Root::Entries, &[(&change_id(1), &e)]);
+  let old = |id, name| v11::SerializedLabel {
+    header: 0u64.into(),
+    group: LabelGroup::EXPENSE,
+    name: SmallString::from_str(name),
+    id,
+  };
+  put_root(
+    &mut txn,
+    Root::Labels,
+    &[(&food, &old(food, "food")), (&idle, &old(idle, "idle"))],
+  );
+  run(&mut txn, 11).unwrap();
+
+  let labels = read_root::<UId, v12::SerializedLabel>(&txn, Root::Labels);
+  let get = |id| labels.iter().find(|(k, _)| *k == id).unwrap().1.clone();
+  let f = get(food);
+  assert_eq!(
+    (f.group, f.vault, f.name.as_str()),
+    (LabelGroup::EXPENSE, UId::nil(), "food")
+  );
+  assert_eq!(
+    read_db::<ChangeId, L64>(&txn, f.entries),
+    vec![(change_id(1), 1u64.into())]
+  );
+  assert!(read_db::<ChangeId, L64>(&txn, get(idle).entries).is_empty());
+}
+
+#[test]
+fn v12_labels_become_roots() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let (id, vault) = (UId::new(), UId::new());
+  let old = v12::SerializedLabel {
+    header: 1u64.into(),
+    entries: 2u64.into(),
+    vault,
+    group: LabelGroup::INCOME,
+    name: SmallString::from_str("salary"),
+    id,
+  };
+  put_root(&mut txn, Root::Labels, &[(&id, &old)]);
+  run(&mut txn, 12).unwrap();
+
+  let labels = read_root::<UId, label::SerializedLabel>(&txn, Root::Labels);
+  let l = &labels[0].1;
+  assert_eq!(
+    (l.header, l.entries, l.vault, l.group),
+    (old.header, old.entries, vault, LabelGroup::INCOME)
+  );
+  assert_eq!(
+    (l.name.as_str(), l.id, l.parent),
+    ("salary", id, UId::nil())
+  );
+}
+
+#[test]
+fn v13_filters_are_not_budgets() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let id = UId::new();
+  let old = v13::SerializedFilter {
+    header: 3u64.into(),
+    is_system: true,
+    id,
+  };
+  put_root(&mut txn, Root::Filters, &[(&id, &old)]);
+  run(&mut txn, 13).unwrap();
+
+  let filters = read_root::<UId, filter::SerializedFilter>(&txn, Root::Filters);
+  let f = &filters[0].1;
+  assert_eq!((f.header, f.is_system, f.id), (old.header, true, id));
+  assert!(!f.is_budget());
+  assert!(read_db::<UId, L64>(&txn, f.labels).is_empty());
+  assert!(read_db::<UId, L64>(&txn, f.compartments).is_empty());
+}
+
+#[test]
+fn v14_compartments_move_into_their_spaces() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  let (cash, food, orphan) = (UId::new(), UId::new(), UId::new());
+  // Change 1 creates cash in main, change 3 creates food in other, and
+  // entry 2 of main posts to both. Nobody knows who created orphan.
+  let e = entry(
+    &mut txn,
+    UId::new(),
+    &[
+      (cash, Money::new(-500, eur())),
+      (food, Money::new(500, eur())),
+    ],
+  );
+  put_root(&mut txn, Root::Entries, &[(&change_id(2), &e)]);
+  put_root(
+    &mut txn,
+    Root::Creators,
+    &[(&cash, &change_id(1)), (&food, &change_id(3))],
+  );
+  let mut compartments = Vec::new();
+  for (id, name) in [(cash, "cash"), (food, "food"), (orphan, "orphan")] {
+    let c = compartment::SerializedCompartment {
+      entries: new_db::<ChangeId, L64>(&mut txn, &[]),
+      counter: 9u64.into(),
+      last_modified: 3u64.into(),
+      closed: 0u64.into(),
+      balance: Money::new(7, eur()).into(),
+      vault: UId::nil(),
+      parent: UId::nil(),
+      kind: CompartmentKind::Expense,
+      name: SmallString::from_str(name),
+      id,
+    };
+    compartments.push((id, c));
+  }
+  let bindings: Vec<_> = compartments.iter().map(|(k, v)| (k, v)).collect();
+  put_root(&mut txn, Root::Compartments, &bindings);
+  let mut space = |changes: &[u64], entries: &[u64]| {
+    let changes: Vec<_> = changes
+      .iter()
+      .enumerate()
+      .map(|(i, c)| (change_id(*c), L64::from(i as u64)))
+      .collect();
+    let entries: Vec<_> = entries
+      .iter()
+      .map(|e| (change_id(*e), L64::from(0u64)))
+      .collect();
+    v14::SerializedSpace {
+      id: UId::new(),
+      vaults: new_db::<UId, L64>(&mut txn, &[]),
+      last_modified: 7,
+      entries: new_db(&mut txn, &entries),
+      labels: new_db::<UId, L64>(&mut txn, &[]),
+      changes: new_db(&mut txn, &changes),
+      revchanges: 5u64.into(),
+      states: 6u64.into(),
+      apply_counter: 2u64.into(),
+      tags: 8u64.into(),
+      tag_policy: TagPolicy::Reject,
+    }
+  };
+  let (main, other) = (space(&[1, 2], &[2]), space(&[3], &[]));
+  put_root::<SmallStr, _>(
+    &mut txn,
+    Root::Spaces,
+    &[
+      (&SmallString::from_str("main"), &main),
+      (&SmallString::from_str("other"), &other),
+    ],
+  );
+  run(&mut txn, 14).unwrap();
+
+  assert!(txn.root(Root::Compartments as usize).is_none());
+  let db: UDb<SmallStr, v15::SerializedSpace> = txn.root_db(Root::Spaces as usize).unwrap();
+  let spaces: Vec<(String, v15::SerializedSpace)> = btree::iter(&txn, &db, None)
+    .unwrap()
+    .map(|x| {
+      let (name, s) = x.unwrap();
+      (name.as_str().to_string(), *s)
+    })
+    .collect();
+  assert_eq!(spaces.len(), 2);
+  for (name, s) in spaces {
+    let old = if name == "main" { main } else { other };
+    assert_eq!(
+      (s.id, s.entries, s.changes, s.tags, s.tag_policy),
+      (old.id, old.entries, old.changes, old.tags, old.tag_policy)
+    );
+    let compartments = read_udb::<UId, compartment::SerializedCompartment>(&txn, s.compartments);
+    let balances: Vec<(UId, Money, u64)> = compartments
+      .iter()
+      .map(|(id, c)| {
+        assert_eq!(
+          (c.kind, c.last_modified),
+          (CompartmentKind::Expense, 3u64.into())
+        );
+        (*id, Money::from(&c.balance), c.counter.as_u64())
+      })
+      .collect();
+    let mut expected = if name == "main" {
+      vec![
+        (cash, Money::new(-500, eur()), 1),
+        (food, Money::new(500, eur()), 1),
+        (orphan, Money::zero(eur()), 0),
+      ]
+    } else {
+      vec![
+        (food, Money::zero(eur()), 0),
+        (orphan, Money::zero(eur()), 0),
+      ]
+    };
+    expected.sort_by_key(|(id, _, _)| *id);
+    assert_eq!(balances, expected);
+    for (id, c) in compartments {
+      let entries = read_db::<ChangeId, L64>(&txn, c.entries);
+      if name == "main" && id != orphan {
+        assert_eq!(entries, vec![(change_id(2), 1u64.into())]);
+      } else {
+        assert!(entries.is_empty());
+      }
+    }
+  }
+}
+
+#[test]
+fn v15_conflicts_move_into_their_spaces() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = raw(&encyc);
+  // Changes 3 and 4 compete for entry 2, changes 6 and 7 for entry 5.
+  put_root(
+    &mut txn,
+    Root::Conflicts,
+    &[
+      (&change_id(2), &change_id(3)),
+      (&change_id(2), &change_id(4)),
+      (&change_id(5), &change_id(6)),
+      (&change_id(5), &change_id(7)),
+    ],
+  );
+  let mut space = |changes: &[u64], entries: &[u64]| {
+    let changes: Vec<_> = changes
+      .iter()
+      .enumerate()
+      .map(|(i, c)| (change_id(*c), L64::from(i as u64)))
+      .collect();
+    let entries: Vec<_> = entries
+      .iter()
+      .map(|e| (change_id(*e), L64::from(0u64)))
+      .collect();
+    v15::SerializedSpace {
+      id: UId::new(),
+      vaults: new_db::<UId, L64>(&mut txn, &[]),
+      last_modified: 7,
+      entries: new_db(&mut txn, &entries),
+      labels: new_db::<UId, L64>(&mut txn, &[]),
+      compartments: new_db::<UId, L64>(&mut txn, &[]),
+      changes: new_db(&mut txn, &changes),
+      revchanges: 5u64.into(),
+      states: 6u64.into(),
+      apply_counter: 2u64.into(),
+      tags: 8u64.into(),
+      tag_policy: TagPolicy::Reject,
+    }
+  };
+  // Main applied both sides of entry 2 but only one of entry 5, other
+  // only one side of entry 2, and third both sides of entry 5.
+  let spaces = [
+    ("main", space(&[2, 3, 4, 5, 6], &[2, 5])),
+    ("other", space(&[2, 3], &[2])),
+    ("third", space(&[5, 6, 7], &[5])),
+  ];
+  let names: Vec<_> = spaces
+    .iter()
+    .map(|(name, s)| (SmallString::from_str(name), *s))
+    .collect();
+  let bindings: Vec<_> = names.iter().map(|(k, v)| (k.as_ref(), v)).collect();
+  put_root::<SmallStr, _>(&mut txn, Root::Spaces, &bindings);
+  run(&mut txn, 15).unwrap();
+
+  assert!(txn.root(Root::Conflicts as usize).is_none());
+  let db: UDb<SmallStr, space::SerializedSpace> = txn.root_db(Root::Spaces as usize).unwrap();
+  for (name, old) in spaces {
+    let key = SmallString::from_str(name);
+    let s = match btree::get(&txn, &db, key.as_ref(), None).unwrap() {
+      Some((k, s)) if k == key.as_ref() => *s,
+      _ => panic!("space {} is gone", name),
+    };
+    assert_eq!(
+      (s.id, s.entries, s.compartments, s.changes, s.tags),
+      (old.id, old.entries, old.compartments, old.changes, old.tags)
+    );
+    let expected = match name {
+      "main" => vec![(change_id(2), change_id(3)), (change_id(2), change_id(4))],
+      "third" => vec![(change_id(5), change_id(6)), (change_id(5), change_id(7))],
+      _ => vec![],
+    };
+    assert_eq!(read_udb::<ChangeId, ChangeId>(&txn, s.conflicts), expected);
+  }
+}
+
+/// A file-backed pristine at version `version`, in a fresh directory.
+fn pristine_at(name: &str, version: u64) -> (PathBuf, Encyc) {
+  let dir = std::env::temp_dir().join(format!("azoni-migrate-{}-{}", std::process::id(), name));
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&dir).unwrap();
+  let path = dir.join("azoni.db");
+  let encyc = Encyc::new(&path).unwrap();
+  encyc.mut_txn_begin().unwrap().commit().unwrap();
+  let mut txn = raw(&encyc);
+  txn.set_root(Root::Version as usize, L64::from(version).0);
+  txn.commit().unwrap();
+  (dir, encyc)
+}
+
+fn backup_of(dir: &std::path::Path, version: u64) -> PathBuf {
+  dir.join(format!("azoni.db.v{}.bak", version))
+}
+
+#[test]
+fn migrate_backs_up_the_pristine() {
+  let (dir, encyc) = pristine_at("backup", 12);
+  let report = encyc.migrate(&MigrateOptions::default()).unwrap();
+  assert_eq!((report.from, report.to), (12, VERSION.as_u64()));
+  assert_eq!(
+    report.applied,
+    MIGRATIONS[11..]
+      .iter()
+      .map(|m| m.description)
+      .collect::<Vec<_>>()
+  );
+  assert!(!report.dry_run);
+  let backup = backup_of(&dir, 12);
+  assert_eq!(report.backup, Some(backup.clone()));
+  assert_eq!(encyc.version().unwrap(), Some(VERSION.as_u64()));
+  encyc.txn_begin().unwrap();
+
+  let old = Encyc::new(&backup).unwrap();
+  assert_eq!(old.version().unwrap(), Some(12));
+  assert!(matches!(
+    old.txn_begin(),
+    Err(EncycError::MigrationNeeded { found: 12, .. })
+  ));
+
+  // Migrating again has nothing to do.
+  assert!(encyc
+    .migrate(&MigrateOptions::default())
+    .unwrap()
+    .is_up_to_date());
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn migrate_without_backup() {
+  let (dir, encyc) = pristine_at("no-backup", 15);
+  let options = MigrateOptions {
+    no_backup: true,
+    ..MigrateOptions::default()
+  };
+  let report = encyc.migrate(&options).unwrap();
+  assert_eq!(report.applied.len(), 1);
+  assert_eq!(report.backup, None);
+  assert!(!backup_of(&dir, 15).exists());
+  assert_eq!(encyc.version().unwrap(), Some(VERSION.as_u64()));
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dry_run_leaves_the_pristine_alone() {
+  let (dir, encyc) = pristine_at("dry-run", 12);
+  let options = MigrateOptions {
+    dry_run: true,
+    ..MigrateOptions::default()
+  };
+  let report = encyc.migrate(&options).unwrap();
+  assert!(report.dry_run);
+  assert_eq!(report.applied.len(), 4);
+  assert_eq!(report.backup, None);
+  assert!(!backup_of(&dir, 12).exists());
+  assert_eq!(encyc.version().unwrap(), Some(12));
+  drop(encyc);
+  let encyc = Encyc::new(dir.join("azoni.db")).unwrap();
+  assert_eq!(encyc.version().unwrap(), Some(12));
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn newer_pristines_are_refused() {
+  let newer = VERSION.as_u64() + 1;
+  let (dir, encyc) = pristine_at("newer", newer);
+  match encyc.migrate(&MigrateOptions::default()) {
+    Err(EncycError::Version { found, expected }) => assert_eq!((found, expected), (newer, VERSION.as_u64())),
+    r => panic!("unexpected result {:?}", r),
+  }
+  assert!(!backup_of(&dir, newer).exists());
+  assert_eq!(encyc.version().unwrap(), Some(newer));
+  std::fs::remove_dir_all(&dir).unwrap();
+}
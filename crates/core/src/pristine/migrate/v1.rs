@@ -0,0 +1,44 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 1, and the migration to version 2: entries gain a
+//! date, a payee, a memo and a database of postings.
+
+use sanakirja::{btree, direct_repr, Storable, UnsizedStorable};
+
+use crate::{
+  models::entry::{self, SerializedPosting},
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{ChangeId, SmallString, UId, L64},
+};
+
+use super::{rewrite_root, RawMutTxn};
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedEntry {
+  pub id: UId,
+  pub changes: L64,
+  pub tags: L64,
+  pub last_modified: L64,
+  pub change_count: L64,
+}
+
+direct_repr!(SerializedEntry);
+impl sanakirja::debug::Check for SerializedEntry {}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  rewrite_root::<ChangeId, SerializedEntry, entry::SerializedEntry>(txn, Root::Entries, |txn, _, e| {
+    let postings: Db<L64, SerializedPosting> = unsafe { btree::create_db_(txn)? };
+    Ok(entry::SerializedEntry {
+      id: e.id,
+      changes: e.changes,
+      tags: e.tags,
+      postings: postings.db.get().into(),
+      date: 0u64.into(),
+      last_modified: e.last_modified,
+      change_count: e.change_count,
+      payee: SmallString::new(),
+      memo: SmallString::new(),
+    })
+  })
+}
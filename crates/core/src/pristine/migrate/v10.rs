@@ -0,0 +1,48 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 10, and the migration to version 11: compartments
+//! have a kind and a parent. Existing compartments become assets, each the
+//! root of its own tree.
+
+use sanakirja::{direct_repr, Storable, UnsizedStorable};
+
+use crate::{
+  models::compartment::{self, CompartmentKind},
+  pristine::{EncycError, Root},
+  types::{SerializedMoney, SmallString, UId, L64},
+};
+
+use super::{rewrite_root, RawMutTxn};
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedCompartment {
+  pub entries: L64,
+  pub counter: L64,
+  pub last_modified: L64,
+  pub closed: L64,
+  pub balance: SerializedMoney,
+  pub vault: UId,
+  pub name: SmallString,
+  pub id: UId,
+}
+
+direct_repr!(SerializedCompartment);
+impl sanakirja::debug::Check for SerializedCompartment {}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  rewrite_root::<UId, SerializedCompartment, compartment::SerializedCompartment>(txn, Root::Compartments, |_, _, c| {
+    Ok(compartment::SerializedCompartment {
+      entries: c.entries,
+      counter: c.counter,
+      last_modified: c.last_modified,
+      closed: c.closed,
+      balance: c.balance,
+      vault: c.vault,
+      parent: UId::nil(),
+      kind: CompartmentKind::Asset,
+      name: c.name.clone(),
+      id: c.id,
+    })
+  })
+}
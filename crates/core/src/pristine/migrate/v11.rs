@@ -0,0 +1,59 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 11, and the migration to version 12: labels keep an
+//! index of the entries they are attached to, and may be filed in a vault.
+//! The index is rebuilt from the `tags` pages of the entries.
+
+use std::collections::HashMap;
+
+use sanakirja::{btree, direct_repr, RootDb, Storable, UnsizedStorable};
+
+use crate::{
+  models::{entry::SerializedEntry, label::LabelGroup},
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{ChangeId, SmallString, UId, L64},
+};
+
+use super::{rewrite_root, v12, RawMutTxn};
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+#[repr(C)]
+pub struct SerializedLabel {
+  pub header: L64,
+  pub group: LabelGroup,
+  pub name: SmallString,
+  pub id: UId,
+}
+
+direct_repr!(SerializedLabel);
+impl sanakirja::debug::Check for SerializedLabel {}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let mut attached: HashMap<UId, Vec<(ChangeId, L64)>> = HashMap::new();
+  if let Some(entries) = txn.root_db::<ChangeId, SerializedEntry, UP<_, _>>(Root::Entries as usize) {
+    for x in btree::iter(txn, &entries, None)? {
+      let (id, e) = x?;
+      let tags: Db<UId, L64> = unsafe { Db::from_page(e.tags.into()) };
+      for t in btree::iter(txn, &tags, None)? {
+        let (label, n) = t?;
+        attached.entry(*label).or_default().push((*id, *n));
+      }
+    }
+  }
+
+  rewrite_root::<UId, SerializedLabel, v12::SerializedLabel>(txn, Root::Labels, |txn, id, l| {
+    let mut entries: Db<ChangeId, L64> = unsafe { btree::create_db_(txn)? };
+    for (entry, n) in attached.get(id).into_iter().flatten() {
+      btree::put(txn, &mut entries, entry, n)?;
+    }
+    Ok(v12::SerializedLabel {
+      header: l.header,
+      entries: entries.db.get().into(),
+      vault: UId::nil(),
+      group: l.group,
+      name: l.name.clone(),
+      id: l.id,
+    })
+  })
+}
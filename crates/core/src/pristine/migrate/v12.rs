@@ -0,0 +1,43 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 12, and the migration to version 13: labels have a
+//! parent. Existing labels become the roots of their own trees.
+
+use sanakirja::{direct_repr, Storable, UnsizedStorable};
+
+use crate::{
+  models::label::{self, LabelGroup},
+  pristine::{EncycError, Root},
+  types::{SmallString, UId, L64},
+};
+
+use super::{rewrite_root, RawMutTxn};
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+#[repr(C)]
+pub struct SerializedLabel {
+  pub header: L64,
+  pub entries: L64,
+  pub vault: UId,
+  pub group: LabelGroup,
+  pub name: SmallString,
+  pub id: UId,
+}
+
+direct_repr!(SerializedLabel);
+impl sanakirja::debug::Check for SerializedLabel {}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  rewrite_root::<UId, SerializedLabel, label::SerializedLabel>(txn, Root::Labels, |_, _, l| {
+    Ok(label::SerializedLabel {
+      header: l.header,
+      entries: l.entries,
+      vault: l.vault,
+      parent: UId::nil(),
+      group: l.group,
+      name: l.name.clone(),
+      id: l.id,
+    })
+  })
+}
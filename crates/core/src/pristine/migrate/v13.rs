@@ -0,0 +1,46 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 13, and the migration to version 14: filters may
+//! define budgets. Existing filters get empty lists of labels and
+//! compartments, and no budget.
+
+use sanakirja::{btree, direct_repr, Storable, UnsizedStorable};
+
+use crate::{
+  models::filter::{self, BudgetPeriod},
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{Currency, Money, SmallString, UId, L64},
+};
+
+use super::{rewrite_root, RawMutTxn};
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedFilter {
+  pub header: L64,
+  pub is_system: bool,
+  pub id: UId,
+}
+
+direct_repr!(SerializedFilter);
+impl sanakirja::debug::Check for SerializedFilter {}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  rewrite_root::<UId, SerializedFilter, filter::SerializedFilter>(txn, Root::Filters, |txn, _, f| {
+    let labels: Db<UId, L64> = unsafe { btree::create_db_(txn)? };
+    let compartments: Db<UId, L64> = unsafe { btree::create_db_(txn)? };
+    Ok(filter::SerializedFilter {
+      header: f.header,
+      labels: labels.db.get().into(),
+      compartments: compartments.db.get().into(),
+      start: 0u64.into(),
+      end: 0u64.into(),
+      limit: Money::zero(Currency::NONE).into(),
+      period: BudgetPeriod::Monthly,
+      rollover: false,
+      is_system: f.is_system,
+      name: SmallString::new(),
+      id: f.id,
+    })
+  })
+}
@@ -0,0 +1,185 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of versions 6 to 14, and the migration to version 15:
+//! compartments move from one global database into the space they were
+//! created in. A compartment goes to every space that applied the change
+//! creating it, or has an entry posting to it, and to every space if its
+//! creator is unknown. Its entries, counter and balance are then computed
+//! again from the entries of that space alone.
+
+use std::collections::{HashMap, HashSet};
+
+use sanakirja::{btree, direct_repr, RootDb, Storable, UnsizedStorable};
+
+use crate::{
+  models::{
+    compartment::SerializedCompartment,
+    entry::{SerializedEntry, SerializedPosting},
+    space::TagPolicy,
+  },
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{ChangeId, Money, MoneyError, SmallStr, SmallString, UId, L64},
+};
+
+use super::{v15, RawMutTxn};
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedSpace {
+  pub id: UId,
+  pub vaults: L64,
+  pub last_modified: u64,
+  pub entries: L64,
+  pub labels: L64,
+  pub changes: L64,
+  pub revchanges: L64,
+  pub states: L64,
+  pub apply_counter: L64,
+  pub tags: L64,
+  pub tag_policy: TagPolicy,
+}
+
+direct_repr!(SerializedSpace);
+impl sanakirja::debug::Check for SerializedSpace {}
+
+/// What the postings of the entries of one space add up to in one
+/// compartment.
+#[derive(Default)]
+struct Postings {
+  entries: HashMap<ChangeId, u64>,
+  counter: u64,
+  amounts: Vec<Money>,
+}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let mut compartments = Vec::new();
+  let old_compartments: Option<UDb<UId, SerializedCompartment>> = txn.root_db(Root::Compartments as usize);
+  if let Some(ref db) = old_compartments {
+    for x in btree::iter(txn, db, None)? {
+      let (_, c) = x?;
+      compartments.push(c.clone());
+    }
+  }
+  let mut creators = HashMap::new();
+  if let Some(db) = txn.root_db::<UId, ChangeId, UP<_, _>>(Root::Creators as usize) {
+    for x in btree::iter(txn, &db, None)? {
+      let (id, change) = x?;
+      creators.insert(*id, *change);
+    }
+  }
+
+  if let Some(old) = txn.root_db::<SmallStr, SerializedSpace, UP<_, _>>(Root::Spaces as usize) {
+    let mut spaces = Vec::new();
+    for x in btree::iter(txn, &old, None)? {
+      let (name, s) = x?;
+      spaces.push((SmallString::from_str(name.as_str()), *s));
+    }
+
+    let mut new: UDb<SmallStr, v15::SerializedSpace> = unsafe { btree::create_db_(txn)? };
+    for (name, s) in spaces {
+      let db = space_compartments(txn, &s, &compartments, &creators)?;
+      let s = v15::SerializedSpace {
+        id: s.id,
+        vaults: s.vaults,
+        last_modified: s.last_modified,
+        entries: s.entries,
+        labels: s.labels,
+        compartments: db,
+        changes: s.changes,
+        revchanges: s.revchanges,
+        states: s.states,
+        apply_counter: s.apply_counter,
+        tags: s.tags,
+        tag_policy: s.tag_policy,
+      };
+      btree::put(txn, &mut new, &name, &s)?;
+    }
+    unsafe { btree::drop(txn, old)? };
+    txn.set_root(Root::Spaces as usize, new.db.get());
+  }
+
+  if let Some(old) = old_compartments {
+    for c in compartments.iter() {
+      let entries: Db<ChangeId, L64> = unsafe { Db::from_page(c.entries.into()) };
+      unsafe { btree::drop(txn, entries)? };
+    }
+    unsafe { btree::drop(txn, old)? };
+    txn.remove_root(Root::Compartments as usize);
+  }
+  Ok(())
+}
+
+/// A new database of the compartments of space `s`, returning its page.
+fn space_compartments(
+  txn: &mut RawMutTxn,
+  s: &SerializedSpace,
+  compartments: &[SerializedCompartment],
+  creators: &HashMap<UId, ChangeId>,
+) -> Result<L64, EncycError> {
+  let mut applied = HashSet::new();
+  let changes: Db<ChangeId, L64> = unsafe { Db::from_page(s.changes.into()) };
+  for x in btree::iter(txn, &changes, None)? {
+    let (id, _) = x?;
+    applied.insert(*id);
+  }
+
+  let mut postings: HashMap<UId, Postings> = HashMap::new();
+  let all_entries = txn.root_db::<ChangeId, SerializedEntry, UP<_, _>>(Root::Entries as usize);
+  let space_entries: Db<ChangeId, L64> = unsafe { Db::from_page(s.entries.into()) };
+  for x in btree::iter(txn, &space_entries, None)? {
+    let (id, _) = x?;
+    let Some(ref all_entries) = all_entries else {
+      break;
+    };
+    let e = match btree::get(txn, all_entries, id, None)? {
+      Some((k, e)) if k == id => e,
+      _ => continue,
+    };
+    let db: Db<L64, SerializedPosting> = unsafe { Db::from_page(e.postings.into()) };
+    for p in btree::iter(txn, &db, None)? {
+      let (_, p) = p?;
+      let c = postings.entry(p.compartment).or_default();
+      *c.entries.entry(*id).or_default() += 1;
+      c.counter += 1;
+      c.amounts.push(Money::from(&p.amount));
+    }
+  }
+
+  let mut db: UDb<UId, SerializedCompartment> = unsafe { btree::create_db_(txn)? };
+  for c in compartments.iter() {
+    let created = creators
+      .get(&c.id)
+      .map_or(true, |change| applied.contains(change));
+    if !created && !postings.contains_key(&c.id) {
+      continue;
+    }
+    let none = Postings::default();
+    let p = postings.get(&c.id).unwrap_or(&none);
+    // Same rule as when posting: the sum of the postings must not
+    // overflow.
+    let mut balance = Money::zero(c.balance.currency);
+    for amount in p.amounts.iter() {
+      balance = balance.checked_add(amount).map_err(|e| fail(c, e))?;
+    }
+    let mut entries: Db<ChangeId, L64> = unsafe { btree::create_db_(txn)? };
+    for (entry, n) in p.entries.iter() {
+      btree::put(txn, &mut entries, entry, &(*n).into())?;
+    }
+    let c = SerializedCompartment {
+      entries: entries.db.get().into(),
+      counter: p.counter.into(),
+      balance: balance.into(),
+      ..c.clone()
+    };
+    btree::put(txn, &mut db, &c.id, &c)?;
+  }
+  Ok(db.db.get().into())
+}
+
+fn fail(c: &SerializedCompartment, e: MoneyError) -> EncycError {
+  EncycError::MigrationFailed(format!(
+    "the postings to compartment {} cannot be added up: {}",
+    c.name.as_str(),
+    e
+  ))
+}
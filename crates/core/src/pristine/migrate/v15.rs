@@ -0,0 +1,112 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 15, and the migration to version 16: conflicts move
+//! from one global database into the spaces they arose in. The conflict on
+//! an entry goes to every space that has the entry and applied all the
+//! changes competing for it.
+
+use std::collections::BTreeMap;
+
+use sanakirja::{btree, direct_repr, RootDb, Storable, UnsizedStorable};
+
+use crate::{
+  models::space::{self, TagPolicy},
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{ChangeId, SmallStr, SmallString, UId, L64},
+};
+
+use super::RawMutTxn;
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedSpace {
+  pub id: UId,
+  pub vaults: L64,
+  pub last_modified: u64,
+  pub entries: L64,
+  pub labels: L64,
+  pub compartments: L64,
+  pub changes: L64,
+  pub revchanges: L64,
+  pub states: L64,
+  pub apply_counter: L64,
+  pub tags: L64,
+  pub tag_policy: TagPolicy,
+}
+
+direct_repr!(SerializedSpace);
+impl sanakirja::debug::Check for SerializedSpace {}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let mut conflicts: BTreeMap<ChangeId, Vec<ChangeId>> = BTreeMap::new();
+  let old_conflicts: Option<UDb<ChangeId, ChangeId>> = txn.root_db(Root::Conflicts as usize);
+  if let Some(ref db) = old_conflicts {
+    for x in btree::iter(txn, db, None)? {
+      let (entry, change) = x?;
+      conflicts.entry(*entry).or_default().push(*change);
+    }
+  }
+
+  if let Some(old) = txn.root_db::<SmallStr, SerializedSpace, UP<_, _>>(Root::Spaces as usize) {
+    let mut spaces = Vec::new();
+    for x in btree::iter(txn, &old, None)? {
+      let (name, s) = x?;
+      spaces.push((SmallString::from_str(name.as_str()), *s));
+    }
+
+    let mut new: UDb<SmallStr, space::SerializedSpace> = unsafe { btree::create_db_(txn)? };
+    for (name, s) in spaces {
+      let db = space_conflicts(txn, &s, &conflicts)?;
+      let s = space::SerializedSpace {
+        id: s.id,
+        vaults: s.vaults,
+        last_modified: s.last_modified,
+        entries: s.entries,
+        labels: s.labels,
+        compartments: s.compartments,
+        conflicts: db,
+        changes: s.changes,
+        revchanges: s.revchanges,
+        states: s.states,
+        apply_counter: s.apply_counter,
+        tags: s.tags,
+        tag_policy: s.tag_policy,
+      };
+      btree::put(txn, &mut new, &name, &s)?;
+    }
+    unsafe { btree::drop(txn, old)? };
+    txn.set_root(Root::Spaces as usize, new.db.get());
+  }
+
+  if let Some(old) = old_conflicts {
+    unsafe { btree::drop(txn, old)? };
+    txn.remove_root(Root::Conflicts as usize);
+  }
+  Ok(())
+}
+
+/// A new database of the conflicts of space `s`, returning its page.
+fn space_conflicts(txn: &mut RawMutTxn, s: &SerializedSpace, conflicts: &BTreeMap<ChangeId, Vec<ChangeId>>) -> Result<L64, EncycError> {
+  let entries: Db<ChangeId, L64> = unsafe { Db::from_page(s.entries.into()) };
+  let changes: Db<ChangeId, L64> = unsafe { Db::from_page(s.changes.into()) };
+  let mut db: UDb<ChangeId, ChangeId> = unsafe { btree::create_db_(txn)? };
+  for (entry, competing) in conflicts.iter() {
+    if !has(txn, &entries, entry)? {
+      continue;
+    }
+    let mut applied = true;
+    for c in competing.iter() {
+      applied &= has(txn, &changes, c)?;
+    }
+    if applied {
+      for c in competing.iter() {
+        btree::put(txn, &mut db, entry, c)?;
+      }
+    }
+  }
+  Ok(db.db.get().into())
+}
+
+fn has(txn: &RawMutTxn, db: &Db<ChangeId, L64>, id: &ChangeId) -> Result<bool, EncycError> {
+  Ok(matches!(btree::get(txn, db, id, None)?, Some((k, _)) if k == id))
+}
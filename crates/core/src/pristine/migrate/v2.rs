@@ -0,0 +1,58 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 2, and the migration to version 3: compartment
+//! balances become signed `Money`.
+
+use std::collections::HashMap;
+
+use sanakirja::{btree, direct_repr, RootDb, Storable, UnsizedStorable};
+
+use crate::{
+  models::entry::{SerializedEntry, SerializedPosting},
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{ChangeId, Currency, Money, SmallString, UId, L64},
+};
+
+use super::{rewrite_root, v9, RawMutTxn};
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedCompartment {
+  pub entries: L64,
+  pub counter: L64,
+  pub last_modified: L64,
+  pub balance: L64,
+  pub name: SmallString,
+  pub id: UId,
+}
+
+direct_repr!(SerializedCompartment);
+impl sanakirja::debug::Check for SerializedCompartment {}
+
+/// Compartments didn't have a currency before version 3: use the one of
+/// the postings that hit them, or `XXX` if there are none.
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let mut currencies = HashMap::new();
+  if let Some(entries) = txn.root_db::<ChangeId, SerializedEntry, UP<_, _>>(Root::Entries as usize) {
+    for x in btree::iter(txn, &entries, None)? {
+      let (_, e) = x?;
+      let postings: Db<L64, SerializedPosting> = unsafe { Db::from_page(e.postings.into()) };
+      for p in btree::iter(txn, &postings, None)? {
+        let (_, p) = p?;
+        currencies.entry(p.compartment).or_insert(p.amount.currency);
+      }
+    }
+  }
+
+  rewrite_root::<UId, SerializedCompartment, v9::SerializedCompartment>(txn, Root::Compartments, |_, id, c| {
+    let currency = currencies.get(id).copied().unwrap_or(Currency::NONE);
+    Ok(v9::SerializedCompartment {
+      entries: c.entries,
+      counter: c.counter,
+      last_modified: c.last_modified,
+      balance: Money::new(c.balance.as_u64() as i64, currency).into(),
+      name: c.name.clone(),
+      id: c.id,
+    })
+  })
+}
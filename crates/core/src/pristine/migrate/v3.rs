@@ -0,0 +1,43 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Migration from version 3 to version 4: entries are created by changes,
+//! and the pristine maps change hashes to local ids.
+
+use sanakirja::{btree, RootDb};
+
+use crate::{
+  models::entry::SerializedEntry,
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{hash::Hasher, ChangeId, SerializedHash},
+};
+
+use super::RawMutTxn;
+
+/// Entries recorded before version 4 were not created by any change. They
+/// are given the hash of their id, so that later changes can refer to
+/// them, although there is no change file behind that hash.
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let mut internal: UDb<SerializedHash, ChangeId> = unsafe { btree::create_db_(txn)? };
+  let mut external: UDb<ChangeId, SerializedHash> = unsafe { btree::create_db_(txn)? };
+
+  let mut ids = Vec::new();
+  if let Some(entries) = txn.root_db::<ChangeId, SerializedEntry, UP<_, _>>(Root::Entries as usize) {
+    for x in btree::iter(txn, &entries, None)? {
+      let (id, _) = x?;
+      ids.push(*id);
+    }
+  }
+  for id in ids {
+    let mut hasher = Hasher::default();
+    hasher.update(b"azoni legacy entry");
+    hasher.update(&id.0.as_u64().to_le_bytes());
+    let hash: SerializedHash = (&hasher.finish()).into();
+    btree::put(txn, &mut internal, &hash, &id)?;
+    btree::put(txn, &mut external, &id, &hash)?;
+  }
+
+  txn.set_root(Root::Internal as usize, internal.db.get());
+  txn.set_root(Root::External as usize, external.db.get());
+  Ok(())
+}
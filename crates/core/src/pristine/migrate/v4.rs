@@ -0,0 +1,121 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 4, and the migration to version 5: spaces keep a log
+//! of the changes applied to them, and the Merkle state it leads to.
+
+use sanakirja::{btree, direct_repr, RootDb, Storable, UnsizedStorable};
+
+use crate::{
+  models::entry::SerializedEntry,
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{ChangeId, Hash, Merkle, Pair, SerializedHash, SerializedMerkle, SmallStr, SmallString, UId, L64},
+};
+
+use super::{v5, RawMutTxn};
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedSpace {
+  pub id: UId,
+  pub vaults: L64,
+  pub last_modified: u64,
+  pub entries: L64,
+  pub labels: L64,
+}
+
+direct_repr!(SerializedSpace);
+impl sanakirja::debug::Check for SerializedSpace {}
+
+/// Spaces didn't record which changes were applied to them before version
+/// 5. Their log is rebuilt from the history of their entries, in date
+/// order. Changes that only created compartments, vaults, labels or
+/// filters cannot be attributed to a space, and are left out.
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let Some(old): Option<UDb<SmallStr, SerializedSpace>> = txn.root_db(Root::Spaces as usize) else {
+    return Ok(());
+  };
+  let mut spaces = Vec::new();
+  for x in btree::iter(txn, &old, None)? {
+    let (name, s) = x?;
+    spaces.push((SmallString::from_str(name.as_str()), *s));
+  }
+
+  let entries: Option<UDb<ChangeId, SerializedEntry>> = txn.root_db(Root::Entries as usize);
+  let external: Option<UDb<ChangeId, SerializedHash>> = txn.root_db(Root::External as usize);
+
+  let mut new: UDb<SmallStr, v5::SerializedSpace> = unsafe { btree::create_db_(txn)? };
+  for (name, s) in spaces {
+    // Entries of the space, oldest first, then the changes that touched
+    // each of them.
+    let mut dated = Vec::new();
+    let space_entries: Db<ChangeId, L64> = unsafe { Db::from_page(s.entries.into()) };
+    for x in btree::iter(txn, &space_entries, None)? {
+      let (id, date) = x?;
+      dated.push((*date, *id));
+    }
+    dated.sort();
+    let mut log = Vec::new();
+    if let Some(ref entries) = entries {
+      for (_, id) in dated {
+        let Some((k, e)) = btree::get(txn, entries, &id, None)? else {
+          continue;
+        };
+        if *k != id {
+          continue;
+        }
+        let history: Db<L64, ChangeId> = unsafe { Db::from_page(e.changes.into()) };
+        for x in btree::iter(txn, &history, None)? {
+          let (_, c) = x?;
+          if !log.contains(c) {
+            log.push(*c);
+          }
+        }
+      }
+    }
+
+    let mut changes: Db<ChangeId, L64> = unsafe { btree::create_db_(txn)? };
+    let mut revchanges: UDb<L64, Pair<ChangeId, SerializedMerkle>> = unsafe { btree::create_db_(txn)? };
+    let mut states: UDb<SerializedMerkle, L64> = unsafe { btree::create_db_(txn)? };
+    let mut state = Merkle::zero();
+    let mut pos = 0u64;
+    for c in log {
+      let hash: Hash = match external {
+        Some(ref external) => match btree::get(txn, external, &c, None)? {
+          Some((k, h)) if *k == c => h.into(),
+          _ => continue,
+        },
+        None => continue,
+      };
+      state = state.next(&hash);
+      let serialized: SerializedMerkle = (&state).into();
+      btree::put(txn, &mut changes, &c, &pos.into())?;
+      btree::put(
+        txn,
+        &mut revchanges,
+        &pos.into(),
+        &Pair {
+          a: c,
+          b: serialized,
+        },
+      )?;
+      btree::put(txn, &mut states, &serialized, &pos.into())?;
+      pos += 1;
+    }
+
+    let s = v5::SerializedSpace {
+      id: s.id,
+      vaults: s.vaults,
+      last_modified: s.last_modified,
+      entries: s.entries,
+      labels: s.labels,
+      changes: changes.db.get().into(),
+      revchanges: revchanges.db.get().into(),
+      states: states.db.get().into(),
+      apply_counter: pos.into(),
+    };
+    btree::put(txn, &mut new, &name, &s)?;
+  }
+  unsafe { btree::drop(txn, old)? };
+  txn.set_root(Root::Spaces as usize, new.db.get());
+  Ok(())
+}
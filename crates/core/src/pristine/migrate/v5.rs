@@ -0,0 +1,64 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of version 5, and the migration to version 6: spaces get named
+//! tags, and a policy for changes to the periods they close.
+
+use sanakirja::{btree, direct_repr, RootDb, Storable, UnsizedStorable};
+
+use crate::{
+  models::space::{SerializedTag, TagPolicy},
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{SmallStr, SmallString, UId, L64},
+};
+
+use super::{v14, RawMutTxn};
+
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedSpace {
+  pub id: UId,
+  pub vaults: L64,
+  pub last_modified: u64,
+  pub entries: L64,
+  pub labels: L64,
+  pub changes: L64,
+  pub revchanges: L64,
+  pub states: L64,
+  pub apply_counter: L64,
+}
+
+direct_repr!(SerializedSpace);
+impl sanakirja::debug::Check for SerializedSpace {}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let Some(old): Option<UDb<SmallStr, SerializedSpace>> = txn.root_db(Root::Spaces as usize) else {
+    return Ok(());
+  };
+  let mut spaces = Vec::new();
+  for x in btree::iter(txn, &old, None)? {
+    let (name, s) = x?;
+    spaces.push((SmallString::from_str(name.as_str()), *s));
+  }
+
+  let mut new: UDb<SmallStr, v14::SerializedSpace> = unsafe { btree::create_db_(txn)? };
+  for (name, s) in spaces {
+    let tags: UDb<SmallStr, SerializedTag> = unsafe { btree::create_db_(txn)? };
+    let s = v14::SerializedSpace {
+      id: s.id,
+      vaults: s.vaults,
+      last_modified: s.last_modified,
+      entries: s.entries,
+      labels: s.labels,
+      changes: s.changes,
+      revchanges: s.revchanges,
+      states: s.states,
+      apply_counter: s.apply_counter,
+      tags: tags.db.get().into(),
+      tag_policy: TagPolicy::default(),
+    };
+    btree::put(txn, &mut new, &name, &s)?;
+  }
+  unsafe { btree::drop(txn, old)? };
+  txn.set_root(Root::Spaces as usize, new.db.get());
+  Ok(())
+}
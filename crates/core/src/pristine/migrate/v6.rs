@@ -0,0 +1,21 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Migration from version 6 to version 7: a new root database registers
+//! the devices that write to the pristine. Nothing else changes.
+
+use sanakirja::btree;
+
+use crate::{
+  models::device::SerializedDevice,
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::SmallStr,
+};
+
+use super::RawMutTxn;
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let devices: UDb<SmallStr, SerializedDevice> = unsafe { btree::create_db_(txn)? };
+  txn.set_root(Root::Devices as usize, devices.db.get());
+  Ok(())
+}
@@ -0,0 +1,21 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Migration from version 7 to version 8: a new root database records the
+//! entries in conflict. Nothing else changes, and no entry is in conflict
+//! yet.
+
+use sanakirja::btree;
+
+use crate::{
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::ChangeId,
+};
+
+use super::RawMutTxn;
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let conflicts: UDb<ChangeId, ChangeId> = unsafe { btree::create_db_(txn)? };
+  txn.set_root(Root::Conflicts as usize, conflicts.db.get());
+  Ok(())
+}
@@ -0,0 +1,27 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Migration from version 8 to version 9: new root databases hold the
+//! dependency graph of changes, and the change that created each
+//! compartment, vault, label and filter. They start empty: the changes
+//! themselves are not in the pristine, so the graph only covers the
+//! changes applied after the migration.
+
+use sanakirja::btree;
+
+use crate::{
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{ChangeId, UId},
+};
+
+use super::RawMutTxn;
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let dependencies: UDb<ChangeId, ChangeId> = unsafe { btree::create_db_(txn)? };
+  txn.set_root(Root::Dependencies as usize, dependencies.db.get());
+  let dependents: UDb<ChangeId, ChangeId> = unsafe { btree::create_db_(txn)? };
+  txn.set_root(Root::Dependents as usize, dependents.db.get());
+  let creators: UDb<UId, ChangeId> = unsafe { btree::create_db_(txn)? };
+  txn.set_root(Root::Creators as usize, creators.db.get());
+  Ok(())
+}
@@ -0,0 +1,92 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Layout of versions 3 to 9, and the migration to version 10:
+//! compartments can be closed and put in a vault. Their balances, counters
+//! and entries were never updated before, so they are computed again from
+//! the entries.
+
+use std::collections::HashMap;
+
+use sanakirja::{btree, direct_repr, RootDb, Storable, UnsizedStorable};
+
+use crate::{
+  models::entry::{SerializedEntry, SerializedPosting},
+  pristine::{sanakirja::types::*, EncycError, Root},
+  types::{ChangeId, Money, MoneyError, SerializedMoney, SmallString, UId, L64},
+};
+
+use super::{rewrite_root, v10, RawMutTxn};
+
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
+pub struct SerializedCompartment {
+  pub entries: L64,
+  pub counter: L64,
+  pub last_modified: L64,
+  pub balance: SerializedMoney,
+  pub name: SmallString,
+  pub id: UId,
+}
+
+direct_repr!(SerializedCompartment);
+impl sanakirja::debug::Check for SerializedCompartment {}
+
+/// What the postings of all entries add up to in one compartment.
+#[derive(Default)]
+struct Postings {
+  entries: HashMap<ChangeId, u64>,
+  counter: u64,
+  amounts: Vec<Money>,
+}
+
+pub(super) fn migrate(txn: &mut RawMutTxn) -> Result<(), EncycError> {
+  let mut postings: HashMap<UId, Postings> = HashMap::new();
+  if let Some(entries) = txn.root_db::<ChangeId, SerializedEntry, UP<_, _>>(Root::Entries as usize) {
+    for x in btree::iter(txn, &entries, None)? {
+      let (id, e) = x?;
+      let db: Db<L64, SerializedPosting> = unsafe { Db::from_page(e.postings.into()) };
+      for p in btree::iter(txn, &db, None)? {
+        let (_, p) = p?;
+        let c = postings.entry(p.compartment).or_default();
+        *c.entries.entry(*id).or_default() += 1;
+        c.counter += 1;
+        c.amounts.push(Money::from(&p.amount));
+      }
+    }
+  }
+
+  rewrite_root::<UId, SerializedCompartment, v10::SerializedCompartment>(txn, Root::Compartments, |txn, id, c| {
+    let old: Db<ChangeId, L64> = unsafe { Db::from_page(c.entries.into()) };
+    unsafe { btree::drop(txn, old)? };
+    let mut entries: Db<ChangeId, L64> = unsafe { btree::create_db_(txn)? };
+    let none = Postings::default();
+    let p = postings.get(id).unwrap_or(&none);
+    // Same rule as when posting: the postings must be in the currency of
+    // the compartment, and their sum must not overflow.
+    let mut balance = Money::zero(c.balance.currency);
+    for amount in p.amounts.iter() {
+      balance = balance.checked_add(amount).map_err(|e| fail(c, e))?;
+    }
+    for (entry, n) in p.entries.iter() {
+      btree::put(txn, &mut entries, entry, &(*n).into())?;
+    }
+    Ok(v10::SerializedCompartment {
+      entries: entries.db.get().into(),
+      counter: p.counter.into(),
+      last_modified: c.last_modified,
+      closed: 0u64.into(),
+      balance: balance.into(),
+      vault: UId::nil(),
+      name: c.name.clone(),
+      id: c.id,
+    })
+  })
+}
+
+fn fail(c: &SerializedCompartment, e: MoneyError) -> EncycError {
+  EncycError::MigrationFailed(format!(
+    "the postings to compartment {} cannot be added up: {}",
+    c.name.as_str(),
+    e
+  ))
+}
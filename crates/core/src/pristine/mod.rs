@@ -13,3 +13,9 @@ pub use txn::*;
 
 mod encyc;
 pub use encyc::*;
+
+mod check;
+pub use check::*;
+
+mod migrate;
+pub use migrate::{MigrateOptions, Migration, MigrationReport};
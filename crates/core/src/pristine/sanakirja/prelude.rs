@@ -5,10 +5,15 @@ use sanakirja::{direct_repr, Storable, UnsizedStorable};
 
 use crate::{
   models::{
-    compartment::SerializedCompartment, entry::SerializedEntry, filter::SerializedFilter, label::SerializedLabel, space::SerializedSpace,
+    compartment::SerializedCompartment,
+    device::SerializedDevice,
+    entry::{SerializedEntry, SerializedPosting},
+    filter::SerializedFilter,
+    label::SerializedLabel,
+    space::{SerializedSpace, SerializedTag},
     vault::SerializedVault,
   },
-  types::{ChangeId, SerializedHash, UId, L64},
+  types::{ChangeId, Pair, SerializedHash, SerializedMerkle, SerializedMoney, UId, L64},
 };
 
 // register sanakirja storage
@@ -24,6 +29,18 @@ impl sanakirja::debug::Check for SerializedHash {}
 direct_repr!(ChangeId);
 impl sanakirja::debug::Check for ChangeId {}
 
+direct_repr!(SerializedMoney);
+impl sanakirja::debug::Check for SerializedMoney {}
+
+direct_repr!(SerializedMerkle);
+impl sanakirja::debug::Check for SerializedMerkle {}
+
+direct_repr!(Pair<ChangeId, SerializedMerkle>);
+impl sanakirja::debug::Check for Pair<ChangeId, SerializedMerkle> {}
+
+direct_repr!(SerializedTag);
+impl sanakirja::debug::Check for SerializedTag {}
+
 direct_repr!(SerializedSpace);
 impl sanakirja::debug::Check for SerializedSpace {}
 
@@ -31,10 +48,16 @@ impl sanakirja::debug::Check for SerializedSpace {}
 direct_repr!(SerializedEntry);
 impl sanakirja::debug::Check for SerializedEntry {}
 
+direct_repr!(SerializedPosting);
+impl sanakirja::debug::Check for SerializedPosting {}
+
 // register model compartment storage
 direct_repr!(SerializedCompartment);
 impl sanakirja::debug::Check for SerializedCompartment {}
 
+direct_repr!(SerializedDevice);
+impl sanakirja::debug::Check for SerializedDevice {}
+
 direct_repr!(SerializedFilter);
 impl sanakirja::debug::Check for SerializedFilter {}
 
@@ -27,10 +27,7 @@ impl UnsizedStorable for SmallStr {
 
   unsafe fn onpage_size(p: *const u8) -> usize {
     let len = *p as usize;
-    debug!(
-      "onpage_size {:?}",
-      std::slice::from_raw_parts(p, 1 + len as usize)
-    );
+    debug!("onpage_size {:?}", std::slice::from_raw_parts(p, 1 + len));
     1 + len
   }
 }
@@ -49,5 +46,5 @@ impl Check for SmallStr {}
 
 unsafe fn smallstr_from_raw_ptr<'a>(p: *const u8) -> &'a SmallStr {
   let len = *p as usize;
-  std::mem::transmute(std::slice::from_raw_parts(p, 1 + len as usize))
+  std::mem::transmute(std::slice::from_raw_parts(p, 1 + len))
 }
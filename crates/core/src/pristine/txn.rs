@@ -2,18 +2,20 @@
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 14.
 #![allow(dead_code)]
 
-use std::sync::Arc;
+use std::{collections::hash_map::Entry, sync::Arc};
 
+use chrono::Utc;
+use log::debug;
 use parking_lot::{Mutex, RwLock};
-use sanakirja::{btree, Env, LoadPage, RootDb, RootPage};
+use sanakirja::{btree, Commit, Env, LoadPage, RootDb, RootPage};
 
 use crate::{
-  models::{compartment::SerializedCompartment, entry::SerializedEntry, filter::SerializedFilter, label::SerializedLabel, space, vault::SerializedVault},
+  models::{device::SerializedDevice, entry::SerializedEntry, filter::SerializedFilter, label::SerializedLabel, space, vault::SerializedVault},
   traits::{MutTxnT, TxnT},
   types::*,
 };
 
-use super::{sanakirja::types::*, Encyc, EncycError};
+use super::{migrate::check_version, sanakirja::types::*, Encyc, EncycError, LimitedTxn};
 
 pub struct ArcTxn<T>(pub Arc<RwLock<T>>);
 
@@ -46,38 +48,56 @@ impl<T> std::ops::Deref for ArcTxn<T> {
   }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[repr(usize)]
 pub enum Root {
   Version,
   Entries,
+  /// Unused since version 15, where compartments moved into their space.
   Compartments,
   Labels,
   Filters,
   Vaults,
   Spaces,
+  Internal,
+  External,
+  Devices,
+  /// Unused since version 16, where conflicts moved into their space.
+  Conflicts,
+  Dependencies,
+  Dependents,
+  Creators,
 }
 
-pub const VERSION: L64 = L64(1u64.to_le());
+pub const VERSION: L64 = L64(16u64.to_le());
 
 impl Encyc {
   pub fn txn_begin(&self) -> Result<Txn, EncycError> {
     let txn = Env::txn_begin(self.env.clone())?;
-    if L64(txn.root(Root::Version as usize)) != VERSION {
-      return Err(EncycError::Version);
+    // A pristine that was never committed to has no roots either, and is
+    // reported as corrupted below.
+    match txn.root(Root::Version as usize) {
+      0 => {}
+      version => check_version(L64(version).as_u64())?,
     }
 
     fn begin(txn: sanakirja::Txn<Arc<Env>>) -> Option<Txn> {
+      let cur_space = read_cur_space(&txn);
       Some(Txn {
         entries: txn.root_db(Root::Entries as usize)?,
-        compartments: txn.root_db(Root::Compartments as usize)?,
         labels: txn.root_db(Root::Labels as usize)?,
         filters: txn.root_db(Root::Filters as usize)?,
         vaults: txn.root_db(Root::Vaults as usize)?,
         spaces: txn.root_db(Root::Spaces as usize)?,
+        internal: txn.root_db(Root::Internal as usize)?,
+        external: txn.root_db(Root::External as usize)?,
+        devices: txn.root_db(Root::Devices as usize)?,
+        dependencies: txn.root_db(Root::Dependencies as usize)?,
+        dependents: txn.root_db(Root::Dependents as usize)?,
+        creators: txn.root_db(Root::Creators as usize)?,
         open_spaces: Mutex::new(HashMap::default()),
         txn,
-        cur_space: None,
+        cur_space,
       })
     }
 
@@ -93,55 +113,79 @@ impl Encyc {
   }
 
   pub fn mut_txn_begin(&self) -> Result<MutTxn<()>, EncycError> {
-    let mut txn = Env::mut_txn_begin(self.env.clone())?;
+    let mut txn = LimitedTxn::new(Env::mut_txn_begin(self.env.clone())?, self.max_size);
     if let Some(version) = txn.root(Root::Version as usize) {
-      if L64(version) != VERSION {
-        return Err(EncycError::Version.into());
-      }
+      check_version(L64(version).as_u64())?;
     } else {
       txn.set_root(Root::Version as usize, VERSION.0);
     }
 
+    let cur_space = read_cur_space(&txn);
     Ok(MutTxn {
       entries: if let Some(db) = txn.root_db(Root::Entries as usize) {
         db
       } else {
-        btree::create_db_(&mut txn)?
-      },
-      compartments: if let Some(db) = txn.root_db(Root::Compartments as usize) {
-        db
-      } else {
-        btree::create_db_(&mut txn)?
+        unsafe { btree::create_db_(&mut txn)? }
       },
       labels: if let Some(db) = txn.root_db(Root::Labels as usize) {
         db
       } else {
-        btree::create_db_(&mut txn)?
+        unsafe { btree::create_db_(&mut txn)? }
       },
       filters: if let Some(db) = txn.root_db(Root::Filters as usize) {
         db
       } else {
-        btree::create_db_(&mut txn)?
+        unsafe { btree::create_db_(&mut txn)? }
       },
       vaults: if let Some(db) = txn.root_db(Root::Vaults as usize) {
         db
       } else {
-        btree::create_db_(&mut txn)?
+        unsafe { btree::create_db_(&mut txn)? }
       },
       spaces: if let Some(db) = txn.root_db(Root::Spaces as usize) {
         db
       } else {
-        btree::create_db_(&mut txn)?
+        unsafe { btree::create_db_(&mut txn)? }
+      },
+      internal: if let Some(db) = txn.root_db(Root::Internal as usize) {
+        db
+      } else {
+        unsafe { btree::create_db_(&mut txn)? }
+      },
+      external: if let Some(db) = txn.root_db(Root::External as usize) {
+        db
+      } else {
+        unsafe { btree::create_db_(&mut txn)? }
+      },
+      devices: if let Some(db) = txn.root_db(Root::Devices as usize) {
+        db
+      } else {
+        unsafe { btree::create_db_(&mut txn)? }
+      },
+      dependencies: if let Some(db) = txn.root_db(Root::Dependencies as usize) {
+        db
+      } else {
+        unsafe { btree::create_db_(&mut txn)? }
+      },
+      dependents: if let Some(db) = txn.root_db(Root::Dependents as usize) {
+        db
+      } else {
+        unsafe { btree::create_db_(&mut txn)? }
+      },
+      creators: if let Some(db) = txn.root_db(Root::Creators as usize) {
+        db
+      } else {
+        unsafe { btree::create_db_(&mut txn)? }
       },
       open_spaces: Mutex::new(HashMap::default()),
       txn,
-      cur_space: None,
+      cur_space,
     })
   }
 }
 
 pub type Txn = GenericTxn<sanakirja::Txn<Arc<Env>>>;
-pub type MutTxn<T> = GenericTxn<sanakirja::MutTxn<Arc<Env>, T>>;
+pub type MutTxn<T> = GenericTxn<LimitedTxn<sanakirja::MutTxn<Arc<Env>, T>>>;
 
 pub struct GenericTxn<T>
 where
@@ -150,12 +194,19 @@ where
   #[doc(hidden)]
   pub txn: T,
 
-  pub entries: UDb<ChangeId, SerializedEntry>,       // like transactions
-  pub compartments: UDb<UId, SerializedCompartment>, // like accounts
-  pub labels: UDb<UId, SerializedLabel>,             // like tags, but can be applied to any entry
-  pub filters: UDb<UId, SerializedFilter>,           // like campaigns, budgets etc. (can be applied to any entry)
+  pub entries: UDb<ChangeId, SerializedEntry>, // like transactions
+  pub labels: UDb<UId, SerializedLabel>,       // like tags, but can be applied to any entry
+  pub filters: UDb<UId, SerializedFilter>,     // like campaigns, budgets etc. (can be applied to any entry)
 
   pub vaults: UDb<UId, SerializedVault>,
+
+  pub internal: UDb<SerializedHash, ChangeId>, // hash of a change to its local id
+  pub external: UDb<ChangeId, SerializedHash>, // and back
+
+  pub devices: UDb<SmallStr, SerializedDevice>, // devices that wrote to the pristine, by name
+  pub dependencies: UDb<ChangeId, ChangeId>,    // changes to the changes they depend on
+  pub dependents: UDb<ChangeId, ChangeId>,      // and back
+  pub creators: UDb<UId, ChangeId>,             // compartments, vaults, labels and filters to the change that created them
   // open_vaults: Mutex<HashMap<UId, VaultRef<Self>>>,
 
   //
@@ -169,72 +220,237 @@ where
 /// a `SpaceRef` whose transaction has been moved to another thread.
 unsafe impl<T: LoadPage<Error = sanakirja::Error> + RootPage> Send for GenericTxn<T> {}
 
-impl<T: LoadPage<Error = sanakirja::Error> + RootPage> TxnT for GenericTxn<T> {}
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> TxnT for GenericTxn<T> {
+  fn load_space(&self, name: &str) -> Result<Option<space::SpaceRef<Self>>, Self::GraphError> {
+    let name = SmallString::from_str(name);
+    match self.open_spaces.lock().entry(name.clone()) {
+      Entry::Vacant(v) => match btree::get(&self.txn, &self.spaces, &name, None)? {
+        Some((name_, b)) if name_ == name.as_ref() => {
+          let space = space::SpaceRef::new(space::Space::from_serialized(name, b));
+          Ok(Some(v.insert(space).clone()))
+        }
+        _ => Ok(None),
+      },
+      Entry::Occupied(occ) => Ok(Some(occ.get().clone())),
+    }
+  }
+
+  fn current_space(&self) -> Option<&str> {
+    self.cur_space.as_deref()
+  }
+
+  fn get_internal(&self, hash: &Hash) -> Result<Option<ChangeId>, Self::GraphError> {
+    let hash: SerializedHash = hash.into();
+    match btree::get(&self.txn, &self.internal, &hash, None)? {
+      Some((k, v)) if *k == hash => Ok(Some(*v)),
+      _ => Ok(None),
+    }
+  }
+
+  fn get_external(&self, id: &ChangeId) -> Result<Option<Hash>, Self::GraphError> {
+    match btree::get(&self.txn, &self.external, id, None)? {
+      Some((k, v)) if k == id => Ok(Some(v.into())),
+      _ => Ok(None),
+    }
+  }
+
+  fn list_changes(&self) -> Result<Vec<(ChangeId, Hash)>, Self::GraphError> {
+    let mut changes = Vec::new();
+    for x in btree::iter(&self.txn, &self.external, None)? {
+      let (id, hash) = x?;
+      changes.push((*id, hash.into()));
+    }
+    Ok(changes)
+  }
+
+  fn space_names(&self) -> Result<Vec<String>, Self::GraphError> {
+    let mut names = Vec::new();
+    for x in btree::iter(&self.txn, &self.spaces, None)? {
+      let (name, _) = x?;
+      names.push(name.as_str().to_string());
+    }
+    Ok(names)
+  }
+}
+
+/// Names of spaces, compartments, vaults and labels are between 1 and
+/// `MAX_LEN` bytes long.
+pub(crate) fn check_name(name: &str) -> Result<(), EncycError> {
+  if name.is_empty() || name.len() > MAX_LEN {
+    return Err(EncycError::InvalidName(name.to_string()));
+  }
+  Ok(())
+}
+
+/// Offset, in the root page, of the name of the current space: one length
+/// byte followed by at most 255 bytes of name, at the very end of the page.
+const CUR_SPACE_OFFSET: usize = 4064 - 256;
+
+fn read_cur_space<T: RootPage>(txn: &T) -> Option<String> {
+  unsafe {
+    let b = txn.root_page();
+    let len = b[CUR_SPACE_OFFSET] as usize;
+    if len == 0 {
+      return None;
+    }
+    std::str::from_utf8(&b[CUR_SPACE_OFFSET + 1..CUR_SPACE_OFFSET + 1 + len])
+      .ok()
+      .map(String::from)
+  }
+}
+
+impl<T: LoadPage<Error = sanakirja::Error> + RootPage> GenericTxn<T> {
+  /// Derive a fresh `ChangeId` from the first bytes of `hash`, stepping over
+  /// ids that are already taken (and over `ChangeId::ROOT`).
+  pub(crate) fn make_changeid(&self, hash: &Hash) -> Result<ChangeId, EncycError> {
+    let bytes = hash.to_bytes();
+    let mut id = ChangeId(L64::from_slice_le(&bytes[1..9]));
+    loop {
+      if !id.is_root() && self.get_external(&id)?.is_none() {
+        return Ok(id);
+      }
+      id = ChangeId(L64::from(id.0.as_u64().wrapping_add(1)));
+    }
+  }
+}
+
+impl MutTxn<()> {
+  fn put_space(&mut self, space: space::SpaceRef<Self>) -> Result<(), EncycError> {
+    let space = space.read();
+    debug!("put_space {:?}", space.name);
+    let serialized = space::SerializedSpace::from(&*space);
+    btree::del(&mut self.txn, &mut self.spaces, &space.name, None)?;
+    btree::put(&mut self.txn, &mut self.spaces, &space.name, &serialized)?;
+    Ok(())
+  }
+}
 
 impl MutTxnT for MutTxn<()> {
   fn open_or_create_space(&mut self, name: &str) -> Result<space::SpaceRef<Self>, Self::GraphError> {
-    todo!("self.open_or_create_space(name={})?", name)
-    // let name = SmallString::from_str(name);
-    // let mut commit = None;
-
-    // let result = match self.open_spaces.lock().entry(name.clone()) {
-    //   Entry::Vacant(v) => {
-    //     let r = match btree::get(&self.txn, &self.spaces, &name, None)? {
-    //       Some((name_, b)) if name_ == name.as_ref() => space::SpaceRef::new(space::Space {
-    //         id: b.id,
-    //         name: name.clone(),
-    //         last_modified: b.last_modified,
-    //         changes: Db::from_page(b.changes.into()),
-    //         vaults: Db::from_page(b.vaults.into()),
-    //       }),
-    //       _ => {
-    //         let br = space::SpaceRef::new(space::Space {
-    //           id: UId::new(),
-    //           name: name.clone(),
-    //           last_modified: 0,
-    //           changes: btree::create_db_(&mut self.txn)?,
-    //           vaults: btree::create_db_(&mut self.txn)?,
-    //         });
-    //         commit = Some(br.clone());
-    //         br
-    //       }
-    //     };
-    //   }
-    //   Entry::Occupied(occ) => todo!(),
-    // };
-
-    // if let Some(commit) = commit {
-    //   todo!("self.put_space(&commit)?");
-    // }
-
-    // Ok(result)
-  }
-
-  fn commit(self) -> Result<(), Self::GraphError> {
-    // use std::ops::DerefMut;
-
-    // {
-    //   let open_spaces = std::mem::replace(self.open_spaces.lock().deref_mut(), HashMap::default());
-    //   for (name, space) in open_spaces {
-    //     debug!("commit_space {:?}", name);
-    //     todo!("self.commit_space(&space)?");
-    //   }
-    // }
-
-    // if let Some(ref cur) = self.cur_space {
-    //   unsafe {
-    //     assert!(cur.len() < 256);
-    //     let b = self.txn.root_page_mut();
-    //     b[4096 - 256] = cur.len() as u8;
-    //     std::ptr::copy(cur.as_ptr(), b.as_mut_ptr().add(4096 - 255), cur.len())
-    //   }
-    // }
-
-    // debug!("{:x} {:x}", self.entries.db, self.spaces.db);
-
-    // self.txn.set_root(Root::Entries as usize, self.entries.db);
-    // self.txn.set_root(Root::Spaces as usize, self.spaces.db);
-
-    todo!("self.commit()?")
+    check_name(name)?;
+    let name = SmallString::from_str(name);
+    let mut commit = None;
+
+    let result = match self.open_spaces.lock().entry(name.clone()) {
+      Entry::Vacant(v) => {
+        let r = match btree::get(&self.txn, &self.spaces, &name, None)? {
+          Some((name_, b)) if name_ == name.as_ref() => space::SpaceRef::new(space::Space::from_serialized(name, b)),
+          _ => {
+            let br = space::SpaceRef::new(space::Space {
+              id: UId::new(),
+              name,
+              last_modified: Utc::now().timestamp() as u64,
+              entries: unsafe { btree::create_db_(&mut self.txn)? },
+              vaults: unsafe { btree::create_db_(&mut self.txn)? },
+              labels: unsafe { btree::create_db_(&mut self.txn)? },
+              compartments: unsafe { btree::create_db_(&mut self.txn)? },
+              conflicts: unsafe { btree::create_db_(&mut self.txn)? },
+              changes: unsafe { btree::create_db_(&mut self.txn)? },
+              revchanges: unsafe { btree::create_db_(&mut self.txn)? },
+              states: unsafe { btree::create_db_(&mut self.txn)? },
+              apply_counter: 0,
+              tags: unsafe { btree::create_db_(&mut self.txn)? },
+              tag_policy: space::TagPolicy::default(),
+            });
+            commit = Some(br.clone());
+            br
+          }
+        };
+        v.insert(r).clone()
+      }
+      Entry::Occupied(occ) => occ.get().clone(),
+    };
+
+    if let Some(commit) = commit {
+      self.put_space(commit)?;
+    }
+
+    Ok(result)
+  }
+
+  fn set_current_space(&mut self, name: &str) -> Result<(), Self::GraphError> {
+    check_name(name)?;
+    self.cur_space = Some(name.to_string());
+    Ok(())
+  }
+
+  fn register_change(&mut self, hash: &Hash) -> Result<ChangeId, Self::GraphError> {
+    if let Some(id) = self.get_internal(hash)? {
+      return Ok(id);
+    }
+    let id = self.make_changeid(hash)?;
+    let shash: SerializedHash = hash.into();
+    btree::put(&mut self.txn, &mut self.internal, &shash, &id)?;
+    btree::put(&mut self.txn, &mut self.external, &id, &shash)?;
+    Ok(id)
+  }
+
+  fn drop_space(&mut self, name: &str) -> Result<bool, Self::GraphError> {
+    let name = SmallString::from_str(name);
+    self.open_spaces.lock().remove(&name);
+    if self.cur_space.as_deref() == Some(name.as_str()) {
+      self.cur_space = None;
+    }
+    Ok(btree::del(&mut self.txn, &mut self.spaces, &name, None)?)
+  }
+
+  fn commit(mut self) -> Result<(), Self::GraphError> {
+    {
+      let open_spaces = std::mem::take(&mut *self.open_spaces.lock());
+      for (name, space) in open_spaces {
+        debug!("commit_space {:?}", name);
+        self.put_space(space)?;
+      }
+    }
+
+    unsafe {
+      let b = self.txn.root_page_mut();
+      let cur = self.cur_space.as_deref().unwrap_or("");
+      b[CUR_SPACE_OFFSET] = cur.len() as u8;
+      std::ptr::copy(
+        cur.as_ptr(),
+        b.as_mut_ptr().add(CUR_SPACE_OFFSET + 1),
+        cur.len(),
+      )
+    }
+
+    debug!("{:x} {:x}", self.entries.db, self.spaces.db);
+
+    self
+      .txn
+      .set_root(Root::Entries as usize, self.entries.db.get());
+    self
+      .txn
+      .set_root(Root::Labels as usize, self.labels.db.get());
+    self
+      .txn
+      .set_root(Root::Filters as usize, self.filters.db.get());
+    self
+      .txn
+      .set_root(Root::Vaults as usize, self.vaults.db.get());
+    self
+      .txn
+      .set_root(Root::Spaces as usize, self.spaces.db.get());
+    self
+      .txn
+      .set_root(Root::Internal as usize, self.internal.db.get());
+    self
+      .txn
+      .set_root(Root::External as usize, self.external.db.get());
+    self
+      .txn
+      .set_root(Root::Devices as usize, self.devices.db.get());
+    self
+      .txn
+      .set_root(Root::Dependencies as usize, self.dependencies.db.get());
+    self
+      .txn
+      .set_root(Root::Dependents as usize, self.dependents.db.get());
+    self
+      .txn
+      .set_root(Root::Creators as usize, self.creators.db.get());
+
+    self.txn.commit()?;
+    Ok(())
   }
 }
@@ -0,0 +1,340 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! A line-oriented protocol to synchronize with a remote pristine over
+//! any byte stream, such as the standard input and output of `azoni
+//! protocol` run through ssh.
+//!
+//! The client opens with `azoni <version>`, which the server echoes, or
+//! answers with an `error` line if it speaks another version. Then each
+//! request is one line, and so is each reply, except where noted:
+//!
+//! - `log <from> <space>`: one `<position> <hash> <state>` line per change
+//!   of the log of `space` from position `from` on, then a `.` line.
+//! - `has <hash>`: `yes` or `no`.
+//! - `get <hash>`: `<length>`, followed by the bytes of the change.
+//! - `put <length>`, followed by the bytes of a change: stores the change
+//!   without applying it, replies `ok <hash>`. A change larger than
+//!   `MAX_CHANGE_SIZE` gets an `error` reply, and the connection is closed.
+//! - `apply <hash> <space>`: applies a stored change to `space`, created
+//!   if needed, and commits. Replies `ok <state>`.
+//! - `quit`: closes the connection.
+//!
+//! Any request may instead get an `error <message>` reply. Since every
+//! `apply` is committed on its own and fetched changes are stored before
+//! being applied, an interrupted transfer resumes where it stopped.
+
+use std::io::{BufRead, Write};
+
+use parking_lot::Mutex;
+use thiserror_impl::Error;
+
+use crate::{
+  apply::apply_change,
+  change::{Change, ChangeError},
+  changestore::ChangeStore,
+  models::space::SpaceTxnT,
+  pristine::Encyc,
+  sync::{space_log, LogEntry},
+  traits::{MutTxnT, TxnT},
+  types::{Base32, Hash, Merkle},
+};
+
+pub const PROTOCOL_VERSION: u64 = 1;
+
+/// Changes larger than this are refused, rather than allocated.
+pub const MAX_CHANGE_SIZE: usize = 1 << 26;
+
+#[derive(Debug, Error)]
+pub enum ProtocolError {
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  #[error(transparent)]
+  Change(#[from] ChangeError),
+  #[error("Protocol version {theirs} is not supported (expected {ours})")]
+  Version { ours: u64, theirs: u64 },
+  #[error("Malformed protocol line: {0:?}")]
+  Malformed(String),
+  #[error("Change of {0} bytes is larger than the limit of {MAX_CHANGE_SIZE} bytes")]
+  TooLarge(usize),
+  #[error("Connection closed by the other side")]
+  Closed,
+  #[error("Remote error: {0}")]
+  Remote(String),
+  #[error("Not supported by remote pristines")]
+  Unsupported,
+}
+
+/// Read a line, without its end. Returns `None` at the end of the stream.
+fn read_line<R: BufRead>(r: &mut R) -> Result<Option<String>, std::io::Error> {
+  let mut line = String::new();
+  if r.read_line(&mut line)? == 0 {
+    return Ok(None);
+  }
+  let len = line.trim_end_matches(['\n', '\r']).len();
+  line.truncate(len);
+  Ok(Some(line))
+}
+
+/// Read `<length>` bytes, after a line holding `<length>`.
+fn read_payload<R: BufRead>(r: &mut R, len: &str) -> Result<Vec<u8>, ProtocolError> {
+  let len: usize = len
+    .parse()
+    .map_err(|_| ProtocolError::Malformed(len.to_string()))?;
+  if len > MAX_CHANGE_SIZE {
+    return Err(ProtocolError::TooLarge(len));
+  }
+  let mut bytes = vec![0; len];
+  r.read_exact(&mut bytes)?;
+  Ok(bytes)
+}
+
+fn parse_hash(s: &str) -> Result<Hash, String> {
+  Hash::from_base32(s.as_bytes()).ok_or_else(|| format!("Invalid hash: {}", s))
+}
+
+/// Serve the protocol on `r` and `w` for the pristine `encyc`, whose
+/// changes are in `changes`, until the client quits or closes the stream.
+pub fn serve<C: ChangeStore, R: BufRead, W: Write>(encyc: &Encyc, changes: &C, mut r: R, mut w: W) -> Result<(), ProtocolError> {
+  let Some(hello) = read_line(&mut r)? else {
+    return Ok(());
+  };
+  match hello.strip_prefix("azoni ").map(str::parse::<u64>) {
+    Some(Ok(PROTOCOL_VERSION)) => writeln!(w, "azoni {}", PROTOCOL_VERSION)?,
+    Some(Ok(theirs)) => {
+      writeln!(
+        w,
+        "error protocol version {} is not supported (expected {})",
+        theirs, PROTOCOL_VERSION
+      )?;
+      w.flush()?;
+      return Err(ProtocolError::Version {
+        ours: PROTOCOL_VERSION,
+        theirs,
+      });
+    }
+    _ => return Err(ProtocolError::Malformed(hello)),
+  }
+  w.flush()?;
+
+  while let Some(line) = read_line(&mut r)? {
+    let (cmd, args) = line.split_once(' ').unwrap_or((&line, ""));
+    // Replies are built in full before being sent, so that an error
+    // halfway through never leaves a partial reply on the stream.
+    let reply = match cmd {
+      "log" => serve_log(encyc, args),
+      "has" => parse_hash(args).map(|h| {
+        if changes.has_change(&h) {
+          b"yes\n".to_vec()
+        } else {
+          b"no\n".to_vec()
+        }
+      }),
+      "get" => serve_get(changes, args),
+      // A length that is not a number has no payload after it: answer
+      // and go on. A length that is too large does, and reading it would
+      // cost as much as accepting it, so answer and close instead.
+      "put" => match read_payload(&mut r, args) {
+        Ok(bytes) => serve_put(changes, &bytes),
+        Err(ProtocolError::Malformed(len)) => Err(format!("Invalid length: {}", len)),
+        Err(e @ ProtocolError::TooLarge(_)) => {
+          writeln!(w, "error {}", e)?;
+          w.flush()?;
+          return Err(e);
+        }
+        Err(e) => return Err(e),
+      },
+      "apply" => serve_apply(encyc, changes, args),
+      "quit" => return Ok(()),
+      _ => Err(format!("Unknown command: {}", cmd)),
+    };
+    match reply {
+      Ok(reply) => w.write_all(&reply)?,
+      Err(e) => writeln!(w, "error {}", e.replace('\n', " "))?,
+    }
+    w.flush()?;
+  }
+  Ok(())
+}
+
+fn serve_log(encyc: &Encyc, args: &str) -> Result<Vec<u8>, String> {
+  let Some((from, name)) = args.split_once(' ') else {
+    return Err(format!("Malformed log request: {}", args));
+  };
+  let from: u64 = from
+    .parse()
+    .map_err(|_| format!("Invalid position: {}", from))?;
+  let txn = encyc.txn_begin().map_err(|e| e.to_string())?;
+  let mut reply = Vec::new();
+  if let Some(space) = txn.load_space(name).map_err(|e| e.to_string())? {
+    let log = space_log(&txn, &space).map_err(|e| e.to_string())?;
+    for (n, e) in log.iter().enumerate().skip(from as usize) {
+      writeln!(reply, "{} {}", n, e.to_line()).map_err(|e| e.to_string())?;
+    }
+  }
+  reply.extend(b".\n");
+  Ok(reply)
+}
+
+fn serve_get<C: ChangeStore>(changes: &C, args: &str) -> Result<Vec<u8>, String> {
+  let hash = parse_hash(args)?;
+  let change = changes.get_change(&hash).map_err(|e| e.to_string())?;
+  let mut bytes = Vec::new();
+  change.serialize(&mut bytes).map_err(|e| e.to_string())?;
+  let mut reply = format!("{}\n", bytes.len()).into_bytes();
+  reply.extend(bytes);
+  Ok(reply)
+}
+
+fn serve_put<C: ChangeStore>(changes: &C, bytes: &[u8]) -> Result<Vec<u8>, String> {
+  let change = Change::deserialize(bytes, None).map_err(|e| e.to_string())?;
+  let hash = change.hash().map_err(|e| e.to_string())?;
+  change.check_signatures(&hash).map_err(|e| e.to_string())?;
+  changes.save_change(&change).map_err(|e| e.to_string())?;
+  Ok(format!("ok {}\n", hash.to_base32()).into_bytes())
+}
+
+fn serve_apply<C: ChangeStore>(encyc: &Encyc, changes: &C, args: &str) -> Result<Vec<u8>, String> {
+  let Some((hash, name)) = args.split_once(' ') else {
+    return Err(format!("Malformed apply request: {}", args));
+  };
+  let hash = parse_hash(hash)?;
+  let mut txn = encyc.mut_txn_begin().map_err(|e| e.to_string())?;
+  let space = txn.open_or_create_space(name).map_err(|e| e.to_string())?;
+  apply_change(changes, &mut txn, &space, &hash).map_err(|e| e.to_string())?;
+  let state = txn.current_state(&space).map_err(|e| e.to_string())?;
+  txn.commit().map_err(|e| e.to_string())?;
+  Ok(format!("ok {}\n", state.to_base32()).into_bytes())
+}
+
+/// The client side of the protocol. It is also the change store of the
+/// remote pristine, so that pulling is a `sync::transfer` from it.
+pub struct Client<R, W> {
+  stream: Mutex<(R, W)>,
+}
+
+impl<R: BufRead, W: Write> Client<R, W> {
+  /// Greet the server on `r` and `w`, checking that it speaks our version.
+  pub fn connect(mut r: R, mut w: W) -> Result<Self, ProtocolError> {
+    writeln!(w, "azoni {}", PROTOCOL_VERSION)?;
+    w.flush()?;
+    let reply = read_line(&mut r)?.ok_or(ProtocolError::Closed)?;
+    if reply != format!("azoni {}", PROTOCOL_VERSION) {
+      return Err(error_reply(reply));
+    }
+    Ok(Client {
+      stream: Mutex::new((r, w)),
+    })
+  }
+
+  /// Send `request`, followed by `payload`, and read the first line of
+  /// the reply.
+  fn request(&self, request: &str, payload: &[u8], reply: impl FnOnce(String, &mut R) -> Result<(), ProtocolError>) -> Result<(), ProtocolError> {
+    let mut stream = self.stream.lock();
+    let (ref mut r, ref mut w) = *stream;
+    writeln!(w, "{}", request)?;
+    w.write_all(payload)?;
+    w.flush()?;
+    let line = read_line(r)?.ok_or(ProtocolError::Closed)?;
+    if line.starts_with("error ") {
+      return Err(error_reply(line));
+    }
+    reply(line, r)
+  }
+
+  /// The log of remote space `space`, from position `from` on. It is
+  /// empty if there is no such space.
+  pub fn log(&self, space: &str, from: u64) -> Result<Vec<LogEntry>, ProtocolError> {
+    let mut log = Vec::new();
+    self.request(
+      &format!("log {} {}", from, space),
+      b"",
+      |mut line, r| loop {
+        if line == "." {
+          return Ok(());
+        }
+        let entry = line
+          .split_once(' ')
+          .and_then(|(_, e)| LogEntry::from_line(e));
+        let Some(entry) = entry else {
+          return Err(ProtocolError::Malformed(line));
+        };
+        log.push(entry);
+        line = read_line(r)?.ok_or(ProtocolError::Closed)?;
+      },
+    )?;
+    Ok(log)
+  }
+
+  /// Apply change `hash`, already stored on the remote, to remote space
+  /// `space`. Returns the new state of the space.
+  pub fn apply(&self, space: &str, hash: &Hash) -> Result<Merkle, ProtocolError> {
+    let mut state = None;
+    self.request(
+      &format!("apply {} {}", hash.to_base32(), space),
+      b"",
+      |line, _| {
+        state = line
+          .strip_prefix("ok ")
+          .and_then(|s| Merkle::from_base32(s.as_bytes()));
+        state.map(|_| ()).ok_or(ProtocolError::Malformed(line))
+      },
+    )?;
+    Ok(state.unwrap_or_else(Merkle::zero))
+  }
+
+  /// Close the connection.
+  pub fn quit(self) -> Result<(), ProtocolError> {
+    let (_, mut w) = self.stream.into_inner();
+    writeln!(w, "quit")?;
+    w.flush()?;
+    Ok(())
+  }
+}
+
+fn error_reply(line: String) -> ProtocolError {
+  match line.strip_prefix("error ") {
+    Some(e) => ProtocolError::Remote(e.to_string()),
+    None => ProtocolError::Malformed(line),
+  }
+}
+
+impl<R: BufRead, W: Write> ChangeStore for Client<R, W> {
+  type Error = ProtocolError;
+
+  fn has_change(&self, hash: &Hash) -> bool {
+    let mut has = false;
+    let r = self.request(&format!("has {}", hash.to_base32()), b"", |line, _| {
+      has = line == "yes";
+      Ok(())
+    });
+    r.is_ok() && has
+  }
+
+  fn get_change(&self, hash: &Hash) -> Result<Change, Self::Error> {
+    let mut bytes = Vec::new();
+    self.request(&format!("get {}", hash.to_base32()), b"", |line, r| {
+      bytes = read_payload(r, &line)?;
+      Ok(())
+    })?;
+    Ok(Change::deserialize(&bytes, Some(hash))?)
+  }
+
+  fn save_change(&self, change: &Change) -> Result<Hash, Self::Error> {
+    let mut bytes = Vec::new();
+    let hash = change.serialize(&mut bytes)?;
+    self.request(
+      &format!("put {}", bytes.len()),
+      &bytes,
+      |line, _| match line.strip_prefix("ok ") {
+        Some(h) if h == hash.to_base32() => Ok(()),
+        _ => Err(ProtocolError::Malformed(line)),
+      },
+    )?;
+    Ok(hash)
+  }
+
+  fn del_change(&self, _: &Hash) -> Result<bool, Self::Error> {
+    Err(ProtocolError::Unsupported)
+  }
+}
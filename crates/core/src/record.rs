@@ -0,0 +1,174 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use crate::{
+  apply::{apply_local_change, ApplyError},
+  change::{Change, Operation},
+  changestore::ChangeStore,
+  history::{closed_period, ClosedPeriod},
+  key::SecretKey,
+  models::{
+    space::{SpaceRef, TagPolicy},
+    ChangeHeader,
+  },
+  traits::{MutTxnT, TxnT},
+  types::{ChangeId, Hash, UId},
+};
+
+#[derive(Debug, Clone)]
+pub struct Recorded {
+  pub hash: Hash,
+  pub id: ChangeId,
+  /// Set if the change alters a period closed by a tag, and the policy of
+  /// the space is to warn about it.
+  pub closed: Option<ClosedPeriod>,
+}
+
+/// Make a change out of `operations`, apply it to `space` and save it to
+/// `changes`. Nothing is saved if the change cannot be applied, or if it
+/// alters a period closed by a tag and the policy of `space` is to reject
+/// such changes. The change is signed with `key`.
+///
+/// The change depends on the changes that created the entries it edits,
+/// deletes or labels, the compartments its postings touch, and the
+/// compartments, vaults and labels its other operations refer to, parents
+/// included. Editing or deleting an entry in conflict resolves the
+/// conflict: the change also depends on all the competing changes, which
+/// were seen here.
+pub fn record<T: MutTxnT, C: ChangeStore>(
+  changes: &C,
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  header: ChangeHeader,
+  operations: Vec<Operation>,
+  key: &SecretKey,
+) -> Result<Recorded, ApplyError<C::Error, T::GraphError>> {
+  let closed = closed_period(txn, space, &operations).map_err(ApplyError::Txn)?;
+  if let Some(closed) = closed.clone() {
+    if txn.tag_policy(space) == TagPolicy::Reject {
+      return Err(ApplyError::ClosedPeriod(closed));
+    }
+  }
+  let dependencies = dependencies(txn, space, &operations).map_err(ApplyError::Txn)?;
+  let mut change = Change::new(header, dependencies, operations);
+  let hash = change.hash()?;
+  change.sign(key, &hash);
+  let id = apply_local_change(txn, space, &change, &hash)?;
+  changes
+    .save_change(&change)
+    .map_err(ApplyError::Changestore)?;
+  Ok(Recorded { hash, id, closed })
+}
+
+/// The dependencies of a change made of `operations`, see `record`.
+fn dependencies<T: MutTxnT>(txn: &T, space: &SpaceRef<T>, operations: &[Operation]) -> Result<Vec<Hash>, T::GraphError> {
+  let mut dependencies = Vec::new();
+  let mut push = |hash: Hash| {
+    if !dependencies.contains(&hash) {
+      dependencies.push(hash)
+    }
+  };
+  for op in operations.iter() {
+    if let Operation::EditEntry { entry, .. } | Operation::DelEntry { entry, .. } = op {
+      push(*entry);
+      if let Some(target) = txn.get_internal(entry)? {
+        for c in txn.entry_conflicts(space, &target)? {
+          if let Some(hash) = txn.get_external(&c)? {
+            push(hash)
+          }
+        }
+      }
+    }
+    if let Operation::AttachLabel { entry, .. } | Operation::DetachLabel { entry, .. } = op {
+      push(*entry)
+    }
+    let mut objects = Vec::new();
+    match op {
+      Operation::AddEntry { entry } | Operation::EditEntry { new: entry, .. } => objects.extend(entry.postings.iter().map(|p| p.compartment)),
+      Operation::RenameCompartment { id, .. }
+      | Operation::CloseCompartment { id }
+      | Operation::ReopenCompartment { id }
+      | Operation::SetCompartmentKind { id, .. }
+      | Operation::RenameLabel { id, .. }
+      | Operation::DelLabel { id, .. }
+      | Operation::AttachLabel { label: id, .. }
+      | Operation::DetachLabel { label: id, .. } => objects.push(*id),
+      Operation::MoveCompartment { id, new, .. }
+      | Operation::SetCompartmentParent { id, new, .. }
+      | Operation::MoveLabel { id, new, .. }
+      | Operation::SetLabelParent { id, new, .. } => objects.extend(std::iter::once(*id).chain(*new)),
+      Operation::SetBudget { id, new, .. } => {
+        objects.push(*id);
+        if let Some(budget) = new {
+          objects.extend(budget.labels.iter().chain(budget.compartments.iter()))
+        }
+      }
+      _ => {}
+    }
+    // Objects added by these same operations have no creator yet.
+    for object in objects.iter() {
+      if let Some(creator) = txn.creator(object)? {
+        if let Some(hash) = txn.get_external(&creator)? {
+          push(hash)
+        }
+      }
+    }
+  }
+  Ok(dependencies)
+}
+
+/// The operations merging label `from` into label `into` in `space`, to be
+/// recorded as one change: every entry labelled `from` gets labelled
+/// `into`, the children of `from` move under `into`, and `from` is
+/// deleted. `into` must not be a descendant of `from`.
+pub fn merge_labels<T: TxnT>(txn: &T, space: &SpaceRef<T>, from: &UId, into: &UId) -> Result<Vec<Operation>, T::GraphError> {
+  let mut operations = Vec::new();
+  let Some(label) = txn.get_label(from)? else {
+    return Ok(operations);
+  };
+  for child in txn.label_children(from)? {
+    if let Some(c) = txn.get_label(&child)? {
+      operations.push(Operation::SetLabelParent {
+        id: child,
+        old: Some(*from),
+        new: Some(*into),
+        group: c.group,
+      })
+    }
+  }
+  if let Some(parent) = label.parent() {
+    operations.push(Operation::SetLabelParent {
+      id: *from,
+      old: Some(parent),
+      new: None,
+      group: label.group,
+    })
+  }
+  for id in txn.label_entries(from)? {
+    if !txn.has_entry(space, &id)? {
+      continue;
+    }
+    let Some(entry) = txn.get_external(&id)? else {
+      continue;
+    };
+    for _ in 0..txn.attachments(&id, from)? {
+      operations.push(Operation::DetachLabel {
+        entry,
+        label: *from,
+      })
+    }
+    if txn.attachments(&id, into)? == 0 {
+      operations.push(Operation::AttachLabel {
+        entry,
+        label: *into,
+      })
+    }
+  }
+  operations.push(Operation::DelLabel {
+    id: *from,
+    name: label.name.as_str().to_string(),
+    group: label.group,
+    vault: label.vault(),
+  });
+  Ok(operations)
+}
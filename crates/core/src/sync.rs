@@ -0,0 +1,112 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+//! Exchanging changes between two copies of a space.
+//!
+//! Each side describes its space by its log: the hash of every change it
+//! applied, in order, with the Merkle state reached after it. Since states
+//! do not depend on the order of changes, two logs with the same state at
+//! some position hold the same changes up to there, and only what comes
+//! after needs comparing.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use thiserror_impl::Error;
+
+use crate::{
+  apply::{apply_change, ApplyError},
+  changestore::ChangeStore,
+  history::ClosedPeriod,
+  models::{graph::GraphTxnT, space::SpaceRef},
+  traits::{MutTxnT, TxnT},
+  types::{Base32, Hash, Merkle},
+};
+
+#[derive(Debug, Error)]
+pub enum SyncError<S: std::error::Error + 'static, C: std::error::Error + 'static, T: std::error::Error + 'static> {
+  #[error("Source changestore error: {0}")]
+  Source(S),
+  #[error(transparent)]
+  Apply(ApplyError<C, T>),
+}
+
+/// Error of `transfer` from store `S` to store `C` and transaction `T`.
+pub type TransferError<S, C, T> = SyncError<<S as ChangeStore>::Error, <C as ChangeStore>::Error, <T as GraphTxnT>::GraphError>;
+
+/// A position in the log of a space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+  pub hash: Hash,
+  /// State of the space after applying `hash`.
+  pub state: Merkle,
+}
+
+impl LogEntry {
+  /// `<hash> <state>`, as sent by the sync protocols.
+  pub fn to_line(&self) -> String {
+    format!("{} {}", self.hash.to_base32(), self.state.to_base32())
+  }
+
+  pub fn from_line(line: &str) -> Option<Self> {
+    let (hash, state) = line.split_once(' ')?;
+    Some(LogEntry {
+      hash: Hash::from_base32(hash.as_bytes())?,
+      state: Merkle::from_base32(state.as_bytes())?,
+    })
+  }
+}
+
+/// The log of `space`, oldest first.
+pub fn space_log<T: TxnT>(txn: &T, space: &SpaceRef<T>) -> Result<Vec<LogEntry>, T::GraphError> {
+  let mut log = Vec::new();
+  for (_, id, state) in txn.log(space, 0)? {
+    if let Some(hash) = txn.get_external(&id)? {
+      log.push(LogEntry { hash, state })
+    }
+  }
+  Ok(log)
+}
+
+/// Changes of `theirs` missing from `ours`, in the order of `theirs`.
+pub fn missing(ours: &[LogEntry], theirs: &[LogEntry]) -> Vec<Hash> {
+  let common = ours
+    .iter()
+    .zip(theirs.iter())
+    .take_while(|(a, b)| a.state == b.state)
+    .count();
+  let known: HashSet<Hash> = ours[common..].iter().map(|e| e.hash).collect();
+  theirs[common..]
+    .iter()
+    .filter(|e| !known.contains(&e.hash))
+    .map(|e| e.hash)
+    .collect()
+}
+
+/// Copy the changes `hashes` from store `from` to store `to`, and apply
+/// them to `space`, in order. On error, the transaction must be dropped
+/// rather than committed. Returns the period closed by a tag that the
+/// changes alter, if the policy of `space` is to warn about it.
+pub fn transfer<S: ChangeStore, C: ChangeStore, T: MutTxnT>(
+  from: &S,
+  to: &C,
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  hashes: &[Hash],
+) -> Result<Option<ClosedPeriod>, TransferError<S, C, T>> {
+  let mut closed = None;
+  for hash in hashes.iter() {
+    if !to.has_change(hash) {
+      let change = from.get_change(hash).map_err(SyncError::Source)?;
+      // Don't leave an invalid change behind in `to` if we fail.
+      change
+        .check_signatures(hash)
+        .map_err(|e| SyncError::Apply(e.into()))?;
+      to.save_change(&change)
+        .map_err(|e| SyncError::Apply(ApplyError::Changestore(e)))?;
+    }
+    let applied = apply_change(to, txn, space, hash).map_err(SyncError::Apply)?;
+    closed = closed.or(applied.closed);
+  }
+  Ok(closed)
+}
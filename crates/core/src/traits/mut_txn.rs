@@ -1,11 +1,31 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 15.
 
-use crate::models::space::SpaceRef;
+use crate::models::{
+  compartment::CompartmentMutTxnT,
+  conflict::ConflictMutTxnT,
+  device::DeviceMutTxnT,
+  entry::EntryMutTxnT,
+  filter::FilterMutTxnT,
+  graph::GraphMutTxnT,
+  label::LabelMutTxnT,
+  space::{SpaceMutTxnT, SpaceRef},
+  vault::VaultMutTxnT,
+};
+
+use crate::types::{ChangeId, Hash};
 
 use super::*;
 
-pub trait MutTxnT: TxnT {
+pub trait MutTxnT:
+  TxnT + SpaceMutTxnT + CompartmentMutTxnT + EntryMutTxnT + VaultMutTxnT + LabelMutTxnT + FilterMutTxnT + DeviceMutTxnT + ConflictMutTxnT + GraphMutTxnT
+{
   fn commit(self) -> Result<(), Self::GraphError>;
   fn open_or_create_space(&mut self, name: &str) -> Result<SpaceRef<Self>, Self::GraphError>;
+  fn set_current_space(&mut self, name: &str) -> Result<(), Self::GraphError>;
+  /// Assign a local id to the change with hash `hash`, unless it already
+  /// has one.
+  fn register_change(&mut self, hash: &Hash) -> Result<ChangeId, Self::GraphError>;
+  /// Forget about space `name`. Returns `false` if there was no such space.
+  fn drop_space(&mut self, name: &str) -> Result<bool, Self::GraphError>;
 }
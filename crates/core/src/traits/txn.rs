@@ -1,6 +1,29 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 15.
 
-use crate::models::{graph::GraphTxnT, space::SpaceTxnT, vault::VaultTxnT};
+use crate::{
+  models::{
+    compartment::CompartmentTxnT,
+    conflict::ConflictTxnT,
+    device::DeviceTxnT,
+    entry::EntryTxnT,
+    filter::FilterTxnT,
+    graph::GraphTxnT,
+    label::LabelTxnT,
+    space::{SpaceRef, SpaceTxnT},
+    vault::VaultTxnT,
+  },
+  types::{ChangeId, Hash},
+};
 
-pub trait TxnT: GraphTxnT + VaultTxnT + SpaceTxnT {}
+pub trait TxnT: GraphTxnT + VaultTxnT + SpaceTxnT + CompartmentTxnT + EntryTxnT + LabelTxnT + FilterTxnT + DeviceTxnT + ConflictTxnT {
+  fn load_space(&self, name: &str) -> Result<Option<SpaceRef<Self>>, Self::GraphError>;
+  fn current_space(&self) -> Option<&str>;
+  fn space_names(&self) -> Result<Vec<String>, Self::GraphError>;
+
+  /// Local id of the change with hash `hash`, if it was ever applied here.
+  fn get_internal(&self, hash: &Hash) -> Result<Option<ChangeId>, Self::GraphError>;
+  fn get_external(&self, id: &ChangeId) -> Result<Option<Hash>, Self::GraphError>;
+  /// All the changes known to this pristine, by local id.
+  fn list_changes(&self) -> Result<Vec<(ChangeId, Hash)>, Self::GraphError>;
+}
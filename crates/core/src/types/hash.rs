@@ -36,14 +36,14 @@ impl Hash {
       Hash::Blake3(ref hash) => {
         let mut out = [0; 1 + BLAKE3_BYTES];
         out[0] = HashAlgorithm::Blake3 as u8;
-        (&mut out[1..]).clone_from_slice(hash);
+        out[1..].clone_from_slice(hash);
         out
       }
     }
   }
 
   pub fn from_bytes(s: &[u8]) -> Option<Self> {
-    if s.len() >= 1 + BLAKE3_BYTES && s[0] == HashAlgorithm::Blake3 as u8 {
+    if s.len() > BLAKE3_BYTES && s[0] == HashAlgorithm::Blake3 as u8 {
       let mut out = [0; BLAKE3_BYTES];
       out.clone_from_slice(&s[1..]);
       Some(Hash::Blake3(out))
@@ -57,7 +57,7 @@ impl Hash {
     if s.len() > BASE32_BYTES {
       return None;
     }
-    (&mut b32[..s.len()]).clone_from_slice(s.as_bytes());
+    b32[..s.len()].clone_from_slice(s.as_bytes());
     let bytes = if let Ok(bytes) = BASE32.decode(&b32) {
       bytes
     } else {
@@ -76,7 +76,7 @@ impl Base32 for Hash {
       Hash::Blake3(ref hash) => {
         let mut b3 = [0; 1 + BLAKE3_BYTES];
         b3[BLAKE3_BYTES] = HashAlgorithm::Blake3 as u8;
-        (&mut b3[..BLAKE3_BYTES]).clone_from_slice(hash);
+        b3[..BLAKE3_BYTES].clone_from_slice(hash);
         BASE32.encode(&b3)
       }
     }
@@ -177,7 +177,7 @@ pub(crate) const HASH_NONE: SerializedHash = SerializedHash {
 
 impl PartialOrd for SerializedHash {
   fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-    Some(self.t.cmp(&other.t))
+    Some(self.cmp(other))
   }
 }
 
@@ -217,7 +217,7 @@ impl std::fmt::Debug for SerializedHash {
 impl<'a> From<&'a SerializedHash> for Hash {
   fn from(value: &'a SerializedHash) -> Self {
     if value.t == HashAlgorithm::Blake3 as u8 {
-      Hash::Blake3(unsafe { value.h.blake3.clone() })
+      Hash::Blake3(unsafe { value.h.blake3 })
     } else if value.t == HashAlgorithm::None as u8 {
       Hash::None
     } else {
@@ -237,9 +237,7 @@ impl<'a> From<&'a Hash> for SerializedHash {
     match value {
       Hash::Blake3(value) => SerializedHash {
         t: HashAlgorithm::Blake3 as u8,
-        h: H {
-          blake3: value.clone(),
-        },
+        h: H { blake3: *value },
       },
       Hash::None => SerializedHash {
         t: 0,
@@ -266,6 +264,9 @@ impl SerializedHash {
     }
   }
 
+  /// # Safety
+  ///
+  /// `b` must point to the first byte of a serialized hash.
   pub unsafe fn size_from_ptr(b: *const u8) -> usize {
     if *b == HashAlgorithm::Blake3 as u8 {
       1 + BLAKE3_BYTES
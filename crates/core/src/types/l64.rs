@@ -54,7 +54,7 @@ impl Ord for L64 {
 
 impl PartialOrd for L64 {
   fn partial_cmp(&self, x: &Self) -> Option<std::cmp::Ordering> {
-    Some(u64::from_le(self.0).cmp(&u64::from_le(x.0)))
+    Some(self.cmp(x))
   }
 }
 
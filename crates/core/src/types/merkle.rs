@@ -53,7 +53,7 @@ impl Merkle {
       return None;
     }
 
-    (&mut b32[..s.len()]).clone_from_slice(s.as_bytes());
+    b32[..s.len()].clone_from_slice(s.as_bytes());
     let bytes = if let Ok(bytes) = BASE32.decode(&b32) {
       bytes
     } else {
@@ -72,7 +72,7 @@ impl Base32 for Merkle {
     match *self {
       Merkle::Ed25519(ref s) => {
         let mut b32 = [0; 33];
-        (&mut b32[..32]).clone_from_slice(s.compress().as_bytes());
+        b32[..32].clone_from_slice(s.compress().as_bytes());
         b32[32] = MerkleAlgorithm::Ed25519 as u8;
         BASE32.encode(&b32)
       }
@@ -172,7 +172,7 @@ impl<'a> From<&'a Merkle> for SerializedMerkle {
         mm[0] = MerkleAlgorithm::Ed25519 as u8;
         let x = x.compress();
         let x = x.as_bytes();
-        (&mut mm[1..]).clone_from_slice(x);
+        mm[1..].clone_from_slice(x);
         SerializedMerkle(mm)
       }
     }
@@ -187,7 +187,7 @@ impl From<Merkle> for SerializedMerkle {
         mm[0] = MerkleAlgorithm::Ed25519 as u8;
         let q = q.compress();
         let q = q.as_bytes();
-        (&mut mm[1..]).copy_from_slice(q);
+        mm[1..].copy_from_slice(q);
         SerializedMerkle(mm)
       }
     }
@@ -10,12 +10,14 @@ mod uid;
 pub use uid::*;
 mod strings;
 pub use strings::*;
-mod hash;
+pub mod hash;
 pub use hash::*;
 mod merkle;
 pub use merkle::*;
 mod change_id;
 pub use change_id::*;
+mod money;
+pub use money::*;
 
 use lazy_static::lazy_static;
 
@@ -34,6 +36,7 @@ pub type HashMap<K, V> = std::collections::HashMap<K, V, Hasher>;
 pub type HashSet<K> = std::collections::HashSet<K, Hasher>;
 
 #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
+#[repr(C)]
 pub struct Pair<A, B> {
   pub a: A,
   pub b: B,
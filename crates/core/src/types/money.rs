@@ -0,0 +1,513 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror_impl::Error;
+
+use super::L64;
+
+pub const CURRENCY_LEN: usize = 16;
+
+/// Number of decimals of the ISO 4217 currencies that don't use 2.
+const ISO_4217_EXPONENTS: &[(&str, u32)] = &[
+  ("BIF", 0),
+  ("CLP", 0),
+  ("DJF", 0),
+  ("GNF", 0),
+  ("ISK", 0),
+  ("JPY", 0),
+  ("KMF", 0),
+  ("KRW", 0),
+  ("PYG", 0),
+  ("RWF", 0),
+  ("UGX", 0),
+  ("UYI", 0),
+  ("VND", 0),
+  ("VUV", 0),
+  ("XAF", 0),
+  ("XOF", 0),
+  ("XPF", 0),
+  ("XXX", 0),
+  ("BHD", 3),
+  ("IQD", 3),
+  ("JOD", 3),
+  ("KWD", 3),
+  ("LYD", 3),
+  ("OMR", 3),
+  ("TND", 3),
+  ("CLF", 4),
+  ("UYW", 4),
+];
+
+/// Number of decimals of user-defined commodities.
+pub const COMMODITY_EXPONENT: u32 = 2;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MoneyError {
+  #[error("Invalid currency code: {0:?}")]
+  Currency(String),
+  #[error("Invalid amount: {0:?}")]
+  Amount(String),
+  #[error("{0:?} has at most {1} decimals")]
+  Precision(String, u32),
+  #[error("Cannot combine amounts in {0} and {1}")]
+  CurrencyMismatch(Currency, Currency),
+  #[error("Amount overflow")]
+  Overflow,
+}
+
+/// A currency: either an ISO 4217 code (three uppercase letters), or a
+/// user-defined commodity of at most 16 letters, digits, `_`, `-` or `.`.
+/// NUL-padded so that it can be stored as is.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(C)]
+pub struct Currency(pub(crate) [u8; CURRENCY_LEN]);
+
+impl Currency {
+  /// The ISO 4217 code for "no currency".
+  pub const NONE: Self = Currency(*b"XXX\0\0\0\0\0\0\0\0\0\0\0\0\0");
+
+  pub fn new(code: &str) -> Result<Self, MoneyError> {
+    let valid = !code.is_empty()
+      && code.len() <= CURRENCY_LEN
+      && code
+        .bytes()
+        .all(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-' || c == b'.')
+      && code.bytes().any(|c| c.is_ascii_alphabetic());
+    if !valid {
+      return Err(MoneyError::Currency(code.to_string()));
+    }
+    let mut c = [0; CURRENCY_LEN];
+    c[..code.len()].copy_from_slice(code.as_bytes());
+    Ok(Currency(c))
+  }
+
+  pub fn as_str(&self) -> &str {
+    let len = self.0.iter().position(|&c| c == 0).unwrap_or(CURRENCY_LEN);
+    std::str::from_utf8(&self.0[..len]).unwrap_or("")
+  }
+
+  pub fn is_iso(&self) -> bool {
+    let s = self.as_str();
+    s.len() == 3 && s.bytes().all(|c| c.is_ascii_uppercase())
+  }
+
+  /// Number of decimals, i.e. minor units per major unit are
+  /// `10^exponent`.
+  pub fn exponent(&self) -> u32 {
+    if !self.is_iso() {
+      return COMMODITY_EXPONENT;
+    }
+    let s = self.as_str();
+    ISO_4217_EXPONENTS
+      .iter()
+      .find(|(code, _)| *code == s)
+      .map(|(_, e)| *e)
+      .unwrap_or(2)
+  }
+}
+
+impl fmt::Display for Currency {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+impl fmt::Debug for Currency {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:?}", self.as_str())
+  }
+}
+
+impl FromStr for Currency {
+  type Err = MoneyError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Currency::new(s)
+  }
+}
+
+/// A signed amount of minor units (cents for EUR, yen for JPY…) of a
+/// currency.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+  pub amount: i64,
+  pub currency: Currency,
+}
+
+impl Money {
+  pub fn new(amount: i64, currency: Currency) -> Self {
+    Money { amount, currency }
+  }
+
+  pub fn zero(currency: Currency) -> Self {
+    Money {
+      amount: 0,
+      currency,
+    }
+  }
+
+  pub fn is_zero(&self) -> bool {
+    self.amount == 0
+  }
+
+  pub fn is_negative(&self) -> bool {
+    self.amount < 0
+  }
+
+  pub fn checked_add(&self, other: &Money) -> Result<Money, MoneyError> {
+    if self.currency != other.currency {
+      return Err(MoneyError::CurrencyMismatch(self.currency, other.currency));
+    }
+    let amount = self
+      .amount
+      .checked_add(other.amount)
+      .ok_or(MoneyError::Overflow)?;
+    Ok(Money::new(amount, self.currency))
+  }
+
+  pub fn checked_sub(&self, other: &Money) -> Result<Money, MoneyError> {
+    self.checked_add(&other.checked_neg()?)
+  }
+
+  pub fn checked_neg(&self) -> Result<Money, MoneyError> {
+    let amount = self.amount.checked_neg().ok_or(MoneyError::Overflow)?;
+    Ok(Money::new(amount, self.currency))
+  }
+
+  fn parse_amount(s: &str, currency: Currency) -> Result<i64, MoneyError> {
+    let err = || MoneyError::Amount(s.to_string());
+    let (negative, digits) = match s.as_bytes().first() {
+      Some(b'-') => (true, &s[1..]),
+      Some(b'+') => (false, &s[1..]),
+      _ => (false, s),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+
+    // Thousands separators are optional, but must be in the right places.
+    let mut groups = int.split(',');
+    let first = groups.next().unwrap_or("");
+    if first.is_empty() || first.len() > 3 && int.contains(',') {
+      return Err(err());
+    }
+    let mut int_digits = first.to_string();
+    for g in groups {
+      if g.len() != 3 {
+        return Err(err());
+      }
+      int_digits.push_str(g);
+    }
+    if !int_digits.bytes().all(|c| c.is_ascii_digit()) || !frac.bytes().all(|c| c.is_ascii_digit()) {
+      return Err(err());
+    }
+
+    let exponent = currency.exponent();
+    if frac.len() > exponent as usize {
+      return Err(MoneyError::Precision(s.to_string(), exponent));
+    }
+    // Accumulate towards the sign, so that `i64::MIN` can be parsed.
+    let mut minor: i64 = 0;
+    for c in int_digits
+      .bytes()
+      .chain(frac.bytes())
+      .chain(std::iter::repeat(b'0').take(exponent as usize - frac.len()))
+    {
+      let digit = (c - b'0') as i64;
+      minor = minor
+        .checked_mul(10)
+        .and_then(|m| {
+          if negative {
+            m.checked_sub(digit)
+          } else {
+            m.checked_add(digit)
+          }
+        })
+        .ok_or(MoneyError::Overflow)?;
+    }
+    Ok(minor)
+  }
+}
+
+impl fmt::Display for Money {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let exponent = self.currency.exponent();
+    let scale = 10u64.pow(exponent);
+    let abs = self.amount.unsigned_abs();
+    let int = (abs / scale).to_string();
+
+    let mut s = String::new();
+    if self.amount < 0 {
+      s.push('-');
+    }
+    for (i, c) in int.chars().enumerate() {
+      if i > 0 && (int.len() - i) % 3 == 0 {
+        s.push(',');
+      }
+      s.push(c);
+    }
+    if exponent > 0 {
+      s.push_str(&format!(
+        ".{:0width$}",
+        abs % scale,
+        width = exponent as usize
+      ));
+    }
+    write!(f, "{} {}", s, self.currency)
+  }
+}
+
+impl fmt::Debug for Money {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self)
+  }
+}
+
+/// Parse `"1,234.56 EUR"` or `"EUR 1,234.56"`.
+impl FromStr for Money {
+  type Err = MoneyError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut words = s.split_whitespace();
+    let (Some(a), Some(b), None) = (words.next(), words.next(), words.next()) else {
+      return Err(MoneyError::Amount(s.to_string()));
+    };
+    let starts_numeric = |w: &str| w.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+');
+    let (amount, code) = if starts_numeric(a) { (a, b) } else { (b, a) };
+    let currency = Currency::new(code)?;
+    Ok(Money::new(Money::parse_amount(amount, currency)?, currency))
+  }
+}
+
+impl Serialize for Money {
+  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(self)
+  }
+}
+
+impl<'de> Deserialize<'de> for Money {
+  fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    let s = String::deserialize(d)?;
+    s.parse().map_err(de::Error::custom)
+  }
+}
+
+impl Serialize for Currency {
+  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(self.as_str())
+  }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+  fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    let s = String::deserialize(d)?;
+    s.parse().map_err(de::Error::custom)
+  }
+}
+
+/// On-disk form of `Money`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(C)]
+pub struct SerializedMoney {
+  pub amount: L64,
+  pub currency: Currency,
+}
+
+impl From<Money> for SerializedMoney {
+  fn from(m: Money) -> Self {
+    SerializedMoney {
+      amount: (m.amount as u64).into(),
+      currency: m.currency,
+    }
+  }
+}
+
+impl<'a> From<&'a SerializedMoney> for Money {
+  fn from(m: &'a SerializedMoney) -> Self {
+    Money::new(m.amount.as_u64() as i64, m.currency)
+  }
+}
+
+impl From<SerializedMoney> for Money {
+  fn from(m: SerializedMoney) -> Self {
+    (&m).into()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn currency(code: &str) -> Currency {
+    Currency::new(code).unwrap()
+  }
+
+  fn parse(s: &str, code: &str) -> Result<i64, MoneyError> {
+    Money::parse_amount(s, currency(code))
+  }
+
+  #[test]
+  fn parse_separators() {
+    assert_eq!(parse("1234.56", "USD"), Ok(123456));
+    assert_eq!(parse("1,234.56", "USD"), Ok(123456));
+    assert_eq!(parse("1,234,567", "USD"), Ok(123456700));
+    assert_eq!(parse("123", "USD"), Ok(12300));
+    assert_eq!(parse("0.5", "USD"), Ok(50));
+    assert_eq!(parse("12.", "USD"), Ok(1200));
+    for s in [
+      "1234,567", "1,23", "1,2345", ",123", "1,,234", "1,234,", "", ".5", "1.2.3", "1 234", "1e3", "--1", "+-1", "12a",
+    ] {
+      assert_eq!(
+        parse(s, "USD"),
+        Err(MoneyError::Amount(s.to_string())),
+        "{:?}",
+        s
+      );
+    }
+  }
+
+  #[test]
+  fn parse_decimals() {
+    assert_eq!(
+      parse("1.234", "USD"),
+      Err(MoneyError::Precision("1.234".to_string(), 2))
+    );
+    assert_eq!(parse("1,234", "JPY"), Ok(1234));
+    assert_eq!(
+      parse("1.5", "JPY"),
+      Err(MoneyError::Precision("1.5".to_string(), 0))
+    );
+    assert_eq!(parse("1.234", "BHD"), Ok(1234));
+    assert_eq!(parse("1.2", "BHD"), Ok(1200));
+    assert_eq!(
+      parse("1.2345", "BHD"),
+      Err(MoneyError::Precision("1.2345".to_string(), 3))
+    );
+    // Commodities have 2 decimals.
+    assert_eq!(parse("3.14", "gold.oz"), Ok(314));
+  }
+
+  #[test]
+  fn parse_signs() {
+    assert_eq!(parse("-1.50", "USD"), Ok(-150));
+    assert_eq!(parse("+1.50", "USD"), Ok(150));
+    assert_eq!(parse("-0", "USD"), Ok(0));
+    assert_eq!(parse("92,233,720,368,547,758.07", "USD"), Ok(i64::MAX));
+    assert_eq!(parse("-92,233,720,368,547,758.08", "USD"), Ok(i64::MIN));
+    assert_eq!(
+      parse("92,233,720,368,547,758.08", "USD"),
+      Err(MoneyError::Overflow)
+    );
+    assert_eq!(
+      parse("-92,233,720,368,547,758.09", "USD"),
+      Err(MoneyError::Overflow)
+    );
+  }
+
+  #[test]
+  fn parse_money() {
+    let usd = currency("USD");
+    assert_eq!("1,234.56 USD".parse(), Ok(Money::new(123456, usd)));
+    assert_eq!("USD -1,234.56".parse(), Ok(Money::new(-123456, usd)));
+    assert_eq!(
+      "1.00 usd".parse::<Money>(),
+      Ok(Money::new(100, currency("usd")))
+    );
+    assert_eq!(
+      "1.00".parse::<Money>(),
+      Err(MoneyError::Amount("1.00".to_string()))
+    );
+    assert_eq!(
+      "1.00 USD EUR".parse::<Money>(),
+      Err(MoneyError::Amount("1.00 USD EUR".to_string()))
+    );
+    assert_eq!(
+      "1.00 U$D".parse::<Money>(),
+      Err(MoneyError::Currency("U$D".to_string()))
+    );
+  }
+
+  #[test]
+  fn display_round_trips() {
+    for (amount, code, s) in [
+      (0, "USD", "0.00 USD"),
+      (5, "USD", "0.05 USD"),
+      (-5, "USD", "-0.05 USD"),
+      (123456, "USD", "1,234.56 USD"),
+      (-123456789, "EUR", "-1,234,567.89 EUR"),
+      (1234567, "JPY", "1,234,567 JPY"),
+      (-1234, "BHD", "-1.234 BHD"),
+      (42, "gold.oz", "0.42 gold.oz"),
+      (i64::MAX, "USD", "92,233,720,368,547,758.07 USD"),
+      (i64::MIN, "USD", "-92,233,720,368,547,758.08 USD"),
+      (i64::MIN, "JPY", "-9,223,372,036,854,775,808 JPY"),
+    ] {
+      let m = Money::new(amount, currency(code));
+      assert_eq!(m.to_string(), s);
+      assert_eq!(s.parse(), Ok(m));
+    }
+  }
+
+  #[test]
+  fn checked_arithmetic() {
+    let usd = |amount| Money::new(amount, currency("USD"));
+    assert_eq!(usd(150).checked_add(&usd(-200)), Ok(usd(-50)));
+    assert_eq!(usd(150).checked_sub(&usd(200)), Ok(usd(-50)));
+    assert_eq!(
+      usd(i64::MAX).checked_add(&usd(1)),
+      Err(MoneyError::Overflow)
+    );
+    assert_eq!(
+      usd(i64::MIN).checked_add(&usd(-1)),
+      Err(MoneyError::Overflow)
+    );
+    assert_eq!(usd(i64::MIN).checked_neg(), Err(MoneyError::Overflow));
+    assert_eq!(
+      usd(0).checked_sub(&usd(i64::MIN)),
+      Err(MoneyError::Overflow)
+    );
+    let eur = Money::new(100, currency("EUR"));
+    assert_eq!(
+      usd(100).checked_add(&eur),
+      Err(MoneyError::CurrencyMismatch(
+        currency("USD"),
+        currency("EUR")
+      ))
+    );
+    assert_eq!(
+      usd(i64::MAX).checked_add(&eur),
+      Err(MoneyError::CurrencyMismatch(
+        currency("USD"),
+        currency("EUR")
+      ))
+    );
+  }
+
+  #[test]
+  fn serde_string_form() {
+    let m = Money::new(-123456, currency("EUR"));
+    let bytes = bincode::serialize(&m).unwrap();
+    assert_eq!(bytes, bincode::serialize("-1,234.56 EUR").unwrap());
+    assert_eq!(bincode::deserialize::<Money>(&bytes).unwrap(), m);
+    let invalid = bincode::serialize("1.234 EUR").unwrap();
+    assert!(bincode::deserialize::<Money>(&invalid).is_err());
+
+    let c = currency("gold.oz");
+    let bytes = bincode::serialize(&c).unwrap();
+    assert_eq!(bytes, bincode::serialize("gold.oz").unwrap());
+    assert_eq!(bincode::deserialize::<Currency>(&bytes).unwrap(), c);
+  }
+
+  #[test]
+  fn serialized_money_round_trips() {
+    for amount in [0, 1, -1, 123456, i64::MAX, i64::MIN] {
+      let m = Money::new(amount, currency("BHD"));
+      let serialized = SerializedMoney::from(m);
+      assert_eq!(serialized.currency, m.currency);
+      assert_eq!(Money::from(&serialized), m);
+      assert_eq!(Money::from(serialized), m);
+    }
+    // The currency is NUL-padded, so that it can be stored as is.
+    let serialized = SerializedMoney::from(Money::zero(currency("USD")));
+    assert_eq!(&serialized.currency.0, b"USD\0\0\0\0\0\0\0\0\0\0\0\0\0");
+  }
+}
@@ -50,6 +50,7 @@ impl SmallString {
     self.len() == 0
   }
 
+  #[allow(clippy::should_implement_trait)]
   pub fn from_str(s: &str) -> Self {
     let mut b = SmallString {
       len: s.len() as u8,
@@ -60,7 +61,7 @@ impl SmallString {
   }
   pub fn clone_from_str(&mut self, s: &str) {
     self.len = s.len() as u8;
-    (&mut self.str[..s.len()]).copy_from_slice(s.as_bytes());
+    self.str[..s.len()].copy_from_slice(s.as_bytes());
   }
 
   /// ```ignore
@@ -75,7 +76,7 @@ impl SmallString {
   pub fn push_str(&mut self, s: &str) {
     let l = self.len as usize;
     assert!(l + s.len() <= 0xff);
-    (&mut self.str[l..l + s.len()]).copy_from_slice(s.as_bytes());
+    self.str[l..l + s.len()].copy_from_slice(s.as_bytes());
     self.len += s.len() as u8;
   }
 
@@ -100,7 +101,7 @@ impl Eq for SmallString {}
 
 impl PartialOrd for SmallString {
   fn partial_cmp(&self, x: &SmallString) -> Option<std::cmp::Ordering> {
-    self.as_str().partial_cmp(x.as_str())
+    Some(self.cmp(x))
   }
 }
 impl Ord for SmallString {
@@ -228,7 +229,7 @@ impl Eq for SmallStr {}
 
 impl PartialOrd for SmallStr {
   fn partial_cmp(&self, x: &SmallStr) -> Option<std::cmp::Ordering> {
-    self.as_str().partial_cmp(x.as_str())
+    Some(self.cmp(x))
   }
 }
 
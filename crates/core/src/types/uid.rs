@@ -4,9 +4,11 @@
 
 use std::fmt::{Display, Formatter};
 
+use serde::{Deserialize, Serialize};
+
 use super::BASE32;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct UId(pub [u8; 16]);
 
 impl UId {
@@ -54,6 +56,12 @@ impl UId {
   }
 }
 
+impl Default for UId {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl Display for UId {
   fn fmt(&self, fmt: &mut Formatter) -> std::fmt::Result {
     write!(fmt, "{}", BASE32.encode(&self.0))
@@ -0,0 +1,409 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use log::debug;
+use thiserror_impl::Error;
+
+use crate::{
+  change::{Change, Operation},
+  changestore::ChangeStore,
+  history::{closed_period, ClosedPeriod},
+  models::{
+    conflict::unmerge,
+    entry::EntryError,
+    entry::InvalidEntry,
+    space::{SpaceRef, TagPolicy},
+    ChangeHeader,
+  },
+  traits::MutTxnT,
+  types::{Base32, ChangeId, Hash, Merkle, UId},
+};
+
+#[derive(Debug, Error)]
+pub enum UnrecordError<C: std::error::Error + 'static, T: std::error::Error + 'static> {
+  #[error("Changestore error: {0}")]
+  Changestore(C),
+  #[error(transparent)]
+  Txn(T),
+  #[error(transparent)]
+  Entry(InvalidEntry),
+  #[error("Compartment {0} does not exist")]
+  UnknownCompartment(UId),
+  #[error("Change {} is not in this space", .0.to_base32())]
+  NotInSpace(Hash),
+  #[error("Change {} is needed by {}", hash.to_base32(), dependents.iter().map(|h| h.to_base32()).collect::<Vec<_>>().join(", "))]
+  Dependents { hash: Hash, dependents: Vec<Hash> },
+  #[error("State {0} is not in this space")]
+  StateNotFound(String),
+  #[error("Unrecording this change alters {0}")]
+  ClosedPeriod(ClosedPeriod),
+}
+
+#[derive(Debug, Clone)]
+pub struct Unrecorded {
+  /// The changes unrecorded, newest first.
+  pub hashes: Vec<Hash>,
+  /// Set if they altered a period closed by a tag, and the policy of the
+  /// space is to warn about it.
+  pub closed: Option<ClosedPeriod>,
+  /// Names of the tags deleted because they were past the new last
+  /// change.
+  pub tags: Vec<String>,
+}
+
+impl<C: std::error::Error + 'static, T: std::error::Error + 'static> From<EntryError<T>> for UnrecordError<C, T> {
+  fn from(e: EntryError<T>) -> Self {
+    match e {
+      EntryError::Txn(e) => UnrecordError::Txn(e),
+      EntryError::Invalid(e) => UnrecordError::Entry(e),
+      EntryError::UnknownCompartment(id) => UnrecordError::UnknownCompartment(id),
+    }
+  }
+}
+
+/// Hashes of the changes applied to `space` after `hash` that depend on it.
+pub fn dependents<T: MutTxnT, C: ChangeStore>(
+  changes: &C,
+  txn: &T,
+  space: &SpaceRef<T>,
+  hash: &Hash,
+) -> Result<Vec<Hash>, UnrecordError<C::Error, T::GraphError>> {
+  let id = internal(txn, space, hash)?;
+  let pos = txn
+    .get_changeset(space, &id)
+    .map_err(UnrecordError::Txn)?
+    .ok_or(UnrecordError::NotInSpace(*hash))?;
+  let change = load_change(changes, txn, hash, &id)?;
+  let graph = txn.dependents(&id).map_err(UnrecordError::Txn)?;
+  let mut dependents = Vec::new();
+  for (_, later_id, _) in txn.log(space, pos + 1).map_err(UnrecordError::Txn)? {
+    let Some(later) = txn.get_external(&later_id).map_err(UnrecordError::Txn)? else {
+      continue;
+    };
+    if graph.contains(&later_id) {
+      dependents.push(later);
+      continue;
+    }
+    // Changes recorded before the dependency graph may still depend on
+    // `hash` implicitly. Those without a file predate the change store, and
+    // can't depend on anything recorded since.
+    if !changes.has_change(&later) {
+      continue;
+    }
+    let c = changes
+      .get_change(&later)
+      .map_err(UnrecordError::Changestore)?;
+    if c.depends_on(&change, hash) {
+      dependents.push(later)
+    }
+  }
+  Ok(dependents)
+}
+
+/// Remove change `hash` from `space`, undoing its operations. Unless
+/// `force` is set, this fails if a later change of `space` depends on it.
+///
+/// Like recording, undoing a change that alters a period closed by a tag
+/// fails if the policy of `space` is to reject such changes. If it is to
+/// warn, the period is returned. Tags past the new last change of `space`
+/// are deleted, since the states they name are gone.
+pub fn unrecord<T: MutTxnT, C: ChangeStore>(
+  changes: &C,
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  hash: &Hash,
+  force: bool,
+) -> Result<Option<ClosedPeriod>, UnrecordError<C::Error, T::GraphError>> {
+  let closed = unrecord_(changes, txn, space, hash, force)?;
+  del_tags_past_head(txn, space).map_err(UnrecordError::Txn)?;
+  Ok(closed)
+}
+
+/// `unrecord`, keeping the tags: while resetting, the changes unrecorded
+/// after the first one must still be checked against them.
+fn unrecord_<T: MutTxnT, C: ChangeStore>(
+  changes: &C,
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  hash: &Hash,
+  force: bool,
+) -> Result<Option<ClosedPeriod>, UnrecordError<C::Error, T::GraphError>> {
+  debug!("unrecord {}", hash.to_base32());
+  let id = internal(txn, space, hash)?;
+  if !force {
+    let dependents = dependents(changes, txn, space, hash)?;
+    if !dependents.is_empty() {
+      return Err(UnrecordError::Dependents {
+        hash: *hash,
+        dependents,
+      });
+    }
+  }
+  let change = load_change(changes, txn, hash, &id)?;
+  let closed = closed_period(txn, space, &change.hashed.operations).map_err(UnrecordError::Txn)?;
+  if let Some(closed) = closed.clone() {
+    if txn.tag_policy(space) == TagPolicy::Reject {
+      return Err(UnrecordError::ClosedPeriod(closed));
+    }
+  }
+  undo(txn, space, &change, &id)?;
+  txn.del_changes(space, &id).map_err(UnrecordError::Txn)?;
+  Ok(closed)
+}
+
+/// Delete the tags of `space` covering positions past its last change,
+/// returning their names.
+fn del_tags_past_head<T: MutTxnT>(txn: &mut T, space: &SpaceRef<T>) -> Result<Vec<String>, T::GraphError> {
+  let head = txn
+    .log(space, 0)?
+    .last()
+    .map(|(pos, _, _)| pos + 1)
+    .unwrap_or(0);
+  let mut deleted = Vec::new();
+  for tag in txn.space_tags(space)? {
+    if tag.position > head {
+      txn.del_tag(space, &tag.name)?;
+      deleted.push(tag.name);
+    }
+  }
+  Ok(deleted)
+}
+
+/// Unrecord, newest first, all the changes of `space` applied after the
+/// one that led to `state`. `Merkle::zero()` resets `space` to its empty
+/// state. Closed periods are checked, and tags past `state` deleted, as
+/// by `unrecord`.
+pub fn reset_to_state<T: MutTxnT, C: ChangeStore>(
+  changes: &C,
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  state: &Merkle,
+) -> Result<Unrecorded, UnrecordError<C::Error, T::GraphError>> {
+  let from = if *state == Merkle::zero() {
+    0
+  } else {
+    let pos = txn
+      .state_position(space, state)
+      .map_err(UnrecordError::Txn)?
+      .ok_or_else(|| UnrecordError::StateNotFound(state.to_base32()))?;
+    pos + 1
+  };
+  let mut unrecorded = Unrecorded {
+    hashes: Vec::new(),
+    closed: None,
+    tags: Vec::new(),
+  };
+  for (_, id, _) in txn
+    .log(space, from)
+    .map_err(UnrecordError::Txn)?
+    .into_iter()
+    .rev()
+  {
+    let Some(hash) = txn.get_external(&id).map_err(UnrecordError::Txn)? else {
+      continue;
+    };
+    let closed = unrecord_(changes, txn, space, &hash, true)?;
+    unrecorded.closed = unrecorded.closed.or(closed);
+    unrecorded.hashes.push(hash);
+  }
+  unrecorded.tags = del_tags_past_head(txn, space).map_err(UnrecordError::Txn)?;
+  Ok(unrecorded)
+}
+
+/// Load change `hash`, whose local id is `id`. Entries recorded before the
+/// change store existed have no change file: they get a stand-in change
+/// that just adds them.
+fn load_change<T: MutTxnT, C: ChangeStore>(changes: &C, txn: &T, hash: &Hash, id: &ChangeId) -> Result<Change, UnrecordError<C::Error, T::GraphError>> {
+  if changes.has_change(hash) {
+    return changes.get_change(hash).map_err(UnrecordError::Changestore);
+  }
+  let operations = match txn.load_entry(id).map_err(UnrecordError::Txn)? {
+    Some(entry) => vec![Operation::AddEntry { entry }],
+    None => Vec::new(),
+  };
+  Ok(Change::new(ChangeHeader::default(), Vec::new(), operations))
+}
+
+fn internal<T: MutTxnT, C: std::error::Error + 'static>(txn: &T, space: &SpaceRef<T>, hash: &Hash) -> Result<ChangeId, UnrecordError<C, T::GraphError>> {
+  let id = txn
+    .get_internal(hash)
+    .map_err(UnrecordError::Txn)?
+    .ok_or(UnrecordError::NotInSpace(*hash))?;
+  match txn.get_changeset(space, &id).map_err(UnrecordError::Txn)? {
+    Some(_) => Ok(id),
+    None => Err(UnrecordError::NotInSpace(*hash)),
+  }
+}
+
+/// Undo the operations of `change`, whose local id is `id`, in reverse
+/// order. Operations whose target is already gone, which can only happen
+/// when forcing, are skipped.
+fn undo<T: MutTxnT, C: std::error::Error + 'static>(
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  change: &Change,
+  id: &ChangeId,
+) -> Result<(), UnrecordError<C, T::GraphError>> {
+  for op in change.hashed.operations.iter().rev() {
+    match op {
+      Operation::AddEntry { .. } => {
+        txn.del_entry(space, id)?;
+        txn.clear_conflicts(space, id).map_err(UnrecordError::Txn)?;
+      }
+      Operation::EditEntry { entry, old, new } => {
+        if let Some(target) = txn.get_internal(entry).map_err(UnrecordError::Txn)? {
+          if !withdraw(txn, space, &target, id)? {
+            // Only the fields this edit won go back: the others may come
+            // from a concurrent edit it was merged with.
+            if let Some(current) = txn.load_entry(&target).map_err(UnrecordError::Txn)? {
+              txn.unreplace_entry(space, &target, &unmerge(old, &current, new), id)?;
+            }
+          }
+        }
+      }
+      Operation::DelEntry { entry, old } => {
+        if let Some(target) = txn.get_internal(entry).map_err(UnrecordError::Txn)? {
+          if !withdraw(txn, space, &target, id)? && !txn.has_entry(space, &target).map_err(UnrecordError::Txn)? {
+            txn.put_entry(space, &target, old)?;
+          }
+        }
+      }
+      Operation::AddCompartment { id, .. } => {
+        // Only `space` loses the compartment. Its creator is kept, like the
+        // rest of the graph: other spaces may still hold it, and their
+        // changes to it depend on this one.
+        txn.del_compartment(space, id).map_err(UnrecordError::Txn)?;
+      }
+      Operation::AddVault { id, .. } => {
+        txn.del_vault(space, id).map_err(UnrecordError::Txn)?;
+        txn.del_creator(id).map_err(UnrecordError::Txn)?;
+      }
+      Operation::AddLabel { id, .. } => {
+        txn.del_label(space, id).map_err(UnrecordError::Txn)?;
+        txn.del_creator(id).map_err(UnrecordError::Txn)?;
+      }
+      Operation::AddFilter { id } => {
+        txn.del_filter(id).map_err(UnrecordError::Txn)?;
+        txn.del_creator(id).map_err(UnrecordError::Txn)?;
+      }
+      Operation::RenameCompartment { id, old, .. } => {
+        txn
+          .rename_compartment(space, id, old)
+          .map_err(UnrecordError::Txn)?;
+      }
+      Operation::CloseCompartment { id } => {
+        txn
+          .set_compartment_closed(space, id, false)
+          .map_err(UnrecordError::Txn)?;
+      }
+      Operation::ReopenCompartment { id } => {
+        txn
+          .set_compartment_closed(space, id, true)
+          .map_err(UnrecordError::Txn)?;
+      }
+      Operation::SetCompartmentKind { id, old, .. } => {
+        let mut tree = txn.descendants(space, id).map_err(UnrecordError::Txn)?;
+        tree.push(*id);
+        for c in tree.iter() {
+          txn
+            .set_compartment_kind(space, c, *old)
+            .map_err(UnrecordError::Txn)?;
+        }
+      }
+      Operation::SetCompartmentParent { id, old, .. } => {
+        txn
+          .set_compartment_parent(space, id, *old)
+          .map_err(UnrecordError::Txn)?;
+      }
+      Operation::MoveCompartment { id, old, .. } => {
+        txn
+          .set_compartment_vault(space, id, *old)
+          .map_err(UnrecordError::Txn)?;
+      }
+      Operation::RenameLabel { id, old, .. } => {
+        txn
+          .rename_label(space, id, old)
+          .map_err(UnrecordError::Txn)?;
+      }
+      Operation::DelLabel {
+        id,
+        name,
+        group,
+        vault,
+      } => {
+        txn
+          .create_label(space, *id, name, *group)
+          .map_err(UnrecordError::Txn)?;
+        txn
+          .set_label_vault(id, *vault)
+          .map_err(UnrecordError::Txn)?;
+      }
+      Operation::MoveLabel { id, old, .. } => {
+        txn.set_label_vault(id, *old).map_err(UnrecordError::Txn)?;
+      }
+      Operation::SetLabelParent { id, old, group, .. } => {
+        txn.set_label_parent(id, *old).map_err(UnrecordError::Txn)?;
+        let mut tree = txn.label_descendants(id).map_err(UnrecordError::Txn)?;
+        tree.push(*id);
+        for l in tree.iter() {
+          txn.set_label_group(l, *group).map_err(UnrecordError::Txn)?;
+        }
+      }
+      Operation::AttachLabel { entry, label } => {
+        if let Some(target) = txn.get_internal(entry).map_err(UnrecordError::Txn)? {
+          txn
+            .detach_label(&target, label)
+            .map_err(UnrecordError::Txn)?;
+        }
+      }
+      Operation::DetachLabel { entry, label } => {
+        if let Some(target) = txn.get_internal(entry).map_err(UnrecordError::Txn)? {
+          txn
+            .attach_label(&target, label)
+            .map_err(UnrecordError::Txn)?;
+        }
+      }
+      Operation::SetBudget { id, old, .. } => {
+        txn
+          .set_budget(id, old.as_ref())
+          .map_err(UnrecordError::Txn)?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Withdraw change `id` from the changes competing in `space` for `entry`,
+/// if it is one of them. Returns `true` if `id` did not set the content of
+/// the entry, in which case there is nothing else to undo. The conflict is
+/// over once only the change that set the content is left; if `id` set it,
+/// the other contenders are still pending and stay in conflict.
+fn withdraw<T: MutTxnT, C: std::error::Error + 'static>(
+  txn: &mut T,
+  space: &SpaceRef<T>,
+  entry: &ChangeId,
+  id: &ChangeId,
+) -> Result<bool, UnrecordError<C, T::GraphError>> {
+  let conflicts = txn
+    .entry_conflicts(space, entry)
+    .map_err(UnrecordError::Txn)?;
+  if !conflicts.contains(id) {
+    return Ok(false);
+  }
+  txn
+    .del_conflict(space, entry, id)
+    .map_err(UnrecordError::Txn)?;
+  let current = txn.last_change(entry).map_err(UnrecordError::Txn)?;
+  if current.as_ref() == Some(id) {
+    return Ok(false);
+  }
+  if conflicts
+    .iter()
+    .all(|c| c == id || Some(c) == current.as_ref())
+  {
+    txn
+      .clear_conflicts(space, entry)
+      .map_err(UnrecordError::Txn)?;
+  }
+  Ok(true)
+}
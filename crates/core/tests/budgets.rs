@@ -0,0 +1,262 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::ApplyError,
+  budget::budget_status,
+  change::Operation,
+  changestore::ChangeStore,
+  models::{
+    compartment::CompartmentTxnT,
+    entry::EntryTxnT,
+    filter::{Budget, BudgetPeriod, FilterTxnT},
+    label::LabelGroup,
+    ChangeHeader,
+  },
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::{Currency, Hash, Money, UId},
+  unrecord::unrecord,
+};
+use chrono::NaiveDate;
+use common::{spend, usd, Repo};
+
+fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+  NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn compartment(repo: &Repo, name: &str) -> UId {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  txn.compartment_by_name(&space, name).unwrap().unwrap().id
+}
+
+fn label(repo: &Repo, name: &str) -> UId {
+  let id = UId::new();
+  repo.record(vec![Operation::AddLabel {
+    id,
+    name: name.to_string(),
+    group: LabelGroup::EXPENSE,
+  }]);
+  id
+}
+
+/// Move `entry` to `date`.
+fn redate(repo: &Repo, entry: &Hash, date: NaiveDate) {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let old = txn
+    .load_entry(&txn.get_internal(entry).unwrap().unwrap())
+    .unwrap()
+    .unwrap();
+  drop(txn);
+  let mut new = old.clone();
+  new.date = date;
+  repo.record(vec![Operation::EditEntry {
+    entry: *entry,
+    old,
+    new,
+  }]);
+}
+
+fn monthly(name: &str, limit: i64, labels: Vec<UId>, compartments: Vec<UId>) -> Budget {
+  Budget {
+    name: name.to_string(),
+    period: BudgetPeriod::Monthly,
+    start: day(2026, 9, 1),
+    end: None,
+    limit: Money::new(limit, usd()),
+    rollover: true,
+    labels,
+    compartments,
+  }
+}
+
+/// Like `Repo::record`, but returns the error instead of panicking.
+fn try_record(repo: &Repo, operations: Vec<Operation>) -> Result<Hash, String> {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  let recorded = record(
+    &repo.changes,
+    &mut txn,
+    &space,
+    ChangeHeader::default(),
+    operations,
+    &repo.key,
+  )
+  .map_err(|e| match e {
+    ApplyError::UnknownLabel(_) => "unknown label".to_string(),
+    ApplyError::BudgetCurrency(c, b) => format!("currency {} {}", c, b),
+    e => e.to_string(),
+  })?;
+  txn.commit().unwrap();
+  Ok(recorded.hash)
+}
+
+#[test]
+fn periods_follow_the_calendar() {
+  let mut b = monthly("food", 1000, vec![], vec![]);
+  b.start = day(2026, 8, 15);
+  assert_eq!(
+    b.periods(day(2026, 10, 10)),
+    vec![
+      (day(2026, 8, 15), day(2026, 8, 31)),
+      (day(2026, 9, 1), day(2026, 9, 30)),
+      (day(2026, 10, 1), day(2026, 10, 31)),
+    ]
+  );
+
+  b.period = BudgetPeriod::Quarterly;
+  b.start = day(2026, 2, 1);
+  b.end = Some(day(2026, 8, 15));
+  assert_eq!(
+    b.periods(day(2026, 12, 31)),
+    vec![
+      (day(2026, 2, 1), day(2026, 3, 31)),
+      (day(2026, 4, 1), day(2026, 6, 30)),
+      (day(2026, 7, 1), day(2026, 8, 15)),
+    ]
+  );
+
+  b.period = BudgetPeriod::Custom;
+  assert_eq!(
+    b.periods(day(2026, 12, 31)),
+    vec![(day(2026, 2, 1), day(2026, 8, 15))]
+  );
+  assert!(b.periods(day(2026, 1, 31)).is_empty());
+}
+
+#[test]
+fn status_counts_each_entry_once_and_rolls_over() {
+  let repo = Repo::new("budgets-status");
+  let (_, groceries) = spend(&repo, "grocer", 1000);
+  let (_, bread) = spend(&repo, "baker", 300);
+  redate(&repo, &bread, day(2026, 9, 10));
+  let food = label(&repo, "food");
+  // The groceries are both labelled and posted to a compartment of the
+  // budget: they count once.
+  repo.record(vec![
+    Operation::AttachLabel {
+      entry: bread,
+      label: food,
+    },
+    Operation::AttachLabel {
+      entry: groceries,
+      label: food,
+    },
+  ]);
+  let budget = monthly(
+    "food",
+    1500,
+    vec![food],
+    vec![compartment(&repo, "food-grocer")],
+  );
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let usd = |amount| Money::new(amount, usd());
+  let status = budget_status(&txn, &space, &budget, day(2026, 10, 15)).unwrap();
+  assert_eq!(status.len(), 2);
+  assert_eq!(
+    (status[0].spent, status[0].remaining),
+    (usd(300), usd(1200))
+  );
+  assert_eq!(status[1].carried, usd(1200));
+  assert_eq!(status[1].available, usd(2700));
+  assert_eq!(
+    (status[1].spent, status[1].remaining),
+    (usd(1000), usd(1700))
+  );
+
+  // Without rollover, an overspent period shows a negative remainder.
+  let budget = Budget {
+    limit: usd(800),
+    rollover: false,
+    ..budget
+  };
+  let status = budget_status(&txn, &space, &budget, day(2026, 10, 15)).unwrap();
+  assert_eq!(status[1].carried, usd(0));
+  assert_eq!(status[1].remaining, usd(-200));
+}
+
+#[test]
+fn budgets_are_recorded_and_unrecorded() {
+  let repo = Repo::new("budgets-record");
+  spend(&repo, "grocer", 1000);
+  let food = label(&repo, "food");
+  let grocer = compartment(&repo, "food-grocer");
+  let id = UId::new();
+  let budget = monthly("food", 1500, vec![food], vec![grocer]);
+  repo.record(vec![
+    Operation::AddFilter { id },
+    Operation::SetBudget {
+      id,
+      old: None,
+      new: Some(budget.clone()),
+    },
+  ]);
+  {
+    let txn = repo.encyc.txn_begin().unwrap();
+    assert_eq!(txn.get_budget(&id).unwrap(), Some(budget.clone()));
+    assert_eq!(txn.budget_by_name("food").unwrap().unwrap().id, id);
+  }
+
+  let other = UId::new();
+  repo.record(vec![Operation::AddFilter { id: other }]);
+  let mut euros = monthly("euros", 1500, vec![], vec![grocer]);
+  euros.limit = Money::new(1500, Currency::new("EUR").unwrap());
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::SetBudget {
+        id: other,
+        old: None,
+        new: Some(euros),
+      }]
+    ),
+    Err("currency food-grocer euros".to_string())
+  );
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::SetBudget {
+        id: other,
+        old: None,
+        new: Some(monthly("other", 1500, vec![UId::new()], vec![])),
+      }]
+    ),
+    Err("unknown label".to_string())
+  );
+
+  // An edit depends on the changes creating the filter and what it still
+  // counts, and unrecording it restores the budget.
+  let edited = Budget {
+    limit: Money::new(2000, usd()),
+    labels: vec![],
+    ..budget.clone()
+  };
+  let edit = repo.record(vec![Operation::SetBudget {
+    id,
+    old: Some(budget.clone()),
+    new: Some(edited.clone()),
+  }]);
+  let dependencies = repo
+    .changes
+    .get_change(&edit)
+    .unwrap()
+    .hashed
+    .dependencies
+    .len();
+  assert_eq!(dependencies, 2);
+  {
+    let txn = repo.encyc.txn_begin().unwrap();
+    assert_eq!(txn.get_budget(&id).unwrap(), Some(edited));
+  }
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  unrecord(&repo.changes, &mut txn, &space, &edit, false).unwrap();
+  txn.commit().unwrap();
+  let txn = repo.encyc.txn_begin().unwrap();
+  assert_eq!(txn.get_budget(&id).unwrap(), Some(budget));
+}
@@ -0,0 +1,95 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::apply_change,
+  change::{Change, ChangeError, Operation},
+  changestore::ChangeStore,
+  models::entry::EntryTxnT,
+  traits::{MutTxnT, TxnT},
+  types::Merkle,
+  unrecord::reset_to_state,
+};
+use common::{spend, state, Repo};
+
+#[test]
+fn recorded_changes_are_stored_under_their_hash() {
+  let repo = Repo::new("changes-stored");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  for hash in [c, e] {
+    assert_eq!(
+      repo.changes.get_change(&hash).unwrap().hash().unwrap(),
+      hash
+    );
+  }
+
+  let change = repo.changes.get_change(&e).unwrap();
+  match &change.hashed.operations[..] {
+    [Operation::AddEntry { entry }] => assert_eq!(entry.payee, "grocer"),
+    ops => panic!("unexpected operations {:?}", ops),
+  }
+  // The entry posts to the compartments added by the first change.
+  assert_eq!(change.hashed.dependencies, vec![c]);
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  let id = txn.get_internal(&e).unwrap().unwrap();
+  assert_eq!(txn.get_external(&id).unwrap(), Some(e));
+  assert!(txn.load_entry(&id).unwrap().is_some());
+}
+
+#[test]
+fn serialized_changes_round_trip() {
+  let repo = Repo::new("changes-round-trip");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  let change = repo.changes.get_change(&e).unwrap();
+
+  let mut bytes = Vec::new();
+  assert_eq!(change.serialize(&mut bytes).unwrap(), e);
+  assert_eq!(Change::deserialize(&bytes, Some(&e)).unwrap(), change);
+  match Change::deserialize(&bytes, Some(&c)) {
+    Err(ChangeError::ChangeHashMismatch { claimed, computed }) => {
+      assert_eq!(claimed, c);
+      assert_eq!(computed, e);
+    }
+    r => panic!("unexpected result {:?}", r),
+  }
+
+  // The version comes first, and is checked before anything else.
+  bytes[0] ^= 1;
+  assert!(matches!(
+    Change::deserialize(&bytes, None),
+    Err(ChangeError::VersionMismatch(_))
+  ));
+}
+
+#[test]
+fn stored_changes_can_be_reapplied() {
+  let repo = Repo::new("changes-reapply");
+  spend(&repo, "grocer", 1000);
+  spend(&repo, "baker", 250);
+  let (before, entries) = {
+    let txn = repo.encyc.txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    (state(&repo), txn.space_entries(&space).unwrap().len())
+  };
+
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let mut unrecorded = reset_to_state(&repo.changes, &mut txn, &space, &Merkle::zero())
+    .unwrap()
+    .hashes;
+  assert_eq!(unrecorded.len(), 4);
+  assert!(txn.space_entries(&space).unwrap().is_empty());
+
+  unrecorded.reverse();
+  for hash in unrecorded.iter() {
+    apply_change(&repo.changes, &mut txn, &space, hash).unwrap();
+  }
+  txn.commit().unwrap();
+  assert_eq!(state(&repo), before);
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  assert_eq!(txn.space_entries(&space).unwrap().len(), entries);
+}
@@ -0,0 +1,135 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+#![allow(dead_code)]
+
+//! Helpers shared by the integration tests.
+
+use std::path::PathBuf;
+
+use azoni_core::{
+  change::Operation,
+  changestore::filesystem::FileSystem,
+  key::SecretKey,
+  models::{
+    entry::{Entry, Posting},
+    space::SpaceTxnT,
+    ChangeHeader,
+  },
+  pristine::Encyc,
+  record::record,
+  sync::{missing, space_log, transfer, LogEntry},
+  traits::{MutTxnT, TxnT},
+  types::{Currency, Hash, Merkle, Money, UId},
+};
+use chrono::NaiveDate;
+
+/// A repository in a temporary directory, removed on drop.
+pub struct Repo {
+  pub dir: PathBuf,
+  pub encyc: Encyc,
+  pub changes: FileSystem,
+  /// Signs the changes recorded by `record`.
+  pub key: SecretKey,
+}
+
+impl Repo {
+  pub fn new(name: &str) -> Self {
+    let dir = std::env::temp_dir().join(format!("azoni-test-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let encyc = Encyc::new(dir.join("azoni.db")).unwrap();
+    // Like `azoni init`, start with an empty main space.
+    let mut txn = encyc.mut_txn_begin().unwrap();
+    txn.open_or_create_space("main").unwrap();
+    txn.commit().unwrap();
+    Repo {
+      encyc,
+      changes: FileSystem::from_root(&dir),
+      dir,
+      key: SecretKey::generate(),
+    }
+  }
+
+  pub fn record(&self, operations: Vec<Operation>) -> Hash {
+    let mut txn = self.encyc.mut_txn_begin().unwrap();
+    let space = txn.open_or_create_space("main").unwrap();
+    let recorded = record(
+      &self.changes,
+      &mut txn,
+      &space,
+      ChangeHeader::default(),
+      operations,
+      &self.key,
+    )
+    .unwrap();
+    txn.commit().unwrap();
+    recorded.hash
+  }
+
+  pub fn log(&self) -> Vec<LogEntry> {
+    let txn = self.encyc.txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    space_log(&txn, &space).unwrap()
+  }
+
+  /// Pull everything `self` lacks from `other`, returning what moved.
+  pub fn pull(&self, other: &Repo) -> Vec<Hash> {
+    let theirs = other.log();
+    let mut txn = self.encyc.mut_txn_begin().unwrap();
+    let space = txn.open_or_create_space("main").unwrap();
+    let hashes = missing(&space_log(&txn, &space).unwrap(), &theirs);
+    transfer(&other.changes, &self.changes, &mut txn, &space, &hashes).unwrap();
+    txn.commit().unwrap();
+    hashes
+  }
+}
+
+impl Drop for Repo {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.dir);
+  }
+}
+
+pub fn usd() -> Currency {
+  "USD".parse().unwrap()
+}
+
+/// A compartment, and an entry moving `amount` out of it.
+pub fn spend(repo: &Repo, payee: &str, amount: i64) -> (Hash, Hash) {
+  let (cash, food) = (UId::new(), UId::new());
+  let c = repo.record(vec![
+    Operation::AddCompartment {
+      id: cash,
+      name: format!("cash-{}", payee),
+      currency: usd(),
+    },
+    Operation::AddCompartment {
+      id: food,
+      name: format!("food-{}", payee),
+      currency: usd(),
+    },
+  ]);
+  let entry = Entry {
+    date: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+    payee: payee.to_string(),
+    memo: String::new(),
+    postings: vec![
+      Posting {
+        compartment: food,
+        amount: Money::new(amount, usd()),
+      },
+      Posting {
+        compartment: cash,
+        amount: Money::new(-amount, usd()),
+      },
+    ],
+  };
+  let e = repo.record(vec![Operation::AddEntry { entry }]);
+  (c, e)
+}
+
+pub fn state(repo: &Repo) -> Merkle {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  txn.current_state(&space).unwrap()
+}
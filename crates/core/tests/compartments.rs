@@ -0,0 +1,342 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::{apply_change, ApplyError},
+  change::Operation,
+  changestore::ChangeStore,
+  models::{
+    compartment::{CompartmentTxnT, SerializedCompartment},
+    entry::{Entry, EntryTxnT, InvalidEntry, Posting},
+    ChangeHeader,
+  },
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::{Hash, Money, MoneyError, UId},
+  unrecord::unrecord,
+};
+use chrono::NaiveDate;
+use common::{spend, usd, Repo};
+
+fn compartment_in(repo: &Repo, space: &str, name: &str) -> Option<SerializedCompartment> {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space(space).unwrap().unwrap();
+  txn.compartment_by_name(&space, name).unwrap().cloned()
+}
+
+fn compartment(repo: &Repo, name: &str) -> SerializedCompartment {
+  compartment_in(repo, "main", name).unwrap()
+}
+
+fn balance_in(repo: &Repo, space: &str, name: &str) -> Money {
+  compartment_in(repo, space, name).unwrap().balance.into()
+}
+
+fn balance(repo: &Repo, name: &str) -> Money {
+  balance_in(repo, "main", name)
+}
+
+/// Like `Repo::record`, in space `space`, created if needed.
+fn record_in(repo: &Repo, space: &str, operations: Vec<Operation>) -> Hash {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space(space).unwrap();
+  let recorded = record(
+    &repo.changes,
+    &mut txn,
+    &space,
+    ChangeHeader::default(),
+    operations,
+    &repo.key,
+  )
+  .unwrap();
+  txn.commit().unwrap();
+  recorded.hash
+}
+
+/// Like `Repo::record`, but returns the error instead of panicking.
+fn try_record(repo: &Repo, operations: Vec<Operation>) -> Result<Hash, String> {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  let recorded = record(
+    &repo.changes,
+    &mut txn,
+    &space,
+    ChangeHeader::default(),
+    operations,
+    &repo.key,
+  )
+  .map_err(|e| match e {
+    ApplyError::ClosedCompartment(name) => format!("closed {}", name),
+    ApplyError::NonZeroBalance(name, _) => format!("not empty {}", name),
+    ApplyError::UnknownVault(_) => "unknown vault".to_string(),
+    ApplyError::Entry(InvalidEntry::Money(MoneyError::Overflow)) => "overflow".to_string(),
+    e => e.to_string(),
+  })?;
+  txn.commit().unwrap();
+  Ok(recorded.hash)
+}
+
+fn unrecord_change(repo: &Repo, hash: &Hash) {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  unrecord(&repo.changes, &mut txn, &space, hash, false).unwrap();
+  txn.commit().unwrap();
+}
+
+#[test]
+fn balances_follow_entries() {
+  let repo = Repo::new("compartments-balances");
+  let (_, e) = spend(&repo, "grocer", 1000);
+  assert_eq!(balance(&repo, "food-grocer"), Money::new(1000, usd()));
+  assert_eq!(balance(&repo, "cash-grocer"), Money::new(-1000, usd()));
+  let food = compartment(&repo, "food-grocer");
+  assert_eq!(food.counter.as_u64(), 1);
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let id = txn.get_internal(&e).unwrap().unwrap();
+  assert_eq!(txn.compartment_entries(&space, &food.id).unwrap(), vec![id]);
+  let old = txn.load_entry(&id).unwrap().unwrap();
+  drop(txn);
+  let mut new = old.clone();
+  new.postings[0].amount = Money::new(1500, usd());
+  new.postings[1].amount = Money::new(-1500, usd());
+  let edit = repo.record(vec![Operation::EditEntry {
+    entry: e,
+    old: old.clone(),
+    new,
+  }]);
+  assert_eq!(balance(&repo, "food-grocer"), Money::new(1500, usd()));
+  assert_eq!(compartment(&repo, "food-grocer").counter.as_u64(), 1);
+
+  unrecord_change(&repo, &edit);
+  assert_eq!(balance(&repo, "food-grocer"), Money::new(1000, usd()));
+
+  repo.record(vec![Operation::DelEntry { entry: e, old }]);
+  assert_eq!(balance(&repo, "food-grocer"), Money::zero(usd()));
+  let food = compartment(&repo, "food-grocer");
+  assert_eq!(food.counter.as_u64(), 0);
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  assert!(txn
+    .compartment_entries(&space, &food.id)
+    .unwrap()
+    .is_empty());
+}
+
+#[test]
+fn overflowing_balances_are_refused() {
+  let repo = Repo::new("compartments-overflow");
+  let (_, e) = spend(&repo, "grocer", i64::MAX);
+  let txn = repo.encyc.txn_begin().unwrap();
+  let mut entry = txn
+    .load_entry(&txn.get_internal(&e).unwrap().unwrap())
+    .unwrap()
+    .unwrap();
+  drop(txn);
+  entry.postings[0].amount = Money::new(1, usd());
+  entry.postings[1].amount = Money::new(-1, usd());
+  assert_eq!(
+    try_record(&repo, vec![Operation::AddEntry { entry }]),
+    Err("overflow".to_string())
+  );
+  assert_eq!(balance(&repo, "food-grocer"), Money::new(i64::MAX, usd()));
+}
+
+#[test]
+fn only_empty_compartments_close() {
+  let repo = Repo::new("compartments-close");
+  let (_, e) = spend(&repo, "grocer", 1000);
+  let food = compartment(&repo, "food-grocer").id;
+  assert_eq!(
+    try_record(&repo, vec![Operation::CloseCompartment { id: food }]),
+    Err("not empty food-grocer".to_string())
+  );
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  let old = txn
+    .load_entry(&txn.get_internal(&e).unwrap().unwrap())
+    .unwrap()
+    .unwrap();
+  drop(txn);
+  repo.record(vec![Operation::DelEntry {
+    entry: e,
+    old: old.clone(),
+  }]);
+  let close = repo.record(vec![Operation::CloseCompartment { id: food }]);
+  assert!(compartment(&repo, "food-grocer").is_closed());
+
+  // No new postings, until the compartment is reopened.
+  let add = Operation::AddEntry { entry: old };
+  assert_eq!(
+    try_record(&repo, vec![add.clone()]),
+    Err("closed food-grocer".to_string())
+  );
+  unrecord_change(&repo, &close);
+  assert!(!compartment(&repo, "food-grocer").is_closed());
+  repo.record(vec![Operation::CloseCompartment { id: food }]);
+  repo.record(vec![Operation::ReopenCompartment { id: food }]);
+  assert!(try_record(&repo, vec![add]).is_ok());
+}
+
+#[test]
+fn rename_and_move_to_a_vault() {
+  let repo = Repo::new("compartments-rename");
+  spend(&repo, "grocer", 1000);
+  let food = compartment(&repo, "food-grocer").id;
+  let vault = UId::new();
+  repo.record(vec![Operation::AddVault {
+    id: vault,
+    name: "home".to_string(),
+  }]);
+
+  let rename = repo.record(vec![Operation::RenameCompartment {
+    id: food,
+    old: "food-grocer".to_string(),
+    new: "groceries".to_string(),
+  }]);
+  let moved = repo.record(vec![Operation::MoveCompartment {
+    id: food,
+    old: None,
+    new: Some(vault),
+  }]);
+  let c = compartment(&repo, "groceries");
+  assert_eq!(c.vault(), Some(vault));
+  assert_eq!(c.balance, Money::new(1000, usd()).into());
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::MoveCompartment {
+        id: food,
+        old: Some(vault),
+        new: Some(UId::new()),
+      }]
+    ),
+    Err("unknown vault".to_string())
+  );
+
+  // Renaming depends on the change that created the compartment, and the
+  // move also on the one that created the vault.
+  let dependencies = |hash| {
+    repo
+      .changes
+      .get_change(hash)
+      .unwrap()
+      .hashed
+      .dependencies
+      .len()
+  };
+  assert_eq!(dependencies(&rename), 1);
+  assert_eq!(dependencies(&moved), 2);
+}
+
+#[test]
+fn spaces_keep_their_own_compartments() {
+  let repo = Repo::new("compartments-spaces");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let other = txn.open_or_create_space("other").unwrap();
+  for hash in [c, e] {
+    apply_change(&repo.changes, &mut txn, &other, &hash).unwrap();
+  }
+  txn.commit().unwrap();
+  assert_eq!(
+    balance_in(&repo, "other", "food-grocer"),
+    Money::new(1000, usd())
+  );
+
+  // Postings in one space leave the balances of the other alone.
+  let (food, cash) = (
+    compartment_in(&repo, "other", "food-grocer").unwrap().id,
+    compartment_in(&repo, "other", "cash-grocer").unwrap().id,
+  );
+  let entry = Entry {
+    date: NaiveDate::from_ymd_opt(2026, 10, 2).unwrap(),
+    payee: "grocer".to_string(),
+    memo: String::new(),
+    postings: vec![
+      Posting {
+        compartment: food,
+        amount: Money::new(250, usd()),
+      },
+      Posting {
+        compartment: cash,
+        amount: Money::new(-250, usd()),
+      },
+    ],
+  };
+  record_in(&repo, "other", vec![Operation::AddEntry { entry }]);
+  assert_eq!(
+    balance_in(&repo, "other", "food-grocer"),
+    Money::new(1250, usd())
+  );
+  assert_eq!(balance(&repo, "food-grocer"), Money::new(1000, usd()));
+
+  // Names are unique within a space only.
+  let (rent, other_rent) = (UId::new(), UId::new());
+  for (space, id) in [("main", rent), ("other", other_rent)] {
+    record_in(
+      &repo,
+      space,
+      vec![Operation::AddCompartment {
+        id,
+        name: "rent".to_string(),
+        currency: usd(),
+      }],
+    );
+  }
+  assert_eq!(compartment(&repo, "rent").id, rent);
+  assert_eq!(
+    compartment_in(&repo, "other", "rent").unwrap().id,
+    other_rent
+  );
+  record_in(
+    &repo,
+    "other",
+    vec![Operation::AddCompartment {
+      id: UId::new(),
+      name: "savings".to_string(),
+      currency: usd(),
+    }],
+  );
+  assert!(compartment_in(&repo, "main", "savings").is_none());
+
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let other = txn.load_space("other").unwrap().unwrap();
+  unrecord(&repo.changes, &mut txn, &other, &e, false).unwrap();
+  txn.commit().unwrap();
+  assert_eq!(
+    balance_in(&repo, "other", "food-grocer"),
+    Money::new(250, usd())
+  );
+  assert_eq!(balance(&repo, "food-grocer"), Money::new(1000, usd()));
+}
+
+#[test]
+fn unrecording_a_compartment_leaves_other_spaces_alone() {
+  let repo = Repo::new("compartments-unrecord-spaces");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let other = txn.open_or_create_space("other").unwrap();
+  for hash in [c, e] {
+    apply_change(&repo.changes, &mut txn, &other, &hash).unwrap();
+  }
+  for hash in [e, c] {
+    unrecord(&repo.changes, &mut txn, &other, &hash, false).unwrap();
+  }
+  txn.commit().unwrap();
+  assert!(compartment_in(&repo, "other", "food-grocer").is_none());
+  assert_eq!(balance(&repo, "food-grocer"), Money::new(1000, usd()));
+
+  // Changes to it in main still depend on the change creating it.
+  let food = compartment(&repo, "food-grocer").id;
+  let rename = repo.record(vec![Operation::RenameCompartment {
+    id: food,
+    old: "food-grocer".to_string(),
+    new: "groceries".to_string(),
+  }]);
+  let change = repo.changes.get_change(&rename).unwrap();
+  assert_eq!(change.hashed.dependencies, vec![c]);
+}
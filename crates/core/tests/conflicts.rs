@@ -0,0 +1,186 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::apply_change,
+  change::Operation,
+  changestore::ChangeStore,
+  models::{
+    conflict::ConflictTxnT,
+    entry::{Entry, EntryTxnT},
+    ChangeHeader,
+  },
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::{Hash, Money},
+  unrecord::unrecord,
+};
+use common::{spend, state, usd, Repo};
+
+fn load(repo: &Repo, entry: &Hash) -> Entry {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let id = txn.get_internal(entry).unwrap().unwrap();
+  txn.load_entry(&id).unwrap().unwrap()
+}
+
+fn conflicts(repo: &Repo, entry: &Hash) -> Vec<Hash> {
+  conflicts_in(repo, "main", entry)
+}
+
+fn conflicts_in(repo: &Repo, space: &str, entry: &Hash) -> Vec<Hash> {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space(space).unwrap().unwrap();
+  let id = txn.get_internal(entry).unwrap().unwrap();
+  let mut hashes: Vec<_> = txn
+    .entry_conflicts(&space, &id)
+    .unwrap()
+    .iter()
+    .map(|c| txn.get_external(c).unwrap().unwrap())
+    .collect();
+  hashes.sort();
+  hashes
+}
+
+/// Edit `entry` in `repo`, from its current content.
+fn edit(repo: &Repo, entry: &Hash, f: impl FnOnce(&mut Entry)) -> Hash {
+  let old = load(repo, entry);
+  let mut new = old.clone();
+  f(&mut new);
+  repo.record(vec![Operation::EditEntry {
+    entry: *entry,
+    old,
+    new,
+  }])
+}
+
+fn set_amount(entry: &mut Entry, amount: i64) {
+  entry.postings[0].amount = Money::new(amount, usd());
+  entry.postings[1].amount = Money::new(-amount, usd());
+}
+
+/// Two copies of a repository with one entry.
+fn copies(name: &str) -> (Repo, Repo, Hash) {
+  let home = Repo::new(&format!("{}-home", name));
+  let laptop = Repo::new(&format!("{}-laptop", name));
+  let (_, e) = spend(&home, "grocer", 1000);
+  laptop.pull(&home);
+  (home, laptop, e)
+}
+
+#[test]
+fn edits_of_different_fields_merge() {
+  let (home, laptop, e) = copies("merge");
+  edit(&home, &e, |e| e.memo = "weekly".to_string());
+  edit(&laptop, &e, |e| set_amount(e, 1200));
+
+  home.pull(&laptop);
+  laptop.pull(&home);
+  for repo in [&home, &laptop] {
+    let entry = load(repo, &e);
+    assert_eq!(entry.memo, "weekly");
+    assert_eq!(entry.postings[0].amount, Money::new(1200, usd()));
+    assert!(conflicts(repo, &e).is_empty());
+  }
+  assert_eq!(state(&home), state(&laptop));
+}
+
+#[test]
+fn unrecording_a_merged_edit_keeps_the_other_side() {
+  let (home, laptop, e) = copies("unmerge");
+  edit(&home, &e, |e| e.memo = "weekly".to_string());
+  let b = edit(&laptop, &e, |e| set_amount(e, 1200));
+  home.pull(&laptop);
+
+  let mut txn = home.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  unrecord(&home.changes, &mut txn, &space, &b, false).unwrap();
+  txn.commit().unwrap();
+  let entry = load(&home, &e);
+  assert_eq!(entry.memo, "weekly");
+  assert_eq!(entry.postings[0].amount, Money::new(1000, usd()));
+}
+
+#[test]
+fn conflicting_amounts_are_kept_until_resolved() {
+  let (home, laptop, e) = copies("amounts");
+  let a = edit(&home, &e, |e| set_amount(e, 1100));
+  let b = edit(&laptop, &e, |e| set_amount(e, 1300));
+
+  home.pull(&laptop);
+  laptop.pull(&home);
+  let mut both = vec![a, b];
+  both.sort();
+  for repo in [&home, &laptop] {
+    assert_eq!(conflicts(repo, &e), both);
+  }
+  // Each copy keeps the amount it had: neither is overwritten.
+  assert_eq!(load(&home, &e).postings[0].amount, Money::new(1100, usd()));
+  assert_eq!(
+    load(&laptop, &e).postings[0].amount,
+    Money::new(1300, usd())
+  );
+
+  // Editing the entry again depends on both sides, and resolves it.
+  let r = edit(&home, &e, |e| set_amount(e, 1200));
+  let resolution = home.changes.get_change(&r).unwrap();
+  let deps = &resolution.hashed.dependencies;
+  assert!([e, a, b].iter().all(|h| deps.contains(h)));
+  laptop.pull(&home);
+  for repo in [&home, &laptop] {
+    assert!(conflicts(repo, &e).is_empty());
+    assert_eq!(load(repo, &e).postings[0].amount, Money::new(1200, usd()));
+  }
+}
+
+#[test]
+fn deleting_an_edited_entry_conflicts() {
+  let (home, laptop, e) = copies("delete");
+  let a = edit(&home, &e, |e| set_amount(e, 1100));
+  let old = load(&laptop, &e);
+  let d = laptop.record(vec![Operation::DelEntry { entry: e, old }]);
+
+  home.pull(&laptop);
+  let mut both = vec![a, d];
+  both.sort();
+  assert_eq!(conflicts(&home, &e), both);
+  assert_eq!(load(&home, &e).postings[0].amount, Money::new(1100, usd()));
+}
+
+#[test]
+fn spaces_keep_their_own_conflicts() {
+  let (home, laptop, e) = copies("spaces");
+  let a = edit(&home, &e, |e| set_amount(e, 1100));
+  let b = edit(&laptop, &e, |e| set_amount(e, 1300));
+  home.pull(&laptop);
+  let mut both = vec![a, b];
+  both.sort();
+  assert_eq!(conflicts(&home, &e), both);
+
+  // A space that never applied `b` has nothing to resolve.
+  let mut txn = home.encyc.mut_txn_begin().unwrap();
+  let other = txn.open_or_create_space("other").unwrap();
+  for entry in home.log().iter().filter(|l| l.hash != b) {
+    apply_change(&home.changes, &mut txn, &other, &entry.hash).unwrap();
+  }
+  let old = load(&home, &e);
+  let mut new = old.clone();
+  set_amount(&mut new, 1200);
+  let r = record(
+    &home.changes,
+    &mut txn,
+    &other,
+    ChangeHeader::default(),
+    vec![Operation::EditEntry { entry: e, old, new }],
+    &home.key,
+  )
+  .unwrap();
+  txn.commit().unwrap();
+  assert!(conflicts_in(&home, "other", &e).is_empty());
+  let edit = home.changes.get_change(&r.hash).unwrap();
+  assert!(!edit.hashed.dependencies.contains(&b));
+
+  // Nor does editing it there resolve the conflict of main.
+  assert_eq!(conflicts(&home, &e), both);
+}
@@ -0,0 +1,144 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::{apply_local_change, ApplyError},
+  change::{Change, Operation},
+  models::{
+    device::{DeviceMutTxnT, DeviceTxnT},
+    ChangeHeader, Device,
+  },
+  pristine::EncycError,
+  traits::MutTxnT,
+  types::UId,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use common::{usd, Repo};
+
+fn day(d: u32) -> DateTime<Utc> {
+  Utc.with_ymd_and_hms(2026, 10, d, 12, 0, 0).unwrap()
+}
+
+fn laptop() -> Device {
+  Device {
+    name: "laptop".to_string(),
+    platform: Some("x86_64".to_string()),
+    os: Some("linux".to_string()),
+    ..Device::default()
+  }
+}
+
+/// A change made on `device` at `timestamp`.
+fn change(device: &Device, timestamp: DateTime<Utc>) -> Change {
+  let header = ChangeHeader {
+    timestamp,
+    authors: Vec::new(),
+    devices: vec![device.clone()],
+  };
+  Change::new(
+    header,
+    Vec::new(),
+    vec![Operation::AddCompartment {
+      id: UId::new(),
+      name: format!("cash-{}", timestamp.timestamp()),
+      currency: usd(),
+    }],
+  )
+}
+
+fn apply_to(repo: &Repo, space: &str, change: &Change) -> Result<(), ApplyError<EncycError, EncycError>> {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space(space).unwrap();
+  apply_local_change(&mut txn, &space, change, &change.hash().unwrap())?;
+  txn.commit().unwrap();
+  Ok(())
+}
+
+fn apply(repo: &Repo, change: &Change) -> Result<(), ApplyError<EncycError, EncycError>> {
+  apply_to(repo, "main", change)
+}
+
+#[test]
+fn writing_devices_are_registered() {
+  let repo = Repo::new("devices-registered");
+  apply(&repo, &change(&laptop(), day(1))).unwrap();
+  apply(&repo, &change(&laptop(), day(3))).unwrap();
+  // Older changes, e.g. pulled late, don't move the last access back.
+  apply(&repo, &change(&laptop(), day(2))).unwrap();
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  let registered = txn.get_device("laptop").unwrap().unwrap();
+  assert_eq!(registered.device.created_at, day(1));
+  assert_eq!(registered.device.last_access, day(3));
+  assert_eq!(registered.device.os.as_deref(), Some("linux"));
+  assert!(!registered.is_revoked());
+  assert_eq!(txn.list_devices().unwrap(), vec![registered]);
+  assert_eq!(txn.get_device("phone").unwrap(), None);
+}
+
+#[test]
+fn touching_keeps_the_alias_and_revocation() {
+  let repo = Repo::new("devices-touch");
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  txn.touch_device(&laptop(), day(1)).unwrap();
+  assert!(txn.rename_device("laptop", Some("old-laptop")).unwrap());
+  assert!(txn.revoke_device("laptop", day(5)).unwrap());
+  assert!(!txn.rename_device("phone", Some("new-phone")).unwrap());
+  assert!(!txn.revoke_device("phone", day(5)).unwrap());
+
+  let upgraded = Device {
+    os_version: Some("6.18".to_string()),
+    ..laptop()
+  };
+  let registered = txn.touch_device(&upgraded, day(2)).unwrap();
+  assert_eq!(registered.device.alias.as_deref(), Some("old-laptop"));
+  assert_eq!(registered.device.os_version.as_deref(), Some("6.18"));
+  assert_eq!(registered.revoked, Some(day(5)));
+  txn.commit().unwrap();
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  assert_eq!(txn.get_device("laptop").unwrap(), Some(registered));
+}
+
+#[test]
+fn changes_from_revoked_devices_are_refused() {
+  let repo = Repo::new("devices-revoked");
+  let before = change(&laptop(), day(1));
+  apply(&repo, &before).unwrap();
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  txn.revoke_device("laptop", day(5)).unwrap();
+  txn.commit().unwrap();
+
+  // Backdating a change doesn't get it past the revocation.
+  for d in [6, 4] {
+    match apply(&repo, &change(&laptop(), day(d))) {
+      Err(ApplyError::RevokedDevice(name)) => assert_eq!(name, "laptop"),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+  assert_eq!(repo.log().len(), 1);
+
+  // Changes the pristine had before the revocation are still accepted.
+  apply_to(&repo, "other", &before).unwrap();
+}
+
+#[test]
+fn changes_without_a_device_are_refused_once_devices_are_registered() {
+  let repo = Repo::new("devices-none");
+  let anonymous = |d| {
+    let mut c = change(&laptop(), day(d));
+    c.hashed.header.devices.clear();
+    c
+  };
+  let first = anonymous(1);
+  apply(&repo, &first).unwrap();
+  apply(&repo, &change(&laptop(), day(2))).unwrap();
+  match apply(&repo, &anonymous(3)) {
+    Err(ApplyError::NoDevice) => (),
+    r => panic!("unexpected result {:?}", r),
+  }
+  assert_eq!(repo.log().len(), 2);
+  apply_to(&repo, "other", &first).unwrap();
+}
@@ -0,0 +1,100 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::{apply_change, ApplyError},
+  change::Operation,
+  models::{entry::EntryTxnT, graph::GraphTxnT},
+  traits::{MutTxnT, TxnT},
+  types::{Hash, Money},
+  unrecord::{dependents, unrecord, UnrecordError},
+};
+use common::{spend, usd, Repo};
+
+fn deps(repo: &Repo, hash: &Hash) -> (Vec<Hash>, Vec<Hash>) {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let id = txn.get_internal(hash).unwrap().unwrap();
+  let external = |ids: Vec<_>| {
+    let mut hashes: Vec<Hash> = ids
+      .iter()
+      .map(|c| txn.get_external(c).unwrap().unwrap())
+      .collect();
+    hashes.sort();
+    hashes
+  };
+  (
+    external(txn.dependencies(&id).unwrap()),
+    external(txn.dependents(&id).unwrap()),
+  )
+}
+
+fn raise(repo: &Repo, entry: &Hash, amount: i64) -> Hash {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let id = txn.get_internal(entry).unwrap().unwrap();
+  let old = txn.load_entry(&id).unwrap().unwrap();
+  drop(txn);
+  let mut new = old.clone();
+  new.postings[0].amount = Money::new(amount, usd());
+  new.postings[1].amount = Money::new(-amount, usd());
+  repo.record(vec![Operation::EditEntry {
+    entry: *entry,
+    old,
+    new,
+  }])
+}
+
+#[test]
+fn recorded_changes_depend_on_what_they_touch() {
+  let repo = Repo::new("graph-record");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  let r = raise(&repo, &e, 1200);
+
+  let sorted = |mut hashes: Vec<Hash>| {
+    hashes.sort();
+    hashes
+  };
+  // The edit depends on the entry, and on the compartments it posts to.
+  assert_eq!(deps(&repo, &c), (vec![], sorted(vec![e, r])));
+  assert_eq!(deps(&repo, &e), (vec![c], vec![r]));
+  assert_eq!(deps(&repo, &r), (sorted(vec![c, e]), vec![]));
+}
+
+#[test]
+fn dependencies_must_be_applied_first() {
+  let home = Repo::new("graph-missing-home");
+  let laptop = Repo::new("graph-missing-laptop");
+  let (c, e) = spend(&home, "grocer", 1000);
+
+  let mut txn = laptop.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  match apply_change(&home.changes, &mut txn, &space, &e) {
+    Err(ApplyError::MissingDependency(h)) => assert_eq!(h, c),
+    r => panic!("unexpected result {:?}", r),
+  }
+  drop(txn);
+
+  laptop.pull(&home);
+  assert_eq!(deps(&laptop, &e).0, vec![c]);
+}
+
+#[test]
+fn dependents_block_unrecord() {
+  let repo = Repo::new("graph-unrecord");
+  let (c, e) = spend(&repo, "grocer", 1000);
+
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  assert_eq!(
+    dependents(&repo.changes, &txn, &space, &c).unwrap(),
+    vec![e]
+  );
+  match unrecord(&repo.changes, &mut txn, &space, &c, false) {
+    Err(UnrecordError::Dependents { dependents, .. }) => assert_eq!(dependents, vec![e]),
+    r => panic!("unexpected result {:?}", r),
+  }
+  unrecord(&repo.changes, &mut txn, &space, &e, false).unwrap();
+  unrecord(&repo.changes, &mut txn, &space, &c, false).unwrap();
+  txn.commit().unwrap();
+}
@@ -0,0 +1,179 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::ApplyError,
+  change::Operation,
+  models::{
+    compartment::{CompartmentKind, CompartmentTxnT, SerializedCompartment},
+    ChangeHeader,
+  },
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::{Money, UId},
+  unrecord::unrecord,
+};
+use common::{spend, usd, Repo};
+
+fn compartment(repo: &Repo, name: &str) -> SerializedCompartment {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  txn
+    .compartment_by_name(&space, name)
+    .unwrap()
+    .unwrap()
+    .clone()
+}
+
+fn add(repo: &Repo, name: &str, kind: CompartmentKind, parent: Option<UId>) -> UId {
+  let id = UId::new();
+  let mut ops = vec![
+    Operation::AddCompartment {
+      id,
+      name: name.to_string(),
+      currency: usd(),
+    },
+    Operation::SetCompartmentKind {
+      id,
+      old: CompartmentKind::Asset,
+      new: kind,
+    },
+  ];
+  if parent.is_some() {
+    ops.push(Operation::SetCompartmentParent {
+      id,
+      old: None,
+      new: parent,
+    })
+  }
+  repo.record(ops);
+  id
+}
+
+/// Like `Repo::record`, but returns the error instead of panicking.
+fn try_record(repo: &Repo, operations: Vec<Operation>) -> Result<(), String> {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  record(
+    &repo.changes,
+    &mut txn,
+    &space,
+    ChangeHeader::default(),
+    operations,
+    &repo.key,
+  )
+  .map_err(|e| match e {
+    ApplyError::CompartmentCycle(name) => format!("cycle {}", name),
+    ApplyError::ParentMismatch(name, parent) => format!("mismatch {} {}", name, parent),
+    ApplyError::WrongSign { name, .. } => format!("sign {}", name),
+    e => e.to_string(),
+  })?;
+  txn.commit().unwrap();
+  Ok(())
+}
+
+#[test]
+fn balances_roll_up_the_tree() {
+  let repo = Repo::new("hierarchy-roll-up");
+  spend(&repo, "grocer", 1000);
+  spend(&repo, "baker", 300);
+  let food = compartment(&repo, "food-grocer");
+  let bread = compartment(&repo, "food-baker");
+  let expenses = add(&repo, "expenses", CompartmentKind::Asset, None);
+  for id in [food.id, bread.id] {
+    repo.record(vec![Operation::SetCompartmentParent {
+      id,
+      old: None,
+      new: Some(expenses),
+    }]);
+  }
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let mut children = txn.children(&space, &expenses).unwrap();
+  children.sort();
+  let mut expected = vec![food.id, bread.id];
+  expected.sort();
+  assert_eq!(children, expected);
+  assert_eq!(
+    txn.total_balance(&space, &expenses).unwrap(),
+    Some(Money::new(1300, usd()))
+  );
+  assert_eq!(
+    txn.compartment_path(&space, &food.id).unwrap(),
+    "expenses:food-grocer"
+  );
+}
+
+#[test]
+fn kinds_follow_the_root() {
+  let repo = Repo::new("hierarchy-kinds");
+  let root = add(&repo, "spending", CompartmentKind::Expense, None);
+  let child = add(&repo, "rent", CompartmentKind::Expense, Some(root));
+  // A child of another kind is refused.
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::SetCompartmentKind {
+        id: child,
+        old: CompartmentKind::Expense,
+        new: CompartmentKind::Income,
+      }]
+    ),
+    Err("mismatch rent spending".to_string())
+  );
+
+  // Changing the kind of the root changes the whole tree, and unrecording
+  // it restores it.
+  let h = repo.record(vec![Operation::SetCompartmentKind {
+    id: root,
+    old: CompartmentKind::Expense,
+    new: CompartmentKind::Liability,
+  }]);
+  assert_eq!(compartment(&repo, "rent").kind, CompartmentKind::Liability);
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  unrecord(&repo.changes, &mut txn, &space, &h, false).unwrap();
+  txn.commit().unwrap();
+  assert_eq!(compartment(&repo, "rent").kind, CompartmentKind::Expense);
+}
+
+#[test]
+fn cycles_and_wrong_signs_are_refused() {
+  let repo = Repo::new("hierarchy-refused");
+  let a = add(&repo, "a", CompartmentKind::Asset, None);
+  let b = add(&repo, "b", CompartmentKind::Asset, Some(a));
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::SetCompartmentParent {
+        id: a,
+        old: None,
+        new: Some(b),
+      }]
+    ),
+    Err("cycle a".to_string())
+  );
+  assert_eq!(compartment(&repo, "a").parent(), None);
+
+  // An expense compartment cannot be credited below zero.
+  spend(&repo, "grocer", 1000);
+  let cash = compartment(&repo, "cash-grocer").id;
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::SetCompartmentKind {
+        id: cash,
+        old: CompartmentKind::Asset,
+        new: CompartmentKind::Expense,
+      }]
+    ),
+    Err("sign cash-grocer".to_string())
+  );
+  assert_eq!(
+    compartment(&repo, "cash-grocer").kind,
+    CompartmentKind::Asset
+  );
+}
@@ -0,0 +1,237 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::ApplyError,
+  change::Operation,
+  history::{entries_as_of, ClosedPeriod},
+  models::{
+    entry::EntryTxnT,
+    space::{SpaceMutTxnT, SpaceTxnT, Tag, TagPolicy},
+  },
+  sync::{missing, space_log, transfer, SyncError},
+  traits::{MutTxnT, TxnT},
+  types::{Merkle, Money, UId},
+  unrecord::{reset_to_state, unrecord, UnrecordError},
+};
+use chrono::{NaiveDate, Utc};
+use common::{spend, usd, Repo};
+
+fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+  NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// Tag the current state of `repo` as closing the books up to `date`, and
+/// set the policy of its space. A tag of the same name is replaced.
+fn tag(repo: &Repo, name: &str, date: NaiveDate, policy: TagPolicy) {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let tag = Tag {
+    name: name.to_string(),
+    state: txn.current_state(&space).unwrap(),
+    position: txn.log(&space, 0).unwrap().len() as u64,
+    date,
+    created: Utc::now(),
+  };
+  txn.del_tag(&space, name).unwrap();
+  txn.put_tag(&space, &tag).unwrap();
+  txn.set_tag_policy(&space, policy);
+  txn.commit().unwrap();
+}
+
+fn q3() -> ClosedPeriod {
+  ClosedPeriod {
+    tag: "q3".to_string(),
+    date: day(2026, 10, 31),
+  }
+}
+
+#[test]
+fn unrecord_checks_closed_periods() {
+  let repo = Repo::new("history-unrecord");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  tag(&repo, "q3", day(2026, 10, 31), TagPolicy::Reject);
+
+  {
+    let mut txn = repo.encyc.mut_txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    match unrecord(&repo.changes, &mut txn, &space, &e, false) {
+      Err(UnrecordError::ClosedPeriod(closed)) => assert_eq!(closed, q3()),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+  // Changes without entries alter no period.
+  tag(&repo, "q3", day(2026, 10, 31), TagPolicy::Reject);
+  {
+    let mut txn = repo.encyc.mut_txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    assert_eq!(
+      unrecord(&repo.changes, &mut txn, &space, &c, true).unwrap(),
+      None
+    );
+  }
+
+  tag(&repo, "q3", day(2026, 10, 31), TagPolicy::Warn);
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  assert_eq!(
+    unrecord(&repo.changes, &mut txn, &space, &e, false).unwrap(),
+    Some(q3())
+  );
+  txn.commit().unwrap();
+  assert_eq!(repo.log().len(), 1);
+}
+
+#[test]
+fn reset_checks_closed_periods() {
+  let repo = Repo::new("history-reset");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  tag(&repo, "q3", day(2026, 10, 31), TagPolicy::Reject);
+
+  {
+    let mut txn = repo.encyc.mut_txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    match reset_to_state(&repo.changes, &mut txn, &space, &Merkle::zero()) {
+      Err(UnrecordError::ClosedPeriod(closed)) => assert_eq!(closed, q3()),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+  assert_eq!(repo.log().len(), 2);
+
+  // A period closed before the entry is not altered.
+  tag(&repo, "q3", day(2026, 9, 30), TagPolicy::Reject);
+  {
+    let mut txn = repo.encyc.mut_txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    let unrecorded = reset_to_state(&repo.changes, &mut txn, &space, &Merkle::zero()).unwrap();
+    assert_eq!(unrecorded.hashes, vec![e, c]);
+    assert_eq!(unrecorded.closed, None);
+  }
+
+  tag(&repo, "q3", day(2026, 10, 31), TagPolicy::Warn);
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let unrecorded = reset_to_state(&repo.changes, &mut txn, &space, &Merkle::zero()).unwrap();
+  assert_eq!(unrecorded.hashes, vec![e, c]);
+  assert_eq!(unrecorded.closed, Some(q3()));
+  txn.commit().unwrap();
+  assert!(repo.log().is_empty());
+}
+
+#[test]
+fn reset_past_a_tag_deletes_it() {
+  let repo = Repo::new("history-reset-tag");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  repo.record(vec![Operation::AddCompartment {
+    id: UId::new(),
+    name: "savings".to_string(),
+    currency: usd(),
+  }]);
+  tag(&repo, "q3", day(2026, 10, 31), TagPolicy::Reject);
+
+  // The last change alters no period, but the entry before it does: the
+  // tag still closes it until the reset is done.
+  {
+    let mut txn = repo.encyc.mut_txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    match reset_to_state(&repo.changes, &mut txn, &space, &Merkle::zero()) {
+      Err(UnrecordError::ClosedPeriod(closed)) => assert_eq!(closed, q3()),
+      r => panic!("unexpected result {:?}", r),
+    }
+  }
+
+  tag(&repo, "q3", day(2026, 9, 30), TagPolicy::Reject);
+  tag(&repo, "now", day(2026, 9, 30), TagPolicy::Reject);
+  let after_c = repo.log()[0].state;
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let unrecorded = reset_to_state(&repo.changes, &mut txn, &space, &after_c).unwrap();
+  assert_eq!(unrecorded.hashes.len(), 2);
+  assert_eq!(unrecorded.hashes[1], e);
+  assert_eq!(unrecorded.tags, vec!["now".to_string(), "q3".to_string()]);
+  assert!(txn.space_tags(&space).unwrap().is_empty());
+  txn.commit().unwrap();
+  assert_eq!(repo.log().len(), 1);
+  assert_eq!(repo.log()[0].hash, c);
+}
+
+#[test]
+fn pulled_changes_follow_the_tag_policy() {
+  let home = Repo::new("history-pull-home");
+  let laptop = Repo::new("history-pull-laptop");
+  let (c, e) = spend(&home, "grocer", 1000);
+  tag(&laptop, "q3", day(2026, 10, 31), TagPolicy::Reject);
+
+  let pull = |laptop: &Repo| {
+    let mut txn = laptop.encyc.mut_txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    let hashes = missing(&space_log(&txn, &space).unwrap(), &home.log());
+    let closed = transfer(&home.changes, &laptop.changes, &mut txn, &space, &hashes)?;
+    txn.commit().unwrap();
+    Ok(closed)
+  };
+  match pull(&laptop) {
+    Err(SyncError::Apply(ApplyError::ClosedPeriod(closed))) => assert_eq!(closed, q3()),
+    r => panic!("unexpected result {:?}", r),
+  }
+  assert!(laptop.log().is_empty());
+
+  tag(&laptop, "q3", day(2026, 10, 31), TagPolicy::Warn);
+  assert_eq!(pull(&laptop).unwrap(), Some(q3()));
+  let pulled: Vec<_> = laptop.log().iter().map(|e| e.hash).collect();
+  assert_eq!(pulled, vec![c, e]);
+}
+
+#[test]
+fn entries_as_of_a_tag() {
+  let repo = Repo::new("history-as-of");
+  let (_, groceries) = spend(&repo, "grocer", 1000);
+  let (_, bread) = spend(&repo, "baker", 300);
+  tag(&repo, "q3", day(2026, 10, 31), TagPolicy::Warn);
+  let position = {
+    let txn = repo.encyc.txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    txn.get_tag(&space, "q3").unwrap().unwrap().position
+  };
+
+  // After the tag: the groceries are edited, the bread deleted, and fish
+  // bought.
+  let (old, bread_entry) = {
+    let txn = repo.encyc.txn_begin().unwrap();
+    let load = |h| {
+      txn
+        .load_entry(&txn.get_internal(h).unwrap().unwrap())
+        .unwrap()
+        .unwrap()
+    };
+    (load(&groceries), load(&bread))
+  };
+  let mut new = old.clone();
+  new.postings[0].amount = Money::new(1200, usd());
+  new.postings[1].amount = Money::new(-1200, usd());
+  repo.record(vec![Operation::EditEntry {
+    entry: groceries,
+    old: old.clone(),
+    new: new.clone(),
+  }]);
+  repo.record(vec![Operation::DelEntry {
+    entry: bread,
+    old: bread_entry.clone(),
+  }]);
+  let (_, fish) = spend(&repo, "fishmonger", 800);
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let then = entries_as_of(&repo.changes, &txn, &space, position).unwrap();
+  assert_eq!(then.len(), 2);
+  assert_eq!(then[&groceries], old);
+  assert_eq!(then[&bread], bread_entry);
+
+  let now = entries_as_of(&repo.changes, &txn, &space, u64::MAX).unwrap();
+  assert_eq!(now.len(), 2);
+  assert_eq!(now[&groceries], new);
+  assert!(now.contains_key(&fish));
+  assert!(!now.contains_key(&bread));
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  changestore::ChangeStore,
+  http::{HttpClient, HttpError, HttpServer},
+  sync::{missing, space_log, transfer},
+  traits::{MutTxnT, TxnT},
+};
+use common::{spend, state, Repo};
+
+/// Serve `remote` on localhost for the duration of `f`, accepting the
+/// changes signed by `authorized`.
+fn with_server<T>(remote: &Repo, authorized: &[&Repo], f: impl FnOnce(HttpClient) -> T) -> T {
+  let server = HttpServer::bind("127.0.0.1:0")
+    .unwrap()
+    .with_authorized_keys(authorized.iter().map(|r| r.key.public_key()).collect());
+  let client = HttpClient::new(&format!("http://{}", server.addr()));
+  std::thread::scope(|s| {
+    let served = s.spawn(|| server.serve(&remote.encyc, &remote.changes));
+    let result = f(client);
+    server.stop();
+    served.join().unwrap().unwrap();
+    result
+  })
+}
+
+#[test]
+fn pull_over_http() {
+  let remote = Repo::new("http-pull-remote");
+  let local = Repo::new("http-pull-local");
+  let (c, e) = spend(&remote, "grocer", 1000);
+
+  let pulled = with_server(&remote, &[], |client| {
+    let mut txn = local.encyc.mut_txn_begin().unwrap();
+    let space = txn.open_or_create_space("main").unwrap();
+    let ours = space_log(&txn, &space).unwrap();
+    let theirs = client.log("main", None).unwrap();
+    let hashes = missing(&ours, &theirs);
+    transfer(&client, &local.changes, &mut txn, &space, &hashes).unwrap();
+    txn.commit().unwrap();
+    hashes
+  });
+
+  assert_eq!(pulled, vec![c, e]);
+  assert_eq!(state(&local), state(&remote));
+}
+
+#[test]
+fn log_since_a_state() {
+  let remote = Repo::new("http-since");
+  let (c, e) = spend(&remote, "grocer", 1000);
+  let log = remote.log();
+
+  with_server(&remote, &[], |client| {
+    let after_c = client.log("main", Some(&log[0].state)).unwrap();
+    assert_eq!(after_c.iter().map(|e| e.hash).collect::<Vec<_>>(), vec![e]);
+    assert!(client.log("main", Some(&log[1].state)).unwrap().is_empty());
+    // An unknown state gets the whole log.
+    let whole = client
+      .log("main", Some(&state(&Repo::new("http-since-other"))))
+      .unwrap();
+    assert_eq!(whole.iter().map(|e| e.hash).collect::<Vec<_>>(), vec![c, e]);
+    assert!(client.log("nowhere", None).unwrap().is_empty());
+  });
+}
+
+#[test]
+fn push_over_http() {
+  let remote = Repo::new("http-push-remote");
+  let local = Repo::new("http-push-local");
+  spend(&remote, "grocer", 1000);
+  local.pull(&remote);
+  let (c, e) = spend(&local, "baker", 250);
+
+  let pushed = with_server(&remote, &[&local], |client| {
+    let theirs = client.log("main", None).unwrap();
+    let hashes = missing(&theirs, &local.log());
+    for hash in hashes.iter() {
+      client
+        .push("main", &local.changes.get_change(hash).unwrap())
+        .unwrap();
+    }
+    assert!(client.has_change(&e));
+    hashes
+  });
+
+  assert_eq!(pushed, vec![c, e]);
+  assert_eq!(state(&local), state(&remote));
+}
+
+#[test]
+fn tampered_changes_are_refused() {
+  let remote = Repo::new("http-tampered-remote");
+  let local = Repo::new("http-tampered-local");
+  let (c, e) = spend(&local, "grocer", 1000);
+  // Swap the operations of a signed change for those of another one.
+  let mut change = local.changes.get_change(&c).unwrap();
+  change.hashed.operations = local.changes.get_change(&e).unwrap().hashed.operations;
+  let forged = change.hash().unwrap();
+
+  with_server(&remote, &[&local], |client| {
+    match client.push("main", &change) {
+      Err(HttpError::Status(403, _)) => {}
+      r => panic!("unexpected result {:?}", r),
+    }
+    assert!(!client.has_change(&forged));
+  });
+  assert!(remote.log().is_empty());
+}
+
+#[test]
+fn unauthorized_changes_are_refused() {
+  let remote = Repo::new("http-unauthorized-remote");
+  let local = Repo::new("http-unauthorized-local");
+  let stranger = Repo::new("http-unauthorized-stranger");
+  let (c, _) = spend(&local, "grocer", 1000);
+  let (d, _) = spend(&stranger, "grocer", 1000);
+  let mut unsigned = local.changes.get_change(&c).unwrap();
+  unsigned.signatures.clear();
+  let signed = stranger.changes.get_change(&d).unwrap();
+
+  with_server(&remote, &[&local], |client| {
+    for change in [&unsigned, &signed] {
+      match client.push("main", change) {
+        Err(HttpError::Status(403, _)) => {}
+        r => panic!("unexpected result {:?}", r),
+      }
+      match client.save_change(change) {
+        Err(HttpError::Status(403, _)) => {}
+        r => panic!("unexpected result {:?}", r),
+      }
+    }
+    assert!(!client.has_change(&c));
+    assert!(!client.has_change(&d));
+  });
+  // Without authorized keys, nothing can be pushed.
+  with_server(&remote, &[], |client| {
+    match client.push("main", &local.changes.get_change(&c).unwrap()) {
+      Err(HttpError::Status(403, _)) => {}
+      r => panic!("unexpected result {:?}", r),
+    }
+  });
+  assert!(remote.log().is_empty());
+}
+
+#[test]
+fn refused_pushes_leave_no_change_behind() {
+  let remote = Repo::new("http-refused-remote");
+  let local = Repo::new("http-refused-local");
+  let (c, e) = spend(&local, "grocer", 1000);
+
+  with_server(&remote, &[&local], |client| {
+    // `e` depends on `c`, which the remote lacks.
+    match client.push("main", &local.changes.get_change(&e).unwrap()) {
+      Err(HttpError::Status(409, _)) => {}
+      r => panic!("unexpected result {:?}", r),
+    }
+    assert!(!client.has_change(&e));
+
+    // A change stored before is kept, even if this push is refused.
+    let change = local.changes.get_change(&c).unwrap();
+    client.push("main", &change).unwrap();
+    match client.push("main", &change) {
+      Err(HttpError::Status(409, _)) => {}
+      r => panic!("unexpected result {:?}", r),
+    }
+    assert!(client.has_change(&c));
+  });
+  assert_eq!(remote.log().len(), 1);
+}
+
+#[test]
+fn space_names_are_escaped() {
+  let remote = Repo::new("http-escaped-remote");
+  let local = Repo::new("http-escaped-local");
+  let (c, e) = spend(&local, "grocer", 1000);
+  let name = "trips/2026 q4?since=%";
+
+  with_server(&remote, &[&local], |client| {
+    for hash in [c, e] {
+      client
+        .push(name, &local.changes.get_change(&hash).unwrap())
+        .unwrap();
+    }
+    let log = client.log(name, None).unwrap();
+    assert_eq!(log.iter().map(|e| e.hash).collect::<Vec<_>>(), vec![c, e]);
+    assert!(client.log("trips", None).unwrap().is_empty());
+  });
+  let txn = remote.encyc.txn_begin().unwrap();
+  assert!(txn.load_space(name).unwrap().is_some());
+}
@@ -0,0 +1,92 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  changestore::ChangeStore,
+  identity::Identity,
+  models::ChangeHeader,
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::Base32,
+};
+use common::Repo;
+
+fn alice(repo: &Repo) -> Identity {
+  Identity::new(
+    "Alice".to_string(),
+    Some("alice@example.com".to_string()),
+    Some(repo.key.public_key()),
+  )
+}
+
+#[test]
+fn authors_carry_the_identity() {
+  let repo = Repo::new("identity-author");
+  let alice = alice(&repo);
+  let author = alice.to_author();
+  assert_eq!(author.id(), Some(alice.id.as_str()));
+  assert_eq!(author.name(), Some("Alice"));
+  assert_eq!(author.email(), Some("alice@example.com"));
+  assert_eq!(
+    author.key(),
+    Some(repo.key.public_key().to_base32().as_str())
+  );
+  assert_eq!(author.to_string(), "Alice <alice@example.com>");
+
+  let anonymous = Identity::new("Bob".to_string(), None, None).to_author();
+  assert_eq!((anonymous.email(), anonymous.key()), (None, None));
+  assert_eq!(anonymous.to_string(), "Bob");
+}
+
+#[test]
+fn renamed_identities_keep_their_id() {
+  let repo = Repo::new("identity-rename");
+  let mut alice = alice(&repo);
+  let before = alice.to_author();
+  alice.display_name = "Alice Liddell".to_string();
+  alice.email = None;
+  let after = alice.to_author();
+  assert_eq!(after.id(), before.id());
+  assert_eq!(after.to_string(), "Alice Liddell");
+  // Ids are unique.
+  assert_ne!(Identity::new("Alice".to_string(), None, None).id, alice.id);
+}
+
+#[test]
+fn identities_serialize() {
+  let repo = Repo::new("identity-serialize");
+  let alice = alice(&repo);
+  let toml = toml::to_string(&alice).unwrap();
+  assert_eq!(toml::from_str::<Identity>(&toml).unwrap(), alice);
+}
+
+#[test]
+fn recorded_changes_name_their_authors() {
+  let repo = Repo::new("identity-record");
+  let alice = alice(&repo);
+  let header = ChangeHeader {
+    authors: vec![alice.to_author()],
+    ..ChangeHeader::default()
+  };
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let recorded = record(
+    &repo.changes,
+    &mut txn,
+    &space,
+    header,
+    Vec::new(),
+    &repo.key,
+  )
+  .unwrap();
+  txn.commit().unwrap();
+
+  let change = repo.changes.get_change(&recorded.hash).unwrap();
+  assert_eq!(change.hashed.header.authors, vec![alice.to_author()]);
+  assert_eq!(
+    change.check_signatures(&recorded.hash).unwrap(),
+    vec![repo.key.public_key()]
+  );
+}
@@ -0,0 +1,326 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::ApplyError,
+  change::Operation,
+  models::{
+    entry::EntryTxnT,
+    label::{LabelGroup, LabelTxnT},
+    ChangeHeader,
+  },
+  record::{merge_labels, record},
+  traits::{MutTxnT, TxnT},
+  types::{Hash, Money, UId},
+  unrecord::unrecord,
+};
+use common::{spend, usd, Repo};
+
+fn label(repo: &Repo, name: &str) -> UId {
+  let id = UId::new();
+  repo.record(vec![Operation::AddLabel {
+    id,
+    name: name.to_string(),
+    group: LabelGroup::EXPENSE,
+  }]);
+  id
+}
+
+/// A new label under `parent`, whose group it takes.
+fn child(repo: &Repo, name: &str, parent: UId) -> UId {
+  let id = label(repo, name);
+  repo.record(vec![Operation::SetLabelParent {
+    id,
+    old: None,
+    new: Some(parent),
+    group: LabelGroup::EXPENSE,
+  }]);
+  id
+}
+
+fn group(repo: &Repo, label: &UId) -> LabelGroup {
+  let txn = repo.encyc.txn_begin().unwrap();
+  txn.get_label(label).unwrap().unwrap().group
+}
+
+/// Names of the labels attached to `entry`, sorted.
+fn labels(repo: &Repo, entry: &Hash) -> Vec<String> {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let id = txn.get_internal(entry).unwrap().unwrap();
+  let mut names: Vec<_> = txn
+    .entry_labels(&id)
+    .unwrap()
+    .iter()
+    .map(|l| txn.get_label(l).unwrap().unwrap().name.as_str().to_string())
+    .collect();
+  names.sort();
+  names
+}
+
+fn entries(repo: &Repo, label: &UId) -> Vec<Hash> {
+  let txn = repo.encyc.txn_begin().unwrap();
+  let mut hashes: Vec<_> = txn
+    .label_entries(label)
+    .unwrap()
+    .iter()
+    .map(|e| txn.get_external(e).unwrap().unwrap())
+    .collect();
+  hashes.sort();
+  hashes
+}
+
+/// Like `Repo::record`, but returns the error instead of panicking.
+fn try_record(repo: &Repo, operations: Vec<Operation>) -> Result<Hash, String> {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  let recorded = record(
+    &repo.changes,
+    &mut txn,
+    &space,
+    ChangeHeader::default(),
+    operations,
+    &repo.key,
+  )
+  .map_err(|e| match e {
+    ApplyError::LabelInUse(name, n) => format!("in use {} {}", name, n),
+    ApplyError::NotAttached(name, _) => format!("not attached {}", name),
+    ApplyError::LabelInTree(name) => format!("in tree {}", name),
+    ApplyError::LabelCycle(name) => format!("cycle {}", name),
+    e => e.to_string(),
+  })?;
+  txn.commit().unwrap();
+  Ok(recorded.hash)
+}
+
+fn unrecord_change(repo: &Repo, hash: &Hash) {
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  unrecord(&repo.changes, &mut txn, &space, hash, false).unwrap();
+  txn.commit().unwrap();
+}
+
+#[test]
+fn attach_and_detach() {
+  let repo = Repo::new("labels-attach");
+  let (_, e) = spend(&repo, "grocer", 1000);
+  let food = label(&repo, "food");
+  repo.record(vec![Operation::AttachLabel {
+    entry: e,
+    label: food,
+  }]);
+  assert_eq!(labels(&repo, &e), vec!["food"]);
+  assert_eq!(entries(&repo, &food), vec![e]);
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::DelLabel {
+        id: food,
+        name: "food".to_string(),
+        group: LabelGroup::EXPENSE,
+        vault: None,
+      }]
+    ),
+    Err("in use food 1".to_string())
+  );
+
+  // Attached twice, by two changes: it takes two detaches.
+  repo.record(vec![Operation::AttachLabel {
+    entry: e,
+    label: food,
+  }]);
+  let detach = Operation::DetachLabel {
+    entry: e,
+    label: food,
+  };
+  repo.record(vec![detach.clone()]);
+  assert_eq!(entries(&repo, &food), vec![e]);
+  let last = repo.record(vec![detach.clone()]);
+  assert!(labels(&repo, &e).is_empty());
+  assert!(entries(&repo, &food).is_empty());
+  assert_eq!(
+    try_record(&repo, vec![detach]),
+    Err("not attached food".to_string())
+  );
+
+  // Unrecording the last detach attaches it again.
+  unrecord_change(&repo, &last);
+  assert_eq!(entries(&repo, &food), vec![e]);
+}
+
+#[test]
+fn merging_moves_entries_in_one_change() {
+  let repo = Repo::new("labels-merge");
+  let (_, a) = spend(&repo, "grocer", 1000);
+  let (_, b) = spend(&repo, "baker", 300);
+  let groceries = label(&repo, "groceries");
+  let food = label(&repo, "food");
+  repo.record(vec![
+    Operation::AttachLabel {
+      entry: a,
+      label: groceries,
+    },
+    Operation::AttachLabel {
+      entry: b,
+      label: groceries,
+    },
+    Operation::AttachLabel {
+      entry: b,
+      label: food,
+    },
+  ]);
+  repo.record(vec![Operation::RenameLabel {
+    id: groceries,
+    old: "groceries".to_string(),
+    new: "shopping".to_string(),
+  }]);
+  assert_eq!(labels(&repo, &a), vec!["shopping"]);
+
+  let ops = {
+    let txn = repo.encyc.txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    merge_labels(&txn, &space, &groceries, &food).unwrap()
+  };
+  let merge = repo.record(ops);
+  assert_eq!(labels(&repo, &a), vec!["food"]);
+  assert_eq!(labels(&repo, &b), vec!["food"]);
+  let mut both = vec![a, b];
+  both.sort();
+  assert_eq!(entries(&repo, &food), both);
+  {
+    let txn = repo.encyc.txn_begin().unwrap();
+    assert!(txn.get_label(&groceries).unwrap().is_none());
+  }
+
+  unrecord_change(&repo, &merge);
+  assert_eq!(labels(&repo, &a), vec!["shopping"]);
+  assert_eq!(labels(&repo, &b), vec!["food", "shopping"]);
+  assert_eq!(entries(&repo, &groceries), both);
+}
+
+#[test]
+fn labels_are_filed_in_vaults() {
+  let repo = Repo::new("labels-vaults");
+  let (_, e) = spend(&repo, "grocer", 1000);
+  let food = label(&repo, "food");
+  let vault = UId::new();
+  repo.record(vec![Operation::AddVault {
+    id: vault,
+    name: "home".to_string(),
+  }]);
+  let moved = repo.record(vec![Operation::MoveLabel {
+    id: food,
+    old: None,
+    new: Some(vault),
+  }]);
+  {
+    let txn = repo.encyc.txn_begin().unwrap();
+    assert_eq!(txn.vault_labels(&vault).unwrap(), vec![food]);
+    assert_eq!(txn.get_label(&food).unwrap().unwrap().vault(), Some(vault));
+  }
+  unrecord_change(&repo, &moved);
+  {
+    let txn = repo.encyc.txn_begin().unwrap();
+    assert!(txn.vault_labels(&vault).unwrap().is_empty());
+  }
+
+  // Deleting an entry takes it out of the index of its labels.
+  repo.record(vec![Operation::AttachLabel {
+    entry: e,
+    label: food,
+  }]);
+  let txn = repo.encyc.txn_begin().unwrap();
+  let old = txn
+    .load_entry(&txn.get_internal(&e).unwrap().unwrap())
+    .unwrap()
+    .unwrap();
+  drop(txn);
+  repo.record(vec![Operation::DelEntry { entry: e, old }]);
+  assert!(entries(&repo, &food).is_empty());
+}
+
+#[test]
+fn trees_roll_up() {
+  let repo = Repo::new("labels-trees");
+  let (_, a) = spend(&repo, "grocer", 1000);
+  let (_, b) = spend(&repo, "cafe", 300);
+  let food = label(&repo, "food");
+  let restaurants = child(&repo, "restaurants", food);
+  let coffee = child(&repo, "coffee", restaurants);
+  repo.record(vec![
+    Operation::AttachLabel {
+      entry: a,
+      label: food,
+    },
+    Operation::AttachLabel {
+      entry: b,
+      label: coffee,
+    },
+  ]);
+
+  {
+    let txn = repo.encyc.txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    assert_eq!(
+      txn.label_path(&coffee).unwrap(),
+      "food > restaurants > coffee"
+    );
+    assert_eq!(txn.tree_entries(&food).unwrap().len(), 2);
+    assert_eq!(txn.tree_entries(&restaurants).unwrap().len(), 1);
+    assert_eq!(
+      txn.label_total(&space, &food).unwrap(),
+      vec![Money::new(1300, usd())]
+    );
+  }
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::SetLabelParent {
+        id: food,
+        old: None,
+        new: Some(coffee),
+        group: LabelGroup::EXPENSE,
+      }]
+    ),
+    Err("cycle food".to_string())
+  );
+  assert_eq!(
+    try_record(
+      &repo,
+      vec![Operation::DelLabel {
+        id: restaurants,
+        name: "restaurants".to_string(),
+        group: LabelGroup::EXPENSE,
+        vault: None,
+      }]
+    ),
+    Err("in tree restaurants".to_string())
+  );
+
+  // Moving a subtree under an income label makes it income.
+  let salary = UId::new();
+  repo.record(vec![Operation::AddLabel {
+    id: salary,
+    name: "salary".to_string(),
+    group: LabelGroup::INCOME,
+  }]);
+  let moved = repo.record(vec![Operation::SetLabelParent {
+    id: restaurants,
+    old: Some(food),
+    new: Some(salary),
+    group: LabelGroup::EXPENSE,
+  }]);
+  assert_eq!(group(&repo, &coffee), LabelGroup::INCOME);
+  {
+    let txn = repo.encyc.txn_begin().unwrap();
+    assert_eq!(txn.tree_entries(&food).unwrap().len(), 1);
+    assert_eq!(
+      txn.label_path(&coffee).unwrap(),
+      "salary > restaurants > coffee"
+    );
+  }
+  unrecord_change(&repo, &moved);
+  assert_eq!(group(&repo, &coffee), LabelGroup::EXPENSE);
+  assert_eq!(group(&repo, &restaurants), LabelGroup::EXPENSE);
+}
@@ -0,0 +1,70 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  models::space::SpaceTxnT,
+  traits::{MutTxnT, TxnT},
+  types::Merkle,
+  unrecord::unrecord,
+};
+use common::{spend, state, Repo};
+
+#[test]
+fn each_change_advances_the_state() {
+  let repo = Repo::new("log-states");
+  assert_eq!(state(&repo), Merkle::zero());
+  let (c, e) = spend(&repo, "grocer", 1000);
+  let (d, f) = spend(&repo, "baker", 250);
+
+  let txn = repo.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  let log = txn.log(&space, 0).unwrap();
+  let mut expected = Merkle::zero();
+  for (n, (hash, (pos, id, merkle))) in [c, e, d, f].iter().zip(log.iter()).enumerate() {
+    expected = expected.next(hash);
+    assert_eq!(*pos, n as u64);
+    assert_eq!(txn.get_external(id).unwrap(), Some(*hash));
+    assert_eq!(*merkle, expected);
+    assert_eq!(txn.get_changeset(&space, id).unwrap(), Some(*pos));
+    assert_eq!(
+      txn.get_revchangeset(&space, *pos).unwrap(),
+      Some((*id, expected))
+    );
+    assert_eq!(txn.state_position(&space, &expected).unwrap(), Some(*pos));
+  }
+  assert_eq!(log.len(), 4);
+  assert_eq!(txn.current_state(&space).unwrap(), expected);
+  assert_eq!(txn.log(&space, 2).unwrap(), log[2..]);
+  assert_eq!(txn.state_position(&space, &Merkle::zero()).unwrap(), None);
+}
+
+#[test]
+fn same_changes_same_state() {
+  let home = Repo::new("log-agree-home");
+  let laptop = Repo::new("log-agree-laptop");
+  spend(&home, "grocer", 1000);
+  laptop.pull(&home);
+  assert_eq!(state(&laptop), state(&home));
+
+  spend(&laptop, "baker", 250);
+  assert_ne!(state(&laptop), state(&home));
+  home.pull(&laptop);
+  assert_eq!(state(&laptop), state(&home));
+}
+
+#[test]
+fn unrecording_restores_the_previous_state() {
+  let repo = Repo::new("log-unrecord");
+  spend(&repo, "grocer", 1000);
+  let (_, e) = spend(&repo, "baker", 250);
+  let log = repo.log();
+
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  unrecord(&repo.changes, &mut txn, &space, &e, false).unwrap();
+  txn.commit().unwrap();
+  assert_eq!(repo.log(), log[..3]);
+  assert_eq!(state(&repo), log[2].state);
+}
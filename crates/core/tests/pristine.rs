@@ -0,0 +1,96 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::ApplyError,
+  change::Operation,
+  models::ChangeHeader,
+  pristine::{Encyc, EncycError},
+  record::record,
+  traits::{MutTxnT, TxnT},
+  types::UId,
+};
+use common::{usd, Repo};
+
+/// A change adding a compartment with a long name, to fill the pristine
+/// quickly.
+fn add_compartment(n: usize) -> Vec<Operation> {
+  vec![Operation::AddCompartment {
+    id: UId::new(),
+    name: format!("{}-{}", n, "x".repeat(200)),
+    currency: usd(),
+  }]
+}
+
+#[test]
+fn full_pristines_refuse_new_pages() {
+  let mut repo = Repo::new("pristine-full");
+  let size = std::fs::metadata(repo.dir.join("azoni.db")).unwrap().len();
+  let encyc = std::mem::replace(&mut repo.encyc, Encyc::new_anony().unwrap());
+  repo.encyc = encyc.with_max_size(size);
+
+  // Record until the pristine needs more than the file it started with.
+  let mut recorded = 0;
+  let max_size = loop {
+    assert!(recorded < 10_000, "the pristine never grew");
+    let mut txn = repo.encyc.mut_txn_begin().unwrap();
+    let space = txn.load_space("main").unwrap().unwrap();
+    let committed = match record(
+      &repo.changes,
+      &mut txn,
+      &space,
+      ChangeHeader::default(),
+      add_compartment(recorded),
+      &repo.key,
+    ) {
+      Ok(_) => txn.commit(),
+      Err(ApplyError::Txn(e)) => Err(e),
+      Err(e) => panic!("unexpected error {:?}", e),
+    };
+    match committed {
+      Ok(()) => recorded += 1,
+      Err(EncycError::PristineFull {
+        size: now,
+        max_size,
+      }) => {
+        assert!(now > max_size);
+        break max_size;
+      }
+      Err(e) => panic!("unexpected error {:?}", e),
+    }
+  };
+  assert_eq!(max_size, size);
+
+  // The refused change left no trace, and the pristine is still readable.
+  assert_eq!(repo.log().len(), recorded);
+
+  // With a higher limit, it takes new changes again.
+  let encyc = std::mem::replace(&mut repo.encyc, Encyc::new_anony().unwrap());
+  repo.encyc = encyc.with_max_size(u64::MAX);
+  repo.record(add_compartment(recorded));
+  assert_eq!(repo.log().len(), recorded + 1);
+}
+
+#[test]
+fn anonymous_pristines_are_limited_too() {
+  let max_size = 1 << 16;
+  let encyc = Encyc::new_anony().unwrap().with_max_size(max_size);
+  let mut txn = encyc.mut_txn_begin().unwrap();
+  for n in 0.. {
+    assert!(n < 10_000, "the pristine never filled up");
+    match txn.open_or_create_space(&format!("space-{}", n)) {
+      Ok(_) => {}
+      Err(EncycError::PristineFull {
+        size,
+        max_size: max,
+      }) => {
+        assert!(size > max_size);
+        assert_eq!(max, max_size);
+        break;
+      }
+      Err(e) => panic!("unexpected error {:?}", e),
+    }
+  }
+}
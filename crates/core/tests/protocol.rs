@@ -0,0 +1,256 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use std::{
+  io::{BufRead, BufReader, Read, Write},
+  sync::mpsc::{channel, Receiver, Sender},
+};
+
+use azoni_core::{
+  changestore::ChangeStore,
+  protocol::{serve, Client, ProtocolError, MAX_CHANGE_SIZE},
+  sync::{missing, space_log, transfer},
+  traits::MutTxnT,
+  types::{Base32, Hash},
+};
+use common::{spend, state, Repo};
+
+/// The reading end of an in-process pipe.
+struct PipeReader {
+  rx: Receiver<Vec<u8>>,
+  buf: Vec<u8>,
+  pos: usize,
+}
+
+impl Read for PipeReader {
+  fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+    if self.pos == self.buf.len() {
+      // A closed channel is the end of the stream.
+      match self.rx.recv() {
+        Ok(buf) => (self.buf, self.pos) = (buf, 0),
+        Err(_) => return Ok(0),
+      }
+    }
+    let n = out.len().min(self.buf.len() - self.pos);
+    out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+    self.pos += n;
+    Ok(n)
+  }
+}
+
+/// The writing end of an in-process pipe.
+struct PipeWriter(Sender<Vec<u8>>);
+
+impl Write for PipeWriter {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    self
+      .0
+      .send(buf.to_vec())
+      .map_err(|_| std::io::ErrorKind::BrokenPipe)?;
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
+}
+
+fn pipe() -> (BufReader<PipeReader>, PipeWriter) {
+  let (tx, rx) = channel();
+  let reader = PipeReader {
+    rx,
+    buf: Vec::new(),
+    pos: 0,
+  };
+  (BufReader::new(reader), PipeWriter(tx))
+}
+
+type PipeClient = Client<BufReader<PipeReader>, PipeWriter>;
+
+/// Serve `remote` on a thread for the duration of `f`, which talks to it
+/// through the streams it is given.
+fn with_remote<T>(remote: &Repo, f: impl FnOnce(BufReader<PipeReader>, PipeWriter) -> T) -> T {
+  let (server_r, client_w) = pipe();
+  let (client_r, server_w) = pipe();
+  std::thread::scope(|s| {
+    s.spawn(|| serve(&remote.encyc, &remote.changes, server_r, server_w));
+    f(client_r, client_w)
+  })
+}
+
+/// Push what `remote` lacks from `local`, one change at a time, stopping
+/// after `limit` changes. Returns what was pushed.
+fn push(local: &Repo, client: &PipeClient, limit: usize) -> Vec<Hash> {
+  let theirs = client.log("main", 0).unwrap();
+  let mut hashes = missing(&theirs, &local.log());
+  hashes.truncate(limit);
+  for hash in hashes.iter() {
+    if !client.has_change(hash) {
+      client
+        .save_change(&local.changes.get_change(hash).unwrap())
+        .unwrap();
+    }
+    client.apply("main", hash).unwrap();
+  }
+  hashes
+}
+
+#[test]
+fn pull_over_protocol() {
+  let remote = Repo::new("proto-pull-remote");
+  let local = Repo::new("proto-pull-local");
+  let (c, e) = spend(&remote, "grocer", 1000);
+
+  let pulled = with_remote(&remote, |r, w| {
+    let client = Client::connect(r, w).unwrap();
+    let theirs = client.log("main", 0).unwrap();
+    let mut txn = local.encyc.mut_txn_begin().unwrap();
+    let space = txn.open_or_create_space("main").unwrap();
+    let hashes = missing(&space_log(&txn, &space).unwrap(), &theirs);
+    transfer(&client, &local.changes, &mut txn, &space, &hashes).unwrap();
+    txn.commit().unwrap();
+    client.quit().unwrap();
+    hashes
+  });
+
+  assert_eq!(pulled, vec![c, e]);
+  assert_eq!(state(&local), state(&remote));
+}
+
+#[test]
+fn push_over_protocol() {
+  let remote = Repo::new("proto-push-remote");
+  let local = Repo::new("proto-push-local");
+  spend(&remote, "grocer", 1000);
+  local.pull(&remote);
+  let (c, e) = spend(&local, "baker", 250);
+
+  let pushed = with_remote(&remote, |r, w| {
+    let client = Client::connect(r, w).unwrap();
+    let pushed = push(&local, &client, usize::MAX);
+    client.quit().unwrap();
+    pushed
+  });
+
+  assert_eq!(pushed, vec![c, e]);
+  assert_eq!(state(&local), state(&remote));
+  assert!(remote.changes.has_change(&e));
+}
+
+#[test]
+fn interrupted_push_resumes() {
+  let remote = Repo::new("proto-resume-remote");
+  let local = Repo::new("proto-resume-local");
+  let (c, e) = spend(&local, "grocer", 1000);
+
+  // The connection drops after the first change.
+  let first = with_remote(&remote, |r, w| {
+    let client = Client::connect(r, w).unwrap();
+    push(&local, &client, 1)
+  });
+  assert_eq!(first, vec![c]);
+
+  let rest = with_remote(&remote, |r, w| {
+    let client = Client::connect(r, w).unwrap();
+    let rest = push(&local, &client, usize::MAX);
+    client.quit().unwrap();
+    rest
+  });
+  assert_eq!(rest, vec![e]);
+  assert_eq!(state(&local), state(&remote));
+}
+
+#[test]
+fn other_versions_are_refused() {
+  let remote = Repo::new("proto-version");
+  let (server_r, mut client_w) = pipe();
+  let (mut client_r, server_w) = pipe();
+  let served = std::thread::scope(|s| {
+    let server = s.spawn(|| serve(&remote.encyc, &remote.changes, server_r, server_w));
+    writeln!(client_w, "azoni 99").unwrap();
+    let mut reply = String::new();
+    client_r.read_line(&mut reply).unwrap();
+    assert!(reply.starts_with("error "), "{:?}", reply);
+    server.join().unwrap()
+  });
+  assert!(matches!(
+    served,
+    Err(ProtocolError::Version { theirs: 99, .. })
+  ));
+}
+
+#[test]
+fn errors_keep_the_connection_usable() {
+  let remote = Repo::new("proto-errors");
+  let stranger = Repo::new("proto-errors-stranger");
+  let (c, _) = spend(&remote, "grocer", 1000);
+  let (unknown, _) = spend(&stranger, "baker", 250);
+
+  with_remote(&remote, |r, w| {
+    let client = Client::connect(r, w).unwrap();
+    assert!(!client.has_change(&unknown));
+    assert!(matches!(
+      client.get_change(&unknown),
+      Err(ProtocolError::Remote(_))
+    ));
+    assert!(matches!(
+      client.apply("main", &unknown),
+      Err(ProtocolError::Remote(_))
+    ));
+    assert_eq!(client.get_change(&c).unwrap().hash().unwrap(), c);
+    client.quit().unwrap();
+  });
+}
+
+#[test]
+fn malformed_puts_keep_the_connection_usable() {
+  let remote = Repo::new("proto-malformed-put");
+  let (c, _) = spend(&remote, "grocer", 1000);
+  let (server_r, mut client_w) = pipe();
+  let (mut client_r, server_w) = pipe();
+  let served = std::thread::scope(|s| {
+    let server = s.spawn(|| serve(&remote.encyc, &remote.changes, server_r, server_w));
+    let mut request = |line: &str| {
+      writeln!(client_w, "{}", line).unwrap();
+      let mut reply = String::new();
+      client_r.read_line(&mut reply).unwrap();
+      reply
+    };
+    assert_eq!(request("azoni 1"), "azoni 1\n");
+    for len in ["many", "-1", "99999999999999999999999"] {
+      let reply = request(&format!("put {}", len));
+      assert!(reply.starts_with("error "), "{:?}", reply);
+    }
+    assert_eq!(request(&format!("has {}", c.to_base32())), "yes\n");
+    writeln!(client_w, "quit").unwrap();
+    server.join().unwrap()
+  });
+  assert!(served.is_ok());
+}
+
+#[test]
+fn oversized_puts_close_the_connection() {
+  let remote = Repo::new("proto-oversized-put");
+  let (server_r, mut client_w) = pipe();
+  let (mut client_r, server_w) = pipe();
+  let served = std::thread::scope(|s| {
+    let server = s.spawn(|| serve(&remote.encyc, &remote.changes, server_r, server_w));
+    writeln!(client_w, "azoni 1").unwrap();
+    let mut reply = String::new();
+    client_r.read_line(&mut reply).unwrap();
+    // The payload follows the length, and must not be read as requests.
+    writeln!(client_w, "put {}", MAX_CHANGE_SIZE + 1).unwrap();
+    client_w.write_all(b"quit\n").unwrap();
+    let mut reply = String::new();
+    client_r.read_line(&mut reply).unwrap();
+    assert!(reply.starts_with("error "), "{:?}", reply);
+    let served = server.join().unwrap();
+    let mut rest = String::new();
+    client_r.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "");
+    served
+  });
+  assert!(matches!(served, Err(ProtocolError::TooLarge(_))));
+}
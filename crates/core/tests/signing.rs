@@ -0,0 +1,130 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use std::collections::BTreeMap;
+
+use azoni_core::{
+  apply::{apply_change, ApplyError},
+  change::{Change, Operation, SignatureError},
+  changestore::ChangeStore,
+  key::{PublicKey, SecretKey, AUTHOR_KEY},
+  models::{Author, ChangeHeader},
+  traits::{MutTxnT, TxnT},
+  types::{Base32, Hash, UId},
+};
+use common::{spend, usd, Repo};
+
+/// A change adding a compartment, by an author with `key` if given.
+fn change(key: Option<&PublicKey>) -> Change {
+  let mut header = ChangeHeader::default();
+  if let Some(key) = key {
+    let author = BTreeMap::from([(AUTHOR_KEY.to_string(), key.to_base32())]);
+    header.authors.push(Author(author));
+  }
+  Change::new(
+    header,
+    Vec::new(),
+    vec![Operation::AddCompartment {
+      id: UId::new(),
+      name: "savings".to_string(),
+      currency: usd(),
+    }],
+  )
+}
+
+/// Save `change` to the change store of `repo`, and apply it to its main
+/// space.
+fn save_and_apply(repo: &Repo, change: &Change) -> Result<Hash, SignatureError> {
+  let hash = repo.changes.save_change(change).unwrap();
+  let mut txn = repo.encyc.mut_txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  match apply_change(&repo.changes, &mut txn, &space, &hash) {
+    Ok(_) => {
+      txn.commit().unwrap();
+      Ok(hash)
+    }
+    Err(ApplyError::Signature(e)) => Err(e),
+    Err(e) => panic!("unexpected error {:?}", e),
+  }
+}
+
+#[test]
+fn recorded_changes_are_signed() {
+  let repo = Repo::new("signing-recorded");
+  let (c, e) = spend(&repo, "grocer", 1000);
+  for hash in [c, e] {
+    let change = repo.changes.get_change(&hash).unwrap();
+    assert_eq!(
+      change.check_signatures(&hash).unwrap(),
+      vec![repo.key.public_key()]
+    );
+  }
+}
+
+#[test]
+fn signing_keeps_the_hash() {
+  let key = SecretKey::generate();
+  let mut change = change(Some(&key.public_key()));
+  let hash = change.hash().unwrap();
+  change.sign(&key, &hash);
+  change.sign(&key, &hash);
+  assert_eq!(change.hash().unwrap(), hash);
+  assert_eq!(change.signatures.len(), 1);
+}
+
+#[test]
+fn tampered_signatures_are_refused() {
+  let repo = Repo::new("signing-tampered");
+  let (key, other) = (SecretKey::generate(), SecretKey::generate());
+
+  // A signature over another hash.
+  let mut tampered = change(None);
+  let hash = tampered.hash().unwrap();
+  tampered.sign(&key, &hash);
+  tampered.signatures[0].signature = key.sign(&change(None).hash().unwrap());
+  assert_eq!(
+    save_and_apply(&repo, &tampered),
+    Err(SignatureError::Invalid(key.public_key().to_base32()))
+  );
+
+  // A valid signature, claimed by another key.
+  tampered.signatures[0].signature = key.sign(&hash);
+  tampered.signatures[0].key = other.public_key();
+  assert_eq!(
+    save_and_apply(&repo, &tampered),
+    Err(SignatureError::Invalid(other.public_key().to_base32()))
+  );
+  assert!(repo.log().is_empty());
+}
+
+#[test]
+fn authors_with_a_key_must_sign() {
+  let repo = Repo::new("signing-authors");
+  let (key, other) = (SecretKey::generate(), SecretKey::generate());
+  let author = key.public_key().to_base32();
+
+  let mut change = change(Some(&key.public_key()));
+  let hash = change.hash().unwrap();
+  change.sign(&other, &hash);
+  assert_eq!(
+    save_and_apply(&repo, &change),
+    Err(SignatureError::Missing(author))
+  );
+  change.sign(&key, &hash);
+  assert_eq!(save_and_apply(&repo, &change), Ok(hash));
+}
+
+#[test]
+fn unsigned_changes_are_refused() {
+  let repo = Repo::new("signing-unsigned");
+  let key = SecretKey::generate();
+  for change in [change(None), change(Some(&key.public_key()))] {
+    assert_eq!(
+      save_and_apply(&repo, &change),
+      Err(SignatureError::Unsigned)
+    );
+  }
+  assert!(repo.log().is_empty());
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use azoni_core::{
+  pristine::Encyc,
+  traits::{MutTxnT, TxnT},
+};
+
+#[test]
+fn space_round_trip() {
+  let encyc = Encyc::new_anony().unwrap();
+
+  let mut txn = encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  let id = space.read().id;
+  txn.commit().unwrap();
+
+  let txn = encyc.txn_begin().unwrap();
+  let space = txn
+    .load_space("main")
+    .unwrap()
+    .expect("space should be persisted");
+  assert_eq!(space.read().id, id);
+  assert_eq!(space.read().name.as_str(), "main");
+  assert!(txn.load_space("other").unwrap().is_none());
+}
+
+#[test]
+fn open_existing_space() {
+  let encyc = Encyc::new_anony().unwrap();
+
+  let mut txn = encyc.mut_txn_begin().unwrap();
+  let id = txn.open_or_create_space("main").unwrap().read().id;
+  txn.commit().unwrap();
+
+  let mut txn = encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  assert_eq!(space.read().id, id);
+  txn.open_or_create_space("savings").unwrap();
+  txn.commit().unwrap();
+
+  let txn = encyc.txn_begin().unwrap();
+  assert_eq!(txn.load_space("main").unwrap().unwrap().read().id, id);
+  assert!(txn.load_space("savings").unwrap().is_some());
+}
+
+#[test]
+fn uncommitted_space_is_dropped() {
+  let encyc = Encyc::new_anony().unwrap();
+
+  let mut txn = encyc.mut_txn_begin().unwrap();
+  txn.open_or_create_space("main").unwrap();
+  txn.commit().unwrap();
+
+  let mut txn = encyc.mut_txn_begin().unwrap();
+  txn.open_or_create_space("scratch").unwrap();
+  drop(txn);
+
+  let txn = encyc.txn_begin().unwrap();
+  assert!(txn.load_space("scratch").unwrap().is_none());
+}
+
+#[test]
+fn current_space_round_trip() {
+  let encyc = Encyc::new_anony().unwrap();
+
+  let mut txn = encyc.mut_txn_begin().unwrap();
+  assert!(txn.current_space().is_none());
+  txn.open_or_create_space("main").unwrap();
+  txn.set_current_space("main").unwrap();
+  txn.commit().unwrap();
+
+  let txn = encyc.txn_begin().unwrap();
+  assert_eq!(txn.current_space(), Some("main"));
+}
+
+#[test]
+fn invalid_space_name() {
+  let encyc = Encyc::new_anony().unwrap();
+  let mut txn = encyc.mut_txn_begin().unwrap();
+  assert!(txn.open_or_create_space("").is_err());
+  assert!(txn.open_or_create_space(&"a".repeat(256)).is_err());
+}
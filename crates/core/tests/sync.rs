@@ -0,0 +1,92 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+mod common;
+
+use azoni_core::{
+  apply::ApplyError,
+  change::SignatureError,
+  changestore::ChangeStore,
+  models::entry::EntryTxnT,
+  sync::{missing, space_log, transfer, SyncError},
+  traits::{MutTxnT, TxnT},
+};
+use common::{spend, state, Repo};
+
+#[test]
+fn pull_into_empty_copy() {
+  let home = Repo::new("empty-home");
+  let usb = Repo::new("empty-usb");
+  let (c, e) = spend(&home, "grocer", 1000);
+
+  assert_eq!(usb.pull(&home), vec![c, e]);
+  assert_eq!(state(&usb), state(&home));
+  assert!(usb.changes.has_change(&e));
+  assert!(usb.pull(&home).is_empty());
+  assert!(home.pull(&usb).is_empty());
+}
+
+#[test]
+fn divergent_copies_converge() {
+  let home = Repo::new("diverge-home");
+  let nas = Repo::new("diverge-nas");
+  spend(&home, "shared", 100);
+  nas.pull(&home);
+
+  let (hc, he) = spend(&home, "bakery", 250);
+  let (nc, ne) = spend(&nas, "garage", 4000);
+  assert_eq!(missing(&home.log(), &nas.log()), vec![nc, ne]);
+  assert_eq!(missing(&nas.log(), &home.log()), vec![hc, he]);
+
+  assert_eq!(home.pull(&nas), vec![nc, ne]);
+  assert_eq!(nas.pull(&home), vec![hc, he]);
+  // Different orders, same changes: the states agree.
+  assert_eq!(state(&home), state(&nas));
+  assert!(missing(&home.log(), &nas.log()).is_empty());
+
+  let txn = home.encyc.txn_begin().unwrap();
+  let space = txn.load_space("main").unwrap().unwrap();
+  assert_eq!(txn.space_entries(&space).unwrap().len(), 3);
+}
+
+#[test]
+fn missing_skips_common_prefix() {
+  let home = Repo::new("prefix-home");
+  let usb = Repo::new("prefix-usb");
+  spend(&home, "a", 1);
+  spend(&home, "b", 2);
+  usb.pull(&home);
+  let (c, e) = spend(&home, "c", 3);
+
+  let ours = usb.log();
+  let theirs = home.log();
+  assert_eq!(ours.len(), 4);
+  assert_eq!(ours[..], theirs[..4]);
+  assert_eq!(missing(&ours, &theirs), vec![c, e]);
+  assert!(missing(&theirs, &ours).is_empty());
+}
+
+#[test]
+fn invalid_change_is_not_transferred() {
+  let home = Repo::new("invalid-home");
+  let usb = Repo::new("invalid-usb");
+  let (c, _) = spend(&home, "grocer", 1000);
+
+  // Corrupt the signature of the first change.
+  let file = home.changes.filename(&c);
+  let mut bytes = std::fs::read(&file).unwrap();
+  *bytes.last_mut().unwrap() ^= 1;
+  std::fs::write(&file, bytes).unwrap();
+
+  let theirs = home.log();
+  let mut txn = usb.encyc.mut_txn_begin().unwrap();
+  let space = txn.open_or_create_space("main").unwrap();
+  let hashes = missing(&space_log(&txn, &space).unwrap(), &theirs);
+  match transfer(&home.changes, &usb.changes, &mut txn, &space, &hashes) {
+    Err(SyncError::Apply(ApplyError::Signature(SignatureError::Invalid(_)))) => {}
+    r => panic!("unexpected result {:?}", r.map(|_| ())),
+  }
+  drop(txn);
+  assert!(!usb.changes.has_change(&c));
+  assert!(usb.log().is_empty());
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2026 und3fy.dev. All rights reserved.
+// Created by und3fined <me@und3fy.dev> on 2026 Oct 18.
+
+use std::process::Command;
+
+/// Environment variable overriding the name of the current device.
+pub const DEVICE_VAR: &str = "AZONI_DEVICE";
+
+/// What we can tell about the machine we are running on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct System {
+  pub hostname: String,
+  /// CPU architecture, e.g. `x86_64`.
+  pub platform: String,
+  /// e.g. `linux`, `macos`, `windows`.
+  pub os: String,
+  pub os_version: Option<String>,
+}
+
+pub fn current() -> System {
+  System {
+    hostname: hostname(),
+    platform: std::env::consts::ARCH.to_string(),
+    os: std::env::consts::OS.to_string(),
+    os_version: os_version(),
+  }
+}
+
+/// `$AZONI_DEVICE`, or the host name of this machine.
+pub fn hostname() -> String {
+  let names = [
+    std::env::var(DEVICE_VAR).ok(),
+    read_trimmed("/proc/sys/kernel/hostname"),
+    read_trimmed("/etc/hostname"),
+    std::env::var("COMPUTERNAME").ok(),
+    std::env::var("HOSTNAME").ok(),
+  ];
+  names
+    .into_iter()
+    .flatten()
+    .find(|s| !s.is_empty())
+    .or_else(|| command_output("hostname", &[]))
+    .unwrap_or_else(|| "localhost".to_string())
+}
+
+fn os_version() -> Option<String> {
+  match std::env::consts::OS {
+    "linux" | "android" => read_trimmed("/proc/sys/kernel/osrelease"),
+    "macos" => command_output("sw_vers", &["-productVersion"]),
+    "windows" => command_output("cmd", &["/C", "ver"]),
+    _ => command_output("uname", &["-r"]),
+  }
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+  let s = std::fs::read_to_string(path).ok()?;
+  Some(s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn command_output(cmd: &str, args: &[&str]) -> Option<String> {
+  let out = Command::new(cmd).args(args).output().ok()?;
+  if !out.status.success() {
+    return None;
+  }
+  let s = String::from_utf8(out.stdout).ok()?;
+  Some(s.trim().to_string()).filter(|s| !s.is_empty())
+}
@@ -1,4 +1,5 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 10.
 
+pub mod device;
 pub mod path;
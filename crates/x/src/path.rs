@@ -1,7 +1,7 @@
 // Copyright (c) 2023 und3fy.dev. All rights reserved.
 // Created by und3fined <me@und3fy.dev> on 2023 Dec 10.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::bail;
 
@@ -12,3 +12,160 @@ pub fn current_dir() -> Result<PathBuf, anyhow::Error> {
     bail!("Cannot access working directory")
   }
 }
+
+/// Name of the directory holding a repository's pristine.
+pub const DOT_DIR: &str = ".azoni";
+
+/// Environment variable pointing to the root of the repository to use.
+pub const DIR_VAR: &str = "AZONI_DIR";
+
+/// Environment variable overriding the user-level configuration directory.
+pub const CONFIG_VAR: &str = "AZONI_CONFIG_DIR";
+
+/// The user-level configuration directory, shared by all repositories:
+/// `$AZONI_CONFIG_DIR`, or `azoni` in the platform's configuration
+/// directory.
+pub fn config_dir() -> Result<PathBuf, anyhow::Error> {
+  if let Some(dir) = std::env::var_os(CONFIG_VAR) {
+    return Ok(PathBuf::from(dir));
+  }
+  match dirs_next::config_dir() {
+    Some(dir) => Ok(dir.join("azoni")),
+    None => bail!(
+      "Cannot find the configuration directory, set ${}",
+      CONFIG_VAR
+    ),
+  }
+}
+
+/// Find the root of the current repository, i.e. the directory containing
+/// `DOT_DIR`:
+///
+/// - if `repository` is given, it must be that root;
+/// - else if `AZONI_DIR` is set, it must be that root;
+/// - else walk up from the current directory, like git does.
+///
+/// Returns `None` if there is no repository there.
+pub fn find_repository_root(repository: Option<&Path>) -> Result<Option<PathBuf>, anyhow::Error> {
+  if let Some(root) = repository {
+    return Ok(is_repository(root).then(|| root.to_path_buf()));
+  }
+
+  if let Some(root) = std::env::var_os(DIR_VAR) {
+    let root = PathBuf::from(root);
+    return Ok(is_repository(&root).then_some(root));
+  }
+
+  let cur = current_dir()?;
+  Ok(
+    cur
+      .ancestors()
+      .find(|p| is_repository(p))
+      .map(Path::to_path_buf),
+  )
+}
+
+fn is_repository(root: &Path) -> bool {
+  root.join(DOT_DIR).is_dir()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Mutex;
+
+  use super::*;
+
+  /// The environment and the current directory are shared by the whole
+  /// process: tests touching them take turns.
+  static ENV: Mutex<()> = Mutex::new(());
+
+  /// A temporary directory holding repository `a`, with `a/sub/dir` in it,
+  /// and repository `b`. Removed on drop.
+  struct Tree(PathBuf);
+
+  impl Tree {
+    fn new(name: &str) -> Self {
+      let root = std::env::temp_dir().join(format!("azoni-x-{}-{}", std::process::id(), name));
+      let _ = std::fs::remove_dir_all(&root);
+      std::fs::create_dir_all(root.join("a").join(DOT_DIR)).unwrap();
+      std::fs::create_dir_all(root.join("a/sub/dir")).unwrap();
+      std::fs::create_dir_all(root.join("b").join(DOT_DIR)).unwrap();
+      Tree(root.canonicalize().unwrap())
+    }
+
+    fn join(&self, path: &str) -> PathBuf {
+      self.0.join(path)
+    }
+  }
+
+  impl Drop for Tree {
+    fn drop(&mut self) {
+      let _ = std::fs::remove_dir_all(&self.0);
+    }
+  }
+
+  /// Run `f` from directory `cwd`, with `AZONI_DIR` set to `dir`.
+  fn from<T>(cwd: &Path, dir: Option<&Path>, f: impl FnOnce() -> T) -> T {
+    let _lock = ENV.lock().unwrap_or_else(|e| e.into_inner());
+    let old = std::env::current_dir().unwrap();
+    std::env::set_current_dir(cwd).unwrap();
+    match dir {
+      Some(dir) => std::env::set_var(DIR_VAR, dir),
+      None => std::env::remove_var(DIR_VAR),
+    }
+    let result = f();
+    std::env::remove_var(DIR_VAR);
+    std::env::set_current_dir(old).unwrap();
+    result
+  }
+
+  #[test]
+  fn explicit_repository_comes_first() {
+    let tree = Tree::new("explicit");
+    let (a, b) = (tree.join("a"), tree.join("b"));
+    let found = from(&tree.join("a/sub"), Some(&a), || {
+      find_repository_root(Some(&b))
+    });
+    assert_eq!(found.unwrap(), Some(b));
+    // Not searched further: a directory that is no repository is not found.
+    let found = from(&a, Some(&a), || {
+      find_repository_root(Some(&tree.join("a/sub")))
+    });
+    assert_eq!(found.unwrap(), None);
+  }
+
+  #[test]
+  fn dir_var_comes_before_the_current_directory() {
+    let tree = Tree::new("var");
+    let (a, b) = (tree.join("a"), tree.join("b"));
+    let found = from(&tree.join("a/sub"), Some(&b), || find_repository_root(None));
+    assert_eq!(found.unwrap(), Some(b));
+    let found = from(&a, Some(&tree.join("a/sub")), || find_repository_root(None));
+    assert_eq!(found.unwrap(), None);
+  }
+
+  #[test]
+  fn walks_up_from_the_current_directory() {
+    let tree = Tree::new("walk");
+    let a = tree.join("a");
+    let found = from(&tree.join("a/sub/dir"), None, || find_repository_root(None));
+    assert_eq!(found.unwrap(), Some(a.clone()));
+    let found = from(&a, None, || find_repository_root(None));
+    assert_eq!(found.unwrap(), Some(a));
+  }
+
+  #[test]
+  fn config_dir_can_be_overridden() {
+    let _lock = ENV.lock().unwrap_or_else(|e| e.into_inner());
+    let old = std::env::var_os(CONFIG_VAR);
+    std::env::set_var(CONFIG_VAR, "/etc/azoni-test");
+    assert_eq!(config_dir().unwrap(), PathBuf::from("/etc/azoni-test"));
+    std::env::remove_var(CONFIG_VAR);
+    if let Ok(dir) = config_dir() {
+      assert!(dir.ends_with("azoni"), "{:?}", dir);
+    }
+    if let Some(old) = old {
+      std::env::set_var(CONFIG_VAR, old);
+    }
+  }
+}